@@ -0,0 +1,13 @@
+-- Migration 008: Generalize stl_files into a model file table
+-- Version: 8
+-- Description: Add format column so 3MF (and other mesh formats) can be stored alongside STL
+
+-- Existing rows are all STL files
+ALTER TABLE stl_files ADD COLUMN format TEXT NOT NULL DEFAULT 'stl';
+
+-- Create index for format-based queries
+CREATE INDEX IF NOT EXISTS idx_stl_files_format ON stl_files(format);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (8, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,31 @@
+-- Migration 009: Mesh geometry analysis
+-- Version: 9
+-- Description: Store bounding box, triangle count, surface area, volume and estimated mass per model file
+
+CREATE TABLE IF NOT EXISTS mesh_analysis (
+    stl_file_id INTEGER PRIMARY KEY,
+    size_x REAL NOT NULL,
+    size_y REAL NOT NULL,
+    size_z REAL NOT NULL,
+    triangle_count INTEGER NOT NULL,
+    surface_area REAL NOT NULL,
+    volume REAL NOT NULL,
+    estimated_mass REAL NOT NULL,
+    -- Geometry fingerprint, used to match re-exported copies of a model
+    geometry_fingerprint TEXT,
+    -- File size and mtime at analysis time, used to skip unchanged files on rescan
+    source_size INTEGER NOT NULL,
+    source_mtime INTEGER NOT NULL,
+    analyzed_at INTEGER NOT NULL,
+    FOREIGN KEY (stl_file_id) REFERENCES stl_files(id) ON DELETE CASCADE
+);
+
+-- Create indexes for dimension-based queries
+CREATE INDEX IF NOT EXISTS idx_mesh_analysis_size ON mesh_analysis(size_x, size_y, size_z);
+
+-- Create index for grouping similar models
+CREATE INDEX IF NOT EXISTS idx_mesh_analysis_fingerprint ON mesh_analysis(geometry_fingerprint);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (9, CAST(strftime('%s', 'now') AS INTEGER));
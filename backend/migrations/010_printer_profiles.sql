@@ -0,0 +1,24 @@
+-- Migration 010: Printer profiles
+-- Version: 10
+-- Description: Named build volumes used to filter search results by build-plate fit
+
+CREATE TABLE IF NOT EXISTS printer_profiles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    build_x REAL NOT NULL CHECK (build_x > 0),
+    build_y REAL NOT NULL CHECK (build_y > 0),
+    build_z REAL NOT NULL CHECK (build_z > 0),
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Seed a few common printers (build volumes in mm)
+INSERT OR IGNORE INTO printer_profiles (name, build_x, build_y, build_z, created_at, updated_at)
+VALUES
+    ('Prusa MK4', 250, 210, 220, CAST(strftime('%s', 'now') AS INTEGER), CAST(strftime('%s', 'now') AS INTEGER)),
+    ('Bambu Lab X1 Carbon', 256, 256, 256, CAST(strftime('%s', 'now') AS INTEGER), CAST(strftime('%s', 'now') AS INTEGER)),
+    ('Elegoo Saturn', 192, 120, 200, CAST(strftime('%s', 'now') AS INTEGER), CAST(strftime('%s', 'now') AS INTEGER));
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (10, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,21 @@
+-- Migration 011: Mesh printability issues
+-- Version: 11
+-- Description: Record per-file mesh problems (non-manifold edges, flipped normals, degenerate triangles, multiple shells)
+
+CREATE TABLE IF NOT EXISTS mesh_issues (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stl_file_id INTEGER NOT NULL,
+    issue_type TEXT NOT NULL CHECK (issue_type IN (
+        'non_manifold_edges', 'open_edges', 'flipped_normals', 'degenerate_triangles', 'multiple_shells'
+    )),
+    count INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    FOREIGN KEY (stl_file_id) REFERENCES stl_files(id) ON DELETE CASCADE
+);
+
+-- Create index for per-file lookups and project filtering
+CREATE INDEX IF NOT EXISTS idx_mesh_issues_file ON mesh_issues(stl_file_id);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (11, CAST(strftime('%s', 'now') AS INTEGER));
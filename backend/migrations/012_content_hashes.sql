@@ -0,0 +1,14 @@
+-- Migration 012: Content hashes for duplicate detection
+-- Version: 12
+-- Description: Store a SHA-256 of each model file's contents, with the size/mtime it was computed for
+
+ALTER TABLE stl_files ADD COLUMN content_hash TEXT;
+ALTER TABLE stl_files ADD COLUMN content_hash_size INTEGER;
+ALTER TABLE stl_files ADD COLUMN content_hash_mtime INTEGER;
+
+-- Create index for grouping identical files
+CREATE INDEX IF NOT EXISTS idx_stl_files_content_hash ON stl_files(content_hash);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (12, CAST(strftime('%s', 'now') AS INTEGER));
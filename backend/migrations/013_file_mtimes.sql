@@ -0,0 +1,10 @@
+-- Migration 013: File modification times
+-- Version: 13
+-- Description: Store each model and image file's mtime so rescans can detect files replaced in place
+
+ALTER TABLE stl_files ADD COLUMN file_mtime INTEGER;
+ALTER TABLE image_files ADD COLUMN file_mtime INTEGER;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (13, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,19 @@
+-- Migration 014: Scan history
+-- Version: 14
+-- Description: Record how each scan ran and every counter it reported in scan_sessions
+
+ALTER TABLE scan_sessions ADD COLUMN mode TEXT NOT NULL DEFAULT 'full';
+ALTER TABLE scan_sessions ADD COLUMN scan_path TEXT;
+ALTER TABLE scan_sessions ADD COLUMN projects_added INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN projects_updated INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN projects_removed INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN files_added INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN files_updated INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN files_removed INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN stl_previews_generated INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN stl_previews_queued INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scan_sessions ADD COLUMN stl_previews_cached INTEGER NOT NULL DEFAULT 0;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (14, CAST(strftime('%s', 'now') AS INTEGER));
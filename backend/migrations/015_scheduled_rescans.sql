@@ -0,0 +1,13 @@
+-- Migration 015: Scheduled rescans
+-- Version: 15
+-- Description: Store the automatic rescan schedule and record what triggered each scan
+
+-- Cron expression (UTC), takes precedence over the interval when both are set
+ALTER TABLE config ADD COLUMN scan_schedule TEXT;
+ALTER TABLE config ADD COLUMN scan_interval_minutes INTEGER;
+
+ALTER TABLE scan_sessions ADD COLUMN triggered_by TEXT NOT NULL DEFAULT 'manual';
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (15, CAST(strftime('%s', 'now') AS INTEGER));
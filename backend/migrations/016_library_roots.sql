@@ -0,0 +1,34 @@
+-- Migration 016: Multiple library roots
+-- Version: 16
+-- Description: Named library roots, each scanned independently, with projects tagged by root
+
+CREATE TABLE library_roots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    path TEXT NOT NULL UNIQUE,
+    last_scan_at INTEGER,
+    created_at INTEGER NOT NULL,
+
+    CHECK (length(name) > 0),
+    CHECK (length(path) > 0)
+);
+
+-- The single configured root becomes the "default" library root
+INSERT INTO library_roots (name, path, last_scan_at, created_at)
+SELECT 'default', root_path, last_scan_at, CAST(strftime('%s', 'now') AS INTEGER)
+FROM config
+WHERE id = 1 AND root_path IS NOT NULL AND length(root_path) > 0;
+
+ALTER TABLE projects ADD COLUMN root_id INTEGER REFERENCES library_roots(id) ON DELETE CASCADE;
+
+UPDATE projects
+SET root_id = (
+    SELECT r.id FROM library_roots r
+    WHERE projects.full_path = r.path OR substr(projects.full_path, 1, length(r.path) + 1) = r.path || '/'
+);
+
+CREATE INDEX idx_projects_root ON projects(root_id);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (16, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,10 @@
+-- Migration 017: Ignore patterns
+-- Version: 17
+-- Description: Store global gitignore-style patterns excluded from scans
+
+-- One pattern per line, relative to the library root
+ALTER TABLE config ADD COLUMN ignore_patterns TEXT;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (17, CAST(strftime('%s', 'now') AS INTEGER));
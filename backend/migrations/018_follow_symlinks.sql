@@ -0,0 +1,17 @@
+-- Migration 018: Follow symlinks
+-- Version: 18
+-- Description: Opt-in symlink following during scans, and the folder links found
+
+ALTER TABLE config ADD COLUMN follow_symlinks INTEGER NOT NULL DEFAULT 0;
+
+-- Symlinked folders that lead to another folder of the same library. The linked
+-- projects are indexed once, under target_path; browsing link_path shows them.
+CREATE TABLE IF NOT EXISTS folder_links (
+    link_path TEXT PRIMARY KEY,
+    target_path TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (18, CAST(strftime('%s', 'now') AS INTEGER));
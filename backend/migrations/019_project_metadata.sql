@@ -0,0 +1,24 @@
+-- Migration 019: Project metadata
+-- Version: 19
+-- Description: Metadata imported from per-project sidecar files
+
+CREATE TABLE IF NOT EXISTS project_metadata (
+    project_id INTEGER PRIMARY KEY,
+    author TEXT,
+    license TEXT,
+    source_url TEXT,
+    scale TEXT,
+    -- JSON object of print settings, as written in the sidecar file
+    print_settings TEXT,
+    -- Names, sizes and modification times of the sidecar files last imported
+    sidecar_signature TEXT,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE CASCADE
+);
+
+-- Tags imported from a sidecar file are replaced when the file changes
+ALTER TABLE project_tags ADD COLUMN from_sidecar INTEGER NOT NULL DEFAULT 0;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (19, CAST(strftime('%s', 'now') AS INTEGER));
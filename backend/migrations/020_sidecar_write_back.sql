@@ -0,0 +1,9 @@
+-- Migration 020: Sidecar write-back
+-- Version: 20
+-- Description: Opt-in mirroring of project tags and metadata into sidecar files
+
+ALTER TABLE config ADD COLUMN write_sidecars INTEGER NOT NULL DEFAULT 0;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (20, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,44 @@
+-- Migration 021: Editable project metadata
+-- Version: 21
+-- Description: Release dates, and project descriptions in the full-text index
+
+ALTER TABLE project_metadata ADD COLUMN release_date TEXT;
+
+-- FTS5 tables cannot gain columns, so the index is rebuilt with the description
+DROP TRIGGER IF EXISTS projects_fts_insert;
+DROP TRIGGER IF EXISTS projects_fts_update;
+DROP TRIGGER IF EXISTS projects_fts_delete;
+DROP TABLE IF EXISTS projects_fts;
+
+CREATE VIRTUAL TABLE projects_fts USING fts5(
+    project_id UNINDEXED,
+    name,
+    full_path,
+    description,
+    tokenize='porter unicode61'
+);
+
+INSERT INTO projects_fts(project_id, name, full_path, description)
+SELECT id, name, full_path, description FROM projects;
+
+CREATE TRIGGER projects_fts_insert AFTER INSERT ON projects
+BEGIN
+    INSERT INTO projects_fts(project_id, name, full_path, description)
+    VALUES (NEW.id, NEW.name, NEW.full_path, NEW.description);
+END;
+
+CREATE TRIGGER projects_fts_update AFTER UPDATE ON projects
+BEGIN
+    UPDATE projects_fts
+    SET name = NEW.name, full_path = NEW.full_path, description = NEW.description
+    WHERE project_id = NEW.id;
+END;
+
+CREATE TRIGGER projects_fts_delete AFTER DELETE ON projects
+BEGIN
+    DELETE FROM projects_fts WHERE project_id = OLD.id;
+END;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (21, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,23 @@
+-- Migration 022: Creators
+-- Version: 22
+-- Description: Designers / creators of projects, derived from a folder level or sidecar authors
+
+CREATE TABLE IF NOT EXISTS creators (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at INTEGER NOT NULL,
+
+    CHECK (length(name) > 0)
+);
+
+-- creator_override marks a creator set by hand; scans keep it and its subfolders inherit it
+ALTER TABLE projects ADD COLUMN creator_id INTEGER REFERENCES creators(id) ON DELETE SET NULL;
+ALTER TABLE projects ADD COLUMN creator_override INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX IF NOT EXISTS idx_projects_creator ON projects(creator_id);
+
+-- Folder level (1 = folders directly in a library root) whose folders are creators; NULL disables
+ALTER TABLE config ADD COLUMN creator_folder_level INTEGER;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (22, CAST(strftime('%s', 'now') AS INTEGER));
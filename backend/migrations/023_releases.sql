@@ -0,0 +1,42 @@
+-- Migration 023: Releases
+-- Version: 23
+-- Description: Releases grouping projects, e.g. a creator's monthly drops, made by hand or
+-- detected from folder names
+
+-- folder_project_id is the folder a release was detected from. Detected releases are
+-- kept in sync with their folder until edited through the API, which clears auto_detected.
+CREATE TABLE IF NOT EXISTS releases (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT,
+    creator_id INTEGER REFERENCES creators(id) ON DELETE SET NULL,
+    release_date TEXT,
+    folder_project_id INTEGER UNIQUE REFERENCES projects(id) ON DELETE SET NULL,
+    auto_detected INTEGER NOT NULL DEFAULT 0,
+    cover_path TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    CHECK (length(name) > 0)
+);
+
+CREATE INDEX IF NOT EXISTS idx_releases_creator ON releases(creator_id);
+CREATE INDEX IF NOT EXISTS idx_releases_date ON releases(release_date);
+
+-- Projects of a release, in the order they are listed and downloaded
+CREATE TABLE IF NOT EXISTS release_projects (
+    release_id INTEGER NOT NULL REFERENCES releases(id) ON DELETE CASCADE,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+
+    PRIMARY KEY (release_id, project_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_release_projects_project ON release_projects(project_id);
+
+-- Folder name pattern of release folders, e.g. 'YYYY-MM*'; NULL disables detection
+ALTER TABLE config ADD COLUMN release_folder_pattern TEXT;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (23, CAST(strftime('%s', 'now') AS INTEGER));
@@ -0,0 +1,39 @@
+-- Migration 024: Collections
+-- Version: 24
+-- Description: User-curated, ordered lists of projects and model files, e.g. print queues
+
+CREATE TABLE IF NOT EXISTS collections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    description TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    CHECK (length(name) > 0)
+);
+
+-- Each item is either a whole project or a single model file
+CREATE TABLE IF NOT EXISTS collection_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+    project_id INTEGER REFERENCES projects(id) ON DELETE CASCADE,
+    stl_file_id INTEGER REFERENCES stl_files(id) ON DELETE CASCADE,
+    quantity INTEGER NOT NULL DEFAULT 1,
+    notes TEXT,
+    position INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    CHECK ((project_id IS NULL) != (stl_file_id IS NULL)),
+    CHECK (quantity > 0),
+    UNIQUE (collection_id, project_id),
+    UNIQUE (collection_id, stl_file_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_items_collection ON collection_items(collection_id, position);
+CREATE INDEX IF NOT EXISTS idx_collection_items_project ON collection_items(project_id);
+CREATE INDEX IF NOT EXISTS idx_collection_items_stl_file ON collection_items(stl_file_id);
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (24, CAST(strftime('%s', 'now') AS INTEGER));
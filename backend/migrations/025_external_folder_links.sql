@@ -0,0 +1,11 @@
+-- Migration 025: External folder links
+-- Version: 25
+-- Description: Record followed symlinks that lead outside the library
+
+-- External links keep their canonical target in target_path. Browsing may only
+-- leave the library root through one of them, and only into its target.
+ALTER TABLE folder_links ADD COLUMN external INTEGER NOT NULL DEFAULT 0;
+
+-- Record migration
+INSERT INTO schema_migrations (version, applied_at)
+VALUES (25, CAST(strftime('%s', 'now') AS INTEGER));
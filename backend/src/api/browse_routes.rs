@@ -1,17 +1,21 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
+use std::path::Path as StdPath;
 use std::sync::Arc;
 
+use crate::config::DEFAULT_ROOT;
+use crate::db::repositories::library_root_repo::LibraryRootRepository;
 use crate::services::folder_service::FolderService;
 
 #[derive(Clone)]
 pub struct BrowseState {
     pub folder_service: Arc<FolderService>,
+    pub library_root_repo: Arc<LibraryRootRepository>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +24,14 @@ pub struct FolderQuery {
     per_page: Option<usize>,
 }
 
+/// Library root and folder path of the root-namespaced browse routes
+#[derive(Debug, Deserialize)]
+pub struct RootFolderPath {
+    id: i64,
+    #[serde(default)]
+    path: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     error: String,
@@ -33,10 +45,104 @@ pub async fn get_folder_contents(
 ) -> impl IntoResponse {
     let folder_path = path.map(|p| p.0).unwrap_or_default();
 
-    match state
-        .folder_service
-        .get_folder_contents(&folder_path, query.page, query.per_page)
-    {
+    match default_folder_service(&state) {
+        Ok(folder_service) => folder_contents(&folder_service, &folder_path, query),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// GET /api/roots/:id/browse/*path - Get folder contents inside one library root
+pub async fn get_root_folder_contents(
+    State(state): State<BrowseState>,
+    Path(params): Path<RootFolderPath>,
+    Query(query): Query<FolderQuery>,
+) -> impl IntoResponse {
+    match root_folder_service(&state, params.id) {
+        Ok(folder_service) => folder_contents(&folder_service, &params.path, query),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// GET /api/browse/breadcrumb/*path - Get breadcrumb trail
+pub async fn get_breadcrumb(
+    State(state): State<BrowseState>,
+    path: Option<Path<String>>,
+) -> impl IntoResponse {
+    let folder_path = path.map(|p| p.0).unwrap_or_default();
+
+    match default_folder_service(&state) {
+        Ok(folder_service) => breadcrumb(&folder_service, &folder_path),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Folder service browsing the default library root, which follows the configured
+/// root path; the path given at startup is browsed until one is configured. Other
+/// roots are browsed through `/api/roots/:id/browse`.
+fn default_folder_service(
+    state: &BrowseState,
+) -> Result<FolderService, (StatusCode, Json<ErrorResponse>)> {
+    match state.library_root_repo.list() {
+        Ok(roots) => Ok(match roots.iter().find(|root| root.name == DEFAULT_ROOT) {
+            Some(root) => state
+                .folder_service
+                .with_root_path(StdPath::new(&root.path)),
+            None => (*state.folder_service).clone(),
+        }),
+        Err(e) => {
+            tracing::error!("Failed to list library roots: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to list library roots: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+/// GET /api/roots/:id/browse/breadcrumb/*path - Get breadcrumb trail inside one library root
+pub async fn get_root_breadcrumb(
+    State(state): State<BrowseState>,
+    Path(params): Path<RootFolderPath>,
+) -> impl IntoResponse {
+    match root_folder_service(&state, params.id) {
+        Ok(folder_service) => breadcrumb(&folder_service, &params.path),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Folder service browsing the library root with the given id
+fn root_folder_service(
+    state: &BrowseState,
+    root_id: i64,
+) -> Result<FolderService, (StatusCode, Json<ErrorResponse>)> {
+    match state.library_root_repo.get(root_id) {
+        Ok(Some(root)) => Ok(state.folder_service.for_root(&root)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Library root {} not found", root_id),
+            }),
+        )),
+        Err(e) => {
+            tracing::error!("Failed to get library root: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to get library root: {}", e),
+                }),
+            ))
+        }
+    }
+}
+
+fn folder_contents(
+    folder_service: &FolderService,
+    folder_path: &str,
+    query: FolderQuery,
+) -> Response {
+    match folder_service.get_folder_contents(folder_path, query.page, query.per_page) {
         Ok(contents) => (StatusCode::OK, Json(contents)).into_response(),
         Err(e) => {
             tracing::error!("Failed to get folder contents: {}", e);
@@ -51,14 +157,8 @@ pub async fn get_folder_contents(
     }
 }
 
-/// GET /api/browse/breadcrumb/*path - Get breadcrumb trail
-pub async fn get_breadcrumb(
-    State(state): State<BrowseState>,
-    path: Option<Path<String>>,
-) -> impl IntoResponse {
-    let folder_path = path.map(|p| p.0).unwrap_or_default();
-
-    match state.folder_service.get_breadcrumb_trail(&folder_path) {
+fn breadcrumb(folder_service: &FolderService, folder_path: &str) -> Response {
+    match folder_service.get_breadcrumb_trail(folder_path) {
         Ok(breadcrumbs) => (StatusCode::OK, Json(breadcrumbs)).into_response(),
         Err(e) => {
             tracing::error!("Failed to get breadcrumb: {}", e);
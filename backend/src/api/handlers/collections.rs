@@ -0,0 +1,136 @@
+use crate::api::handlers::files::zip_download;
+use crate::api::routes::AppState;
+use crate::models::collection::{
+    AddCollectionItem, CollectionRequest, CollectionSummary, CollectionWithItems,
+    ReorderCollectionItems, UpdateCollectionItem,
+};
+use crate::utils::error::AppError;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+
+fn get_collection_or_404(state: &AppState, id: i64) -> Result<CollectionWithItems, AppError> {
+    state
+        .collection_repo
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Collection {} not found", id)))
+}
+
+/// GET /api/collections - Collections by name, with their item counts
+pub async fn list_collections(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CollectionSummary>>, AppError> {
+    Ok(Json(state.collection_repo.list()?))
+}
+
+/// POST /api/collections - Create an empty collection
+pub async fn create_collection(
+    State(state): State<AppState>,
+    Json(request): Json<CollectionRequest>,
+) -> Result<(StatusCode, Json<CollectionWithItems>), AppError> {
+    request.validate().map_err(AppError::ValidationError)?;
+    let name = request
+        .name
+        .as_deref()
+        .ok_or_else(|| AppError::ValidationError("name is required".to_string()))?;
+    let id = state
+        .collection_repo
+        .create(name, request.description.as_deref())?;
+
+    tracing::info!(collection_id = id, name = %name.trim(), "Created collection");
+    Ok((
+        StatusCode::CREATED,
+        Json(get_collection_or_404(&state, id)?),
+    ))
+}
+
+/// GET /api/collections/:id - A collection with its items in order
+pub async fn get_collection(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// PATCH /api/collections/:id - Rename a collection or edit its description
+pub async fn update_collection(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<CollectionRequest>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    request.validate().map_err(AppError::ValidationError)?;
+    state.collection_repo.update(id, &request)?;
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// DELETE /api/collections/:id - Remove a collection; its projects and files are untouched
+pub async fn delete_collection(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.collection_repo.delete(id)?;
+
+    tracing::info!(collection_id = id, "Removed collection");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/collections/:id/items - Add a project or a single model file
+pub async fn add_collection_item(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<AddCollectionItem>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    request.validate().map_err(AppError::ValidationError)?;
+    state.collection_repo.add_item(id, &request)?;
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// PUT /api/collections/:id/items - Reorder the items of a collection
+pub async fn reorder_collection_items(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<ReorderCollectionItems>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    state.collection_repo.reorder_items(id, &request.item_ids)?;
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// PATCH /api/collections/:id/items/:item_id - Change an item's quantity, notes or position
+pub async fn update_collection_item(
+    State(state): State<AppState>,
+    Path((id, item_id)): Path<(i64, i64)>,
+    Json(request): Json<UpdateCollectionItem>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    request.validate().map_err(AppError::ValidationError)?;
+    state.collection_repo.update_item(id, item_id, &request)?;
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// DELETE /api/collections/:id/items/:item_id - Take an item out of a collection
+pub async fn remove_collection_item(
+    State(state): State<AppState>,
+    Path((id, item_id)): Path<(i64, i64)>,
+) -> Result<Json<CollectionWithItems>, AppError> {
+    state.collection_repo.remove_item(id, item_id)?;
+    Ok(Json(get_collection_or_404(&state, id)?))
+}
+
+/// GET /api/collections/:id/download - Every file the collection refers to as one ZIP
+pub async fn download_collection_zip(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    let collection = get_collection_or_404(&state, id)?;
+    let name = collection.collection.name.clone();
+
+    zip_download(&name, |zip_path| async move {
+        state
+            .download_service
+            .create_collection_zip(&collection, &zip_path)
+            .await
+    })
+    .await
+}
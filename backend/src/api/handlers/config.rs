@@ -1,5 +1,6 @@
+use crate::api::handlers::roots::canonical_root_path;
 use crate::api::routes::AppState;
-use crate::config::{AppConfig, UpdateConfigRequest};
+use crate::config::{AppConfig, UpdateConfigRequest, DEFAULT_ROOT};
 use crate::utils::error::AppError;
 use axum::{extract::State, Json};
 use std::path::Path;
@@ -28,6 +29,11 @@ pub async fn update_config(
         request.root_path
     );
 
+    // Moving the default root drops what was indexed from it, which a running scan
+    // may be writing to
+    let scan_state = state.scan_state.lock().await;
+    let mut request = request;
+
     // Validate root_path if provided
     if let Some(ref root_path) = request.root_path {
         let path = Path::new(root_path);
@@ -45,11 +51,40 @@ pub async fn update_config(
                 root_path
             )));
         }
+
+        let roots = state.library_root_repo.list()?;
+        let default_root = roots
+            .iter()
+            .find(|root| root.name == DEFAULT_ROOT)
+            .map(|root| root.id);
+        let path = canonical_root_path(&roots, path, default_root)?;
+        if scan_state.is_scanning
+            && roots
+                .iter()
+                .any(|root| Some(root.id) == default_root && Path::new(&root.path) != path)
+        {
+            return Err(AppError::BadRequest(
+                "Cannot change the root path while a scan is running".to_string(),
+            ));
+        }
+        request.root_path = Some(path.to_string_lossy().to_string());
     }
 
     match state.config_service.update_config(&request) {
         Ok(config) => {
             info!("Configuration updated successfully");
+            // Creators named by folders follow the new level right away
+            if request.creator_folder_level.is_some() {
+                state
+                    .creator_repo
+                    .assign_creators(config.creator_folder_level)?;
+            }
+            // Releases are detected again with the new pattern and creators
+            if request.release_folder_pattern.is_some() || request.creator_folder_level.is_some() {
+                state
+                    .release_repo
+                    .assign_releases(config.release_pattern().as_ref())?;
+            }
             Ok(Json(config))
         }
         Err(e) => {
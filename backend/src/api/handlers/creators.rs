@@ -0,0 +1,35 @@
+use crate::api::routes::AppState;
+use crate::models::creator::CreatorSummary;
+use crate::utils::error::AppError;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct CreatorsQuery {
+    /// Only creators whose name contains this text
+    pub q: Option<String>,
+}
+
+/// GET /api/creators - Creators with their project counts and latest projects
+pub async fn list_creators(
+    State(state): State<AppState>,
+    Query(query): Query<CreatorsQuery>,
+) -> Result<Json<Vec<CreatorSummary>>, AppError> {
+    let q = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    Ok(Json(state.creator_repo.list(q)?))
+}
+
+/// GET /api/creators/:id - One creator; their projects are found with `/api/search?creator=`
+pub async fn get_creator(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<CreatorSummary>, AppError> {
+    let creator = state
+        .creator_repo
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Creator {} not found", id)))?;
+    Ok(Json(creator))
+}
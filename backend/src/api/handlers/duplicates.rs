@@ -0,0 +1,51 @@
+use crate::api::routes::AppState;
+use crate::models::duplicate::{DuplicateGroup, DuplicateMode};
+use crate::utils::error::AppError;
+use crate::utils::pagination::{PaginationMeta, PaginationParams};
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicatesQuery {
+    /// `exact` (default) for byte-identical files, `similar` for matching geometry
+    #[serde(default)]
+    pub mode: DuplicateMode,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicatesResponse {
+    pub data: Vec<DuplicateGroup>,
+    pub meta: PaginationMeta,
+    /// Bytes that could be reclaimed across all duplicate groups
+    pub total_wasted_bytes: i64,
+}
+
+pub async fn list_duplicates(
+    State(state): State<AppState>,
+    Query(query): Query<DuplicatesQuery>,
+) -> Result<Json<DuplicatesResponse>, AppError> {
+    let pagination = PaginationParams {
+        page: query.page.unwrap_or(1),
+        per_page: query.per_page.unwrap_or(20),
+    };
+    pagination.validate().map_err(AppError::ValidationError)?;
+
+    let report = state
+        .duplicate_service
+        .find_duplicates(query.mode, &pagination)?;
+
+    Ok(Json(DuplicatesResponse {
+        data: report.groups,
+        meta: PaginationMeta::new(
+            pagination.page,
+            pagination.per_page,
+            report.total_groups as u32,
+        ),
+        total_wasted_bytes: report.total_wasted_bytes,
+    }))
+}
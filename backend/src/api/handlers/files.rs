@@ -1,13 +1,20 @@
 use crate::api::routes::AppState;
+use crate::models::mesh_analysis::{MeshAnalysis, MeshIssue};
+use crate::models::stl_file::ModelFormat;
+use crate::utils::archive;
 use crate::utils::error::AppError;
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::{Path as AxumPath, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
+    Json,
 };
-use rusqlite::params;
-use serde::Deserialize;
+use futures::stream::{self, Stream, StreamExt};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use tokio::fs::File;
 use tokio_util::io::ReaderStream;
 
@@ -17,6 +24,44 @@ pub struct FileQueryParams {
     pub file_type: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct FileAnalysisResponse {
+    pub stl_file_id: i64,
+    pub filename: String,
+    pub format: String,
+    /// None until the file has been analyzed (3MF files are never analyzed)
+    pub analysis: Option<MeshAnalysis>,
+    pub issues: Vec<MeshIssue>,
+}
+
+/// Geometry analysis and printability issues of a model file
+pub async fn get_file_analysis(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<i64>,
+) -> Result<Json<FileAnalysisResponse>, AppError> {
+    let (filename, format): (String, String) = {
+        let conn = state.pool.get()?;
+        conn.query_row(
+            "SELECT filename, format FROM stl_files WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("File not found with id: {}", id)))?
+    };
+
+    let analysis = state.mesh_analysis_service.get_analysis(id)?;
+    let issues = state.mesh_analysis_service.get_issues(id)?;
+
+    Ok(Json(FileAnalysisResponse {
+        stl_file_id: id,
+        filename,
+        format,
+        analysis,
+        issues,
+    }))
+}
+
 /// Serve image by database ID
 pub async fn serve_image_by_id(
     State(state): State<AppState>,
@@ -147,12 +192,17 @@ pub async fn download_file(
     let (file_path, filename, content_type) = match params.file_type.as_str() {
         "stl" => {
             let mut stmt =
-                conn.prepare("SELECT file_path, filename FROM stl_files WHERE id = ?1")?;
+                conn.prepare("SELECT file_path, filename, format FROM stl_files WHERE id = ?1")?;
 
-            let (path, name): (String, String) =
-                stmt.query_row([id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            let (path, name, format): (String, String, String) =
+                stmt.query_row([id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
 
-            (path, name, "model/stl".to_string())
+            let mime_type = ModelFormat::parse(&format)
+                .map(|f| f.mime_type())
+                .unwrap_or("application/octet-stream")
+                .to_string();
+
+            (path, name, mime_type)
         }
         "image" => {
             let mut stmt =
@@ -181,25 +231,59 @@ pub async fn download_file(
 
     drop(conn);
 
-    let file = File::open(&file_path).await?;
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
+    let body = if archive::split_path(Path::new(&file_path)).is_some() {
+        // Check the member exists before committing to a response
+        let member_path = PathBuf::from(&file_path);
+        tokio::task::spawn_blocking(move || archive::member_size(&member_path))
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Archive task failed: {}", e)))??;
+        Body::from_stream(archive_member_stream(PathBuf::from(file_path)))
+    } else {
+        let file = File::open(&file_path).await?;
+        Body::from_stream(ReaderStream::new(file))
+    };
 
     Ok(Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, content_type)
-        .header(
-            header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", filename),
-        )
+        .header(header::CONTENT_DISPOSITION, attachment(&filename))
         .body(body)
         .unwrap())
 }
 
+/// Stream a file stored inside an archive, decompressing it on a blocking thread
+fn archive_member_stream(path: PathBuf) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+
+    tokio::task::spawn_blocking(move || {
+        let streamed = archive::read_file(&path, |reader, _| {
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let n = reader.read(&mut buffer)?;
+                // Stop early once the client has gone away
+                if n == 0
+                    || tx
+                        .blocking_send(Ok(Bytes::copy_from_slice(&buffer[..n])))
+                        .is_err()
+                {
+                    return Ok(());
+                }
+            }
+        });
+        if let Err(e) = streamed {
+            let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+        }
+    });
+
+    stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    })
+}
+
 pub async fn download_project_zip(
     State(state): State<AppState>,
     AxumPath(project_id): AxumPath<i64>,
-) -> Result<impl IntoResponse, AppError> {
+) -> Result<Response, AppError> {
     let conn = state.pool.get()?;
     let project_name: String = conn.query_row(
         "SELECT name FROM projects WHERE id = ?1",
@@ -208,32 +292,78 @@ pub async fn download_project_zip(
     )?;
     drop(conn);
 
-    let temp_dir = std::env::temp_dir();
-    let zip_filename = format!("{}.zip", project_name.replace("/", "_"));
-    let zip_path = temp_dir.join(&zip_filename);
-
-    state
-        .download_service
-        .create_project_zip(project_id, &zip_path)
-        .await?;
+    zip_download(&project_name, |zip_path| async move {
+        state
+            .download_service
+            .create_project_zip(project_id, &zip_path)
+            .await
+    })
+    .await
+}
 
+/// Build a ZIP in a temporary file with `create` and send it as `<name>.zip`. The
+/// file is removed once the response body is dropped.
+pub(crate) async fn zip_download<F, Fut>(name: &str, create: F) -> Result<Response, AppError>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = Result<(), AppError>>,
+{
+    let zip_path = tempfile::Builder::new()
+        .prefix("glyptotheka-")
+        .suffix(".zip")
+        .tempfile()?
+        .into_temp_path();
+    create(zip_path.to_path_buf()).await?;
+
+    // The stream owns the temporary file, so concurrent downloads never share one
     let file = File::open(&zip_path).await?;
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
-    let zip_path_clone = zip_path.clone();
-    tokio::spawn(async move {
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-        let _ = tokio::fs::remove_file(zip_path_clone).await;
-    });
+    let body = Body::from_stream(ReaderStream::new(file).map(move |chunk| {
+        let _ = &zip_path;
+        chunk
+    }));
 
-    Ok(Response::builder()
+    Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "application/zip")
         .header(
             header::CONTENT_DISPOSITION,
-            format!("attachment; filename=\"{}\"", zip_filename),
+            attachment(&format!("{}.zip", name)),
         )
         .body(body)
-        .unwrap())
+        .map_err(|e| AppError::InternalServer(format!("Failed to build response: {}", e)))
+}
+
+/// Characters left as they are in an RFC 5987 `filename*` value
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+/// `Content-Disposition` of a download (RFC 6266): the UTF-8 filename, with an ASCII
+/// fallback for old clients
+pub(crate) fn attachment(filename: &str) -> HeaderValue {
+    let fallback: String = filename
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' | '/' => '_',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '_',
+        })
+        .collect();
+    let value = format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback,
+        utf8_percent_encode(filename, ATTR_CHAR)
+    );
+    // Only printable ASCII is left, which is always a valid header value
+    HeaderValue::from_str(&value).unwrap_or_else(|_| HeaderValue::from_static("attachment"))
 }
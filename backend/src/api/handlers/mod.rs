@@ -1,6 +1,11 @@
+pub mod collections;
 pub mod config;
+pub mod creators;
+pub mod duplicates;
 pub mod files;
 pub mod projects;
+pub mod releases;
+pub mod roots;
 pub mod scan;
 pub mod search;
 pub mod tags;
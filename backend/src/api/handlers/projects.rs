@@ -1,6 +1,7 @@
+use crate::api::handlers::tags::write_back_sidecar;
 use crate::api::routes::AppState;
 use crate::models::image_file::ImageFile;
-use crate::models::project::{Project, ProjectWithRelations, StlCategory};
+use crate::models::project::{Project, ProjectWithRelations, StlCategory, UpdateProject};
 use crate::models::stl_file::StlFile;
 use crate::utils::error::AppError;
 use axum::{
@@ -48,6 +49,34 @@ pub async fn get_project(
     Ok(Json(project))
 }
 
+/// PATCH /api/projects/:id - Edit a project's description and metadata
+pub async fn update_project(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(update): Json<UpdateProject>,
+) -> Result<Json<ProjectWithRelations>, AppError> {
+    update.validate().map_err(AppError::ValidationError)?;
+    state.project_repo.update_details(id, &update)?;
+    if let Some(ref creator) = update.creator {
+        let creator = Some(creator.trim()).filter(|c| !c.is_empty());
+        state.creator_repo.set_override(id, creator)?;
+    }
+    // Authors and creators set by hand pass on to subfolders
+    if update.creator.is_some() || update.author.is_some() {
+        let config = state.config_service.get_config()?;
+        state
+            .creator_repo
+            .assign_creators_under(id, config.creator_folder_level)?;
+    }
+    write_back_sidecar(&state, id);
+
+    let project = state
+        .project_repo
+        .get_with_relations(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Project {} not found", id)))?;
+    Ok(Json(project))
+}
+
 pub async fn get_project_children(
     State(state): State<AppState>,
     Path(id): Path<i64>,
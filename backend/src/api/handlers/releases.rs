@@ -0,0 +1,177 @@
+use crate::api::handlers::files::zip_download;
+use crate::api::routes::AppState;
+use crate::models::release::{
+    AddReleaseProject, CreateRelease, ReleaseSummary, ReleaseWithProjects, SetReleaseProjects,
+    UpdateRelease,
+};
+use crate::utils::error::AppError;
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio_util::io::ReaderStream;
+
+#[derive(Debug, Deserialize)]
+pub struct ReleasesQuery {
+    /// Only releases whose name contains this text
+    pub q: Option<String>,
+    pub creator_id: Option<i64>,
+}
+
+fn get_release_or_404(state: &AppState, id: i64) -> Result<ReleaseWithProjects, AppError> {
+    state
+        .release_repo
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Release {} not found", id)))
+}
+
+/// GET /api/releases - Releases, newest first
+pub async fn list_releases(
+    State(state): State<AppState>,
+    Query(query): Query<ReleasesQuery>,
+) -> Result<Json<Vec<ReleaseSummary>>, AppError> {
+    let q = query.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    Ok(Json(state.release_repo.list(query.creator_id, q)?))
+}
+
+/// POST /api/releases - Create a release by hand
+pub async fn create_release(
+    State(state): State<AppState>,
+    Json(request): Json<CreateRelease>,
+) -> Result<(StatusCode, Json<ReleaseWithProjects>), AppError> {
+    request.validate().map_err(AppError::ValidationError)?;
+    let id = state.release_repo.create(&request)?;
+
+    tracing::info!(release_id = id, name = %request.name.trim(), "Created release");
+    Ok((StatusCode::CREATED, Json(get_release_or_404(&state, id)?)))
+}
+
+/// GET /api/releases/:id - A release with its projects in order
+pub async fn get_release(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<ReleaseWithProjects>, AppError> {
+    Ok(Json(get_release_or_404(&state, id)?))
+}
+
+/// PATCH /api/releases/:id - Edit a release's name, description, creator or date
+pub async fn update_release(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(update): Json<UpdateRelease>,
+) -> Result<Json<ReleaseWithProjects>, AppError> {
+    update.validate().map_err(AppError::ValidationError)?;
+    state.release_repo.update(id, &update)?;
+    Ok(Json(get_release_or_404(&state, id)?))
+}
+
+/// DELETE /api/releases/:id - Remove a release; its projects are untouched
+pub async fn delete_release(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    state.release_repo.delete(id)?;
+    if let Err(e) = state.composite_preview_service.delete_release_cover(id) {
+        tracing::warn!("Failed to delete cover of release {}: {}", id, e);
+    }
+
+    tracing::info!(release_id = id, "Removed release");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /api/releases/:id/projects - Replace the projects of a release, in order
+pub async fn set_release_projects(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<SetReleaseProjects>,
+) -> Result<Json<ReleaseWithProjects>, AppError> {
+    state.release_repo.set_projects(id, &request.project_ids)?;
+    Ok(Json(get_release_or_404(&state, id)?))
+}
+
+/// POST /api/releases/:id/projects - Add a project, or move it to a new position
+pub async fn add_release_project(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    Json(request): Json<AddReleaseProject>,
+) -> Result<Json<ReleaseWithProjects>, AppError> {
+    state
+        .release_repo
+        .add_project(id, request.project_id, request.position)?;
+    Ok(Json(get_release_or_404(&state, id)?))
+}
+
+/// DELETE /api/releases/:id/projects/:project_id - Take a project out of a release
+pub async fn remove_release_project(
+    State(state): State<AppState>,
+    Path((id, project_id)): Path<(i64, i64)>,
+) -> Result<Json<ReleaseWithProjects>, AppError> {
+    state.release_repo.remove_project(id, project_id)?;
+    Ok(Json(get_release_or_404(&state, id)?))
+}
+
+/// GET /api/releases/:id/cover - Composite of its projects' images, generated when
+/// first requested after the projects changed
+pub async fn get_release_cover(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let cover_path = match state.release_repo.cover_path(id)? {
+        Some(path) if std::path::Path::new(&path).exists() => PathBuf::from(path),
+        _ => {
+            let images = state.release_repo.cover_images(id)?;
+            if images.is_empty() {
+                return Err(AppError::NotFound(format!(
+                    "Release {} has no images for a cover",
+                    id
+                )));
+            }
+            let composite_service = state.composite_preview_service.clone();
+            let path = tokio::task::spawn_blocking(move || {
+                composite_service.generate_release_cover(id, &images)
+            })
+            .await
+            .map_err(|e| AppError::InternalServer(format!("Cover task failed: {}", e)))??;
+            state
+                .release_repo
+                .set_cover_path(id, &path.to_string_lossy())?;
+            path
+        }
+    };
+
+    let file = File::open(&cover_path)
+        .await
+        .map_err(|e| AppError::InternalServer(format!("Failed to open cover file: {}", e)))?;
+    let body = Body::from_stream(ReaderStream::new(file));
+
+    // Covers change with the release's projects
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/png")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(body)
+        .unwrap())
+}
+
+/// GET /api/releases/:id/download - All projects of a release as one ZIP, a folder
+/// per project
+pub async fn download_release_zip(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    let release = get_release_or_404(&state, id)?;
+
+    zip_download(&release.release.name, |zip_path| async move {
+        state
+            .download_service
+            .create_release_zip(id, &zip_path)
+            .await
+    })
+    .await
+}
@@ -0,0 +1,98 @@
+use crate::api::routes::AppState;
+use crate::models::library_root::{CreateLibraryRoot, LibraryRoot};
+use crate::utils::error::AppError;
+use axum::{
+    extract::{Path as AxumPath, State},
+    http::StatusCode,
+    Json,
+};
+use std::path::{Path, PathBuf};
+
+pub async fn list_roots(State(state): State<AppState>) -> Result<Json<Vec<LibraryRoot>>, AppError> {
+    Ok(Json(state.library_root_repo.list()?))
+}
+
+pub async fn create_root(
+    State(state): State<AppState>,
+    Json(request): Json<CreateLibraryRoot>,
+) -> Result<(StatusCode, Json<LibraryRoot>), AppError> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::ValidationError(
+            "Library root name cannot be empty".to_string(),
+        ));
+    }
+
+    let path = Path::new(&request.path);
+    if !path.is_dir() {
+        return Err(AppError::ValidationError(format!(
+            "Path is not an existing directory: {}",
+            request.path
+        )));
+    }
+
+    let roots = state.library_root_repo.list()?;
+    if let Some(root) = roots.iter().find(|r| r.name.eq_ignore_ascii_case(name)) {
+        return Err(AppError::ValidationError(format!(
+            "A library root named '{}' already exists",
+            root.name
+        )));
+    }
+    let path = canonical_root_path(&roots, path, None)?;
+
+    let id = state.library_root_repo.create(&CreateLibraryRoot {
+        name: name.to_string(),
+        path: path.to_string_lossy().to_string(),
+    })?;
+    let root = state
+        .library_root_repo
+        .get(id)?
+        .ok_or_else(|| AppError::InternalServer("Library root was not created".to_string()))?;
+
+    tracing::info!(root = %root.name, path = %root.path, "Added library root");
+    Ok((StatusCode::CREATED, Json(root)))
+}
+
+/// The canonical form of a new path for a library root, which must not overlap any of
+/// `roots` other than the root being moved (`except`)
+pub(crate) fn canonical_root_path(
+    roots: &[LibraryRoot],
+    path: &Path,
+    except: Option<i64>,
+) -> Result<PathBuf, AppError> {
+    let path = path.canonicalize()?;
+
+    // Nested roots would index the same folders twice
+    if let Some(root) = roots.iter().find(|r| {
+        Some(r.id) != except && (path.starts_with(&r.path) || Path::new(&r.path).starts_with(&path))
+    }) {
+        return Err(AppError::ValidationError(format!(
+            "{} overlaps library root '{}' ({})",
+            path.display(),
+            root.name,
+            root.path
+        )));
+    }
+
+    Ok(path)
+}
+
+/// Remove a library root and everything indexed from it; the files on disk are untouched
+pub async fn delete_root(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<i64>,
+) -> Result<StatusCode, AppError> {
+    let scan_state = state.scan_state.lock().await;
+    if scan_state.is_scanning {
+        return Err(AppError::BadRequest(
+            "Cannot remove a library root while a scan is running".to_string(),
+        ));
+    }
+
+    if !state.library_root_repo.delete(id)? {
+        return Err(AppError::NotFound(format!("Library root {} not found", id)));
+    }
+
+    tracing::info!(root_id = id, "Removed library root");
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,12 +1,37 @@
 use crate::api::routes::AppState;
+use crate::db::repositories::scan_session_repo::ScanSessionRepository;
+use crate::models::scan_progress::ScanProgress;
+use crate::models::scan_session::{
+    ScanCounts, ScanMode, ScanSession, ScanSessionDetail, ScanSessionStatus, ScanTrigger,
+};
+use crate::services::rescan::RescanResult;
+use crate::services::scan_progress::ScanProgressTracker;
 use crate::utils::error::AppError;
-use axum::{extract::State, Json};
+use crate::utils::fs::resolve_library_dir;
+use crate::utils::pagination::{PaginatedResponse, PaginationMeta, PaginationParams};
+use axum::{
+    extract::{Path as AxumPath, Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::MutexGuard;
+
+/// How often the event stream checks for new progress
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScanRequest {
     pub force: Option<bool>,
     pub clean: Option<bool>,
+    /// Folder to scan, relative to the library root; the whole library when omitted
+    pub path: Option<String>,
+    /// Library root to scan; every root when omitted
+    pub root_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +46,10 @@ pub struct ScanStatus {
     pub files_updated: Option<usize>,
     pub files_removed: Option<usize>,
     pub errors: Option<Vec<String>>,
+    /// Whether the finished scan was cancelled before completing
+    pub cancelled: Option<bool>,
+    /// Live progress while a scan is running
+    pub progress: Option<ScanProgress>,
 }
 
 pub struct ScanState {
@@ -31,16 +60,126 @@ pub struct ScanState {
 #[derive(Debug, Clone)]
 pub enum ScanResult {
     Initial(crate::services::scanner::ScanResult),
-    Rescan(crate::services::rescan::RescanResult),
+    Rescan(RescanResult),
+}
+
+impl ScanResult {
+    pub fn cancelled(&self) -> bool {
+        match self {
+            ScanResult::Initial(r) => r.cancelled,
+            ScanResult::Rescan(r) => r.cancelled,
+        }
+    }
+
+    pub fn errors(&self) -> &[String] {
+        match self {
+            ScanResult::Initial(r) => &r.errors,
+            ScanResult::Rescan(r) => &r.errors,
+        }
+    }
+
+    fn errors_mut(&mut self) -> &mut Vec<String> {
+        match self {
+            ScanResult::Initial(r) => &mut r.errors,
+            ScanResult::Rescan(r) => &mut r.errors,
+        }
+    }
+
+    /// Combine the results of scanning several library roots
+    fn merge(self, other: ScanResult) -> ScanResult {
+        match (self, other) {
+            (ScanResult::Initial(mut a), ScanResult::Initial(b)) => {
+                a.projects_found += b.projects_found;
+                a.files_processed += b.files_processed;
+                a.stl_previews_generated += b.stl_previews_generated;
+                a.stl_previews_queued += b.stl_previews_queued;
+                a.errors.extend(b.errors);
+                a.cancelled |= b.cancelled;
+                ScanResult::Initial(a)
+            }
+            (a, b) => {
+                let (mut a, b) = (a.into_rescan(), b.into_rescan());
+                a.projects_found += b.projects_found;
+                a.projects_added += b.projects_added;
+                a.projects_updated += b.projects_updated;
+                a.projects_removed += b.projects_removed;
+                a.files_processed += b.files_processed;
+                a.files_added += b.files_added;
+                a.files_updated += b.files_updated;
+                a.files_removed += b.files_removed;
+                a.stl_previews_regenerated += b.stl_previews_regenerated;
+                a.stl_previews_cached += b.stl_previews_cached;
+                a.errors.extend(b.errors);
+                a.cancelled |= b.cancelled;
+                ScanResult::Rescan(a)
+            }
+        }
+    }
+
+    /// A full scan expressed as a rescan that added everything it found
+    fn into_rescan(self) -> RescanResult {
+        match self {
+            ScanResult::Initial(r) => RescanResult {
+                projects_found: r.projects_found,
+                projects_added: r.projects_found,
+                files_processed: r.files_processed,
+                files_added: r.files_processed,
+                stl_previews_regenerated: r.stl_previews_generated,
+                errors: r.errors,
+                cancelled: r.cancelled,
+                ..RescanResult::default()
+            },
+            ScanResult::Rescan(r) => r,
+        }
+    }
+
+    pub fn counts(&self) -> ScanCounts {
+        match self {
+            // Everything a full scan finds counts as added
+            ScanResult::Initial(r) => ScanCounts {
+                projects_found: r.projects_found,
+                projects_added: r.projects_found,
+                files_processed: r.files_processed,
+                files_added: r.files_processed,
+                stl_previews_generated: r.stl_previews_generated,
+                stl_previews_queued: r.stl_previews_queued,
+                ..ScanCounts::default()
+            },
+            ScanResult::Rescan(r) => ScanCounts {
+                projects_found: r.projects_found,
+                projects_added: r.projects_added,
+                projects_updated: r.projects_updated,
+                projects_removed: r.projects_removed,
+                files_processed: r.files_processed,
+                files_added: r.files_added,
+                files_updated: r.files_updated,
+                files_removed: r.files_removed,
+                stl_previews_generated: r.stl_previews_regenerated,
+                stl_previews_queued: 0,
+                stl_previews_cached: r.stl_previews_cached,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScanHistoryQuery {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
 }
 
 pub async fn start_scan(
     State(state): State<AppState>,
     Json(req): Json<ScanRequest>,
 ) -> Result<Json<ScanStatus>, AppError> {
-    tracing::info!("Scan request received: force={:?}, clean={:?}", req.force, req.clean);
+    tracing::info!(
+        "Scan request received: force={:?}, clean={:?}, path={:?}",
+        req.force,
+        req.clean,
+        req.path
+    );
     
-    let mut scan_state = state.scan_state.lock().await;
+    let scan_state = state.scan_state.lock().await;
 
     if scan_state.is_scanning {
         tracing::info!("Scan already in progress, returning current status");
@@ -55,75 +194,99 @@ pub async fn start_scan(
             files_updated: None,
             files_removed: None,
             errors: None,
+            cancelled: None,
+            progress: Some(state.scan_progress.snapshot()),
         }));
     }
 
-    tracing::info!("Getting config...");
-    let config = state.config_service.get_config().map_err(|e| {
-        tracing::error!("Failed to get config: {:?}", e);
-        e
-    })?;
-    
-    let root_path = config
-        .root_path
-        .ok_or_else(|| {
-            tracing::error!("Root path not configured");
-            AppError::ValidationError("Root path not configured".to_string())
-        })?;
-    
-    tracing::info!("Root path: {}", root_path);
+    let roots = match req.root_id {
+        Some(id) => vec![state
+            .library_root_repo
+            .get(id)?
+            .ok_or_else(|| AppError::NotFound(format!("Library root {} not found", id)))?],
+        None => state.library_root_repo.list()?,
+    };
+    if roots.is_empty() {
+        tracing::error!("Root path not configured");
+        return Err(AppError::ValidationError(
+            "Root path not configured".to_string(),
+        ));
+    }
 
     let force = req.force.unwrap_or(false);
     let clean = req.clean.unwrap_or(false);
-    let has_been_scanned = config.last_scan_at.is_some();
-    
-    tracing::info!("Scan settings: force={}, clean={}, has_been_scanned={}", force, clean, has_been_scanned);
 
-    // If clean is requested, clear all database entries and cache before scanning
-    if clean {
-        tracing::info!("Clean rescan requested - clearing all data");
-        tracing::info!("Clearing project repository...");
-        if let Err(e) = state.project_repo.clear_all() {
-            tracing::error!("Failed to clear project repository: {:?}", e);
-            return Err(e);
+    tracing::info!(
+        "Scan settings: force={}, clean={}, roots={}",
+        force,
+        clean,
+        roots.len()
+    );
+
+    if let Some(path) = req.path.as_deref() {
+        if clean {
+            return Err(AppError::ValidationError(format!(
+                "A clean scan always covers the whole library, not {}",
+                path
+            )));
         }
-        tracing::info!("Project repository cleared successfully");
-        
-        tracing::info!("Clearing image cache...");
-        if let Err(e) = state.image_cache_service.clear_all() {
-            tracing::warn!("Failed to clear image cache: {}", e);
+        if roots.len() > 1 {
+            return Err(AppError::ValidationError(format!(
+                "Scanning {} requires a root_id when several library roots are configured",
+                path
+            )));
         }
-        tracing::info!("Image cache cleared");
     }
 
-    scan_state.is_scanning = true;
-    scan_state.result = None;
-    drop(scan_state);
+    // If clean is requested, clear the database entries (and the cache for the whole library) before scanning
+    if clean {
+        if let Some(root_id) = req.root_id {
+            tracing::info!("Clean rescan of library root {} requested", root_id);
+            state.project_repo.clear_root(root_id)?;
+        } else {
+            tracing::info!("Clean rescan requested - clearing all data");
+            tracing::info!("Clearing project repository...");
+            if let Err(e) = state.project_repo.clear_all() {
+                tracing::error!("Failed to clear project repository: {:?}", e);
+                return Err(e);
+            }
+            tracing::info!("Project repository cleared successfully");
 
-    let scanner = state.scanner_service.clone();
-    let rescan_service = state.rescan_service.clone();
-    let scan_state_arc = state.scan_state.clone();
-    let config_service = state.config_service.clone();
+            tracing::info!("Clearing image cache...");
+            if let Err(e) = state.image_cache_service.clear_all() {
+                tracing::warn!("Failed to clear image cache: {}", e);
+            }
+            tracing::info!("Image cache cleared");
+        }
+    }
 
-    tokio::spawn(async move {
-        // If clean was requested, always do a full scan
-        let result = if clean || force || !has_been_scanned {
-            // Initial scan or forced full rescan
-            scanner.scan(&root_path).map(ScanResult::Initial)
+    let mut jobs = Vec::with_capacity(roots.len());
+    for root in roots {
+        let scan_path = match req.path.as_deref() {
+            Some(path) => resolve_library_dir(Path::new(&root.path), path)?,
+            None => PathBuf::from(&root.path),
+        };
+        let mode = if clean {
+            ScanMode::Clean
+        } else if force || root.last_scan_at.is_none() {
+            ScanMode::Full
         } else {
-            // Incremental rescan
-            rescan_service.rescan(&root_path).map(ScanResult::Rescan)
+            ScanMode::Incremental
         };
 
-        let mut state = scan_state_arc.lock().await;
-        state.is_scanning = false;
-
-        if result.is_ok() {
-            let _ = config_service.update_last_scan();
-        }
+        tracing::info!("Root path: {} ({:?})", root.path, mode);
+        jobs.push(ScanJob {
+            root_id: root.id,
+            partial: scan_path != Path::new(&root.path),
+            root_path: root.path,
+            scan_path,
+            mode,
+            trigger: ScanTrigger::Manual,
+            changed: Vec::new(),
+        });
+    }
 
-        state.result = result.ok();
-    });
+    spawn_scan(&state, scan_state, jobs);
 
     Ok(Json(ScanStatus {
         is_scanning: true,
@@ -136,11 +299,179 @@ pub async fn start_scan(
         files_updated: None,
         files_removed: None,
         errors: None,
+        cancelled: None,
+        progress: Some(state.scan_progress.snapshot()),
+    }))
+}
+
+/// Start an incremental rescan of every library root for the rescan schedule.
+/// Returns `false` without scanning when a scan is already running or no root is configured.
+pub async fn start_scheduled_rescan(state: &AppState) -> Result<bool, AppError> {
+    let scan_state = state.scan_state.lock().await;
+    if scan_state.is_scanning {
+        return Ok(false);
+    }
+
+    let jobs: Vec<ScanJob> = state
+        .library_root_repo
+        .list()?
+        .into_iter()
+        .map(|root| ScanJob {
+            root_id: root.id,
+            scan_path: PathBuf::from(&root.path),
+            root_path: root.path,
+            partial: false,
+            mode: ScanMode::Incremental,
+            trigger: ScanTrigger::Scheduled,
+            changed: Vec::new(),
+        })
+        .collect();
+    if jobs.is_empty() {
+        return Ok(false);
+    }
+
+    spawn_scan(state, scan_state, jobs);
+    Ok(true)
+}
+
+/// Apply filesystem changes seen by the watcher as an incremental rescan of the
+/// library roots they are under. Returns `false` without scanning when a scan is
+/// already running, so that the caller can hold on to the changes and try again.
+pub async fn start_watched_rescan(state: &AppState, changed: &[PathBuf]) -> Result<bool, AppError> {
+    let scan_state = state.scan_state.lock().await;
+    if scan_state.is_scanning {
+        return Ok(false);
+    }
+
+    let roots = state.library_root_repo.list()?;
+    let mut jobs: Vec<ScanJob> = Vec::new();
+    for path in changed {
+        let Some(root) = roots.iter().find(|root| path.starts_with(&root.path)) else {
+            continue;
+        };
+        match jobs.iter_mut().find(|job| job.root_id == root.id) {
+            Some(job) => {
+                job.scan_path = common_ancestor(&job.scan_path, path);
+                job.changed.push(path.clone());
+            }
+            None => jobs.push(ScanJob {
+                root_id: root.id,
+                root_path: root.path.clone(),
+                scan_path: path.parent().unwrap_or(path).to_path_buf(),
+                partial: true,
+                mode: ScanMode::Incremental,
+                trigger: ScanTrigger::Watcher,
+                changed: vec![path.clone()],
+            }),
+        }
+    }
+    for job in &mut jobs {
+        if !job.scan_path.starts_with(&job.root_path) {
+            job.scan_path = PathBuf::from(&job.root_path);
+        }
+    }
+    if jobs.is_empty() {
+        return Ok(true);
+    }
+
+    spawn_scan(state, scan_state, jobs);
+    Ok(true)
+}
+
+fn common_ancestor(a: &Path, b: &Path) -> PathBuf {
+    a.components()
+        .zip(b.components())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a)
+        .collect()
+}
+
+/// Ask the running scan to stop after the folder it is working on
+pub async fn cancel_scan(State(state): State<AppState>) -> Result<Json<ScanStatus>, AppError> {
+    let scan_state = state.scan_state.lock().await;
+
+    if !scan_state.is_scanning {
+        return Err(AppError::BadRequest("No scan is running".to_string()));
+    }
+
+    tracing::info!("Cancelling running scan");
+    state.scan_cancellation.cancel();
+
+    Ok(Json(current_status(&scan_state, &state.scan_progress)))
+}
+
+pub async fn list_scan_history(
+    State(state): State<AppState>,
+    Query(query): Query<ScanHistoryQuery>,
+) -> Result<Json<PaginatedResponse<ScanSession>>, AppError> {
+    let pagination = PaginationParams {
+        page: query.page.unwrap_or(1),
+        per_page: query.per_page.unwrap_or(20),
+    };
+    pagination.validate().map_err(AppError::ValidationError)?;
+
+    let (sessions, total) = state.scan_session_repo.list(&pagination)?;
+
+    Ok(Json(PaginatedResponse {
+        data: sessions,
+        meta: PaginationMeta::new(pagination.page, pagination.per_page, total as u32),
     }))
 }
 
+pub async fn get_scan_session(
+    State(state): State<AppState>,
+    AxumPath(id): AxumPath<i64>,
+) -> Result<Json<ScanSessionDetail>, AppError> {
+    let session = state
+        .scan_session_repo
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("Scan session {} not found", id)))?;
+
+    Ok(Json(session))
+}
+
 pub async fn get_scan_status(State(state): State<AppState>) -> Result<Json<ScanStatus>, AppError> {
     let scan_state = state.scan_state.lock().await;
+    Ok(Json(current_status(&scan_state, &state.scan_progress)))
+}
+
+/// Stream scan status as Server-Sent Events: `progress` events while the scan runs,
+/// then a single `complete` event with the result, after which the stream ends
+pub async fn scan_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = stream::unfold(Some((state, String::new())), |cursor| async move {
+        let (state, mut last) = cursor?;
+        loop {
+            let status = {
+                let scan_state = state.scan_state.lock().await;
+                current_status(&scan_state, &state.scan_progress)
+            };
+            let data = serde_json::to_string(&status).unwrap_or_default();
+
+            if !status.is_scanning {
+                let event = Event::default().event("complete").data(data);
+                return Some((Ok(event), None));
+            }
+            if data != last {
+                last = data.clone();
+                let event = Event::default().event("progress").data(data);
+                return Some((Ok(event), Some((state, last))));
+            }
+
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn current_status(scan_state: &ScanState, progress: &ScanProgressTracker) -> ScanStatus {
+    let progress = if scan_state.is_scanning {
+        Some(progress.snapshot())
+    } else {
+        None
+    };
 
     let status = if let Some(ref result) = scan_state.result {
         match result {
@@ -159,6 +490,8 @@ pub async fn get_scan_status(State(state): State<AppState>) -> Result<Json<ScanS
                 } else {
                     Some(r.errors.clone())
                 },
+                cancelled: Some(r.cancelled),
+                progress: None,
             },
             ScanResult::Rescan(r) => ScanStatus {
                 is_scanning: scan_state.is_scanning,
@@ -175,6 +508,8 @@ pub async fn get_scan_status(State(state): State<AppState>) -> Result<Json<ScanS
                 } else {
                     Some(r.errors.clone())
                 },
+                cancelled: Some(r.cancelled),
+                progress: None,
             },
         }
     } else {
@@ -189,8 +524,143 @@ pub async fn get_scan_status(State(state): State<AppState>) -> Result<Json<ScanS
             files_updated: None,
             files_removed: None,
             errors: None,
+            cancelled: None,
+            progress: None,
         }
     };
 
-    Ok(Json(status))
+    ScanStatus { progress, ..status }
+}
+
+/// The scan of one library root
+struct ScanJob {
+    root_id: i64,
+    root_path: String,
+    scan_path: PathBuf,
+    /// Whether only a subtree of the root is scanned
+    partial: bool,
+    mode: ScanMode,
+    trigger: ScanTrigger,
+    /// Paths reported by the watcher; only these are applied instead of walking `scan_path`
+    changed: Vec<PathBuf>,
+}
+
+/// Mark a scan as running and scan the roots one after another in the background,
+/// recording each of them in the scan history
+fn spawn_scan(state: &AppState, mut scan_state: MutexGuard<'_, ScanState>, jobs: Vec<ScanJob>) {
+    scan_state.is_scanning = true;
+    scan_state.result = None;
+    state.scan_progress.start();
+    state.scan_cancellation.reset();
+    drop(scan_state);
+
+    let scanner = state.scanner_service.clone();
+    let rescan_service = state.rescan_service.clone();
+    let scan_state_arc = state.scan_state.clone();
+    let config_service = state.config_service.clone();
+    let scan_progress = state.scan_progress.clone();
+    let scan_cancellation = state.scan_cancellation.clone();
+    let scan_session_repo = state.scan_session_repo.clone();
+    let library_root_repo = state.library_root_repo.clone();
+
+    tokio::spawn(async move {
+        let mut combined: Option<ScanResult> = None;
+        let mut failures = Vec::new();
+
+        for job in jobs {
+            if scan_cancellation.is_cancelled() {
+                break;
+            }
+
+            let session_id = scan_session_repo
+                .start(
+                    &job.root_path,
+                    job.partial.then(|| job.scan_path.to_str().unwrap_or("")),
+                    job.mode,
+                    job.trigger,
+                )
+                .map_err(|e| tracing::warn!("Failed to record scan of {}: {}", job.root_path, e))
+                .ok();
+
+            // If clean was requested, always do a full scan
+            let result = if job.mode != ScanMode::Incremental {
+                // Initial scan or forced full rescan
+                scanner
+                    .scan_subtree(&job.root_path, &job.scan_path)
+                    .map(ScanResult::Initial)
+            } else if !job.changed.is_empty() {
+                rescan_service
+                    .rescan_paths(&job.root_path, &job.changed)
+                    .map(ScanResult::Rescan)
+            } else {
+                // Incremental rescan
+                rescan_service
+                    .rescan_subtree(&job.root_path, &job.scan_path)
+                    .map(ScanResult::Rescan)
+            };
+
+            if let Some(session_id) = session_id {
+                if let Err(e) = record_session(&scan_session_repo, session_id, &result) {
+                    tracing::warn!("Failed to record scan session {}: {}", session_id, e);
+                }
+            }
+
+            match result {
+                Ok(result) => {
+                    // Only a completed scan of a whole root counts as its last full scan
+                    let cancelled = result.cancelled();
+                    if !cancelled && !job.partial {
+                        let _ = library_root_repo.update_last_scan(job.root_id);
+                        let _ = config_service.update_last_scan();
+                    }
+
+                    combined = Some(match combined {
+                        Some(previous) => previous.merge(result),
+                        None => result,
+                    });
+                    if cancelled {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Scan of {} failed: {}", job.root_path, e);
+                    failures.push(format!("{}: {}", job.root_path, e));
+                }
+            }
+        }
+
+        // Roots that could not be scanned at all are reported with the others
+        if let Some(ref mut result) = combined {
+            result.errors_mut().extend(failures);
+        }
+
+        let mut state = scan_state_arc.lock().await;
+        state.is_scanning = false;
+        scan_progress.finish();
+        state.result = combined;
+    });
+}
+
+/// Store how a scan ended in `scan_sessions`
+fn record_session(
+    repo: &ScanSessionRepository,
+    session_id: i64,
+    result: &Result<ScanResult, AppError>,
+) -> Result<(), AppError> {
+    match result {
+        Ok(r) => {
+            let status = if r.cancelled() {
+                ScanSessionStatus::Cancelled
+            } else {
+                ScanSessionStatus::Completed
+            };
+            repo.finish(session_id, status, &r.counts(), r.errors())
+        }
+        Err(e) => repo.finish(
+            session_id,
+            ScanSessionStatus::Failed,
+            &ScanCounts::default(),
+            &[e.to_string()],
+        ),
+    }
 }
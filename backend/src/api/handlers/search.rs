@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::api::routes::AppState;
 use crate::services::search::SearchParams;
+use crate::utils::error::AppError;
 
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
@@ -18,6 +19,14 @@ pub struct SearchQuery {
     pub per_page: usize,
     #[serde(default = "default_leaf_only")]
     pub leaf_only: bool,
+    pub max_x: Option<f64>,
+    pub max_y: Option<f64>,
+    pub max_z: Option<f64>,
+    pub min_z: Option<f64>,
+    pub printer: Option<String>,
+    pub has_issues: Option<bool>,
+    pub root_id: Option<i64>,
+    pub creator: Option<String>,
 }
 
 fn default_page() -> usize {
@@ -68,13 +77,22 @@ pub async fn search_projects(
         page: query.page,
         per_page: query.per_page.min(100),
         leaf_only: query.leaf_only,
+        max_x: query.max_x,
+        max_y: query.max_y,
+        max_z: query.max_z,
+        min_z: query.min_z,
+        printer: query.printer.filter(|s| !s.trim().is_empty()),
+        has_issues: query.has_issues,
+        root_id: query.root_id,
+        creator: query.creator.filter(|s| !s.trim().is_empty()),
     };
 
     let result = state.search_service.search(&params).map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({ "error": e.to_string() })),
-        )
+        let status = match e {
+            AppError::ValidationError(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({ "error": e.to_string() })))
     })?;
 
     let data: Vec<serde_json::Value> = result
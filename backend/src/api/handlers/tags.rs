@@ -80,6 +80,18 @@ pub struct RemoveTagQuery {
     pub tag_name: String,
 }
 
+/// Mirror the project's tags and metadata into its sidecar file when write-back is
+/// enabled. The change itself is kept if the file cannot be written.
+pub(crate) fn write_back_sidecar(state: &AppState, project_id: i64) {
+    if let Err(e) = state.sidecar_service.write_back(project_id) {
+        tracing::warn!(
+            "Failed to write sidecar metadata for project {}: {}",
+            project_id,
+            e
+        );
+    }
+}
+
 // T095: POST /api/projects/:id/tags - Add tag to project
 pub async fn add_tag_to_project(
     State(state): State<AppState>,
@@ -116,6 +128,8 @@ pub async fn add_tag_to_project(
             )
         })?;
 
+    write_back_sidecar(&state, project_id);
+
     // Return updated project with tags
     let tags = state.tag_repo.get_project_tags(project_id).map_err(|e| {
         (
@@ -173,6 +187,8 @@ pub async fn remove_tag_from_project(
             )
         })?;
 
+    write_back_sidecar(&state, project_id);
+
     // Return updated project with tags
     let tags = state.tag_repo.get_project_tags(project_id).map_err(|e| {
         (
@@ -2,3 +2,5 @@ pub mod browse_routes;
 pub mod handlers;
 pub mod middleware;
 pub mod routes;
+pub mod scheduler;
+pub mod watcher;
@@ -1,18 +1,31 @@
+use crate::api::browse_routes::BrowseState;
 use crate::api::handlers::scan::ScanState;
-use crate::api::handlers::{config, files, projects, scan, search, tags};
+use crate::api::handlers::{
+    collections, config, creators, duplicates, files, projects, releases, roots, scan, search, tags,
+};
 use crate::config::ConfigService;
 use crate::db::connection::DbPool;
+use crate::db::repositories::collection_repo::CollectionRepository;
+use crate::db::repositories::creator_repo::CreatorRepository;
 use crate::db::repositories::file_repo::FileRepository;
+use crate::db::repositories::library_root_repo::LibraryRootRepository;
 use crate::db::repositories::project_repo::ProjectRepository;
+use crate::db::repositories::release_repo::ReleaseRepository;
+use crate::db::repositories::scan_session_repo::ScanSessionRepository;
 use crate::db::repositories::tag_repo::TagRepository;
+use crate::services::composite_preview::CompositePreviewService;
 use crate::services::download::DownloadService;
+use crate::services::duplicates::DuplicateService;
 use crate::services::image_cache::ImageCacheService;
+use crate::services::mesh_analysis::MeshAnalysisService;
 use crate::services::rescan::RescanService;
+use crate::services::scan_progress::{ScanCancellation, ScanProgressTracker};
 use crate::services::scanner::ScannerService;
 use crate::services::search::SearchService;
+use crate::services::sidecar::SidecarService;
 use crate::services::stl_preview::StlPreviewService;
 use axum::{
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use std::path::PathBuf;
@@ -33,15 +46,27 @@ pub struct AppState {
     pub search_service: Arc<SearchService>,
     pub download_service: Arc<DownloadService>,
     pub stl_preview_service: Arc<StlPreviewService>,
+    pub mesh_analysis_service: Arc<MeshAnalysisService>,
+    pub duplicate_service: Arc<DuplicateService>,
+    pub sidecar_service: Arc<SidecarService>,
     pub scan_state: Arc<Mutex<ScanState>>,
+    pub scan_progress: ScanProgressTracker,
+    pub scan_cancellation: ScanCancellation,
+    pub scan_session_repo: Arc<ScanSessionRepository>,
+    pub library_root_repo: Arc<LibraryRootRepository>,
+    pub creator_repo: Arc<CreatorRepository>,
+    pub release_repo: Arc<ReleaseRepository>,
+    pub composite_preview_service: Arc<CompositePreviewService>,
+    pub collection_repo: Arc<CollectionRepository>,
 }
 
-pub fn create_router(
+/// Build the shared services behind the API routes
+pub fn create_state(
     pool: DbPool,
     cache_dir: PathBuf,
     ignored_keywords: Vec<String>,
     root_path: PathBuf,
-) -> Router {
+) -> (AppState, BrowseState) {
     let image_cache = Arc::new(ImageCacheService::new(cache_dir.clone(), pool.clone()));
 
     let stl_preview = Arc::new(StlPreviewService::new((*image_cache).clone(), pool.clone()));
@@ -52,21 +77,33 @@ pub fn create_router(
         100,
     ));
 
-    // Initialize services with composite preview and STL preview support
+    let mesh_analysis = MeshAnalysisService::new(pool.clone());
+    let scan_progress = ScanProgressTracker::new();
+    let scan_cancellation = ScanCancellation::new();
+
+    // Initialize services with composite preview, STL preview and mesh analysis support
     let scanner_service = Arc::new(
         ScannerService::new(pool.clone())
             .with_composite_preview(cache_dir.clone())
             .with_stl_preview((*stl_preview).clone(), preview_queue.clone())
-            .with_ignored_keywords(ignored_keywords.clone()),
+            .with_mesh_analysis(mesh_analysis.clone())
+            .with_ignored_keywords(ignored_keywords.clone())
+            .with_progress(scan_progress.clone())
+            .with_cancellation(scan_cancellation.clone()),
     );
 
     let rescan_service = Arc::new(
         RescanService::with_cache(pool.clone(), (*image_cache).clone())
             .with_composite_preview(cache_dir.clone())
             .with_stl_preview((*stl_preview).clone(), preview_queue.clone())
-            .with_ignored_keywords(ignored_keywords.clone()),
+            .with_mesh_analysis(mesh_analysis.clone())
+            .with_ignored_keywords(ignored_keywords.clone())
+            .with_progress(scan_progress.clone())
+            .with_cancellation(scan_cancellation.clone()),
     );
 
+    let library_root_repo = Arc::new(LibraryRootRepository::new(pool.clone()));
+
     // Initialize folder service for browse functionality
     let folder_service = Arc::new(
         crate::services::folder_service::FolderService::new(pool.clone(), root_path)
@@ -88,15 +125,33 @@ pub fn create_router(
         search_service: Arc::new(SearchService::new(pool.clone(), ignored_keywords)),
         download_service: Arc::new(DownloadService::new(pool.clone())),
         stl_preview_service: stl_preview,
+        mesh_analysis_service: Arc::new(mesh_analysis),
+        duplicate_service: Arc::new(DuplicateService::new(pool.clone())),
+        sidecar_service: Arc::new(SidecarService::new(pool.clone())),
         scan_state: Arc::new(Mutex::new(ScanState {
             is_scanning: false,
             result: None,
         })),
+        scan_progress,
+        scan_cancellation,
+        scan_session_repo: Arc::new(ScanSessionRepository::new(pool.clone())),
+        library_root_repo: library_root_repo.clone(),
+        creator_repo: Arc::new(CreatorRepository::new(pool.clone())),
+        release_repo: Arc::new(ReleaseRepository::new(pool.clone())),
+        composite_preview_service: Arc::new(CompositePreviewService::new(cache_dir)),
+        collection_repo: Arc::new(CollectionRepository::new(pool.clone())),
     };
 
     // Create browse state for folder navigation routes
-    let browse_state = crate::api::browse_routes::BrowseState { folder_service };
+    let browse_state = BrowseState {
+        folder_service,
+        library_root_repo,
+    };
+
+    (state, browse_state)
+}
 
+pub fn router(state: AppState, browse_state: BrowseState) -> Router {
     // Create browse router with its own state
     let browse_router = Router::new()
         .route(
@@ -115,18 +170,46 @@ pub fn create_router(
             "/api/browse/breadcrumb/*path",
             get(crate::api::browse_routes::get_breadcrumb),
         )
+        // Browse inside one library root
+        .route(
+            "/api/roots/:id/browse",
+            get(crate::api::browse_routes::get_root_folder_contents),
+        )
+        .route(
+            "/api/roots/:id/browse/*path",
+            get(crate::api::browse_routes::get_root_folder_contents),
+        )
+        .route(
+            "/api/roots/:id/browse/breadcrumb",
+            get(crate::api::browse_routes::get_root_breadcrumb),
+        )
+        .route(
+            "/api/roots/:id/browse/breadcrumb/*path",
+            get(crate::api::browse_routes::get_root_breadcrumb),
+        )
         .with_state(browse_state);
 
     Router::new()
         // Config routes
         .route("/api/config", get(config::get_config))
         .route("/api/config", post(config::update_config))
+        // Library root routes
+        .route("/api/roots", get(roots::list_roots))
+        .route("/api/roots", post(roots::create_root))
+        .route("/api/roots/:id", delete(roots::delete_root))
         // Scan routes
         .route("/api/scan", post(scan::start_scan))
         .route("/api/scan/status", get(scan::get_scan_status))
+        .route("/api/scan/events", get(scan::scan_events))
+        .route("/api/scan/cancel", post(scan::cancel_scan))
+        .route("/api/scan/history", get(scan::list_scan_history))
+        .route("/api/scan/history/:id", get(scan::get_scan_session))
         // Project routes
         .route("/api/projects", get(projects::list_root_projects))
-        .route("/api/projects/:id", get(projects::get_project))
+        .route(
+            "/api/projects/:id",
+            get(projects::get_project).patch(projects::update_project),
+        )
         .route(
             "/api/projects/:id/children",
             get(projects::get_project_children),
@@ -145,6 +228,55 @@ pub fn create_router(
         .route("/api/images/:hash", get(files::serve_image))
         .route("/api/previews/:hash", get(files::serve_preview))
         .route("/api/files/:id", get(files::download_file))
+        .route("/api/files/:id/analysis", get(files::get_file_analysis))
+        // Creator routes
+        .route("/api/creators", get(creators::list_creators))
+        .route("/api/creators/:id", get(creators::get_creator))
+        // Release routes
+        .route("/api/releases", get(releases::list_releases))
+        .route("/api/releases", post(releases::create_release))
+        .route(
+            "/api/releases/:id",
+            get(releases::get_release)
+                .patch(releases::update_release)
+                .delete(releases::delete_release),
+        )
+        .route(
+            "/api/releases/:id/projects",
+            put(releases::set_release_projects).post(releases::add_release_project),
+        )
+        .route(
+            "/api/releases/:id/projects/:project_id",
+            delete(releases::remove_release_project),
+        )
+        .route("/api/releases/:id/cover", get(releases::get_release_cover))
+        .route(
+            "/api/releases/:id/download",
+            get(releases::download_release_zip),
+        )
+        // Collection routes
+        .route("/api/collections", get(collections::list_collections))
+        .route("/api/collections", post(collections::create_collection))
+        .route(
+            "/api/collections/:id",
+            get(collections::get_collection)
+                .patch(collections::update_collection)
+                .delete(collections::delete_collection),
+        )
+        .route(
+            "/api/collections/:id/items",
+            post(collections::add_collection_item).put(collections::reorder_collection_items),
+        )
+        .route(
+            "/api/collections/:id/items/:item_id",
+            patch(collections::update_collection_item).delete(collections::remove_collection_item),
+        )
+        .route(
+            "/api/collections/:id/download",
+            get(collections::download_collection_zip),
+        )
+        // Duplicate detection routes
+        .route("/api/duplicates", get(duplicates::list_duplicates))
         // Search routes
         .route("/api/search", get(search::search_projects))
         // Tags routes
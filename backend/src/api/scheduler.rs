@@ -0,0 +1,62 @@
+use crate::api::handlers::scan::start_scheduled_rescan;
+use crate::api::routes::AppState;
+use crate::services::scan_schedule::ScanSchedule;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+
+/// How often the scheduler checks whether a rescan is due; schedule changes
+/// made through the config API are picked up on the next check
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Run incremental rescans at the times configured in `config`
+/// (`scan_schedule` or `scan_interval_minutes`) for as long as the server runs
+pub fn spawn_scan_scheduler(state: AppState) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut schedule: Option<ScanSchedule> = None;
+        let mut next_run: Option<i64> = None;
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+            let now = unix_now();
+
+            let configured = match state.config_service.get_scan_schedule() {
+                Ok(configured) => configured,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to read scan schedule");
+                    continue;
+                }
+            };
+            if configured != schedule {
+                next_run = configured.as_ref().and_then(|s| s.next_after(now));
+                match next_run {
+                    Some(at) => tracing::info!(next_run = at, "Scheduled rescans enabled"),
+                    None if schedule.is_some() => tracing::info!("Scheduled rescans disabled"),
+                    None => {}
+                }
+                schedule = configured;
+            }
+
+            let due = matches!(next_run, Some(at) if now >= at);
+            if !due {
+                continue;
+            }
+
+            match start_scheduled_rescan(&state).await {
+                Ok(true) => tracing::info!("Started scheduled rescan"),
+                Ok(false) => tracing::info!(
+                    "Skipping scheduled rescan: a scan is already running or no root path is set"
+                ),
+                Err(e) => tracing::warn!(error = %e, "Failed to start scheduled rescan"),
+            }
+            next_run = schedule.as_ref().and_then(|s| s.next_after(now));
+        }
+    })
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
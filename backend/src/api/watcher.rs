@@ -0,0 +1,128 @@
+use crate::api::handlers::scan::start_watched_rescan;
+use crate::api::routes::AppState;
+use crate::utils::error::AppError;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Quiet period after the last event before changes are applied
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// How often the watched folders are matched against the configured library roots
+const ROOTS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Watches every library root and applies changes to the index as they happen.
+/// Events are debounced so that copying a whole release results in one update,
+/// which runs as a scan like any other: it is recorded in the scan history, reports
+/// progress and can be cancelled. Changes seen while another scan runs are applied
+/// once it has finished. Watching stops when the watcher is dropped.
+pub struct LibraryWatcher {
+    task: JoinHandle<()>,
+}
+
+impl LibraryWatcher {
+    pub fn start(state: AppState, debounce: Duration) -> Result<Self, AppError> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) => {
+                    if is_relevant(&event.kind) && !event.paths.is_empty() {
+                        let _ = sender.send(event.paths);
+                    }
+                }
+                Err(e) => warn!("Filesystem watcher error: {}", e),
+            })
+            .map_err(|e| AppError::InternalServer(format!("Failed to create watcher: {}", e)))?;
+
+        let mut watched = HashSet::new();
+        watch_roots(&state, &mut watcher, &mut watched);
+
+        let task = tokio::spawn(async move {
+            let mut pending: HashSet<PathBuf> = HashSet::new();
+            let mut roots_check = tokio::time::interval(ROOTS_INTERVAL);
+
+            loop {
+                tokio::select! {
+                    paths = receiver.recv() => match paths {
+                        Some(paths) => pending.extend(paths),
+                        None => break,
+                    },
+                    _ = roots_check.tick() => watch_roots(&state, &mut watcher, &mut watched),
+                    // The library has been quiet for `debounce`
+                    _ = tokio::time::sleep(debounce), if !pending.is_empty() => {
+                        let changed: Vec<PathBuf> = pending.iter().cloned().collect();
+                        match start_watched_rescan(&state, &changed).await {
+                            Ok(true) => {
+                                debug!("Applying {} filesystem changes", changed.len());
+                                pending.clear();
+                            }
+                            // Kept until the running scan has finished
+                            Ok(false) => {}
+                            Err(e) => {
+                                warn!("Failed to apply filesystem changes: {}", e);
+                                pending.clear();
+                            }
+                        }
+                    }
+                }
+            }
+
+            debug!("Filesystem watcher stopped");
+        });
+
+        Ok(Self { task })
+    }
+}
+
+impl Drop for LibraryWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Watch library roots added since the last check and stop watching removed ones
+fn watch_roots(state: &AppState, watcher: &mut RecommendedWatcher, watched: &mut HashSet<PathBuf>) {
+    let roots: HashSet<PathBuf> = match state.library_root_repo.list() {
+        Ok(roots) => roots
+            .into_iter()
+            .map(|root| PathBuf::from(root.path))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to list library roots to watch: {}", e);
+            return;
+        }
+    };
+
+    for root in watched.difference(&roots) {
+        let _ = watcher.unwatch(root);
+        info!("Stopped watching {}", root.display());
+    }
+    watched.retain(|root| roots.contains(root));
+
+    for root in roots {
+        if watched.contains(&root) {
+            continue;
+        }
+        match watcher.watch(&root, RecursiveMode::Recursive) {
+            Ok(()) => {
+                info!("Watching {} for changes", root.display());
+                watched.insert(root);
+            }
+            Err(e) => warn!("Failed to watch {}: {}", root.display(), e),
+        }
+    }
+}
+
+/// Creates, content changes, renames and removals; access and metadata-only events are ignored
+fn is_relevant(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Create(_) | EventKind::Remove(_) => true,
+        EventKind::Modify(modify) => !matches!(modify, notify::event::ModifyKind::Metadata(_)),
+        _ => false,
+    }
+}
@@ -1,7 +1,14 @@
 use crate::db::connection::DbPool;
+use crate::services::scan_schedule::ScanSchedule;
 use crate::utils::error::AppError;
+use crate::utils::ignore::{IgnorePattern, IgnoreRules};
+use crate::utils::release_pattern::ReleasePattern;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Name of the library root that follows the configured root path
+pub const DEFAULT_ROOT: &str = "default";
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -27,6 +34,40 @@ pub struct AppConfig {
     pub images_per_page: i64,
     pub created_at: i64,
     pub updated_at: i64,
+    pub printer_profiles: Vec<PrinterProfile>,
+    /// Cron expression (UTC) for automatic rescans
+    pub scan_schedule: Option<String>,
+    /// Minutes between automatic rescans, used when no cron expression is set
+    pub scan_interval_minutes: Option<i64>,
+    /// Gitignore-style patterns excluded from scans, relative to the library root
+    pub ignore_patterns: Vec<String>,
+    /// Follow symlinked folders and files during scans
+    pub follow_symlinks: bool,
+    /// Mirror tags and metadata edited through the API into each project's sidecar file
+    pub write_sidecars: bool,
+    /// Folder level whose folders name the creators (1 = folders directly in a library root)
+    pub creator_folder_level: Option<i64>,
+    /// Folder name pattern of release folders, e.g. `YYYY-MM*`
+    pub release_folder_pattern: Option<String>,
+}
+
+impl AppConfig {
+    /// The release folder pattern; patterns are checked when saved, so None means
+    /// release detection is off
+    pub fn release_pattern(&self) -> Option<ReleasePattern> {
+        self.release_folder_pattern
+            .as_deref()
+            .and_then(|pattern| ReleasePattern::parse(pattern).ok())
+    }
+}
+
+/// A named printer build volume (mm), used to filter search results by fit
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrinterProfile {
+    pub name: String,
+    pub build_x: f64,
+    pub build_y: f64,
+    pub build_z: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +75,28 @@ pub struct UpdateConfigRequest {
     pub root_path: Option<String>,
     pub cache_max_size_mb: Option<i64>,
     pub images_per_page: Option<i64>,
+    /// Replaces the full list of printer profiles when provided
+    #[serde(default)]
+    pub printer_profiles: Option<Vec<PrinterProfile>>,
+    /// Cron expression for automatic rescans; an empty string disables it
+    #[serde(default)]
+    pub scan_schedule: Option<String>,
+    /// Minutes between automatic rescans; 0 disables them
+    #[serde(default)]
+    pub scan_interval_minutes: Option<i64>,
+    /// Replaces the global ignore patterns when provided
+    #[serde(default)]
+    pub ignore_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub follow_symlinks: Option<bool>,
+    #[serde(default)]
+    pub write_sidecars: Option<bool>,
+    /// Folder level whose folders are creators; 0 disables it
+    #[serde(default)]
+    pub creator_folder_level: Option<i64>,
+    /// Folder name pattern of release folders; an empty string disables detection
+    #[serde(default)]
+    pub release_folder_pattern: Option<String>,
 }
 
 pub struct ConfigService {
@@ -48,11 +111,13 @@ impl ConfigService {
     pub fn get_config(&self) -> Result<AppConfig, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, root_path, last_scan_at, cache_max_size_mb, images_per_page, created_at, updated_at
+            "SELECT id, root_path, last_scan_at, cache_max_size_mb, images_per_page, created_at, updated_at,
+                    scan_schedule, scan_interval_minutes, ignore_patterns, follow_symlinks,
+                    write_sidecars, creator_folder_level, release_folder_pattern
              FROM config WHERE id = 1"
         )?;
 
-        let config = stmt.query_row([], |row| {
+        let mut config = stmt.query_row([], |row| {
             Ok(AppConfig {
                 id: row.get(0)?,
                 root_path: row.get(1)?,
@@ -61,42 +126,257 @@ impl ConfigService {
                 images_per_page: row.get(4)?,
                 created_at: row.get(5)?,
                 updated_at: row.get(6)?,
+                printer_profiles: Vec::new(),
+                scan_schedule: row.get(7)?,
+                scan_interval_minutes: row.get(8)?,
+                ignore_patterns: row
+                    .get::<_, Option<String>>(9)?
+                    .map(|patterns| patterns.lines().map(str::to_string).collect())
+                    .unwrap_or_default(),
+                follow_symlinks: row.get(10)?,
+                write_sidecars: row.get(11)?,
+                creator_folder_level: row.get(12)?,
+                release_folder_pattern: row.get(13)?,
             })
         })?;
 
+        config.printer_profiles = self.list_printer_profiles()?;
+
         Ok(config)
     }
 
-    pub fn update_config(&self, updates: &UpdateConfigRequest) -> Result<AppConfig, AppError> {
+    pub fn list_printer_profiles(&self) -> Result<Vec<PrinterProfile>, AppError> {
         let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT name, build_x, build_y, build_z FROM printer_profiles ORDER BY name",
+        )?;
+
+        let profiles = stmt
+            .query_map([], |row| {
+                Ok(PrinterProfile {
+                    name: row.get(0)?,
+                    build_x: row.get(1)?,
+                    build_y: row.get(2)?,
+                    build_z: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(profiles)
+    }
+
+    /// Look up a printer profile by name (case-insensitive)
+    pub fn get_printer_profile(&self, name: &str) -> Result<Option<PrinterProfile>, AppError> {
+        Ok(self
+            .list_printer_profiles()?
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim())))
+    }
+
+    /// Writes every field that is set, all or nothing: the whole request is
+    /// validated before anything is written, and the writes share one transaction.
+    pub fn update_config(&self, updates: &UpdateConfigRequest) -> Result<AppConfig, AppError> {
+        if let Some(ref printer_profiles) = updates.printer_profiles {
+            validate_printer_profiles(printer_profiles)?;
+        }
+
+        let scan_schedule = updates.scan_schedule.as_deref().map(str::trim);
+        if let Some(scan_schedule) = scan_schedule {
+            ScanSchedule::from_config(Some(scan_schedule), None)
+                .map_err(AppError::ValidationError)?;
+        }
+
+        if updates
+            .scan_interval_minutes
+            .is_some_and(|minutes| minutes < 0)
+        {
+            return Err(AppError::ValidationError(
+                "Scan interval cannot be negative".to_string(),
+            ));
+        }
+
+        let ignore_patterns = match updates.ignore_patterns {
+            Some(ref ignore_patterns) => {
+                let mut patterns = Vec::new();
+                for pattern in ignore_patterns {
+                    if IgnorePattern::parse(pattern)
+                        .map_err(AppError::ValidationError)?
+                        .is_some()
+                    {
+                        patterns.push(pattern.trim());
+                    }
+                }
+                Some(patterns)
+            }
+            None => None,
+        };
+
+        if updates.creator_folder_level.is_some_and(|level| level < 0) {
+            return Err(AppError::ValidationError(
+                "Creator folder level cannot be negative".to_string(),
+            ));
+        }
+
+        let release_folder_pattern = updates.release_folder_pattern.as_deref().map(str::trim);
+        if let Some(release_folder_pattern) = release_folder_pattern {
+            if !release_folder_pattern.is_empty() {
+                ReleasePattern::parse(release_folder_pattern).map_err(AppError::ValidationError)?;
+            }
+        }
+
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)?
             .as_secs() as i64;
+        let mut conn = self.pool.get()?;
+        // Files, images and tags of the projects dropped below go with them
+        conn.execute("PRAGMA foreign_keys = ON", [])?;
+        let tx = conn.transaction()?;
 
         if let Some(ref root_path) = updates.root_path {
-            conn.execute(
+            tx.execute(
                 "UPDATE config SET root_path = ?1, updated_at = ?2 WHERE id = 1",
                 params![root_path, now],
             )?;
+
+            // The configured root path is the default library root. Projects indexed
+            // from its old path are dropped when it moves; the next scan indexes the new one.
+            tx.execute(
+                "DELETE FROM projects WHERE root_id =
+                 (SELECT id FROM library_roots WHERE name = ?1 AND path != ?2)",
+                params![DEFAULT_ROOT, root_path],
+            )?;
+            tx.execute(
+                "INSERT INTO library_roots (name, path, created_at)
+                 SELECT ?1, ?2, ?3
+                 WHERE NOT EXISTS (SELECT 1 FROM library_roots WHERE path = ?2)
+                 ON CONFLICT(name) DO UPDATE SET path = excluded.path, last_scan_at = NULL",
+                params![DEFAULT_ROOT, root_path, now],
+            )?;
         }
 
         if let Some(cache_max_size_mb) = updates.cache_max_size_mb {
-            conn.execute(
+            tx.execute(
                 "UPDATE config SET cache_max_size_mb = ?1, updated_at = ?2 WHERE id = 1",
                 params![cache_max_size_mb, now],
             )?;
         }
 
         if let Some(images_per_page) = updates.images_per_page {
-            conn.execute(
+            tx.execute(
                 "UPDATE config SET images_per_page = ?1, updated_at = ?2 WHERE id = 1",
                 params![images_per_page, now],
             )?;
         }
 
+        if let Some(ref printer_profiles) = updates.printer_profiles {
+            tx.execute("DELETE FROM printer_profiles", [])?;
+            for profile in printer_profiles {
+                tx.execute(
+                    "INSERT INTO printer_profiles (name, build_x, build_y, build_z, created_at, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    params![
+                        profile.name.trim(),
+                        profile.build_x,
+                        profile.build_y,
+                        profile.build_z,
+                        now,
+                        now
+                    ],
+                )?;
+            }
+        }
+
+        if let Some(scan_schedule) = scan_schedule {
+            tx.execute(
+                "UPDATE config SET scan_schedule = ?1, updated_at = ?2 WHERE id = 1",
+                params![(!scan_schedule.is_empty()).then_some(scan_schedule), now],
+            )?;
+        }
+
+        if let Some(scan_interval_minutes) = updates.scan_interval_minutes {
+            tx.execute(
+                "UPDATE config SET scan_interval_minutes = ?1, updated_at = ?2 WHERE id = 1",
+                params![
+                    (scan_interval_minutes > 0).then_some(scan_interval_minutes),
+                    now
+                ],
+            )?;
+        }
+
+        if let Some(patterns) = ignore_patterns {
+            tx.execute(
+                "UPDATE config SET ignore_patterns = ?1, updated_at = ?2 WHERE id = 1",
+                params![(!patterns.is_empty()).then(|| patterns.join("\n")), now],
+            )?;
+        }
+
+        if let Some(follow_symlinks) = updates.follow_symlinks {
+            tx.execute(
+                "UPDATE config SET follow_symlinks = ?1, updated_at = ?2 WHERE id = 1",
+                params![follow_symlinks, now],
+            )?;
+        }
+
+        if let Some(write_sidecars) = updates.write_sidecars {
+            tx.execute(
+                "UPDATE config SET write_sidecars = ?1, updated_at = ?2 WHERE id = 1",
+                params![write_sidecars, now],
+            )?;
+        }
+
+        if let Some(creator_folder_level) = updates.creator_folder_level {
+            tx.execute(
+                "UPDATE config SET creator_folder_level = ?1, updated_at = ?2 WHERE id = 1",
+                params![
+                    (creator_folder_level > 0).then_some(creator_folder_level),
+                    now
+                ],
+            )?;
+        }
+
+        if let Some(release_folder_pattern) = release_folder_pattern {
+            tx.execute(
+                "UPDATE config SET release_folder_pattern = ?1, updated_at = ?2 WHERE id = 1",
+                params![
+                    (!release_folder_pattern.is_empty()).then_some(release_folder_pattern),
+                    now
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        drop(conn);
+
         self.get_config()
     }
 
+    /// Ignore rules for scanning `root`: the global patterns plus `.glyptoignore` files,
+    /// and whether symlinks are followed. Scans go on without the global patterns and
+    /// without following symlinks if the config cannot be read.
+    pub fn ignore_rules(&self, root: &Path) -> IgnoreRules {
+        match self.get_config() {
+            Ok(config) => IgnoreRules::new(root, &config.ignore_patterns)
+                .with_follow_symlinks(config.follow_symlinks),
+            Err(e) => {
+                tracing::warn!("Ignoring global ignore patterns: {}", e);
+                IgnoreRules::new(root, &[])
+            }
+        }
+    }
+
+    /// Automatic rescan schedule, or None when disabled or misconfigured
+    pub fn get_scan_schedule(&self) -> Result<Option<ScanSchedule>, AppError> {
+        let config = self.get_config()?;
+        Ok(ScanSchedule::from_config(
+            config.scan_schedule.as_deref(),
+            config.scan_interval_minutes,
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!("Ignoring scan schedule: {}", e);
+            None
+        }))
+    }
+
     pub fn update_last_scan(&self) -> Result<(), AppError> {
         let conn = self.pool.get()?;
         let now = std::time::SystemTime::now()
@@ -111,3 +391,31 @@ impl ConfigService {
         Ok(())
     }
 }
+
+/// Printer profiles need unique, non-empty names and a positive build volume
+fn validate_printer_profiles(profiles: &[PrinterProfile]) -> Result<(), AppError> {
+    let mut seen = std::collections::HashSet::new();
+    for profile in profiles {
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return Err(AppError::ValidationError(
+                "Printer profile name cannot be empty".to_string(),
+            ));
+        }
+        if !seen.insert(name.to_lowercase()) {
+            return Err(AppError::ValidationError(format!(
+                "Duplicate printer profile: {}",
+                name
+            )));
+        }
+        let dims = [profile.build_x, profile.build_y, profile.build_z];
+        if dims.iter().any(|d| !d.is_finite() || *d <= 0.0) {
+            return Err(AppError::ValidationError(format!(
+                "Build volume of printer profile '{}' must be positive",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
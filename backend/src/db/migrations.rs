@@ -42,6 +42,96 @@ pub const MIGRATIONS: &[Migration] = &[
         description: "Add STL file categories",
         sql: include_str!("../../migrations/007_stl_categories.sql"),
     },
+    Migration {
+        version: 8,
+        description: "Add model file format column",
+        sql: include_str!("../../migrations/008_model_formats.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "Add mesh analysis table",
+        sql: include_str!("../../migrations/009_mesh_analysis.sql"),
+    },
+    Migration {
+        version: 10,
+        description: "Add printer profiles",
+        sql: include_str!("../../migrations/010_printer_profiles.sql"),
+    },
+    Migration {
+        version: 11,
+        description: "Add mesh issues table",
+        sql: include_str!("../../migrations/011_mesh_issues.sql"),
+    },
+    Migration {
+        version: 12,
+        description: "Add model file content hashes",
+        sql: include_str!("../../migrations/012_content_hashes.sql"),
+    },
+    Migration {
+        version: 13,
+        description: "Add file modification times",
+        sql: include_str!("../../migrations/013_file_mtimes.sql"),
+    },
+    Migration {
+        version: 14,
+        description: "Add scan history",
+        sql: include_str!("../../migrations/014_scan_history.sql"),
+    },
+    Migration {
+        version: 15,
+        description: "Add scheduled rescans",
+        sql: include_str!("../../migrations/015_scheduled_rescans.sql"),
+    },
+    Migration {
+        version: 16,
+        description: "Add library roots",
+        sql: include_str!("../../migrations/016_library_roots.sql"),
+    },
+    Migration {
+        version: 17,
+        description: "Add ignore patterns",
+        sql: include_str!("../../migrations/017_ignore_patterns.sql"),
+    },
+    Migration {
+        version: 18,
+        description: "Add symlink following",
+        sql: include_str!("../../migrations/018_follow_symlinks.sql"),
+    },
+    Migration {
+        version: 19,
+        description: "Add project metadata",
+        sql: include_str!("../../migrations/019_project_metadata.sql"),
+    },
+    Migration {
+        version: 20,
+        description: "Add sidecar write-back",
+        sql: include_str!("../../migrations/020_sidecar_write_back.sql"),
+    },
+    Migration {
+        version: 21,
+        description: "Add editable project metadata",
+        sql: include_str!("../../migrations/021_editable_project_metadata.sql"),
+    },
+    Migration {
+        version: 22,
+        description: "Add creators",
+        sql: include_str!("../../migrations/022_creators.sql"),
+    },
+    Migration {
+        version: 23,
+        description: "Add releases",
+        sql: include_str!("../../migrations/023_releases.sql"),
+    },
+    Migration {
+        version: 24,
+        description: "Add collections",
+        sql: include_str!("../../migrations/024_collections.sql"),
+    },
+    Migration {
+        version: 25,
+        description: "Record external folder links",
+        sql: include_str!("../../migrations/025_external_folder_links.sql"),
+    },
 ];
 
 pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
@@ -101,7 +191,7 @@ mod tests {
             })
             .unwrap();
 
-        assert_eq!(version, 7);
+        assert_eq!(version, 25);
 
         let table_exists: bool = conn
             .query_row(
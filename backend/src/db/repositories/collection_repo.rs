@@ -0,0 +1,367 @@
+use crate::db::connection::DbPool;
+use crate::models::collection::{
+    AddCollectionItem, Collection, CollectionItem, CollectionRequest, CollectionSummary,
+    CollectionWithItems, UpdateCollectionItem,
+};
+use crate::models::project::Project;
+use crate::models::stl_file::StlFile;
+use crate::utils::error::AppError;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+
+pub struct CollectionRepository {
+    pool: DbPool,
+}
+
+impl CollectionRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn list(&self) -> Result<Vec<CollectionSummary>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.name, c.description, c.created_at, c.updated_at,
+                    (SELECT COUNT(*) FROM collection_items ci WHERE ci.collection_id = c.id)
+             FROM collections c
+             ORDER BY c.name COLLATE NOCASE, c.id",
+        )?;
+        let collections = stmt
+            .query_map([], |row| {
+                Ok(CollectionSummary {
+                    collection: collection_from_row(row)?,
+                    item_count: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(collections)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<CollectionWithItems>, AppError> {
+        let conn = self.pool.get()?;
+        let collection = conn
+            .query_row(
+                "SELECT id, name, description, created_at, updated_at FROM collections
+                 WHERE id = ?1",
+                params![id],
+                collection_from_row,
+            )
+            .optional()?;
+        let Some(collection) = collection else {
+            return Ok(None);
+        };
+
+        // Items of a model file are listed with the file's project
+        let mut stmt = conn.prepare(
+            "SELECT ci.id, ci.quantity, ci.notes, ci.created_at, ci.updated_at,
+                    p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description,
+                    p.folder_level, p.created_at, p.updated_at, p.root_id,
+                    s.id, s.project_id, s.filename, s.file_path, s.file_size, s.format,
+                    s.category, s.preview_path, s.preview_generated_at, s.created_at,
+                    s.updated_at
+             FROM collection_items ci
+             LEFT JOIN stl_files s ON s.id = ci.stl_file_id
+             INNER JOIN projects p ON p.id = COALESCE(ci.project_id, s.project_id)
+             WHERE ci.collection_id = ?1
+             ORDER BY ci.position",
+        )?;
+        let items = stmt
+            .query_map(params![id], |row| {
+                let stl_file = match row.get::<_, Option<i64>>(15)? {
+                    Some(stl_file_id) => Some(StlFile {
+                        id: stl_file_id,
+                        project_id: row.get(16)?,
+                        filename: row.get(17)?,
+                        file_path: row.get(18)?,
+                        file_size: row.get(19)?,
+                        format: row.get(20)?,
+                        category: row.get(21)?,
+                        preview_path: row.get(22)?,
+                        preview_generated_at: row.get(23)?,
+                        created_at: row.get(24)?,
+                        updated_at: row.get(25)?,
+                        analysis: None,
+                    }),
+                    None => None,
+                };
+                Ok(CollectionItem {
+                    id: row.get(0)?,
+                    quantity: row.get(1)?,
+                    notes: row.get(2)?,
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    project: Project {
+                        id: row.get(5)?,
+                        name: row.get(6)?,
+                        full_path: row.get(7)?,
+                        parent_id: row.get(8)?,
+                        is_leaf: row.get(9)?,
+                        description: row.get(10)?,
+                        folder_level: row.get(11)?,
+                        created_at: row.get(12)?,
+                        updated_at: row.get(13)?,
+                        root_id: row.get(14)?,
+                    },
+                    stl_file,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(CollectionWithItems { collection, items }))
+    }
+
+    pub fn create(&self, name: &str, description: Option<&str>) -> Result<i64, AppError> {
+        let conn = self.pool.get()?;
+        let now = now()?;
+        conn.execute(
+            "INSERT INTO collections (name, description, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?3)",
+            params![name.trim(), non_empty(description), now],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn update(&self, id: i64, update: &CollectionRequest) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        touch(&tx, id)?;
+
+        if let Some(ref name) = update.name {
+            tx.execute(
+                "UPDATE collections SET name = ?1 WHERE id = ?2",
+                params![name.trim(), id],
+            )?;
+        }
+        if let Some(ref description) = update.description {
+            tx.execute(
+                "UPDATE collections SET description = ?1 WHERE id = ?2",
+                params![non_empty(Some(description)), id],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn delete(&self, id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let deleted = conn.execute("DELETE FROM collections WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Collection {} not found", id)));
+        }
+
+        Ok(())
+    }
+
+    /// Add a project or model file at `position` (appended when None). Returns the
+    /// new item's id.
+    pub fn add_item(&self, id: i64, item: &AddCollectionItem) -> Result<i64, AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let now = now()?;
+        touch(&tx, id)?;
+
+        let (table, target_id) = match (item.project_id, item.stl_file_id) {
+            (Some(project_id), _) => ("projects", project_id),
+            (None, Some(stl_file_id)) => ("stl_files", stl_file_id),
+            (None, None) => {
+                return Err(AppError::ValidationError(
+                    "Either project_id or stl_file_id is required".to_string(),
+                ))
+            }
+        };
+        let exists: bool = tx.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM {} WHERE id = ?1)", table),
+            params![target_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            let kind = if table == "projects" {
+                "Project"
+            } else {
+                "Model file"
+            };
+            return Err(AppError::ValidationError(format!(
+                "{} {} not found",
+                kind, target_id
+            )));
+        }
+
+        let already_added: bool = tx.query_row(
+            "SELECT EXISTS (
+                 SELECT 1 FROM collection_items
+                 WHERE collection_id = ?1 AND (project_id = ?2 OR stl_file_id = ?3)
+             )",
+            params![id, item.project_id, item.stl_file_id],
+            |row| row.get(0),
+        )?;
+        if already_added {
+            return Err(AppError::ValidationError(
+                "The collection already has this item".to_string(),
+            ));
+        }
+
+        let mut item_ids = item_ids(&tx, id)?;
+        tx.execute(
+            "INSERT INTO collection_items
+                 (collection_id, project_id, stl_file_id, quantity, notes, position,
+                  created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+            params![
+                id,
+                item.project_id,
+                item.stl_file_id,
+                item.quantity.unwrap_or(1),
+                non_empty(item.notes.as_deref()),
+                item_ids.len() as i64,
+                now
+            ],
+        )?;
+        let item_id = tx.last_insert_rowid();
+
+        let position = item.position.unwrap_or(item_ids.len()).min(item_ids.len());
+        item_ids.insert(position, item_id);
+        write_positions(&tx, &item_ids)?;
+        tx.commit()?;
+
+        Ok(item_id)
+    }
+
+    pub fn update_item(
+        &self,
+        id: i64,
+        item_id: i64,
+        update: &UpdateCollectionItem,
+    ) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let now = now()?;
+        touch(&tx, id)?;
+
+        let updated = tx.execute(
+            "UPDATE collection_items SET updated_at = ?1 WHERE id = ?2 AND collection_id = ?3",
+            params![now, item_id, id],
+        )?;
+        if updated == 0 {
+            return Err(item_not_found(id, item_id));
+        }
+        if let Some(quantity) = update.quantity {
+            tx.execute(
+                "UPDATE collection_items SET quantity = ?1 WHERE id = ?2",
+                params![quantity, item_id],
+            )?;
+        }
+        if let Some(ref notes) = update.notes {
+            tx.execute(
+                "UPDATE collection_items SET notes = ?1 WHERE id = ?2",
+                params![non_empty(Some(notes)), item_id],
+            )?;
+        }
+        if let Some(position) = update.position {
+            let mut item_ids = item_ids(&tx, id)?;
+            item_ids.retain(|&i| i != item_id);
+            item_ids.insert(position.min(item_ids.len()), item_id);
+            write_positions(&tx, &item_ids)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn remove_item(&self, id: i64, item_id: i64) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        touch(&tx, id)?;
+
+        let deleted = tx.execute(
+            "DELETE FROM collection_items WHERE id = ?1 AND collection_id = ?2",
+            params![item_id, id],
+        )?;
+        if deleted == 0 {
+            return Err(item_not_found(id, item_id));
+        }
+        write_positions(&tx, &item_ids(&tx, id)?)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Put the items of a collection in the given order; every item must be listed once
+    pub fn reorder_items(&self, id: i64, ordered_ids: &[i64]) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        touch(&tx, id)?;
+
+        let mut current = item_ids(&tx, id)?;
+        let mut requested = ordered_ids.to_vec();
+        current.sort_unstable();
+        requested.sort_unstable();
+        if current != requested {
+            return Err(AppError::ValidationError(
+                "item_ids must list every item of the collection once".to_string(),
+            ));
+        }
+        write_positions(&tx, ordered_ids)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+/// Bump a collection's updated_at, failing if it does not exist
+fn touch(tx: &Transaction, id: i64) -> Result<(), AppError> {
+    let updated = tx.execute(
+        "UPDATE collections SET updated_at = ?1 WHERE id = ?2",
+        params![now()?, id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Collection {} not found", id)));
+    }
+
+    Ok(())
+}
+
+fn item_ids(conn: &Connection, collection_id: i64) -> Result<Vec<i64>, AppError> {
+    let ids = conn
+        .prepare("SELECT id FROM collection_items WHERE collection_id = ?1 ORDER BY position")?
+        .query_map(params![collection_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ids)
+}
+
+fn write_positions(tx: &Transaction, item_ids: &[i64]) -> Result<(), AppError> {
+    for (position, item_id) in item_ids.iter().enumerate() {
+        tx.execute(
+            "UPDATE collection_items SET position = ?1 WHERE id = ?2",
+            params![position as i64, item_id],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn item_not_found(id: i64, item_id: i64) -> AppError {
+    AppError::NotFound(format!("Item {} not found in collection {}", item_id, id))
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn now() -> Result<i64, AppError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64)
+}
+
+fn collection_from_row(row: &Row) -> rusqlite::Result<Collection> {
+    Ok(Collection {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
@@ -0,0 +1,325 @@
+use crate::db::connection::DbPool;
+use crate::models::creator::{Creator, CreatorSummary};
+use crate::models::project::Project;
+use crate::utils::error::AppError;
+use rusqlite::{params, OptionalExtension, Row, Transaction};
+use std::collections::HashMap;
+
+/// Latest projects listed with each creator
+const LATEST_PROJECTS: usize = 4;
+
+pub struct CreatorRepository {
+    pool: DbPool,
+}
+
+impl CreatorRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creators by name, optionally those whose name contains `query`
+    pub fn list(&self, query: Option<&str>) -> Result<Vec<CreatorSummary>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, created_at FROM creators
+             WHERE ?1 IS NULL OR instr(lower(name), lower(?1)) > 0
+             ORDER BY name COLLATE NOCASE",
+        )?;
+        let creators = stmt
+            .query_map(params![query], creator_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        drop(conn);
+
+        creators
+            .into_iter()
+            .map(|creator| self.summarize(creator))
+            .collect()
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<CreatorSummary>, AppError> {
+        let conn = self.pool.get()?;
+        let creator = conn
+            .query_row(
+                "SELECT id, name, created_at FROM creators WHERE id = ?1",
+                params![id],
+                creator_from_row,
+            )
+            .optional()?;
+        drop(conn);
+
+        creator.map(|creator| self.summarize(creator)).transpose()
+    }
+
+    /// Look up a creator by name (case-insensitive)
+    pub fn find_by_name(&self, name: &str) -> Result<Option<Creator>, AppError> {
+        let conn = self.pool.get()?;
+        let creator = conn
+            .query_row(
+                "SELECT id, name, created_at FROM creators WHERE name = ?1 COLLATE NOCASE",
+                params![name.trim()],
+                creator_from_row,
+            )
+            .optional()?;
+
+        Ok(creator)
+    }
+
+    fn summarize(&self, creator: Creator) -> Result<CreatorSummary, AppError> {
+        let conn = self.pool.get()?;
+        let project_count: usize = conn.query_row(
+            "SELECT COUNT(*) FROM projects WHERE creator_id = ?1 AND is_leaf = 1",
+            params![creator.id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level,
+                    p.created_at, p.updated_at, p.root_id
+             FROM projects p
+             LEFT JOIN project_metadata m ON m.project_id = p.id
+             WHERE p.creator_id = ?1 AND p.is_leaf = 1
+             ORDER BY m.release_date IS NULL, m.release_date DESC, p.created_at DESC, p.id DESC
+             LIMIT ?2",
+        )?;
+        let latest_projects = stmt
+            .query_map(params![creator.id, LATEST_PROJECTS as i64], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    full_path: row.get(2)?,
+                    parent_id: row.get(3)?,
+                    is_leaf: row.get(4)?,
+                    description: row.get(5)?,
+                    folder_level: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(CreatorSummary {
+            creator,
+            project_count,
+            latest_projects,
+        })
+    }
+
+    /// Set a project's creator by hand, or with None go back to the derived one. The
+    /// creator is applied to its subfolders by the next `assign_creators`.
+    pub fn set_override(&self, project_id: i64, name: Option<&str>) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let creator_id = match name {
+            Some(name) => Some(get_or_create(&tx, name)?),
+            None => None,
+        };
+        let updated = tx.execute(
+            "UPDATE projects SET creator_id = ?1, creator_override = ?2 WHERE id = ?3",
+            params![creator_id, creator_id.is_some(), project_id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::NotFound(format!(
+                "Project {} not found",
+                project_id
+            )));
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Derive the creator of every project: a creator set by hand, else the project's
+    /// author, else the name of its folder at `folder_level`, else its parent's creator.
+    /// Creators left without projects or releases are removed. Returns the number of
+    /// projects whose creator changed.
+    pub fn assign_creators(&self, folder_level: Option<i64>) -> Result<usize, AppError> {
+        self.derive_creators(None, folder_level)
+    }
+
+    /// Like `assign_creators`, but only for `project_id` and its subfolders, after an
+    /// edit there; the rest of the library keeps its creators. Detected releases whose
+    /// folder is in the subtree take the new creator of their folder.
+    pub fn assign_creators_under(
+        &self,
+        project_id: i64,
+        folder_level: Option<i64>,
+    ) -> Result<usize, AppError> {
+        self.derive_creators(Some(project_id), folder_level)
+    }
+
+    fn derive_creators(
+        &self,
+        subtree: Option<i64>,
+        folder_level: Option<i64>,
+    ) -> Result<usize, AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+
+        struct ProjectRow {
+            id: i64,
+            parent_id: Option<i64>,
+            name: String,
+            folder_level: i64,
+            creator_id: Option<i64>,
+            creator_override: bool,
+            author: Option<String>,
+        }
+
+        // Parents come before their children
+        let rows = tx
+            .prepare(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT id FROM projects WHERE id = ?1
+                     UNION ALL
+                     SELECT p.id FROM projects p JOIN subtree s ON p.parent_id = s.id
+                 )
+                 SELECT p.id, p.parent_id, p.name, p.folder_level, p.creator_id, p.creator_override,
+                        m.author
+                 FROM projects p
+                 LEFT JOIN project_metadata m ON m.project_id = p.id
+                 WHERE ?1 IS NULL OR p.id IN (SELECT id FROM subtree)
+                 ORDER BY p.folder_level, p.id",
+            )?
+            .query_map(params![subtree], |row| {
+                Ok(ProjectRow {
+                    id: row.get(0)?,
+                    parent_id: row.get(1)?,
+                    name: row.get(2)?,
+                    folder_level: row.get(3)?,
+                    creator_id: row.get(4)?,
+                    creator_override: row.get(5)?,
+                    author: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let creator_names: HashMap<i64, String> = tx
+            .prepare("SELECT id, name FROM creators")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        let mut derived: HashMap<i64, Option<String>> = HashMap::with_capacity(rows.len());
+        // The top of a subtree inherits from a parent that is not re-derived
+        if let Some(project_id) = subtree {
+            let parent = tx
+                .query_row(
+                    "SELECT p.parent_id, c.name FROM projects p
+                     JOIN projects parent ON parent.id = p.parent_id
+                     LEFT JOIN creators c ON c.id = parent.creator_id
+                     WHERE p.id = ?1",
+                    params![project_id],
+                    |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+                )
+                .optional()?;
+            if let Some((parent_id, name)) = parent {
+                derived.insert(parent_id, name);
+            }
+        }
+        let mut creator_ids: HashMap<String, i64> = creator_names
+            .iter()
+            .map(|(id, name)| (name.to_lowercase(), *id))
+            .collect();
+        let mut changed = 0;
+
+        for row in rows {
+            let author = row
+                .author
+                .as_deref()
+                .map(str::trim)
+                .filter(|a| !a.is_empty());
+            let name = if row.creator_override {
+                row.creator_id
+                    .and_then(|id| creator_names.get(&id).cloned())
+            } else if let Some(author) = author {
+                Some(author.to_string())
+            } else if Some(row.folder_level) == folder_level {
+                Some(row.name.trim().to_string()).filter(|n| !n.is_empty())
+            } else {
+                row.parent_id
+                    .and_then(|parent_id| derived.get(&parent_id).cloned().flatten())
+            };
+
+            if !row.creator_override {
+                let creator_id = match &name {
+                    Some(name) => Some(match creator_ids.get(&name.to_lowercase()) {
+                        Some(&id) => id,
+                        None => {
+                            let id = get_or_create(&tx, name)?;
+                            creator_ids.insert(name.to_lowercase(), id);
+                            id
+                        }
+                    }),
+                    None => None,
+                };
+                if creator_id != row.creator_id {
+                    tx.execute(
+                        "UPDATE projects SET creator_id = ?1 WHERE id = ?2",
+                        params![creator_id, row.id],
+                    )?;
+                    changed += 1;
+                }
+            }
+            derived.insert(row.id, name);
+        }
+
+        // A full pass leaves releases to `assign_releases`
+        if let Some(project_id) = subtree {
+            tx.execute(
+                "WITH RECURSIVE subtree(id) AS (
+                     SELECT id FROM projects WHERE id = ?1
+                     UNION ALL
+                     SELECT p.id FROM projects p JOIN subtree s ON p.parent_id = s.id
+                 )
+                 UPDATE releases
+                 SET creator_id = (SELECT creator_id FROM projects WHERE id = folder_project_id),
+                     updated_at = ?2
+                 WHERE auto_detected = 1
+                   AND folder_project_id IN (SELECT id FROM subtree)
+                   AND creator_id IS NOT
+                       (SELECT creator_id FROM projects WHERE id = folder_project_id)",
+                params![project_id, now()?],
+            )?;
+        }
+
+        tx.execute(
+            "DELETE FROM creators
+             WHERE NOT EXISTS (SELECT 1 FROM projects WHERE creator_id = creators.id)
+               AND NOT EXISTS (SELECT 1 FROM releases WHERE creator_id = creators.id)",
+            [],
+        )?;
+        tx.commit()?;
+
+        Ok(changed)
+    }
+}
+
+pub(crate) fn get_or_create(tx: &Transaction, name: &str) -> Result<i64, AppError> {
+    let name = name.trim();
+    tx.execute(
+        "INSERT OR IGNORE INTO creators (name, created_at) VALUES (?1, ?2)",
+        params![name, now()?],
+    )?;
+    let id = tx.query_row(
+        "SELECT id FROM creators WHERE name = ?1 COLLATE NOCASE",
+        params![name],
+        |row| row.get(0),
+    )?;
+
+    Ok(id)
+}
+
+fn now() -> Result<i64, AppError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64)
+}
+
+fn creator_from_row(row: &Row) -> rusqlite::Result<Creator> {
+    Ok(Creator {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
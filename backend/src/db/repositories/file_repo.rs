@@ -1,9 +1,12 @@
 use crate::db::connection::DbPool;
 use crate::models::image_file::{CreateImageFile, ImageFile};
-use crate::models::stl_file::{CreateStlFile, StlFile};
+use crate::models::mesh_analysis::MeshAnalysis;
+use crate::models::stl_file::{CreateStlFile, ModelFormat, StlFile};
 use crate::utils::error::AppError;
+use crate::utils::fs::file_mtime;
 use rusqlite::params;
 use std::collections::HashMap;
+use std::path::Path;
 
 #[derive(Clone)]
 pub struct FileRepository {
@@ -24,9 +27,9 @@ impl FileRepository {
 
         // Use INSERT OR IGNORE to handle duplicate file_path gracefully
         match conn.execute(
-            "INSERT OR IGNORE INTO stl_files (project_id, filename, file_path, file_size, category, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![file.project_id, file.filename, file.file_path, file.file_size, file.category, now, now],
+            "INSERT OR IGNORE INTO stl_files (project_id, filename, file_path, file_size, format, category, file_mtime, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![file.project_id, file.filename, file.file_path, file.file_size, file.format, file.category, file.file_mtime, now, now],
         ) {
             Ok(0) => {
                 // File already exists, get its ID
@@ -42,27 +45,94 @@ impl FileRepository {
         }
     }
 
+    /// Insert many model files in one transaction. Files already indexed keep their row.
+    /// Returns the ids in the order of `files`.
+    pub fn create_stl_files(&self, files: &[CreateStlFile]) -> Result<Vec<i64>, AppError> {
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        let tx = conn.transaction()?;
+        let mut ids = Vec::with_capacity(files.len());
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO stl_files (project_id, filename, file_path, file_size, format, category, file_mtime, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8)",
+            )?;
+            let mut select = tx.prepare("SELECT id FROM stl_files WHERE file_path = ?1")?;
+
+            for file in files {
+                let inserted = insert.execute(params![
+                    file.project_id,
+                    file.filename,
+                    file.file_path,
+                    file.file_size,
+                    file.format,
+                    file.category,
+                    file.file_mtime,
+                    now
+                ])?;
+                let id = if inserted == 0 {
+                    select.query_row(params![file.file_path], |row| row.get(0))?
+                } else {
+                    tx.last_insert_rowid()
+                };
+                ids.push(id);
+            }
+        }
+        tx.commit()?;
+
+        Ok(ids)
+    }
+
     pub fn get_stl_files_by_project(&self, project_id: i64) -> Result<Vec<StlFile>, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, project_id, filename, file_path, file_size, category, preview_path, 
-                    preview_generated_at, created_at, updated_at
-             FROM stl_files WHERE project_id = ?1 ORDER BY category NULLS FIRST, filename",
+            "SELECT s.id, s.project_id, s.filename, s.file_path, s.file_size, s.format, s.category, s.preview_path, 
+                    s.preview_generated_at, s.created_at, s.updated_at,
+                    m.size_x, m.size_y, m.size_z, m.triangle_count, m.surface_area, m.volume,
+                    m.estimated_mass, m.source_size, m.source_mtime, m.analyzed_at, m.geometry_fingerprint
+             FROM stl_files s
+             LEFT JOIN mesh_analysis m ON m.stl_file_id = s.id
+             WHERE s.project_id = ?1 ORDER BY s.category NULLS FIRST, s.filename",
         )?;
 
         let files = stmt
             .query_map(params![project_id], |row| {
+                let id: i64 = row.get(0)?;
+                let analyzed_at: Option<i64> = row.get(20)?;
+                let analysis = match analyzed_at {
+                    Some(analyzed_at) => Some(MeshAnalysis {
+                        stl_file_id: id,
+                        size_x: row.get(11)?,
+                        size_y: row.get(12)?,
+                        size_z: row.get(13)?,
+                        triangle_count: row.get(14)?,
+                        surface_area: row.get(15)?,
+                        volume: row.get(16)?,
+                        estimated_mass: row.get(17)?,
+                        geometry_fingerprint: row.get(21)?,
+                        source_size: row.get(18)?,
+                        source_mtime: row.get(19)?,
+                        analyzed_at,
+                    }),
+                    None => None,
+                };
+
                 Ok(StlFile {
-                    id: row.get(0)?,
+                    id,
                     project_id: row.get(1)?,
                     filename: row.get(2)?,
                     file_path: row.get(3)?,
                     file_size: row.get(4)?,
-                    category: row.get(5)?,
-                    preview_path: row.get(6)?,
-                    preview_generated_at: row.get(7)?,
-                    created_at: row.get(8)?,
-                    updated_at: row.get(9)?,
+                    format: row.get(5)?,
+                    category: row.get(6)?,
+                    preview_path: row.get(7)?,
+                    preview_generated_at: row.get(8)?,
+                    created_at: row.get(9)?,
+                    updated_at: row.get(10)?,
+                    analysis,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -80,8 +150,8 @@ impl FileRepository {
         conn.execute(
             "INSERT INTO image_files (project_id, filename, file_path, file_size, source_type, 
                                      source_project_id, display_order, image_priority, image_source, 
-                                     created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                                     file_mtime, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
             params![
                 file.project_id,
                 file.filename,
@@ -92,6 +162,7 @@ impl FileRepository {
                 file.display_order,
                 file.image_priority,
                 file.image_source,
+                file.file_mtime,
                 now,
                 now
             ],
@@ -158,12 +229,17 @@ impl FileRepository {
         file_size: i64,
         category: Option<&str>,
     ) -> Result<i64, AppError> {
+        // Format follows the file extension; anything unrecognised is stored as STL
+        let format = ModelFormat::from_path(Path::new(file_path)).unwrap_or(ModelFormat::Stl);
+
         let file = CreateStlFile {
             project_id,
             filename: filename.to_string(),
             file_path: file_path.to_string(),
             file_size,
+            format: format.as_str().to_string(),
             category: category.map(|s| s.to_string()),
+            file_mtime: file_mtime(Path::new(file_path)),
         };
         self.create_stl_file(&file)
     }
@@ -189,6 +265,7 @@ impl FileRepository {
             display_order,
             image_priority: 100, // Default: regular images
             image_source: "regular".to_string(),
+            file_mtime: file_mtime(Path::new(file_path)),
         };
         self.create_image_file(&file)
     }
@@ -211,6 +288,7 @@ impl FileRepository {
             display_order: 0,
             image_priority: 50, // STL previews
             image_source: "stl_preview".to_string(),
+            file_mtime: file_mtime(Path::new(file_path)),
         };
         self.create_image_file(&file)
     }
@@ -275,12 +353,68 @@ impl FileRepository {
         Ok(count)
     }
 
+    /// Record the size and mtime of a model file that changed on disk
+    pub fn update_stl_file_signature(
+        &self,
+        id: i64,
+        file_size: i64,
+        file_mtime: i64,
+    ) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        conn.execute(
+            "UPDATE stl_files SET file_size = ?1, file_mtime = ?2, updated_at = ?3 WHERE id = ?4",
+            params![file_size, file_mtime, now, id],
+        )?;
+        Ok(())
+    }
+
+    /// Forget the rendered preview of a model file so it is generated again
+    pub fn reset_stl_preview(&self, id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE stl_files SET preview_path = NULL, preview_generated_at = NULL WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// Record the size and mtime of an image that changed on disk
+    pub fn update_image_file_signature(
+        &self,
+        id: i64,
+        file_size: i64,
+        file_mtime: i64,
+    ) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        conn.execute(
+            "UPDATE image_files SET file_size = ?1, file_mtime = ?2, updated_at = ?3 WHERE id = ?4",
+            params![file_size, file_mtime, now, id],
+        )?;
+        Ok(())
+    }
+
     pub fn delete_stl_file(&self, id: i64) -> Result<(), AppError> {
         let conn = self.pool.get()?;
         conn.execute("DELETE FROM stl_files WHERE id = ?1", params![id])?;
         Ok(())
     }
 
+    /// Remove the images a project inherited from its parent folders
+    pub fn delete_inherited_images(&self, project_id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "DELETE FROM image_files WHERE project_id = ?1 AND source_type = 'inherited'",
+            params![project_id],
+        )?;
+        Ok(())
+    }
+
     pub fn delete_image_file(&self, id: i64) -> Result<(), AppError> {
         let conn = self.pool.get()?;
         conn.execute("DELETE FROM image_files WHERE id = ?1", params![id])?;
@@ -451,6 +585,7 @@ mod tests {
                 display_order INTEGER NOT NULL,
                 image_priority INTEGER NOT NULL,
                 image_source TEXT NOT NULL,
+                file_mtime INTEGER,
                 created_at INTEGER NOT NULL,
                 updated_at INTEGER NOT NULL,
                 FOREIGN KEY(project_id) REFERENCES projects(id)
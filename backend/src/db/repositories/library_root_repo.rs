@@ -0,0 +1,99 @@
+use crate::db::connection::DbPool;
+use crate::models::library_root::{CreateLibraryRoot, LibraryRoot};
+use crate::utils::error::AppError;
+use rusqlite::{params, OptionalExtension, Row};
+
+pub struct LibraryRootRepository {
+    pool: DbPool,
+}
+
+impl LibraryRootRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    pub fn list(&self) -> Result<Vec<LibraryRoot>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, path, last_scan_at, created_at FROM library_roots ORDER BY id",
+        )?;
+
+        let roots = stmt
+            .query_map([], root_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(roots)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<LibraryRoot>, AppError> {
+        let conn = self.pool.get()?;
+        let root = conn
+            .query_row(
+                "SELECT id, name, path, last_scan_at, created_at FROM library_roots WHERE id = ?1",
+                params![id],
+                root_from_row,
+            )
+            .optional()?;
+
+        Ok(root)
+    }
+
+    /// Add a root and claim the projects already indexed below its path
+    pub fn create(&self, root: &CreateLibraryRoot) -> Result<i64, AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        conn.execute(
+            "INSERT INTO library_roots (name, path, created_at) VALUES (?1, ?2, ?3)",
+            params![root.name, root.path, now],
+        )?;
+        let id = conn.last_insert_rowid();
+
+        conn.execute(
+            "UPDATE projects SET root_id = ?2
+             WHERE root_id IS NULL
+               AND (full_path = ?1 OR substr(full_path, 1, length(?1) + 1) = ?1 || '/')",
+            params![root.path, id],
+        )?;
+
+        Ok(id)
+    }
+
+    /// Remove a root together with everything indexed from it
+    pub fn delete(&self, id: i64) -> Result<bool, AppError> {
+        let conn = self.pool.get()?;
+
+        // Files, images and tags of the root's projects go with them
+        conn.execute("PRAGMA foreign_keys = ON", [])?;
+        conn.execute("DELETE FROM projects WHERE root_id = ?1", params![id])?;
+        let deleted = conn.execute("DELETE FROM library_roots WHERE id = ?1", params![id])?;
+
+        Ok(deleted > 0)
+    }
+
+    pub fn update_last_scan(&self, id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        conn.execute(
+            "UPDATE library_roots SET last_scan_at = ?1 WHERE id = ?2",
+            params![now, id],
+        )?;
+
+        Ok(())
+    }
+}
+
+fn root_from_row(row: &Row) -> rusqlite::Result<LibraryRoot> {
+    Ok(LibraryRoot {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        last_scan_at: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
@@ -1,5 +1,10 @@
+pub mod collection_repo;
+pub mod creator_repo;
 pub mod file_repo;
 pub mod inheritance_repo;
+pub mod library_root_repo;
 pub mod preview_repo;
 pub mod project_repo;
+pub mod release_repo;
+pub mod scan_session_repo;
 pub mod tag_repo;
@@ -1,8 +1,14 @@
 use crate::db::connection::DbPool;
-use crate::models::project::{CreateProject, Project, ProjectWithRelations};
+use crate::models::creator::Creator;
+use crate::models::duplicate::DuplicateProject;
+use crate::models::project::{
+    CreateProject, Project, ProjectMetadata, ProjectWithRelations, UpdateProject,
+};
 use crate::models::tag::Tag;
 use crate::utils::error::AppError;
 use rusqlite::{params, OptionalExtension};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 pub struct ProjectRepository {
     pub(crate) pool: DbPool,
@@ -34,9 +40,15 @@ impl ProjectRepository {
             0
         };
 
+        // The project belongs to the library root its path lies in
         conn.execute(
-            "INSERT INTO projects (name, full_path, parent_id, is_leaf, folder_level, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO projects (name, full_path, parent_id, is_leaf, folder_level, created_at, updated_at, root_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, (
+                 SELECT r.id FROM library_roots r
+                 WHERE ?2 = r.path OR substr(?2, 1, length(r.path) + 1) = r.path || '/'
+                 ORDER BY length(r.path) DESC
+                 LIMIT 1
+             ))",
             params![
                 project.name,
                 project.full_path,
@@ -51,10 +63,131 @@ impl ProjectRepository {
         Ok(conn.last_insert_rowid())
     }
 
+    /// Create the projects for `folders`, and for every folder between them and `root`,
+    /// in one transaction. Existing projects are reused and `folders` are marked as leaves.
+    /// Returns the project id of every folder touched.
+    pub fn ensure_hierarchy(
+        &self,
+        root: &Path,
+        folders: &[&Path],
+    ) -> Result<HashMap<PathBuf, i64>, AppError> {
+        // Parents sort before their children, so each parent id is known when it is needed
+        let mut wanted: BTreeMap<&Path, bool> = BTreeMap::new();
+        for folder in folders {
+            wanted.insert(folder, true);
+            for ancestor in folder.ancestors().skip(1).take_while(|a| *a >= root) {
+                wanted.entry(ancestor).or_insert(false);
+            }
+        }
+
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let tx = conn.transaction()?;
+        let mut ids: HashMap<PathBuf, i64> = HashMap::new();
+        let mut levels: HashMap<i64, i32> = HashMap::new();
+        {
+            let mut select =
+                tx.prepare("SELECT id, is_leaf, folder_level FROM projects WHERE full_path = ?1")?;
+            let mut mark_leaf = tx.prepare(
+                "UPDATE projects SET is_leaf = 1, updated_at = ?2 WHERE id = ?1 AND is_leaf = 0",
+            )?;
+            let mut insert = tx.prepare(
+                "INSERT INTO projects (name, full_path, parent_id, is_leaf, folder_level, created_at, updated_at, root_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, (
+                     SELECT r.id FROM library_roots r
+                     WHERE ?2 = r.path OR substr(?2, 1, length(r.path) + 1) = r.path || '/'
+                     ORDER BY length(r.path) DESC
+                     LIMIT 1
+                 ))",
+            )?;
+
+            for (folder, is_leaf) in wanted {
+                let full_path = folder.to_str().unwrap_or("");
+                let existing = select
+                    .query_row(params![full_path], |row| {
+                        Ok((row.get::<_, i64>(0)?, row.get::<_, bool>(1)?, row.get(2)?))
+                    })
+                    .optional()?;
+
+                let id = match existing {
+                    Some((id, leaf, level)) => {
+                        if is_leaf && !leaf {
+                            mark_leaf.execute(params![id, now])?;
+                        }
+                        levels.insert(id, level);
+                        id
+                    }
+                    None => {
+                        let parent_id = if folder != root {
+                            folder.parent().and_then(|parent| ids.get(parent).copied())
+                        } else {
+                            None
+                        };
+                        let level = parent_id
+                            .and_then(|parent_id| levels.get(&parent_id))
+                            .map_or(0, |level| level + 1);
+                        let name = folder
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("Unknown");
+                        insert.execute(params![name, full_path, parent_id, is_leaf, level, now])?;
+                        let id = tx.last_insert_rowid();
+                        levels.insert(id, level);
+                        id
+                    }
+                };
+                ids.insert(folder.to_path_buf(), id);
+            }
+        }
+        tx.commit()?;
+
+        Ok(ids)
+    }
+
+    /// Replace the folder links found under `scope` by the links of the latest walk,
+    /// both those leading into the library and the followed ones leading outside
+    pub fn replace_folder_links(
+        &self,
+        scope: &Path,
+        links: &[(PathBuf, PathBuf)],
+        external_links: &[(PathBuf, PathBuf)],
+    ) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let scope = scope.to_str().unwrap_or("");
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM folder_links
+             WHERE link_path = ?1 OR substr(link_path, 1, length(?1) + 1) = ?1 || '/'",
+            params![scope],
+        )?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO folder_links
+                     (link_path, target_path, external, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (link, target) in links {
+                insert.execute(params![link.to_str(), target.to_str(), false, now])?;
+            }
+            for (link, target) in external_links {
+                insert.execute(params![link.to_str(), target.to_str(), true, now])?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
     pub fn get_by_id(&self, id: i64) -> Result<Option<Project>, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects WHERE id = ?1",
         )?;
 
@@ -70,6 +203,7 @@ impl ProjectRepository {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })
             .optional()?;
@@ -80,7 +214,7 @@ impl ProjectRepository {
     pub fn get_by_path(&self, path: &str) -> Result<Option<Project>, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects WHERE full_path = ?1",
         )?;
 
@@ -96,6 +230,7 @@ impl ProjectRepository {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })
             .optional()?;
@@ -106,7 +241,7 @@ impl ProjectRepository {
     pub fn list_root(&self) -> Result<Vec<Project>, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects WHERE parent_id IS NULL ORDER BY name",
         )?;
 
@@ -122,6 +257,7 @@ impl ProjectRepository {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -132,7 +268,7 @@ impl ProjectRepository {
     pub fn list_children(&self, parent_id: i64) -> Result<Vec<Project>, AppError> {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects WHERE parent_id = ?1 ORDER BY name",
         )?;
 
@@ -148,6 +284,7 @@ impl ProjectRepository {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -197,8 +334,55 @@ impl ProjectRepository {
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
+        // Other projects sharing identical model files
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.name, p.full_path, COUNT(DISTINCT o.content_hash) as shared_files
+             FROM stl_files s
+             INNER JOIN stl_files o ON o.content_hash = s.content_hash AND o.project_id != s.project_id
+             INNER JOIN projects p ON p.id = o.project_id
+             WHERE s.project_id = ?1 AND s.content_hash IS NOT NULL
+             GROUP BY p.id
+             ORDER BY shared_files DESC, p.full_path",
+        )?;
+
+        let also_appears_in = stmt
+            .query_map(params![id], |row| {
+                Ok(DuplicateProject {
+                    project_id: row.get(0)?,
+                    name: row.get(1)?,
+                    full_path: row.get(2)?,
+                    shared_files: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
         // T037: Fetch inherited images with preview metadata
         let inherited_images = self.get_project_preview_images(id)?;
+        let metadata = self.get_metadata(id)?;
+        let creator = conn
+            .query_row(
+                "SELECT c.id, c.name, c.created_at
+                 FROM creators c
+                 INNER JOIN projects p ON p.creator_id = c.id
+                 WHERE p.id = ?1",
+                params![id],
+                |row| {
+                    Ok(Creator {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        created_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+        let collection_count: usize = conn.query_row(
+            "SELECT COUNT(DISTINCT ci.collection_id)
+             FROM collection_items ci
+             LEFT JOIN stl_files s ON s.id = ci.stl_file_id
+             WHERE ci.project_id = ?1 OR s.project_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
 
         Ok(Some(ProjectWithRelations {
             project,
@@ -207,9 +391,106 @@ impl ProjectRepository {
             image_count,
             inherited_images,
             tags,
+            also_appears_in,
+            metadata,
+            creator,
+            collection_count,
         }))
     }
 
+    /// Metadata imported from the project's sidecar files or edited
+    pub fn get_metadata(&self, project_id: i64) -> Result<Option<ProjectMetadata>, AppError> {
+        let conn = self.pool.get()?;
+        let metadata = conn
+            .query_row(
+                "SELECT author, license, source_url, release_date, scale, print_settings
+                 FROM project_metadata WHERE project_id = ?1",
+                params![project_id],
+                |row| {
+                    Ok(ProjectMetadata {
+                        author: row.get(0)?,
+                        license: row.get(1)?,
+                        source_url: row.get(2)?,
+                        release_date: row.get(3)?,
+                        scale: row.get(4)?,
+                        print_settings: row
+                            .get::<_, Option<String>>(5)?
+                            .and_then(|settings| serde_json::from_str(&settings).ok()),
+                    })
+                },
+            )
+            .optional()?;
+        Ok(metadata)
+    }
+
+    /// Apply an edit of a project's description and metadata. Fields left out are kept;
+    /// empty strings clear them. The sidecar signature is kept, so the edits stay until
+    /// the sidecar files change.
+    pub fn update_details(&self, id: i64, update: &UpdateProject) -> Result<(), AppError> {
+        let cleared = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|v| Some(v.trim()).filter(|v| !v.is_empty()).map(str::to_string))
+        };
+        let mut metadata = self.get_metadata(id)?.unwrap_or_default();
+        let fields = [
+            (&update.author, &mut metadata.author),
+            (&update.license, &mut metadata.license),
+            (&update.source_url, &mut metadata.source_url),
+            (&update.release_date, &mut metadata.release_date),
+            (&update.scale, &mut metadata.scale),
+        ];
+        for (value, field) in fields {
+            if let Some(value) = cleared(value) {
+                *field = value;
+            }
+        }
+
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        let tx = conn.transaction()?;
+        let updated = match update.description.as_deref() {
+            Some(description) => tx.execute(
+                "UPDATE projects SET description = ?1, updated_at = ?2 WHERE id = ?3",
+                params![Some(description).filter(|d| !d.trim().is_empty()), now, id],
+            )?,
+            None => tx.execute(
+                "UPDATE projects SET updated_at = ?1 WHERE id = ?2",
+                params![now, id],
+            )?,
+        };
+        if updated == 0 {
+            return Err(AppError::NotFound(format!("Project {} not found", id)));
+        }
+        tx.execute(
+            "INSERT INTO project_metadata
+                 (project_id, author, license, source_url, release_date, scale, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(project_id) DO UPDATE SET
+                 author = excluded.author,
+                 license = excluded.license,
+                 source_url = excluded.source_url,
+                 release_date = excluded.release_date,
+                 scale = excluded.scale,
+                 updated_at = excluded.updated_at",
+            params![
+                id,
+                metadata.author,
+                metadata.license,
+                metadata.source_url,
+                metadata.release_date,
+                metadata.scale,
+                now
+            ],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
     /// T037, T039: Get preview images for a project (all images for carousel)
     /// Returns all images prioritized: direct images > inherited > STL previews
     pub fn get_project_preview_images(
@@ -269,6 +550,23 @@ impl ProjectRepository {
         Ok(())
     }
 
+    /// Recompute is_leaf from whether the project holds model files of its own
+    pub fn refresh_is_leaf(&self, id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        conn.execute(
+            "UPDATE projects
+             SET is_leaf = EXISTS (SELECT 1 FROM stl_files WHERE project_id = ?1), updated_at = ?2
+             WHERE id = ?1
+               AND is_leaf != EXISTS (SELECT 1 FROM stl_files WHERE project_id = ?1)",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
     pub fn delete(&self, id: i64) -> Result<(), AppError> {
         let conn = self.pool.get()?;
         conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
@@ -279,15 +577,15 @@ impl ProjectRepository {
         let conn = self.pool.get()?;
         let mut stmt = conn.prepare(
             "WITH RECURSIVE parent_chain AS (
-                SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, 0 as level
+                SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id, 0 as level
                 FROM projects
                 WHERE id = ?1
                 UNION ALL
-                SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.created_at, p.updated_at, pc.level + 1
+                SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at, p.root_id, pc.level + 1
                 FROM projects p
                 JOIN parent_chain pc ON p.id = pc.parent_id
             )
-            SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
             FROM parent_chain
             ORDER BY level DESC"
         )?;
@@ -304,6 +602,7 @@ impl ProjectRepository {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -311,6 +610,14 @@ impl ProjectRepository {
         Ok(projects)
     }
 
+    /// Remove every project of one library root, with its files and images
+    pub fn clear_root(&self, root_id: i64) -> Result<usize, AppError> {
+        let conn = self.pool.get()?;
+        conn.execute("PRAGMA foreign_keys = ON", [])?;
+        let count = conn.execute("DELETE FROM projects WHERE root_id = ?1", params![root_id])?;
+        Ok(count)
+    }
+
     /// Clear all projects and related data from the database
     pub fn clear_all(&self) -> Result<(), AppError> {
         let conn = self.pool.get()?;
@@ -322,10 +629,20 @@ impl ProjectRepository {
         // Note: Tables may not exist on first run, so we ignore errors
         let _ = conn.execute("DELETE FROM image_inheritance", []);
         let _ = conn.execute("DELETE FROM project_tags", []);
+        let _ = conn.execute("DELETE FROM project_metadata", []);
         let _ = conn.execute("DELETE FROM project_previews", []);
         let _ = conn.execute("DELETE FROM stl_files", []);
         let _ = conn.execute("DELETE FROM image_files", []);
         let _ = conn.execute("DELETE FROM cached_files", []);
+        // Collections and releases lose their items; detected releases come back with
+        // the next scan
+        let _ = conn.execute("DELETE FROM collection_items", []);
+        let _ = conn.execute("DELETE FROM release_projects", []);
+        let _ = conn.execute("DELETE FROM releases WHERE auto_detected = 1", []);
+        let _ = conn.execute(
+            "UPDATE releases SET folder_project_id = NULL, cover_path = NULL",
+            [],
+        );
         let _ = conn.execute("DELETE FROM projects", []);
 
         // Also clear FTS index
@@ -0,0 +1,566 @@
+use crate::db::connection::DbPool;
+use crate::db::repositories::creator_repo::get_or_create;
+use crate::models::creator::Creator;
+use crate::models::project::Project;
+use crate::models::release::{
+    CreateRelease, Release, ReleaseSummary, ReleaseWithProjects, UpdateRelease,
+};
+use crate::utils::error::AppError;
+use crate::utils::release_pattern::ReleasePattern;
+use rusqlite::{params, Connection, OptionalExtension, Row, Transaction};
+use std::collections::{HashMap, HashSet};
+
+/// Images combined into a release cover
+const COVER_IMAGES: usize = 4;
+
+const RELEASE_COLUMNS: &str = "r.id, r.name, r.description, r.creator_id, r.release_date,
+    r.folder_project_id, r.auto_detected, r.created_at, r.updated_at,
+    c.id, c.name, c.created_at";
+
+pub struct ReleaseRepository {
+    pool: DbPool,
+}
+
+impl ReleaseRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Releases, newest first, optionally of one creator or whose name contains `query`
+    pub fn list(
+        &self,
+        creator_id: Option<i64>,
+        query: Option<&str>,
+    ) -> Result<Vec<ReleaseSummary>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {},
+                    (SELECT COUNT(*) FROM release_projects rp WHERE rp.release_id = r.id)
+             FROM releases r
+             LEFT JOIN creators c ON c.id = r.creator_id
+             WHERE (?1 IS NULL OR r.creator_id = ?1)
+               AND (?2 IS NULL OR instr(lower(r.name), lower(?2)) > 0)
+             ORDER BY r.release_date IS NULL, r.release_date DESC, r.name COLLATE NOCASE, r.id",
+            RELEASE_COLUMNS
+        ))?;
+        let releases = stmt
+            .query_map(params![creator_id, query], |row| {
+                Ok(ReleaseSummary {
+                    release: release_from_row(row)?,
+                    creator: creator_from_row(row)?,
+                    project_count: row.get(12)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(releases)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<ReleaseWithProjects>, AppError> {
+        let conn = self.pool.get()?;
+        let release = conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM releases r
+                     LEFT JOIN creators c ON c.id = r.creator_id
+                     WHERE r.id = ?1",
+                    RELEASE_COLUMNS
+                ),
+                params![id],
+                |row| Ok((release_from_row(row)?, creator_from_row(row)?)),
+            )
+            .optional()?;
+        let Some((release, creator)) = release else {
+            return Ok(None);
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level,
+                    p.created_at, p.updated_at, p.root_id
+             FROM release_projects rp
+             INNER JOIN projects p ON p.id = rp.project_id
+             WHERE rp.release_id = ?1
+             ORDER BY rp.position",
+        )?;
+        let projects = stmt
+            .query_map(params![id], |row| {
+                Ok(Project {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    full_path: row.get(2)?,
+                    parent_id: row.get(3)?,
+                    is_leaf: row.get(4)?,
+                    description: row.get(5)?,
+                    folder_level: row.get(6)?,
+                    created_at: row.get(7)?,
+                    updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(ReleaseWithProjects {
+            release,
+            creator,
+            projects,
+        }))
+    }
+
+    pub fn create(&self, release: &CreateRelease) -> Result<i64, AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let now = now()?;
+
+        let creator_id = match non_empty(release.creator.as_deref()) {
+            Some(name) => Some(get_or_create(&tx, name)?),
+            None => None,
+        };
+        tx.execute(
+            "INSERT INTO releases (name, description, creator_id, release_date, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![
+                release.name.trim(),
+                non_empty(release.description.as_deref()),
+                creator_id,
+                non_empty(release.release_date.as_deref()),
+                now
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+
+        check_projects_exist(&tx, &release.project_ids)?;
+        replace_members(&tx, id, &release.project_ids)?;
+        tx.commit()?;
+
+        Ok(id)
+    }
+
+    /// Update the given fields. A detected release is no longer kept in sync with its
+    /// folder afterwards.
+    pub fn update(&self, id: i64, update: &UpdateRelease) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        mark_edited(&tx, id)?;
+
+        if let Some(ref name) = update.name {
+            tx.execute(
+                "UPDATE releases SET name = ?1 WHERE id = ?2",
+                params![name.trim(), id],
+            )?;
+        }
+        if let Some(ref description) = update.description {
+            tx.execute(
+                "UPDATE releases SET description = ?1 WHERE id = ?2",
+                params![non_empty(Some(description)), id],
+            )?;
+        }
+        if let Some(ref creator) = update.creator {
+            let creator_id = match non_empty(Some(creator)) {
+                Some(name) => Some(get_or_create(&tx, name)?),
+                None => None,
+            };
+            tx.execute(
+                "UPDATE releases SET creator_id = ?1 WHERE id = ?2",
+                params![creator_id, id],
+            )?;
+        }
+        if let Some(ref release_date) = update.release_date {
+            tx.execute(
+                "UPDATE releases SET release_date = ?1 WHERE id = ?2",
+                params![non_empty(Some(release_date)), id],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn delete(&self, id: i64) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let deleted = conn.execute("DELETE FROM releases WHERE id = ?1", params![id])?;
+        if deleted == 0 {
+            return Err(AppError::NotFound(format!("Release {} not found", id)));
+        }
+
+        Ok(())
+    }
+
+    /// Replace the projects of a release, in the given order
+    pub fn set_projects(&self, id: i64, project_ids: &[i64]) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        mark_edited(&tx, id)?;
+        check_projects_exist(&tx, project_ids)?;
+        replace_members(&tx, id, project_ids)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Add a project at `position` (appended when None); a project already in the
+    /// release is moved there
+    pub fn add_project(
+        &self,
+        id: i64,
+        project_id: i64,
+        position: Option<usize>,
+    ) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        mark_edited(&tx, id)?;
+        check_projects_exist(&tx, &[project_id])?;
+
+        let mut project_ids = member_ids(&tx, id)?;
+        project_ids.retain(|&p| p != project_id);
+        let position = position.unwrap_or(project_ids.len()).min(project_ids.len());
+        project_ids.insert(position, project_id);
+        replace_members(&tx, id, &project_ids)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn remove_project(&self, id: i64, project_id: i64) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        mark_edited(&tx, id)?;
+
+        let mut project_ids = member_ids(&tx, id)?;
+        let count = project_ids.len();
+        project_ids.retain(|&p| p != project_id);
+        if project_ids.len() == count {
+            return Err(AppError::NotFound(format!(
+                "Project {} is not in release {}",
+                project_id, id
+            )));
+        }
+        replace_members(&tx, id, &project_ids)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Images for the release cover: the best image of each project in turn, until
+    /// there are enough
+    pub fn cover_images(&self, id: i64) -> Result<Vec<String>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT rp.project_id, i.file_path
+             FROM release_projects rp
+             INNER JOIN image_files i ON i.project_id = rp.project_id
+             WHERE rp.release_id = ?1
+             ORDER BY rp.position, i.source_type = 'direct' DESC, i.image_priority DESC,
+                      i.display_order ASC, i.created_at ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut per_project: Vec<Vec<String>> = Vec::new();
+        let mut last_project = None;
+        for (project_id, path) in rows {
+            if last_project != Some(project_id) {
+                per_project.push(Vec::new());
+                last_project = Some(project_id);
+            }
+            if let Some(paths) = per_project.last_mut() {
+                paths.push(path);
+            }
+        }
+
+        // Projects inheriting the same image only contribute it once
+        let mut seen = HashSet::new();
+        let mut images = Vec::new();
+        let rounds = per_project.iter().map(Vec::len).max().unwrap_or(0);
+        for round in 0..rounds {
+            for paths in &per_project {
+                if images.len() == COVER_IMAGES {
+                    return Ok(images);
+                }
+                if let Some(path) = paths.get(round) {
+                    if seen.insert(path.clone()) {
+                        images.push(path.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(images)
+    }
+
+    /// Path of the generated cover, if it is still current
+    pub fn cover_path(&self, id: i64) -> Result<Option<String>, AppError> {
+        let conn = self.pool.get()?;
+        conn.query_row(
+            "SELECT cover_path FROM releases WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::NotFound(format!("Release {} not found", id)))
+    }
+
+    pub fn set_cover_path(&self, id: i64, cover_path: &str) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE releases SET cover_path = ?1 WHERE id = ?2",
+            params![cover_path, id],
+        )?;
+
+        Ok(())
+    }
+
+    /// Detect releases from folders whose names match `pattern`. A detected release
+    /// holds the projects with model files in its folder and takes the folder's name
+    /// and creator. Detected releases are updated to follow their folder, and removed
+    /// when it is gone or no longer matches; releases edited by hand are left alone.
+    /// Returns the number of releases created, updated or removed.
+    pub fn assign_releases(&self, pattern: Option<&ReleasePattern>) -> Result<usize, AppError> {
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        let now = now()?;
+        let mut changed = 0;
+        let mut folders = HashSet::new();
+
+        if let Some(pattern) = pattern {
+            struct ProjectRow {
+                id: i64,
+                parent_id: Option<i64>,
+                name: String,
+                is_leaf: bool,
+                creator_id: Option<i64>,
+            }
+
+            let rows = tx
+                .prepare(
+                    "SELECT id, parent_id, name, is_leaf, creator_id FROM projects
+                     ORDER BY full_path",
+                )?
+                .query_map([], |row| {
+                    Ok(ProjectRow {
+                        id: row.get(0)?,
+                        parent_id: row.get(1)?,
+                        name: row.get(2)?,
+                        is_leaf: row.get(3)?,
+                        creator_id: row.get(4)?,
+                    })
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut children: HashMap<i64, Vec<usize>> = HashMap::new();
+            for (idx, row) in rows.iter().enumerate() {
+                if let Some(parent_id) = row.parent_id {
+                    children.entry(parent_id).or_default().push(idx);
+                }
+            }
+
+            for (idx, folder) in rows.iter().enumerate() {
+                let Some(release_date) = pattern.match_name(&folder.name) else {
+                    continue;
+                };
+                folders.insert(folder.id);
+
+                // Projects with model files in the folder, in path order
+                let mut project_ids = Vec::new();
+                let mut stack = vec![idx];
+                while let Some(idx) = stack.pop() {
+                    let row = &rows[idx];
+                    if row.is_leaf {
+                        project_ids.push(row.id);
+                    }
+                    if let Some(child_indices) = children.get(&row.id) {
+                        stack.extend(child_indices.iter().rev());
+                    }
+                }
+
+                let existing = tx
+                    .query_row(
+                        "SELECT id, auto_detected, name, release_date, creator_id FROM releases
+                         WHERE folder_project_id = ?1",
+                        params![folder.id],
+                        |row| {
+                            Ok((
+                                row.get::<_, i64>(0)?,
+                                row.get::<_, bool>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, Option<String>>(3)?,
+                                row.get::<_, Option<i64>>(4)?,
+                            ))
+                        },
+                    )
+                    .optional()?;
+
+                match existing {
+                    // Edited by hand
+                    Some((_, false, ..)) => {}
+                    Some((id, true, name, date, creator_id)) => {
+                        let mut updated = replace_members(&tx, id, &project_ids)?;
+                        if name != folder.name
+                            || date != release_date
+                            || creator_id != folder.creator_id
+                        {
+                            tx.execute(
+                                "UPDATE releases
+                                 SET name = ?1, release_date = ?2, creator_id = ?3, updated_at = ?4
+                                 WHERE id = ?5",
+                                params![folder.name, release_date, folder.creator_id, now, id],
+                            )?;
+                            updated = true;
+                        }
+                        if updated {
+                            changed += 1;
+                        }
+                    }
+                    None => {
+                        tx.execute(
+                            "INSERT INTO releases (name, creator_id, release_date, folder_project_id,
+                                                   auto_detected, created_at, updated_at)
+                             VALUES (?1, ?2, ?3, ?4, 1, ?5, ?5)",
+                            params![
+                                folder.name,
+                                folder.creator_id,
+                                release_date,
+                                folder.id,
+                                now
+                            ],
+                        )?;
+                        replace_members(&tx, tx.last_insert_rowid(), &project_ids)?;
+                        changed += 1;
+                    }
+                }
+            }
+        }
+
+        let stale = tx
+            .prepare("SELECT id, folder_project_id FROM releases WHERE auto_detected = 1")?
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|(_, folder)| !folder.is_some_and(|f| folders.contains(&f)))
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>();
+        for id in stale {
+            tx.execute("DELETE FROM releases WHERE id = ?1", params![id])?;
+            changed += 1;
+        }
+        tx.commit()?;
+
+        Ok(changed)
+    }
+}
+
+/// Stop syncing a release with its folder, failing if it does not exist
+fn mark_edited(tx: &Transaction, id: i64) -> Result<(), AppError> {
+    let updated = tx.execute(
+        "UPDATE releases SET auto_detected = 0, updated_at = ?1 WHERE id = ?2",
+        params![now()?, id],
+    )?;
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Release {} not found", id)));
+    }
+
+    Ok(())
+}
+
+fn check_projects_exist(conn: &Connection, project_ids: &[i64]) -> Result<(), AppError> {
+    for &project_id in project_ids {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM projects WHERE id = ?1)",
+            params![project_id],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(AppError::ValidationError(format!(
+                "Project {} not found",
+                project_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+fn member_ids(conn: &Connection, release_id: i64) -> Result<Vec<i64>, AppError> {
+    let ids = conn
+        .prepare("SELECT project_id FROM release_projects WHERE release_id = ?1 ORDER BY position")?
+        .query_map(params![release_id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ids)
+}
+
+/// Store the projects of a release in order, dropping repeated ids. Returns whether
+/// they changed; the cover is generated again when they did.
+fn replace_members(
+    tx: &Transaction,
+    release_id: i64,
+    project_ids: &[i64],
+) -> Result<bool, AppError> {
+    let mut seen = HashSet::new();
+    let project_ids: Vec<i64> = project_ids
+        .iter()
+        .copied()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    if member_ids(tx, release_id)? == project_ids {
+        return Ok(false);
+    }
+
+    tx.execute(
+        "DELETE FROM release_projects WHERE release_id = ?1",
+        params![release_id],
+    )?;
+    for (position, project_id) in project_ids.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO release_projects (release_id, project_id, position) VALUES (?1, ?2, ?3)",
+            params![release_id, project_id, position as i64],
+        )?;
+    }
+    tx.execute(
+        "UPDATE releases SET cover_path = NULL WHERE id = ?1",
+        params![release_id],
+    )?;
+
+    Ok(true)
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn now() -> Result<i64, AppError> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64)
+}
+
+fn release_from_row(row: &Row) -> rusqlite::Result<Release> {
+    Ok(Release {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        creator_id: row.get(3)?,
+        release_date: row.get(4)?,
+        folder_project_id: row.get(5)?,
+        auto_detected: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+    })
+}
+
+/// The creator joined after the release columns, if the release has one
+fn creator_from_row(row: &Row) -> rusqlite::Result<Option<Creator>> {
+    let Some(id) = row.get::<_, Option<i64>>(9)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(Creator {
+        id,
+        name: row.get(10)?,
+        created_at: row.get(11)?,
+    }))
+}
@@ -0,0 +1,191 @@
+use crate::db::connection::DbPool;
+use crate::models::scan_session::{
+    ScanCounts, ScanMode, ScanSession, ScanSessionDetail, ScanSessionStatus, ScanTrigger,
+};
+use crate::utils::error::AppError;
+use crate::utils::pagination::PaginationParams;
+use rusqlite::{params, OptionalExtension, Row};
+
+const SESSION_COLUMNS: &str = "id, root_path, scan_path, mode, status, started_at, completed_at,
+     projects_found, projects_added, projects_updated, projects_removed,
+     files_processed, files_added, files_updated, files_removed,
+     stl_previews_generated, stl_previews_queued, stl_previews_cached, errors_count, triggered_by";
+
+pub struct ScanSessionRepository {
+    pool: DbPool,
+}
+
+impl ScanSessionRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a scan that is starting and return its session id
+    pub fn start(
+        &self,
+        root_path: &str,
+        scan_path: Option<&str>,
+        mode: ScanMode,
+        trigger: ScanTrigger,
+    ) -> Result<i64, AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        conn.execute(
+            "INSERT INTO scan_sessions (root_path, scan_path, mode, triggered_by, status, started_at)
+             VALUES (?1, ?2, ?3, ?4, 'running', ?5)",
+            params![root_path, scan_path, mode.as_str(), trigger.as_str(), now],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record how a scan ended, with its counters and full error list
+    pub fn finish(
+        &self,
+        id: i64,
+        status: ScanSessionStatus,
+        counts: &ScanCounts,
+        errors: &[String],
+    ) -> Result<(), AppError> {
+        let conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let error_log = if errors.is_empty() {
+            None
+        } else {
+            serde_json::to_string(errors).ok()
+        };
+
+        conn.execute(
+            "UPDATE scan_sessions
+             SET status = ?1, completed_at = ?2,
+                 projects_found = ?3, projects_added = ?4, projects_updated = ?5,
+                 projects_removed = ?6, files_processed = ?7, files_added = ?8,
+                 files_updated = ?9, files_removed = ?10, stl_previews_generated = ?11,
+                 stl_previews_queued = ?12, stl_previews_cached = ?13,
+                 errors_count = ?14, error_log = ?15
+             WHERE id = ?16",
+            params![
+                status.as_str(),
+                now,
+                counts.projects_found as i64,
+                counts.projects_added as i64,
+                counts.projects_updated as i64,
+                counts.projects_removed as i64,
+                counts.files_processed as i64,
+                counts.files_added as i64,
+                counts.files_updated as i64,
+                counts.files_removed as i64,
+                counts.stl_previews_generated as i64,
+                counts.stl_previews_queued as i64,
+                counts.stl_previews_cached as i64,
+                errors.len() as i64,
+                error_log,
+                id
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark sessions left running by a previous server process as failed
+    pub fn fail_interrupted(&self) -> Result<usize, AppError> {
+        let conn = self.pool.get()?;
+        let count = conn.execute(
+            "UPDATE scan_sessions SET status = 'failed', completed_at = started_at
+             WHERE status = 'running'",
+            [],
+        )?;
+        Ok(count)
+    }
+
+    /// Recorded scans, most recent first, with the total number of sessions
+    pub fn list(
+        &self,
+        pagination: &PaginationParams,
+    ) -> Result<(Vec<ScanSession>, usize), AppError> {
+        let conn = self.pool.get()?;
+
+        let total: i64 =
+            conn.query_row("SELECT COUNT(*) FROM scan_sessions", [], |row| row.get(0))?;
+
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM scan_sessions ORDER BY started_at DESC, id DESC LIMIT ?1 OFFSET ?2",
+            SESSION_COLUMNS
+        ))?;
+        let sessions = stmt
+            .query_map(
+                params![pagination.limit(), pagination.offset()],
+                session_from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((sessions, total as usize))
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<ScanSessionDetail>, AppError> {
+        let conn = self.pool.get()?;
+
+        let detail = conn
+            .query_row(
+                &format!(
+                    "SELECT {}, error_log FROM scan_sessions WHERE id = ?1",
+                    SESSION_COLUMNS
+                ),
+                params![id],
+                |row| {
+                    let error_log: Option<String> = row.get(20)?;
+                    Ok(ScanSessionDetail {
+                        session: session_from_row(row)?,
+                        errors: error_log
+                            .map(|log| parse_error_log(&log))
+                            .unwrap_or_default(),
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(detail)
+    }
+}
+
+fn session_from_row(row: &Row) -> rusqlite::Result<ScanSession> {
+    let mode: String = row.get(3)?;
+    let status: String = row.get(4)?;
+    let trigger: String = row.get(19)?;
+    let count =
+        |idx: usize| -> rusqlite::Result<usize> { Ok(row.get::<_, i64>(idx)?.max(0) as usize) };
+
+    Ok(ScanSession {
+        id: row.get(0)?,
+        root_path: row.get(1)?,
+        scan_path: row.get(2)?,
+        mode: ScanMode::parse(&mode).unwrap_or(ScanMode::Full),
+        triggered_by: ScanTrigger::parse(&trigger).unwrap_or(ScanTrigger::Manual),
+        status: ScanSessionStatus::parse(&status).unwrap_or(ScanSessionStatus::Failed),
+        started_at: row.get(5)?,
+        completed_at: row.get(6)?,
+        counts: ScanCounts {
+            projects_found: count(7)?,
+            projects_added: count(8)?,
+            projects_updated: count(9)?,
+            projects_removed: count(10)?,
+            files_processed: count(11)?,
+            files_added: count(12)?,
+            files_updated: count(13)?,
+            files_removed: count(14)?,
+            stl_previews_generated: count(15)?,
+            stl_previews_queued: count(16)?,
+            stl_previews_cached: count(17)?,
+        },
+        errors_count: count(18)?,
+    })
+}
+
+/// Error logs are stored as a JSON array; older rows may hold plain lines
+fn parse_error_log(log: &str) -> Vec<String> {
+    serde_json::from_str(log).unwrap_or_else(|_| log.lines().map(str::to_string).collect())
+}
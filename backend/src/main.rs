@@ -43,6 +43,15 @@ async fn main() {
 
     tracing::info!("Database migrations completed successfully");
 
+    // Scans that were running when the server stopped never finished
+    match db::repositories::scan_session_repo::ScanSessionRepository::new(pool.clone())
+        .fail_interrupted()
+    {
+        Ok(0) => {}
+        Ok(count) => tracing::warn!(count, "Marked interrupted scans as failed"),
+        Err(e) => tracing::warn!(error = %e, "Failed to update interrupted scans"),
+    }
+
     // Initialize root_path from environment variable if not already set
     if let Ok(root_path) = std::env::var("ROOT_PATH") {
         let config_service = config::ConfigService::new(pool.clone());
@@ -53,6 +62,14 @@ async fn main() {
                     root_path: Some(root_path.clone()),
                     cache_max_size_mb: None,
                     images_per_page: None,
+                    printer_profiles: None,
+                    scan_schedule: None,
+                    scan_interval_minutes: None,
+                    ignore_patterns: None,
+                    follow_symlinks: None,
+                    write_sidecars: None,
+                    creator_folder_level: None,
+                    release_folder_pattern: None,
                 };
                 config_service
                     .update_config(&update)
@@ -80,7 +97,7 @@ async fn main() {
 
     tracing::info!(keywords = ?ignored_keywords, "Initialized ignored keywords for search");
 
-    // Get root_path from config for folder service
+    // `/api/browse` follows the default library root; this path is browsed until one exists
     let config_service = config::ConfigService::new(pool.clone());
     let root_path = config_service
         .get_config()
@@ -89,10 +106,33 @@ async fn main() {
         .unwrap_or_else(|| std::env::var("ROOT_PATH").unwrap_or_else(|_| "./example".to_string()));
     let root_path = PathBuf::from(root_path);
 
-    tracing::info!(root_path = ?root_path, "Using root path for folder service");
+    tracing::info!(root_path = ?root_path, "Using fallback root path for folder browsing");
 
     // Build application with routes and middleware
-    let api_routes = api::routes::create_router(pool, cache_path, ignored_keywords, root_path);
+    let (state, browse_state) =
+        api::routes::create_state(pool, cache_path, ignored_keywords, root_path);
+
+    // Rescan the library at the times configured in `config`
+    let _scan_scheduler = api::scheduler::spawn_scan_scheduler(state.clone());
+
+    // Optionally watch the library roots and index changes without a full rescan
+    let watch_library = std::env::var("WATCH_LIBRARY")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    let _library_watcher = if watch_library {
+        match api::watcher::LibraryWatcher::start(state.clone(), api::watcher::DEFAULT_DEBOUNCE) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::warn!(error = %e, "Filesystem watcher disabled");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let api_routes = api::routes::router(state, browse_state);
 
     let frontend_path = std::env::var("FRONTEND_PATH").unwrap_or_else(|_| "frontend".to_string());
     let serve_dir = ServeDir::new(&frontend_path)
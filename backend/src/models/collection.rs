@@ -0,0 +1,139 @@
+use crate::models::project::Project;
+use crate::models::stl_file::StlFile;
+use serde::{Deserialize, Serialize};
+
+/// A user-curated, ordered list of projects and model files, e.g. a print queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Collection {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A collection in the list of collections
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionSummary {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub item_count: usize,
+}
+
+/// A collection with its items in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionWithItems {
+    #[serde(flatten)]
+    pub collection: Collection,
+    pub items: Vec<CollectionItem>,
+}
+
+/// A whole project or a single model file in a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionItem {
+    pub id: i64,
+    /// Copies to print
+    pub quantity: i64,
+    pub notes: Option<String>,
+    /// The project, or the project the model file belongs to
+    pub project: Project,
+    /// Set when the item is a single model file
+    pub stl_file: Option<StlFile>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Body of `POST /api/collections` and `PATCH /api/collections/:id`; missing fields
+/// are left unchanged when updating and an empty description clears it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollectionRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Body of `POST /api/collections/:id/items`, with either `project_id` or `stl_file_id`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddCollectionItem {
+    pub project_id: Option<i64>,
+    pub stl_file_id: Option<i64>,
+    /// Defaults to 1
+    pub quantity: Option<i64>,
+    pub notes: Option<String>,
+    /// Index to insert the item at; appended when missing
+    pub position: Option<usize>,
+}
+
+/// Body of `PATCH /api/collections/:id/items/:item_id`; an empty string clears the notes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateCollectionItem {
+    pub quantity: Option<i64>,
+    pub notes: Option<String>,
+    /// Index to move the item to
+    pub position: Option<usize>,
+}
+
+/// Body of `PUT /api/collections/:id/items`: every item of the collection, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderCollectionItems {
+    pub item_ids: Vec<i64>,
+}
+
+const MAX_NAME_LENGTH: usize = 200;
+const MAX_DESCRIPTION_LENGTH: usize = 20_000;
+const MAX_NOTES_LENGTH: usize = 2000;
+const MAX_QUANTITY: i64 = 10_000;
+
+impl CollectionRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(name) = self.name.as_deref().map(str::trim) {
+            if name.is_empty() {
+                return Err("name cannot be empty".to_string());
+            }
+            if name.chars().count() > MAX_NAME_LENGTH {
+                return Err(format!(
+                    "name must be at most {} characters",
+                    MAX_NAME_LENGTH
+                ));
+            }
+        }
+        if self
+            .description
+            .as_deref()
+            .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(format!(
+                "description must be at most {} characters",
+                MAX_DESCRIPTION_LENGTH
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn validate_item(quantity: Option<i64>, notes: Option<&str>) -> Result<(), String> {
+    if quantity.is_some_and(|q| !(1..=MAX_QUANTITY).contains(&q)) {
+        return Err(format!("quantity must be between 1 and {}", MAX_QUANTITY));
+    }
+    if notes.is_some_and(|n| n.chars().count() > MAX_NOTES_LENGTH) {
+        return Err(format!(
+            "notes must be at most {} characters",
+            MAX_NOTES_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+impl AddCollectionItem {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.project_id.is_some() == self.stl_file_id.is_some() {
+            return Err("Either project_id or stl_file_id is required".to_string());
+        }
+        validate_item(self.quantity, self.notes.as_deref())
+    }
+}
+
+impl UpdateCollectionItem {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_item(self.quantity, self.notes.as_deref())
+    }
+}
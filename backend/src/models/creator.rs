@@ -0,0 +1,21 @@
+use crate::models::project::Project;
+use serde::{Deserialize, Serialize};
+
+/// A designer whose projects are grouped together, e.g. a Patreon creator
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Creator {
+    pub id: i64,
+    pub name: String,
+    pub created_at: i64,
+}
+
+/// A creator with the number of their projects and their latest releases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorSummary {
+    #[serde(flatten)]
+    pub creator: Creator,
+    /// Projects with model files of their own
+    pub project_count: usize,
+    /// Newest projects first, by release date and then by when they were indexed
+    pub latest_projects: Vec<Project>,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// A model file that is one copy within a duplicate group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub stl_file_id: i64,
+    pub filename: String,
+    pub file_path: String,
+    pub project_id: i64,
+    pub project_name: String,
+    pub project_path: String,
+}
+
+/// How model files are matched when looking for duplicates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateMode {
+    /// Byte-identical files, matched by content hash
+    #[default]
+    Exact,
+    /// Geometrically identical meshes, matched by geometry fingerprint within a
+    /// tolerance
+    Similar,
+}
+
+/// A set of model files that are copies of each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateGroup {
+    /// Set in exact mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Set in similar mode: the fingerprint of the group's first file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geometry_fingerprint: Option<String>,
+    /// Size of the smallest copy
+    pub file_size: i64,
+    pub copies: usize,
+    /// Bytes that could be reclaimed by keeping only the smallest copy
+    pub wasted_bytes: i64,
+    pub files: Vec<DuplicateFile>,
+}
+
+/// Another project that contains copies of this project's files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateProject {
+    pub project_id: i64,
+    pub name: String,
+    pub full_path: String,
+    pub shared_files: usize,
+}
@@ -27,4 +27,6 @@ pub struct CreateImageFile {
     pub display_order: i32,
     pub image_priority: i32,
     pub image_source: String,
+    /// Modification time (seconds since epoch) when the file was indexed
+    pub file_mtime: Option<i64>,
 }
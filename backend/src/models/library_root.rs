@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// A folder tree holding part of the library, scanned independently of the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryRoot {
+    pub id: i64,
+    pub name: String,
+    pub path: String,
+    pub last_scan_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateLibraryRoot {
+    pub name: String,
+    pub path: String,
+}
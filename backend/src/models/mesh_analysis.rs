@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Geometry measurements of a model file. Lengths are in millimetres,
+/// areas in mm², volumes in mm³ and mass in grams.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshAnalysis {
+    pub stl_file_id: i64,
+    pub size_x: f64,
+    pub size_y: f64,
+    pub size_z: f64,
+    pub triangle_count: i64,
+    pub surface_area: f64,
+    pub volume: f64,
+    pub estimated_mass: f64,
+    /// Shape fingerprint shared by geometrically identical models
+    #[serde(default)]
+    pub geometry_fingerprint: Option<String>,
+    pub source_size: i64,
+    pub source_mtime: i64,
+    pub analyzed_at: i64,
+}
+
+/// A printability problem found while validating a model file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MeshIssue {
+    /// One of `non_manifold_edges`, `open_edges`, `flipped_normals`,
+    /// `degenerate_triangles` or `multiple_shells`
+    pub issue_type: String,
+    pub count: i64,
+    pub message: String,
+}
@@ -1,6 +1,14 @@
 pub mod cached_file;
+pub mod collection;
+pub mod creator;
+pub mod duplicate;
 pub mod folder;
 pub mod image_file;
+pub mod library_root;
+pub mod mesh_analysis;
 pub mod project;
+pub mod release;
+pub mod scan_progress;
+pub mod scan_session;
 pub mod stl_file;
 pub mod tag;
@@ -1,3 +1,5 @@
+use crate::models::creator::Creator;
+use crate::models::duplicate::DuplicateProject;
 use crate::models::stl_file::StlFile;
 use crate::models::tag::Tag;
 use serde::{Deserialize, Serialize};
@@ -13,6 +15,8 @@ pub struct Project {
     pub folder_level: i32,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Library root the project was found in
+    pub root_id: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +28,128 @@ pub struct ProjectWithRelations {
     pub image_count: usize,
     pub inherited_images: Vec<ImagePreview>,
     pub tags: Vec<Tag>,
+    /// Other projects containing byte-identical copies of this project's files
+    pub also_appears_in: Vec<DuplicateProject>,
+    /// Metadata imported from sidecar files or edited, if the project has any
+    pub metadata: Option<ProjectMetadata>,
+    pub creator: Option<Creator>,
+    /// Collections holding the project or one of its model files
+    pub collection_count: usize,
+}
+
+/// Project metadata, read from a `glyptotheka.json` / `.yaml` sidecar file or edited
+/// through `PATCH /api/projects/:id`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectMetadata {
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub source_url: Option<String>,
+    /// Release date as `YYYY-MM-DD`
+    pub release_date: Option<String>,
+    pub scale: Option<String>,
+    /// Print settings as written in the sidecar file
+    pub print_settings: Option<serde_json::Value>,
+}
+
+/// Fields of `PATCH /api/projects/:id`. Missing fields are left unchanged and an
+/// empty string clears a field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateProject {
+    /// Markdown
+    pub description: Option<String>,
+    #[serde(alias = "designer")]
+    pub author: Option<String>,
+    pub license: Option<String>,
+    pub source_url: Option<String>,
+    /// `YYYY-MM-DD`
+    pub release_date: Option<String>,
+    pub scale: Option<String>,
+    /// Sets the creator by hand; an empty string goes back to the derived creator
+    pub creator: Option<String>,
+}
+
+const MAX_DESCRIPTION_LENGTH: usize = 20_000;
+const MAX_FIELD_LENGTH: usize = 200;
+const MAX_URL_LENGTH: usize = 2048;
+
+impl UpdateProject {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref description) = self.description {
+            if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+                return Err(format!(
+                    "description must be at most {} characters",
+                    MAX_DESCRIPTION_LENGTH
+                ));
+            }
+        }
+
+        for (field, value) in [
+            ("author", &self.author),
+            ("license", &self.license),
+            ("scale", &self.scale),
+            ("creator", &self.creator),
+        ] {
+            if value
+                .as_deref()
+                .is_some_and(|v| v.trim().chars().count() > MAX_FIELD_LENGTH)
+            {
+                return Err(format!(
+                    "{} must be at most {} characters",
+                    field, MAX_FIELD_LENGTH
+                ));
+            }
+        }
+
+        if let Some(url) = self.source_url.as_deref().map(str::trim) {
+            if !url.is_empty() {
+                let valid = (url.starts_with("https://") || url.starts_with("http://"))
+                    && url.len() <= MAX_URL_LENGTH
+                    && !url.chars().any(char::is_whitespace);
+                if !valid {
+                    return Err(format!(
+                        "source_url must be an http(s) URL of at most {} characters",
+                        MAX_URL_LENGTH
+                    ));
+                }
+            }
+        }
+
+        if let Some(date) = self.release_date.as_deref().map(str::trim) {
+            if !date.is_empty() && !is_valid_date(date) {
+                return Err(format!("release_date must be YYYY-MM-DD, got '{}'", date));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `date` is a calendar date written as `YYYY-MM-DD`
+pub(crate) fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+    let (Ok(year), Ok(month), Ok(day)) = (
+        year.parse::<u32>(),
+        month.parse::<u32>(),
+        day.parse::<u32>(),
+    ) else {
+        return false;
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,138 @@
+use crate::models::creator::Creator;
+use crate::models::project::{is_valid_date, Project};
+use serde::{Deserialize, Serialize};
+
+/// Projects published together, e.g. a creator's monthly Patreon release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub creator_id: Option<i64>,
+    /// Release date as `YYYY-MM-DD`
+    pub release_date: Option<String>,
+    /// Folder the release was detected from
+    pub folder_project_id: Option<i64>,
+    /// Detected from the folder pattern and kept in sync with the folder; editing the
+    /// release through the API turns this off
+    pub auto_detected: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// A release in the list of releases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseSummary {
+    #[serde(flatten)]
+    pub release: Release,
+    pub creator: Option<Creator>,
+    pub project_count: usize,
+}
+
+/// A release with its projects, in release order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseWithProjects {
+    #[serde(flatten)]
+    pub release: Release,
+    pub creator: Option<Creator>,
+    pub projects: Vec<Project>,
+}
+
+/// Body of `POST /api/releases`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateRelease {
+    pub name: String,
+    pub description: Option<String>,
+    /// Creator name; created if it does not exist yet
+    pub creator: Option<String>,
+    /// `YYYY-MM-DD`
+    pub release_date: Option<String>,
+    #[serde(default)]
+    pub project_ids: Vec<i64>,
+}
+
+/// Fields of `PATCH /api/releases/:id`. Missing fields are left unchanged and an
+/// empty string clears a field.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdateRelease {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub creator: Option<String>,
+    pub release_date: Option<String>,
+}
+
+/// Body of `PUT /api/releases/:id/projects`: the projects of the release, in order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetReleaseProjects {
+    pub project_ids: Vec<i64>,
+}
+
+/// Body of `POST /api/releases/:id/projects`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddReleaseProject {
+    pub project_id: i64,
+    /// Index to insert the project at; appended when missing
+    pub position: Option<usize>,
+}
+
+const MAX_NAME_LENGTH: usize = 200;
+const MAX_DESCRIPTION_LENGTH: usize = 20_000;
+
+fn validate_fields(
+    name: Option<&str>,
+    description: Option<&str>,
+    creator: Option<&str>,
+    release_date: Option<&str>,
+) -> Result<(), String> {
+    if let Some(name) = name.map(str::trim) {
+        if name.is_empty() {
+            return Err("name cannot be empty".to_string());
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!(
+                "name must be at most {} characters",
+                MAX_NAME_LENGTH
+            ));
+        }
+    }
+    if description.is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH) {
+        return Err(format!(
+            "description must be at most {} characters",
+            MAX_DESCRIPTION_LENGTH
+        ));
+    }
+    if creator.is_some_and(|c| c.trim().chars().count() > MAX_NAME_LENGTH) {
+        return Err(format!(
+            "creator must be at most {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+    if let Some(date) = release_date.map(str::trim) {
+        if !date.is_empty() && !is_valid_date(date) {
+            return Err(format!("release_date must be YYYY-MM-DD, got '{}'", date));
+        }
+    }
+    Ok(())
+}
+
+impl CreateRelease {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_fields(
+            Some(&self.name),
+            self.description.as_deref(),
+            self.creator.as_deref(),
+            self.release_date.as_deref(),
+        )
+    }
+}
+
+impl UpdateRelease {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_fields(
+            self.name.as_deref(),
+            self.description.as_deref(),
+            self.creator.as_deref(),
+            self.release_date.as_deref(),
+        )
+    }
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+/// Stage a running scan is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanPhase {
+    /// Walking the library to find project folders
+    Walking,
+    /// Adding, updating and removing model and image files
+    Indexing,
+    /// Indexing parent folder images and propagating them to projects
+    Inheritance,
+    /// Generating composite previews
+    Previews,
+}
+
+/// Snapshot of a running scan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub phase: Option<ScanPhase>,
+    pub folders_visited: usize,
+    pub files_processed: usize,
+    pub current_path: Option<String>,
+    /// Work items finished in the current phase
+    pub phase_completed: usize,
+    /// Work items in the current phase, when known up front
+    pub phase_total: Option<usize>,
+    pub elapsed_seconds: u64,
+    /// Time left in the current phase, extrapolated from its progress so far
+    pub estimated_remaining_seconds: Option<u64>,
+}
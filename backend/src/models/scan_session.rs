@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a scan as recorded in `scan_sessions`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanSessionStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl ScanSessionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanSessionStatus::Running => "running",
+            ScanSessionStatus::Completed => "completed",
+            ScanSessionStatus::Failed => "failed",
+            ScanSessionStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "running" => Some(ScanSessionStatus::Running),
+            "completed" => Some(ScanSessionStatus::Completed),
+            "failed" => Some(ScanSessionStatus::Failed),
+            "cancelled" => Some(ScanSessionStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// How a scan went through the library
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanMode {
+    /// Full scan with the scanner service (first scan or forced)
+    Full,
+    /// Incremental rescan that only applies changes
+    Incremental,
+    /// Full scan after clearing the database and cache
+    Clean,
+}
+
+impl ScanMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanMode::Full => "full",
+            ScanMode::Incremental => "incremental",
+            ScanMode::Clean => "clean",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "full" => Some(ScanMode::Full),
+            "incremental" => Some(ScanMode::Incremental),
+            "clean" => Some(ScanMode::Clean),
+            _ => None,
+        }
+    }
+}
+
+/// What started a scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanTrigger {
+    /// Requested through the API
+    Manual,
+    /// Started by the configured rescan schedule
+    Scheduled,
+    /// Started by the filesystem watcher to apply changes it saw
+    Watcher,
+}
+
+impl ScanTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanTrigger::Manual => "manual",
+            ScanTrigger::Scheduled => "scheduled",
+            ScanTrigger::Watcher => "watcher",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "manual" => Some(ScanTrigger::Manual),
+            "scheduled" => Some(ScanTrigger::Scheduled),
+            "watcher" => Some(ScanTrigger::Watcher),
+            _ => None,
+        }
+    }
+}
+
+/// Counters reported by a finished scan
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScanCounts {
+    pub projects_found: usize,
+    pub projects_added: usize,
+    pub projects_updated: usize,
+    pub projects_removed: usize,
+    pub files_processed: usize,
+    pub files_added: usize,
+    pub files_updated: usize,
+    pub files_removed: usize,
+    pub stl_previews_generated: usize,
+    pub stl_previews_queued: usize,
+    pub stl_previews_cached: usize,
+}
+
+/// A scan recorded in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSession {
+    pub id: i64,
+    pub root_path: String,
+    /// Subtree that was scanned, when not the whole library
+    pub scan_path: Option<String>,
+    pub mode: ScanMode,
+    pub triggered_by: ScanTrigger,
+    pub status: ScanSessionStatus,
+    pub started_at: i64,
+    pub completed_at: Option<i64>,
+    #[serde(flatten)]
+    pub counts: ScanCounts,
+    pub errors_count: usize,
+}
+
+/// A recorded scan with its full error list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSessionDetail {
+    #[serde(flatten)]
+    pub session: ScanSession,
+    pub errors: Vec<String>,
+}
@@ -1,4 +1,6 @@
+use crate::models::mesh_analysis::MeshAnalysis;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StlFile {
@@ -7,11 +9,15 @@ pub struct StlFile {
     pub filename: String,
     pub file_path: String,
     pub file_size: i64,
+    pub format: String,
     pub category: Option<String>,
     pub preview_path: Option<String>,
     pub preview_generated_at: Option<i64>,
     pub created_at: i64,
     pub updated_at: i64,
+    /// Geometry analysis, if the file has been analyzed
+    #[serde(default)]
+    pub analysis: Option<MeshAnalysis>,
 }
 
 #[derive(Debug, Clone)]
@@ -20,5 +26,64 @@ pub struct CreateStlFile {
     pub filename: String,
     pub file_path: String,
     pub file_size: i64,
+    pub format: String,
     pub category: Option<String>,
+    /// Modification time (seconds since epoch) when the file was indexed
+    pub file_mtime: Option<i64>,
+}
+
+/// Model file formats recognised by the scanner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFormat {
+    Stl,
+    ThreeMf,
+    Obj,
+    Ply,
+}
+
+impl ModelFormat {
+    /// Detect the model format from a file extension (case-insensitive)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("stl") {
+            Some(ModelFormat::Stl)
+        } else if ext.eq_ignore_ascii_case("3mf") {
+            Some(ModelFormat::ThreeMf)
+        } else if ext.eq_ignore_ascii_case("obj") {
+            Some(ModelFormat::Obj)
+        } else if ext.eq_ignore_ascii_case("ply") {
+            Some(ModelFormat::Ply)
+        } else {
+            None
+        }
+    }
+
+    /// Value stored in the `format` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModelFormat::Stl => "stl",
+            ModelFormat::ThreeMf => "3mf",
+            ModelFormat::Obj => "obj",
+            ModelFormat::Ply => "ply",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ModelFormat::Stl => "model/stl",
+            ModelFormat::ThreeMf => "model/3mf",
+            ModelFormat::Obj => "model/obj",
+            ModelFormat::Ply => "model/x-ply",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "stl" => Some(ModelFormat::Stl),
+            "3mf" => Some(ModelFormat::ThreeMf),
+            "obj" => Some(ModelFormat::Obj),
+            "ply" => Some(ModelFormat::Ply),
+            _ => None,
+        }
+    }
 }
@@ -1,6 +1,7 @@
+use crate::utils::archive;
 use crate::utils::error::AppError;
 use image::{imageops, Rgba, RgbaImage};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{error, info, warn};
 
 pub struct CompositePreviewService {
@@ -22,18 +23,57 @@ impl CompositePreviewService {
         project_id: i64,
         image_paths: &[String],
     ) -> Result<PathBuf, AppError> {
+        info!(
+            "Generating composite preview for project {} with {} images",
+            project_id,
+            image_paths.len().min(4)
+        );
+        let composite = self.compose(image_paths)?;
+
+        // Save to cache
+        let preview_path = self
+            .cache_dir
+            .join("previews")
+            .join(format!("project_{}_composite.png", project_id));
+
+        composite
+            .save(&preview_path)
+            .map_err(|e| AppError::InternalServer(format!("Failed to save preview: {}", e)))?;
+
+        info!("Composite preview saved to {:?}", preview_path);
+        Ok(preview_path)
+    }
+
+    /// Generate the cover of a release from up to 4 images of its projects
+    pub fn generate_release_cover(
+        &self,
+        release_id: i64,
+        image_paths: &[String],
+    ) -> Result<PathBuf, AppError> {
+        info!(
+            "Generating cover for release {} with {} images",
+            release_id,
+            image_paths.len().min(4)
+        );
+        let composite = self.compose(image_paths)?;
+
+        let cover_path = self.release_cover_path(release_id);
+        composite
+            .save(&cover_path)
+            .map_err(|e| AppError::InternalServer(format!("Failed to save cover: {}", e)))?;
+
+        info!("Release cover saved to {:?}", cover_path);
+        Ok(cover_path)
+    }
+
+    /// Combine up to 4 images into one 800x800 image
+    fn compose(&self, image_paths: &[String]) -> Result<RgbaImage, AppError> {
         if image_paths.is_empty() {
             return Err(AppError::ValidationError(
                 "No images provided for preview generation".to_string(),
             ));
         }
 
-        let count = image_paths.len().min(4);
-        info!(
-            "Generating composite preview for project {} with {} images",
-            project_id, count
-        );
-
         // Load and resize images
         let mut images = Vec::new();
         for (idx, path) in image_paths.iter().take(4).enumerate() {
@@ -57,25 +97,12 @@ impl CompositePreviewService {
         }
 
         // Create composite based on image count
-        let composite = match images.len() {
+        Ok(match images.len() {
             1 => self.create_single_preview(&images[0]),
             2 => self.create_two_image_preview(&images[0], &images[1]),
             3 => self.create_three_image_preview(&images[0], &images[1], &images[2]),
             _ => self.create_four_image_preview(&images[0], &images[1], &images[2], &images[3]),
-        };
-
-        // Save to cache
-        let preview_path = self
-            .cache_dir
-            .join("previews")
-            .join(format!("project_{}_composite.png", project_id));
-
-        composite
-            .save(&preview_path)
-            .map_err(|e| AppError::InternalServer(format!("Failed to save preview: {}", e)))?;
-
-        info!("Composite preview saved to {:?}", preview_path);
-        Ok(preview_path)
+        })
     }
 
     /// Load an image and resize it to fit within the specified dimensions
@@ -85,9 +112,18 @@ impl CompositePreviewService {
         width: u32,
         height: u32,
     ) -> Result<RgbaImage, AppError> {
-        let img = image::open(path).map_err(|e| {
-            AppError::InternalServer(format!("Failed to load image {}: {}", path, e))
-        })?;
+        let img = if archive::split_path(Path::new(path)).is_some() {
+            // Images inside an archive are decoded from memory
+            let data = archive::read_file(Path::new(path), |reader, size| {
+                let mut data = Vec::with_capacity(size as usize);
+                reader.read_to_end(&mut data)?;
+                Ok(data)
+            })?;
+            image::load_from_memory(&data)
+        } else {
+            image::open(path)
+        }
+        .map_err(|e| AppError::InternalServer(format!("Failed to load image {}: {}", path, e)))?;
 
         let resized = img.resize_exact(width, height, imageops::FilterType::Lanczos3);
         Ok(resized.to_rgba8())
@@ -170,4 +206,22 @@ impl CompositePreviewService {
 
         Ok(())
     }
+
+    /// Delete the cover of a release
+    pub fn delete_release_cover(&self, release_id: i64) -> Result<(), AppError> {
+        let cover_path = self.release_cover_path(release_id);
+        if cover_path.exists() {
+            std::fs::remove_file(&cover_path)
+                .map_err(|e| AppError::InternalServer(format!("Failed to delete cover: {}", e)))?;
+            info!("Deleted cover for release {}", release_id);
+        }
+
+        Ok(())
+    }
+
+    fn release_cover_path(&self, release_id: i64) -> PathBuf {
+        self.cache_dir
+            .join("previews")
+            .join(format!("release_{}_cover.png", release_id))
+    }
 }
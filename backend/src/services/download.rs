@@ -1,17 +1,36 @@
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
+use std::path::Path;
 use zip::write::{FileOptions, ZipWriter};
 use zip::CompressionMethod;
 
 use crate::db::connection::DbPool;
+use crate::models::collection::CollectionWithItems;
 use crate::models::image_file::ImageFile;
-use crate::models::stl_file::StlFile;
+use crate::models::stl_file::{ModelFormat, StlFile};
+use crate::services::mesh::obj_companion_files;
+use crate::utils::archive;
 use crate::utils::error::AppError;
 
 pub struct DownloadService {
     pool: DbPool,
 }
 
+/// Files of one project to put in a ZIP
+struct ProjectFiles {
+    stl_files: Vec<StlFile>,
+    image_files: Vec<ImageFile>,
+}
+
+/// A line of a collection's `collection.txt`
+struct ManifestLine {
+    quantity: i64,
+    notes: Option<String>,
+    /// The item is a single model file rather than a whole project
+    single_file: bool,
+}
+
 impl DownloadService {
     pub fn new(pool: DbPool) -> Self {
         Self { pool }
@@ -22,10 +41,177 @@ impl DownloadService {
         project_id: i64,
         output_path: &std::path::Path,
     ) -> Result<(), AppError> {
+        let files = self.project_files(project_id)?;
+
+        // Create ZIP file in blocking task to avoid blocking async runtime
+        let output_path = output_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let file = File::create(&output_path).map_err(|e| {
+                AppError::InternalServer(format!("Failed to create ZIP file: {}", e))
+            })?;
+            let mut zip = ZipWriter::new(file);
+
+            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+            Self::add_project_files_sync(&mut zip, "", files, &mut HashMap::new(), options)?;
+
+            zip.finish()
+                .map_err(|e| AppError::InternalServer(format!("Failed to finish ZIP: {}", e)))?;
+
+            Ok::<(), AppError>(())
+        })
+        .await
+        .map_err(|e| AppError::InternalServer(format!("ZIP task failed: {}", e)))??;
+
+        Ok(())
+    }
+
+    /// ZIP the projects of a release, each in a folder named after the project
+    pub async fn create_release_zip(
+        &self,
+        release_id: i64,
+        output_path: &std::path::Path,
+    ) -> Result<(), AppError> {
+        let projects: Vec<(i64, String)> = {
+            let conn = self.pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT p.id, p.name
+                 FROM release_projects rp
+                 INNER JOIN projects p ON p.id = rp.project_id
+                 WHERE rp.release_id = ?1
+                 ORDER BY rp.position",
+            )?;
+            let projects = stmt
+                .query_map([release_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            projects
+        };
+
+        let mut folders = HashSet::new();
+        let mut contents = Vec::with_capacity(projects.len());
+        for (project_id, name) in projects {
+            let folder = unique_folder(&mut folders, &name);
+            contents.push((folder, self.project_files(project_id)?));
+        }
+
+        let output_path = output_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let file = File::create(&output_path).map_err(|e| {
+                AppError::InternalServer(format!("Failed to create ZIP file: {}", e))
+            })?;
+            let mut zip = ZipWriter::new(file);
+
+            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+            let mut written = HashMap::new();
+            for (folder, files) in contents {
+                Self::add_project_files_sync(
+                    &mut zip,
+                    &format!("{}/", folder),
+                    files,
+                    &mut written,
+                    options,
+                )?;
+            }
+
+            zip.finish()
+                .map_err(|e| AppError::InternalServer(format!("Failed to finish ZIP: {}", e)))?;
+
+            Ok::<(), AppError>(())
+        })
+        .await
+        .map_err(|e| AppError::InternalServer(format!("ZIP task failed: {}", e)))??;
+
+        Ok(())
+    }
+
+    /// ZIP the files of a collection, a folder per project, with a `collection.txt`
+    /// listing the quantity and notes of each item
+    pub async fn create_collection_zip(
+        &self,
+        collection: &CollectionWithItems,
+        output_path: &std::path::Path,
+    ) -> Result<(), AppError> {
+        let mut folders = HashSet::new();
+        let mut project_folders: HashMap<i64, String> = HashMap::new();
+        let mut contents = Vec::with_capacity(collection.items.len());
+        for item in &collection.items {
+            let folder = project_folders
+                .entry(item.project.id)
+                .or_insert_with(|| unique_folder(&mut folders, &item.project.name))
+                .clone();
+            let files = match item.stl_file {
+                Some(ref stl_file) => ProjectFiles {
+                    stl_files: vec![stl_file.clone()],
+                    image_files: Vec::new(),
+                },
+                None => self.project_files(item.project.id)?,
+            };
+            let line = ManifestLine {
+                quantity: item.quantity,
+                notes: item.notes.clone(),
+                single_file: item.stl_file.is_some(),
+            };
+            contents.push((folder, files, line));
+        }
+        let title = collection.collection.name.clone();
+
+        let output_path = output_path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let file = File::create(&output_path).map_err(|e| {
+                AppError::InternalServer(format!("Failed to create ZIP file: {}", e))
+            })?;
+            let mut zip = ZipWriter::new(file);
+
+            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+            // A project's folder holds both the project and single files picked from it
+            let mut written = HashMap::new();
+            let mut manifest = format!("{}\n\n", title);
+            for (folder, files, line) in contents {
+                let entries = Self::add_project_files_sync(
+                    &mut zip,
+                    &format!("{}/", folder),
+                    files,
+                    &mut written,
+                    options,
+                )?;
+
+                // Single files are listed under the name they were written with
+                let entry = match entries.into_iter().next() {
+                    Some(entry) if line.single_file => entry,
+                    _ => format!("{}/", folder),
+                };
+                manifest.push_str(&format!("{} x {}", line.quantity, entry));
+                if let Some(ref notes) = line.notes {
+                    manifest.push_str(&format!(" - {}", notes.replace('\n', " ")));
+                }
+                manifest.push('\n');
+            }
+
+            zip.start_file("collection.txt", options).map_err(|e| {
+                AppError::InternalServer(format!("Failed to start ZIP entry: {}", e))
+            })?;
+            zip.write_all(manifest.as_bytes())
+                .map_err(|e| AppError::InternalServer(format!("Failed to write to ZIP: {}", e)))?;
+
+            zip.finish()
+                .map_err(|e| AppError::InternalServer(format!("Failed to finish ZIP: {}", e)))?;
+
+            Ok::<(), AppError>(())
+        })
+        .await
+        .map_err(|e| AppError::InternalServer(format!("ZIP task failed: {}", e)))??;
+
+        Ok(())
+    }
+
+    /// Model and image files of a project
+    fn project_files(&self, project_id: i64) -> Result<ProjectFiles, AppError> {
         let stl_files = {
             let conn = self.pool.get()?;
             let mut stmt = conn.prepare(
-                "SELECT id, project_id, filename, file_path, file_size, category, preview_path, preview_generated_at, created_at, updated_at
+                "SELECT id, project_id, filename, file_path, file_size, format, category, preview_path, preview_generated_at, created_at, updated_at
                  FROM stl_files
                  WHERE project_id = ?1
                  ORDER BY filename",
@@ -39,11 +225,13 @@ impl DownloadService {
                         filename: row.get(2)?,
                         file_path: row.get(3)?,
                         file_size: row.get(4)?,
-                        category: row.get(5)?,
-                        preview_path: row.get(6)?,
-                        preview_generated_at: row.get(7)?,
-                        created_at: row.get(8)?,
-                        updated_at: row.get(9)?,
+                        format: row.get(5)?,
+                        category: row.get(6)?,
+                        preview_path: row.get(7)?,
+                        preview_generated_at: row.get(8)?,
+                        created_at: row.get(9)?,
+                        updated_at: row.get(10)?,
+                        analysis: None,
                     })
                 })?
                 .collect();
@@ -82,45 +270,66 @@ impl DownloadService {
             files?
         };
 
-        // Create ZIP file in blocking task to avoid blocking async runtime
-        let output_path = output_path.to_path_buf();
-        tokio::task::spawn_blocking(move || {
-            let file = File::create(&output_path).map_err(|e| {
-                AppError::InternalServer(format!("Failed to create ZIP file: {}", e))
-            })?;
-            let mut zip = ZipWriter::new(file);
+        Ok(ProjectFiles {
+            stl_files,
+            image_files,
+        })
+    }
 
-            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    /// Add a project's files to the ZIP, with entry names starting with `prefix`.
+    /// `written` maps the entry names used so far to their source file: companion
+    /// files can be shared between meshes and a file can be added twice, which is
+    /// written once, while different files with the same name get numbered entries.
+    fn add_project_files_sync(
+        zip: &mut ZipWriter<File>,
+        prefix: &str,
+        files: ProjectFiles,
+        written: &mut HashMap<String, String>,
+        options: FileOptions<()>,
+    ) -> Result<Vec<String>, AppError> {
+        let mut model_entries = Vec::with_capacity(files.stl_files.len());
 
-            // Add STL files
-            for stl_file in stl_files {
-                Self::add_file_to_zip_sync(
-                    &mut zip,
-                    &stl_file.file_path,
-                    &stl_file.filename,
-                    options,
-                )?;
+        // Add model files (STL, 3MF, OBJ, PLY)
+        for stl_file in files.stl_files {
+            let entry_name = format!("{}{}", prefix, stl_file.filename);
+            let (entry_name, new) = unique_entry(written, &entry_name, &stl_file.file_path);
+            model_entries.push(entry_name.clone());
+            if !new {
+                continue;
             }
+            Self::add_file_to_zip_sync(zip, &stl_file.file_path, &entry_name, options)?;
 
-            // Add image files
-            for image_file in image_files {
-                Self::add_file_to_zip_sync(
-                    &mut zip,
-                    &image_file.file_path,
-                    &image_file.filename,
-                    options,
-                )?;
+            // Keep OBJ material libraries and textures next to their mesh
+            if ModelFormat::parse(&stl_file.format) == Some(ModelFormat::Obj) {
+                let model_path = Path::new(&stl_file.file_path);
+                let base_dir = model_path.parent().unwrap_or(Path::new(""));
+                for companion in obj_companion_files(model_path) {
+                    let entry_name = format!(
+                        "{}{}",
+                        prefix,
+                        companion.to_string_lossy().replace('\\', "/")
+                    );
+                    // Meshes refer to companions by name, so they are never renamed
+                    if written.contains_key(&entry_name) {
+                        continue;
+                    }
+                    let companion_path = base_dir.join(&companion).to_string_lossy().to_string();
+                    Self::add_file_to_zip_sync(zip, &companion_path, &entry_name, options)?;
+                    written.insert(entry_name, companion_path);
+                }
             }
+        }
 
-            zip.finish()
-                .map_err(|e| AppError::InternalServer(format!("Failed to finish ZIP: {}", e)))?;
-
-            Ok::<(), AppError>(())
-        })
-        .await
-        .map_err(|e| AppError::InternalServer(format!("ZIP task failed: {}", e)))??;
+        // Add image files
+        for image_file in files.image_files {
+            let entry_name = format!("{}{}", prefix, image_file.filename);
+            let (entry_name, new) = unique_entry(written, &entry_name, &image_file.file_path);
+            if new {
+                Self::add_file_to_zip_sync(zip, &image_file.file_path, &entry_name, options)?;
+            }
+        }
 
-        Ok(())
+        Ok(model_entries)
     }
 
     fn add_file_to_zip_sync(
@@ -129,25 +338,70 @@ impl DownloadService {
         filename: &str,
         options: FileOptions<()>,
     ) -> Result<(), AppError> {
-        let mut file = File::open(file_path).map_err(|e| {
-            AppError::InternalServer(format!("Failed to open file {}: {}", file_path, e))
-        })?;
-
-        zip.start_file(filename, options)
-            .map_err(|e| AppError::InternalServer(format!("Failed to start ZIP entry: {}", e)))?;
-
-        let mut buffer = vec![0u8; 8192];
-        loop {
-            let n = file
-                .read(&mut buffer)
-                .map_err(|e| AppError::InternalServer(format!("Failed to read file: {}", e)))?;
-            if n == 0 {
-                break;
+        // Files indexed inside an archive are copied straight out of it
+        archive::read_file(Path::new(file_path), |file, _| {
+            zip.start_file(filename, options).map_err(|e| {
+                AppError::InternalServer(format!("Failed to start ZIP entry: {}", e))
+            })?;
+
+            let mut buffer = vec![0u8; 8192];
+            loop {
+                let n = file
+                    .read(&mut buffer)
+                    .map_err(|e| AppError::InternalServer(format!("Failed to read file: {}", e)))?;
+                if n == 0 {
+                    break;
+                }
+                zip.write_all(&buffer[..n]).map_err(|e| {
+                    AppError::InternalServer(format!("Failed to write to ZIP: {}", e))
+                })?;
             }
-            zip.write_all(&buffer[..n])
-                .map_err(|e| AppError::InternalServer(format!("Failed to write to ZIP: {}", e)))?;
+
+            Ok(())
+        })
+        .map_err(|e| match e {
+            AppError::IoError(msg) => {
+                AppError::InternalServer(format!("Failed to open file {}: {}", file_path, msg))
+            }
+            other => other,
+        })
+    }
+}
+
+/// The entry name to write `source` under, recorded in `written`: `name`, or
+/// `name (2)`, `name (3)`… when another file has it. The flag is false when `source`
+/// is already in the ZIP under the returned name and must not be written again.
+fn unique_entry(written: &mut HashMap<String, String>, name: &str, source: &str) -> (String, bool) {
+    let path = Path::new(name);
+    let mut entry = name.to_string();
+    let mut n = 2;
+    while let Some(existing) = written.get(&entry) {
+        if existing == source {
+            return (entry, false);
         }
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let numbered = match path.extension() {
+            Some(ext) => format!("{} ({}).{}", stem, n, ext.to_string_lossy()),
+            None => format!("{} ({})", stem, n),
+        };
+        entry = match path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            Some(parent) => format!("{}/{}", parent.to_string_lossy(), numbered),
+            None => numbered,
+        };
+        n += 1;
+    }
+    written.insert(entry.clone(), source.to_string());
+    (entry, true)
+}
 
-        Ok(())
+/// A ZIP folder name for a project; projects with the same name get numbered folders
+fn unique_folder(folders: &mut HashSet<String>, name: &str) -> String {
+    let base = name.replace(['/', '\\'], "_");
+    let mut folder = base.clone();
+    let mut n = 2;
+    while !folders.insert(folder.to_lowercase()) {
+        folder = format!("{} ({})", base, n);
+        n += 1;
     }
+    folder
 }
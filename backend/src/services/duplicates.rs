@@ -0,0 +1,291 @@
+use crate::db::connection::DbPool;
+use crate::models::duplicate::{DuplicateFile, DuplicateGroup, DuplicateMode};
+use crate::services::mesh::{fingerprints_match, parse_fingerprint, FINGERPRINT_TOLERANCE};
+use crate::utils::archive;
+use crate::utils::error::AppError;
+use crate::utils::fs::file_signature;
+use crate::utils::pagination::PaginationParams;
+use rusqlite::{params, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::debug;
+
+#[derive(Debug, Clone)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub total_groups: usize,
+    pub total_wasted_bytes: i64,
+}
+
+#[derive(Clone)]
+pub struct DuplicateService {
+    pool: DbPool,
+}
+
+impl DuplicateService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// SHA-256 of a file's contents (on disk or inside an archive), hex encoded
+    pub fn hash_file(path: &Path) -> Result<String, AppError> {
+        archive::read_file(path, |file, _| {
+            let mut hasher = Sha256::new();
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
+            Ok(format!("{:x}", hasher.finalize()))
+        })
+    }
+
+    /// Hash a model file unless its stored hash was computed for the current size and mtime.
+    /// Returns true if the file was (re)hashed.
+    pub fn hash_if_changed(&self, stl_file_id: i64, path: &Path) -> Result<bool, AppError> {
+        let (size, mtime) = file_signature(path)?;
+
+        let existing: Option<(Option<String>, Option<i64>, Option<i64>)> = {
+            let conn = self.pool.get()?;
+            conn.query_row(
+                "SELECT content_hash, content_hash_size, content_hash_mtime FROM stl_files WHERE id = ?1",
+                params![stl_file_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+        };
+
+        if let Some((Some(_), Some(hashed_size), Some(hashed_mtime))) = existing {
+            if hashed_size == size && hashed_mtime == mtime {
+                debug!("Content hash up to date for {}", path.display());
+                return Ok(false);
+            }
+        }
+
+        let hash = Self::hash_file(path)?;
+        let conn = self.pool.get()?;
+        conn.execute(
+            "UPDATE stl_files SET content_hash = ?1, content_hash_size = ?2, content_hash_mtime = ?3
+             WHERE id = ?4",
+            params![hash, size, mtime, stl_file_id],
+        )?;
+
+        Ok(true)
+    }
+
+    /// Groups of duplicate model files, largest waste first
+    pub fn find_duplicates(
+        &self,
+        mode: DuplicateMode,
+        pagination: &PaginationParams,
+    ) -> Result<DuplicateReport, AppError> {
+        match mode {
+            DuplicateMode::Exact => self.find_identical_files(pagination),
+            DuplicateMode::Similar => self.find_similar_models(pagination),
+        }
+    }
+
+    fn find_identical_files(
+        &self,
+        pagination: &PaginationParams,
+    ) -> Result<DuplicateReport, AppError> {
+        let conn = self.pool.get()?;
+
+        let (total_groups, total_wasted_bytes): (usize, i64) = conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(wasted), 0)
+             FROM (
+                 SELECT (COUNT(*) - 1) * MAX(content_hash_size) AS wasted
+                 FROM stl_files
+                 WHERE content_hash IS NOT NULL
+                 GROUP BY content_hash
+                 HAVING COUNT(*) > 1
+             )",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT content_hash, MAX(content_hash_size), COUNT(*)
+             FROM stl_files
+             WHERE content_hash IS NOT NULL
+             GROUP BY content_hash
+             HAVING COUNT(*) > 1
+             ORDER BY (COUNT(*) - 1) * MAX(content_hash_size) DESC, content_hash
+             LIMIT ?1 OFFSET ?2",
+        )?;
+
+        let summaries = stmt
+            .query_map(params![pagination.limit(), pagination.offset()], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, usize>(2)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut files_stmt = conn.prepare(
+            "SELECT s.id, s.filename, s.file_path, p.id, p.name, p.full_path
+             FROM stl_files s
+             INNER JOIN projects p ON p.id = s.project_id
+             WHERE s.content_hash = ?1
+             ORDER BY p.full_path, s.filename",
+        )?;
+
+        let mut groups = Vec::with_capacity(summaries.len());
+        for (content_hash, file_size, copies) in summaries {
+            let files = files_stmt
+                .query_map(params![content_hash], duplicate_file_from_row)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            groups.push(DuplicateGroup {
+                content_hash: Some(content_hash),
+                geometry_fingerprint: None,
+                file_size,
+                copies,
+                wasted_bytes: (copies as i64 - 1) * file_size,
+                files,
+            });
+        }
+
+        Ok(DuplicateReport {
+            groups,
+            total_groups,
+            total_wasted_bytes,
+        })
+    }
+
+    /// Meshes whose fingerprints match within the fingerprint tolerance. Matching is
+    /// not exact, so files are grouped here rather than by the database.
+    fn find_similar_models(
+        &self,
+        pagination: &PaginationParams,
+    ) -> Result<DuplicateReport, AppError> {
+        let conn = self.pool.get()?;
+
+        // (file id, file size, fingerprint, parsed components), by radius
+        let mut meshes: Vec<(i64, i64, String, [f64; 6])> = conn
+            .prepare(
+                "SELECT s.id, s.file_size, m.geometry_fingerprint
+                 FROM stl_files s
+                 INNER JOIN mesh_analysis m ON m.stl_file_id = s.id
+                 WHERE m.geometry_fingerprint IS NOT NULL AND m.triangle_count > 0",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, i64>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })?
+            .filter_map(|row| match row {
+                Ok((id, size, fingerprint)) => parse_fingerprint(&fingerprint)
+                    .map(|components| Ok((id, size, fingerprint, components))),
+                Err(e) => Some(Err(e)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        meshes.sort_by(|a, b| a.3[0].total_cmp(&b.3[0]).then(a.0.cmp(&b.0)));
+
+        // Only meshes with a close radius can match, so each mesh is compared with
+        // the following ones until the radius is out of tolerance
+        let mut parent: Vec<usize> = (0..meshes.len()).collect();
+        for i in 0..meshes.len() {
+            for j in i + 1..meshes.len() {
+                if meshes[j].3[0] * (1.0 - FINGERPRINT_TOLERANCE) > meshes[i].3[0] {
+                    break;
+                }
+                if fingerprints_match(&meshes[i].3, &meshes[j].3) {
+                    let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for i in 0..meshes.len() {
+            let root = find(&mut parent, i);
+            members.entry(root).or_default().push(i);
+        }
+        // (fingerprint, smallest size, wasted bytes, file ids)
+        let mut summaries: Vec<(String, i64, i64, Vec<i64>)> = members
+            .into_values()
+            .filter(|group| group.len() > 1)
+            .map(|group| {
+                let sizes = group.iter().map(|&i| meshes[i].1);
+                let smallest = sizes.clone().min().unwrap_or(0);
+                let first = group.iter().min_by_key(|&&i| meshes[i].0).unwrap();
+                (
+                    meshes[*first].2.clone(),
+                    smallest,
+                    sizes.sum::<i64>() - smallest,
+                    group.iter().map(|&i| meshes[i].0).collect(),
+                )
+            })
+            .collect();
+        summaries.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+        let total_groups = summaries.len();
+        let total_wasted_bytes = summaries.iter().map(|summary| summary.2).sum();
+
+        let mut files_stmt = conn.prepare(
+            "SELECT s.id, s.filename, s.file_path, p.id, p.name, p.full_path
+             FROM stl_files s
+             INNER JOIN projects p ON p.id = s.project_id
+             WHERE s.id = ?1",
+        )?;
+
+        let mut groups = Vec::new();
+        for (fingerprint, file_size, wasted_bytes, file_ids) in summaries
+            .into_iter()
+            .skip(pagination.offset() as usize)
+            .take(pagination.limit() as usize)
+        {
+            let mut files = file_ids
+                .iter()
+                .map(|id| files_stmt.query_row(params![id], duplicate_file_from_row))
+                .collect::<Result<Vec<_>, _>>()?;
+            files.sort_by(|a, b| {
+                (&a.project_path, &a.filename).cmp(&(&b.project_path, &b.filename))
+            });
+
+            groups.push(DuplicateGroup {
+                content_hash: None,
+                geometry_fingerprint: Some(fingerprint),
+                file_size,
+                copies: files.len(),
+                wasted_bytes,
+                files,
+            });
+        }
+
+        Ok(DuplicateReport {
+            groups,
+            total_groups,
+            total_wasted_bytes,
+        })
+    }
+}
+
+fn duplicate_file_from_row(row: &rusqlite::Row) -> rusqlite::Result<DuplicateFile> {
+    Ok(DuplicateFile {
+        stl_file_id: row.get(0)?,
+        filename: row.get(1)?,
+        file_path: row.get(2)?,
+        project_id: row.get(3)?,
+        project_name: row.get(4)?,
+        project_path: row.get(5)?,
+    })
+}
+
+/// Union-find root of `i`
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
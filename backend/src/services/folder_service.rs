@@ -1,10 +1,13 @@
+use crate::config::ConfigService;
 use crate::db::connection::DbPool;
 use crate::models::image_file::ImageFile;
+use crate::models::library_root::LibraryRoot;
 use crate::models::project::{ImagePreview, Project, StlCategory};
 use crate::models::stl_file::StlFile;
 use anyhow::Result;
+use rusqlite::OptionalExtension;
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct FolderContents {
@@ -15,6 +18,8 @@ pub struct FolderContents {
     pub total_projects: usize,
     pub is_leaf_project: bool, // T042: Indicates if current path is a leaf project (should show project view, not browse view)
     pub project_details: Option<ProjectDetails>, // Project details when path IS a project
+    /// Path of the folder shown when `current_path` lies in a symlinked folder
+    pub link_target: Option<String>,
 }
 
 /// T038: Project with preview metadata for folder-level display
@@ -39,6 +44,8 @@ pub struct FolderInfo {
     pub path: String,
     pub project_count: usize,
     pub has_images: bool,
+    /// Path of the folder a symlinked folder leads to
+    pub link_target: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -47,9 +54,12 @@ pub struct BreadcrumbItem {
     pub path: String,
 }
 
+#[derive(Clone)]
 pub struct FolderService {
     pool: DbPool,
     root_path: PathBuf,
+    /// Label of the first breadcrumb
+    root_name: String,
     ignored_keywords: Vec<String>,
 }
 
@@ -58,10 +68,28 @@ impl FolderService {
         Self {
             pool,
             root_path,
+            root_name: "Root".to_string(),
             ignored_keywords: Vec::new(),
         }
     }
 
+    /// The same service browsing another library root
+    pub fn for_root(&self, root: &LibraryRoot) -> Self {
+        Self {
+            root_path: PathBuf::from(&root.path),
+            root_name: root.name.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// The same service browsing the folders below another path
+    pub fn with_root_path(&self, root_path: &Path) -> Self {
+        Self {
+            root_path: root_path.to_path_buf(),
+            ..self.clone()
+        }
+    }
+
     pub fn with_ignored_keywords(mut self, keywords: Vec<String>) -> Self {
         self.ignored_keywords = keywords;
         self
@@ -85,14 +113,17 @@ impl FolderService {
         // Validate path security
         self.validate_path(relative_path)?;
 
-        let full_path = self.root_path.join(relative_path);
+        // A symlinked folder shows the folder it leads to, indexed under its real path
+        let link_target = self.resolve_link(relative_path)?;
+        let contents_path = link_target.as_deref().unwrap_or(relative_path);
+        let full_path = self.root_path.join(contents_path);
 
         // Check if this path itself is a project (leaf node)
-        let is_leaf_project = self.is_path_a_project(relative_path)?;
+        let is_leaf_project = self.is_path_a_project(contents_path)?;
 
         let project_details = if is_leaf_project {
             // This path is a project - fetch its details
-            Some(self.get_project_details_by_path(relative_path)?)
+            Some(self.get_project_details_by_path(contents_path)?)
         } else {
             None
         };
@@ -110,13 +141,13 @@ impl FolderService {
         let offset = (page - 1) * per_page;
 
         let projects = if !is_leaf_project {
-            self.get_projects_at_path(relative_path, per_page, offset)?
+            self.get_projects_at_path(contents_path, per_page, offset)?
         } else {
             Vec::new()
         };
 
         let total_projects = if !is_leaf_project {
-            self.count_projects_at_path(relative_path)?
+            self.count_projects_at_path(contents_path)?
         } else {
             0
         };
@@ -129,13 +160,14 @@ impl FolderService {
             total_projects,
             is_leaf_project,
             project_details,
+            link_target,
         })
     }
 
     /// Get breadcrumb trail for the given path
     pub fn get_breadcrumb_trail(&self, relative_path: &str) -> Result<Vec<BreadcrumbItem>> {
         let mut breadcrumbs = vec![BreadcrumbItem {
-            name: "Root".to_string(),
+            name: self.root_name.clone(),
             path: "".to_string(),
         }];
 
@@ -161,17 +193,55 @@ impl FolderService {
         Ok(breadcrumbs)
     }
 
-    /// Validate path to prevent directory traversal attacks
+    /// Path stored in `projects.full_path` for a folder relative to the root
+    fn db_path(&self, relative_path: &str) -> String {
+        let root = self.root_path.to_string_lossy();
+        let root = root.trim_end_matches('/');
+        if relative_path.is_empty() {
+            root.to_string()
+        } else {
+            format!("{}/{}", root, relative_path)
+        }
+    }
+
+    /// Where `relative_path` leads when it lies in a symlinked folder recorded by the
+    /// last scan, relative to the root. None when no folder link is involved.
+    fn resolve_link(&self, relative_path: &str) -> Result<Option<String>> {
+        let conn = self.pool.get()?;
+        let db_path = self.db_path(relative_path);
+
+        let link: Option<(String, String)> = conn
+            .query_row(
+                "SELECT link_path, target_path FROM folder_links
+                 WHERE external = 0
+                   AND (?1 = link_path OR substr(?1, 1, length(link_path) + 1) = link_path || '/')
+                 ORDER BY length(link_path) DESC
+                 LIMIT 1",
+                [&db_path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        Ok(link.and_then(|(link_path, target_path)| {
+            let rest = Path::new(&db_path).strip_prefix(&link_path).ok()?;
+            let target = Path::new(&target_path);
+            let target = if rest.as_os_str().is_empty() {
+                target.to_path_buf()
+            } else {
+                target.join(rest)
+            };
+            target
+                .strip_prefix(&self.root_path)
+                .ok()
+                .map(|path| path.to_string_lossy().to_string())
+        }))
+    }
+
     /// Check if the given path corresponds to an existing project
     fn is_path_a_project(&self, relative_path: &str) -> Result<bool> {
         let conn = self.pool.get()?;
 
-        // Build the full database path with /projects prefix
-        let db_path = if relative_path.is_empty() {
-            "/projects".to_string()
-        } else {
-            format!("/projects/{}", relative_path)
-        };
+        let db_path = self.db_path(relative_path);
 
         tracing::debug!(
             "Checking if path is a project: relative='{}', db_path='{}'",
@@ -192,21 +262,57 @@ impl FolderService {
         Ok(exists)
     }
 
+    /// Validate path to prevent directory traversal attacks. Only plain folder names
+    /// are accepted, and a path may only lead outside the root through a symlink the
+    /// last scan followed, into that symlink's own target.
     fn validate_path(&self, relative_path: &str) -> Result<()> {
-        if relative_path.contains("..") {
-            anyhow::bail!("Path contains invalid '..' sequence");
+        if !Path::new(relative_path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            anyhow::bail!("Path must be relative to the root, without '.' or '..'");
         }
 
-        let full_path = self.root_path.join(relative_path);
-        let canonical_path = full_path.canonicalize().ok();
+        let canonical = match self.root_path.join(relative_path).canonicalize() {
+            Ok(canonical) => canonical,
+            // Missing folders are shown empty
+            Err(_) => return Ok(()),
+        };
+        let canonical_root = self
+            .root_path
+            .canonicalize()
+            .unwrap_or_else(|_| self.root_path.clone());
+        if canonical.starts_with(&canonical_root) {
+            return Ok(());
+        }
 
-        if let Some(canonical) = canonical_path {
-            if !canonical.starts_with(&self.root_path) {
-                anyhow::bail!("Path escapes root directory");
-            }
+        if self.follows_symlinks() && self.through_external_link(relative_path, &canonical)? {
+            return Ok(());
         }
+        anyhow::bail!("Path escapes root directory")
+    }
 
-        Ok(())
+    /// Whether `relative_path` lies in a symlinked folder leading outside the root,
+    /// recorded by the last scan, and `canonical` is inside that link's target
+    fn through_external_link(&self, relative_path: &str, canonical: &Path) -> Result<bool> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT target_path FROM folder_links
+             WHERE external = 1
+               AND (?1 = link_path OR substr(?1, 1, length(link_path) + 1) = link_path || '/')",
+        )?;
+        let targets = stmt
+            .query_map([self.db_path(relative_path)], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(targets.iter().any(|target| canonical.starts_with(target)))
+    }
+
+    fn follows_symlinks(&self) -> bool {
+        ConfigService::new(self.pool.clone())
+            .get_config()
+            .map(|config| config.follow_symlinks)
+            .unwrap_or(false)
     }
 
     /// Get immediate child folders
@@ -217,6 +323,7 @@ impl FolderService {
 
         let mut folders = Vec::new();
         let entries = std::fs::read_dir(full_path)?;
+        let ignore = ConfigService::new(self.pool.clone()).ignore_rules(&self.root_path);
 
         for entry in entries.flatten() {
             let path = entry.path();
@@ -227,20 +334,29 @@ impl FolderService {
                         continue;
                     }
 
+                    // Skip folders excluded by ignore patterns
+                    if ignore.is_ignored(&path, true) {
+                        continue;
+                    }
+
                     let relative_path = path
                         .strip_prefix(&self.root_path)
                         .unwrap_or(&path)
                         .to_string_lossy()
                         .to_string();
 
-                    let project_count = self.count_projects_at_path(&relative_path).unwrap_or(0);
-                    let has_images = self.folder_has_images(&relative_path).unwrap_or(false);
+                    // A symlinked folder counts the projects of the folder it leads to
+                    let link_target = self.resolve_link(&relative_path).unwrap_or(None);
+                    let contents_path = link_target.as_deref().unwrap_or(&relative_path);
+                    let project_count = self.count_projects_at_path(contents_path).unwrap_or(0);
+                    let has_images = self.folder_has_images(contents_path).unwrap_or(false);
 
                     folders.push(FolderInfo {
                         name: name.to_string(),
                         path: relative_path,
                         project_count,
                         has_images,
+                        link_target,
                     });
                 }
             }
@@ -260,14 +376,9 @@ impl FolderService {
     ) -> Result<Vec<ProjectWithPreview>> {
         let conn = self.pool.get()?;
 
-        // Build the full database path with /projects prefix
-        let db_path = if path.is_empty() {
-            "/projects".to_string()
-        } else {
-            format!("/projects/{}", path)
-        };
+        let db_path = self.db_path(path);
 
-        let query = "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+        let query = "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects 
              WHERE full_path LIKE ?1 || '/%' AND full_path NOT LIKE ?1 || '/%/%'
              ORDER BY name COLLATE NOCASE
@@ -296,6 +407,7 @@ impl FolderService {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -366,12 +478,7 @@ impl FolderService {
     fn count_projects_at_path(&self, path: &str) -> Result<usize> {
         let conn = self.pool.get()?;
 
-        // Build the full database path with /projects prefix
-        let db_path = if path.is_empty() {
-            "/projects".to_string()
-        } else {
-            format!("/projects/{}", path)
-        };
+        let db_path = self.db_path(path);
 
         let count: usize = conn.query_row(
             "SELECT COUNT(*) FROM projects 
@@ -403,16 +510,11 @@ impl FolderService {
     fn get_project_details_by_path(&self, relative_path: &str) -> Result<ProjectDetails> {
         let conn = self.pool.get()?;
 
-        // Build the full database path with /projects prefix
-        let db_path = if relative_path.is_empty() {
-            "/projects".to_string()
-        } else {
-            format!("/projects/{}", relative_path)
-        };
+        let db_path = self.db_path(relative_path);
 
         // Get the project
         let project: Project = conn.query_row(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at
+            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at, root_id
              FROM projects 
              WHERE full_path = ?1",
             [&db_path],
@@ -427,13 +529,14 @@ impl FolderService {
                     folder_level: row.get(6)?,
                     created_at: row.get(7)?,
                     updated_at: row.get(8)?,
+                    root_id: row.get(9)?,
                 })
             },
         )?;
 
         // Get STL files and group by category
         let mut stmt = conn.prepare(
-            "SELECT id, filename, file_path, file_size, category, project_id, created_at, updated_at, preview_path, preview_generated_at
+            "SELECT id, filename, file_path, file_size, category, project_id, created_at, updated_at, preview_path, preview_generated_at, format
              FROM stl_files
              WHERE project_id = ?1
              ORDER BY category, filename",
@@ -452,6 +555,8 @@ impl FolderService {
                     updated_at: row.get(7)?,
                     preview_path: row.get(8)?,
                     preview_generated_at: row.get(9)?,
+                    format: row.get(10)?,
+                    analysis: None,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -1,4 +1,5 @@
 use crate::db::connection::DbPool;
+use crate::utils::archive;
 use crate::utils::error::AppError;
 use rusqlite::params;
 use sha2::{Digest, Sha256};
@@ -47,7 +48,7 @@ impl ImageCacheService {
         }
 
         let original = Path::new(original_path);
-        if !original.exists() {
+        if !archive::exists(original) {
             return Err(AppError::NotFound(format!(
                 "Image not found: {}",
                 original_path
@@ -62,7 +63,12 @@ impl ImageCacheService {
         let cache_filename = format!("{}.{}", hash, ext);
         let cache_path = self.cache_dir.join("images").join(cache_filename);
 
-        fs::copy(original, &cache_path)?;
+        // Images inside an archive are copied straight out of it
+        archive::read_file(original, |reader, _| {
+            let mut cached = fs::File::create(&cache_path)?;
+            std::io::copy(reader, &mut cached)?;
+            Ok(())
+        })?;
 
         let file_size = fs::metadata(&cache_path)?.len() as i64;
         let now = std::time::SystemTime::now()
@@ -178,6 +184,28 @@ impl ImageCacheService {
         }
     }
 
+    /// Drop the cached copy or rendered preview of a file that changed on disk.
+    /// Returns true if a cache entry existed.
+    pub fn invalidate(&self, original_path: &str) -> Result<bool, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt =
+            conn.prepare("SELECT id, cache_path FROM cached_files WHERE original_path = ?1")?;
+
+        let entries: Vec<(i64, String)> = stmt
+            .query_map(params![original_path], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, cache_path) in &entries {
+            let cache_file = Path::new(cache_path);
+            if cache_file.exists() {
+                let _ = fs::remove_file(cache_file);
+            }
+            conn.execute("DELETE FROM cached_files WHERE id = ?1", params![id])?;
+        }
+
+        Ok(!entries.is_empty())
+    }
+
     /// Clean up orphaned cache entries where original files no longer exist
     pub fn cleanup_orphaned(&self) -> Result<usize, AppError> {
         let conn = self.pool.get()?;
@@ -0,0 +1,1226 @@
+use crate::models::stl_file::ModelFormat;
+use crate::utils::archive;
+use crate::utils::error::AppError;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Larger model files are neither rendered nor analyzed
+pub const MAX_MESH_FILE_SIZE: u64 = 100 * 1024 * 1024;
+
+pub type Vertex = [f32; 3];
+pub type Triangle = [Vertex; 3];
+
+/// Triangle soup loaded from a mesh file
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub triangles: Vec<Triangle>,
+}
+
+impl Mesh {
+    /// Load a mesh from disk or from an archive based on its file extension.
+    /// 3MF packages are not supported here (their previews come from the embedded thumbnail).
+    pub fn load(path: &Path) -> Result<Self, AppError> {
+        let format = ModelFormat::from_path(path).ok_or_else(|| {
+            AppError::ValidationError(format!("Unsupported mesh format: {}", path.display()))
+        })?;
+
+        archive::read_file(path, |file, file_len| {
+            let reader = BufReader::new(file);
+
+            match format {
+                ModelFormat::Stl => Self::from_stl(reader, file_len),
+                ModelFormat::Obj => Self::from_obj(reader),
+                ModelFormat::Ply => Self::from_ply(reader),
+                other => Err(AppError::ValidationError(format!(
+                    "Mesh loading not supported for {} files",
+                    other.as_str()
+                ))),
+            }
+        })
+    }
+
+    /// Parse a binary or ASCII STL file.
+    /// Binary files are recognised by their triangle count matching the file length,
+    /// since some exporters write binary STLs whose header starts with "solid".
+    pub fn from_stl<R: BufRead>(mut reader: R, file_len: u64) -> Result<Self, AppError> {
+        let mut header = [0u8; 84];
+        let header_len = read_up_to(&mut reader, &mut header)?;
+
+        if header_len == 84 {
+            let count = u32::from_le_bytes([header[80], header[81], header[82], header[83]]) as u64;
+            if 84 + count * 50 == file_len || !header.starts_with(b"solid") {
+                return Self::from_binary_stl(reader, count, file_len);
+            }
+        }
+
+        // ASCII: re-attach the bytes consumed while sniffing the header
+        let chained = std::io::Cursor::new(header[..header_len].to_vec()).chain(reader);
+        Self::from_ascii_stl(BufReader::new(chained))
+    }
+
+    fn from_binary_stl<R: Read>(
+        mut reader: R,
+        count: u64,
+        file_len: u64,
+    ) -> Result<Self, AppError> {
+        // The header's count is only trusted when the file is as long as it implies
+        let records = file_len.saturating_sub(84) / 50;
+        if count != records {
+            return Err(AppError::ValidationError(format!(
+                "Binary STL declares {} triangles but holds {}",
+                count, records
+            )));
+        }
+        let mut triangles = Vec::with_capacity(records as usize);
+        let mut record = [0u8; 50];
+
+        for _ in 0..count {
+            reader.read_exact(&mut record).map_err(|_| {
+                AppError::ValidationError("Unexpected end of binary STL data".to_string())
+            })?;
+
+            // Skip the stored normal (12 bytes); it is recomputed when needed
+            let mut triangle = [[0f32; 3]; 3];
+            for (v, vertex) in triangle.iter_mut().enumerate() {
+                for (c, value) in vertex.iter_mut().enumerate() {
+                    let offset = 12 + v * 12 + c * 4;
+                    *value = f32::from_le_bytes([
+                        record[offset],
+                        record[offset + 1],
+                        record[offset + 2],
+                        record[offset + 3],
+                    ]);
+                }
+            }
+            triangles.push(triangle);
+        }
+
+        Ok(Self { triangles })
+    }
+
+    fn from_ascii_stl<R: BufRead>(reader: R) -> Result<Self, AppError> {
+        let mut triangles = Vec::new();
+        let mut current: Vec<Vertex> = Vec::with_capacity(3);
+
+        for line in reader.lines() {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("vertex") => {
+                    let coords: Vec<f32> = parts
+                        .take(3)
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| {
+                            AppError::ValidationError(format!("Invalid STL vertex: {}", e))
+                        })?;
+                    if coords.len() != 3 {
+                        return Err(AppError::ValidationError(
+                            "STL vertex needs 3 coordinates".to_string(),
+                        ));
+                    }
+                    current.push([coords[0], coords[1], coords[2]]);
+                }
+                Some("endfacet") => {
+                    if current.len() == 3 {
+                        triangles.push([current[0], current[1], current[2]]);
+                    }
+                    current.clear();
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { triangles })
+    }
+
+    /// Parse a Wavefront OBJ file. Polygons are fan-triangulated.
+    pub fn from_obj<R: BufRead>(reader: R) -> Result<Self, AppError> {
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut triangles = Vec::new();
+
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut parts = line.split_whitespace();
+
+            match parts.next() {
+                Some("v") => {
+                    let coords: Vec<f32> = parts
+                        .take(3)
+                        .map(|p| p.parse::<f32>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| obj_error(line_no, &e.to_string()))?;
+                    if coords.len() != 3 {
+                        return Err(obj_error(line_no, "vertex needs 3 coordinates"));
+                    }
+                    vertices.push([coords[0], coords[1], coords[2]]);
+                }
+                Some("f") => {
+                    let indices = parts
+                        .map(|p| resolve_obj_index(p, vertices.len()))
+                        .collect::<Option<Vec<usize>>>()
+                        .ok_or_else(|| obj_error(line_no, "invalid face index"))?;
+
+                    for i in 1..indices.len().saturating_sub(1) {
+                        triangles.push([
+                            vertices[indices[0]],
+                            vertices[indices[i]],
+                            vertices[indices[i + 1]],
+                        ]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { triangles })
+    }
+
+    /// Parse a PLY file (ASCII, binary little endian or binary big endian)
+    pub fn from_ply<R: BufRead>(mut reader: R) -> Result<Self, AppError> {
+        let header = PlyHeader::read(&mut reader)?;
+
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut triangles = Vec::new();
+
+        let mut values: Box<dyn PlyValueReader> = match header.encoding {
+            PlyEncoding::Ascii => Box::new(AsciiValues::new(reader)),
+            PlyEncoding::BinaryLittleEndian => Box::new(BinaryValues::new(reader, false)),
+            PlyEncoding::BinaryBigEndian => Box::new(BinaryValues::new(reader, true)),
+        };
+
+        for element in &header.elements {
+            for _ in 0..element.count {
+                let mut position = [0f32; 3];
+                let mut face: Vec<usize> = Vec::new();
+
+                for property in &element.properties {
+                    match property {
+                        PlyProperty::Scalar { kind, name } => {
+                            let value = values.read(*kind)?;
+                            if element.name == "vertex" {
+                                match name.as_str() {
+                                    "x" => position[0] = value as f32,
+                                    "y" => position[1] = value as f32,
+                                    "z" => position[2] = value as f32,
+                                    _ => {}
+                                }
+                            }
+                        }
+                        PlyProperty::List {
+                            count_kind,
+                            item_kind,
+                            name,
+                        } => {
+                            let count = values.read(*count_kind)? as usize;
+                            let is_face_indices = element.name == "face"
+                                && (name == "vertex_indices" || name == "vertex_index");
+                            for _ in 0..count {
+                                let value = values.read(*item_kind)?;
+                                if is_face_indices {
+                                    face.push(value as usize);
+                                }
+                            }
+                        }
+                    }
+                }
+
+                if element.name == "vertex" {
+                    vertices.push(position);
+                } else if element.name == "face" {
+                    if face.iter().any(|&i| i >= vertices.len()) {
+                        return Err(AppError::ValidationError(
+                            "PLY face references unknown vertex".to_string(),
+                        ));
+                    }
+                    for i in 1..face.len().saturating_sub(1) {
+                        triangles.push([
+                            vertices[face[0]],
+                            vertices[face[i]],
+                            vertices[face[i + 1]],
+                        ]);
+                    }
+                }
+            }
+        }
+
+        Ok(Self { triangles })
+    }
+
+    /// Write the mesh as a binary STL file
+    pub fn write_binary_stl(&self, path: &Path) -> Result<(), AppError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(&[0u8; 80])?;
+        writer.write_all(&(self.triangles.len() as u32).to_le_bytes())?;
+
+        for triangle in &self.triangles {
+            for value in face_normal(triangle) {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            for vertex in triangle {
+                for value in vertex {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+            writer.write_all(&0u16.to_le_bytes())?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Geometry measurements of a mesh, in model units (millimetres for 3D printing files)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshStats {
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+    pub triangle_count: usize,
+    pub surface_area: f64,
+    pub volume: f64,
+}
+
+impl MeshStats {
+    /// Bounding box extent along X, Y and Z
+    pub fn size(&self) -> [f64; 3] {
+        [
+            self.max[0] - self.min[0],
+            self.max[1] - self.min[1],
+            self.max[2] - self.min[2],
+        ]
+    }
+}
+
+impl Mesh {
+    /// Compute bounding box, surface area and enclosed volume.
+    /// Volume sums signed tetrahedra against the origin, which is exact for closed
+    /// meshes and an approximation otherwise.
+    pub fn stats(&self) -> MeshStats {
+        let mut min = [f64::INFINITY; 3];
+        let mut max = [f64::NEG_INFINITY; 3];
+        let mut surface_area = 0.0;
+        let mut signed_volume = 0.0;
+
+        for triangle in &self.triangles {
+            let [a, b, c] = triangle.map(|v| v.map(f64::from));
+
+            for vertex in [a, b, c] {
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(vertex[axis]);
+                    max[axis] = max[axis].max(vertex[axis]);
+                }
+            }
+
+            surface_area += triangle_area(triangle);
+
+            signed_volume += (a[0] * (b[1] * c[2] - b[2] * c[1])
+                - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0]))
+                / 6.0;
+        }
+
+        if self.triangles.is_empty() {
+            min = [0.0; 3];
+            max = [0.0; 3];
+        }
+
+        MeshStats {
+            min,
+            max,
+            triangle_count: self.triangles.len(),
+            surface_area,
+            volume: signed_volume.abs(),
+        }
+    }
+}
+
+/// Topology problems found in a mesh. All counts are zero for a clean, closed mesh.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshValidation {
+    /// Triangles with (near) zero area or repeated vertices
+    pub degenerate_triangles: usize,
+    /// Edges used by only one triangle (holes in the surface)
+    pub open_edges: usize,
+    /// Edges shared by more than two triangles
+    pub non_manifold_edges: usize,
+    /// Edges whose two triangles disagree on winding, i.e. one normal is flipped
+    pub inconsistent_edges: usize,
+    /// Closed, consistently wound mesh whose normals all point inwards
+    pub inverted: bool,
+    /// Number of disconnected shells
+    pub shells: usize,
+}
+
+/// Triangles smaller than this (in mm²) are considered degenerate
+const DEGENERATE_AREA: f64 = 1e-10;
+
+impl Mesh {
+    /// Check the mesh for printability problems. Vertices are welded by exact
+    /// position, which matches how STL exporters write shared corners.
+    pub fn validate(&self) -> MeshValidation {
+        let mut validation = MeshValidation::default();
+
+        let mut vertex_ids: HashMap<[u32; 3], usize> = HashMap::new();
+        let mut faces: Vec<[usize; 3]> = Vec::with_capacity(self.triangles.len());
+        let mut signed_volume = 0.0;
+
+        for triangle in &self.triangles {
+            let ids = triangle.map(|v| {
+                let next = vertex_ids.len();
+                // Normalise -0.0 so it welds with 0.0
+                *vertex_ids
+                    .entry(v.map(|c| (c + 0.0).to_bits()))
+                    .or_insert(next)
+            });
+
+            let area = triangle_area(triangle);
+            if ids[0] == ids[1] || ids[1] == ids[2] || ids[0] == ids[2] || area < DEGENERATE_AREA {
+                validation.degenerate_triangles += 1;
+                continue;
+            }
+
+            let [a, b, c] = triangle.map(|v| v.map(f64::from));
+            signed_volume += (a[0] * (b[1] * c[2] - b[2] * c[1])
+                - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0]))
+                / 6.0;
+
+            faces.push(ids);
+        }
+
+        // Undirected edge -> (uses, uses in the min->max direction)
+        let mut edges: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+        for face in &faces {
+            for (from, to) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])] {
+                let entry = edges.entry((from.min(to), from.max(to))).or_insert((0, 0));
+                entry.0 += 1;
+                if from < to {
+                    entry.1 += 1;
+                }
+            }
+        }
+
+        for &(uses, forward) in edges.values() {
+            match uses {
+                1 => validation.open_edges += 1,
+                // Consistently wound neighbours traverse a shared edge in opposite directions
+                2 if forward != 1 => validation.inconsistent_edges += 1,
+                2 => {}
+                _ => validation.non_manifold_edges += 1,
+            }
+        }
+
+        let is_closed_and_consistent = !faces.is_empty()
+            && validation.open_edges == 0
+            && validation.non_manifold_edges == 0
+            && validation.inconsistent_edges == 0;
+        validation.inverted = is_closed_and_consistent && signed_volume < 0.0;
+
+        // Shells: connected components of faces sharing vertices
+        let mut parent: Vec<usize> = (0..vertex_ids.len()).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for face in &faces {
+            let root = find(&mut parent, face[0]);
+            for &v in &face[1..] {
+                let other = find(&mut parent, v);
+                parent[other] = root;
+            }
+        }
+        let roots: HashSet<usize> = faces
+            .iter()
+            .map(|face| find(&mut parent, face[0]))
+            .collect();
+        validation.shells = roots.len();
+
+        validation
+    }
+}
+
+/// Significant digits stored per fingerprint component, well above the tolerance
+/// fingerprints are compared with
+const FINGERPRINT_DIGITS: usize = 6;
+
+/// Relative difference, against the model's radius, up to which two fingerprint
+/// components are considered equal. Re-exports (ASCII vs binary STL, OBJ round
+/// trips) differ only in float noise well below this.
+pub const FINGERPRINT_TOLERANCE: f64 = 1e-3;
+
+impl Mesh {
+    /// Geometry fingerprint that ignores file encoding, triangle order, vertex
+    /// winding, position and orientation. Meshes of the same shape produce
+    /// fingerprints that `fingerprints_match`.
+    ///
+    /// Components, all lengths: the distance from the surface centroid to the
+    /// farthest vertex, surface area, volume, and the three rotation invariants of
+    /// the surface's second moment about its centroid.
+    pub fn fingerprint(&self) -> String {
+        let stats = self.stats();
+
+        // Accumulate the first and second moments of the surface, relative to
+        // the bounding box minimum to keep the sums well conditioned
+        let relative = |v: &Vertex| {
+            let v = v.map(f64::from);
+            [
+                v[0] - stats.min[0],
+                v[1] - stats.min[1],
+                v[2] - stats.min[2],
+            ]
+        };
+        let mut area = 0.0;
+        let mut first = [0.0; 3];
+        let mut second = [[0.0; 3]; 3];
+        for triangle in &self.triangles {
+            let vertices = triangle.map(|v| relative(&v));
+            let triangle_area = triangle_area(triangle);
+            let sum = [0, 1, 2].map(|i| vertices.iter().map(|v| v[i]).sum::<f64>());
+
+            area += triangle_area;
+            for i in 0..3 {
+                first[i] += triangle_area * sum[i] / 3.0;
+                for j in 0..3 {
+                    let products: f64 = vertices.iter().map(|v| v[i] * v[j]).sum();
+                    second[i][j] += triangle_area * (products + sum[i] * sum[j]) / 12.0;
+                }
+            }
+        }
+
+        let mut centroid = [0.0; 3];
+        let mut covariance = [[0.0; 3]; 3];
+        if area > 0.0 {
+            centroid = first.map(|f| f / area);
+            for i in 0..3 {
+                for j in 0..3 {
+                    covariance[i][j] = second[i][j] / area - centroid[i] * centroid[j];
+                }
+            }
+        }
+
+        // Unlike bounding box extents, this does not change when the model is rotated
+        let radius = self
+            .triangles
+            .iter()
+            .flatten()
+            .map(|v| {
+                let v = relative(v);
+                ((v[0] - centroid[0]).powi(2)
+                    + (v[1] - centroid[1]).powi(2)
+                    + (v[2] - centroid[2]).powi(2))
+                .sqrt()
+            })
+            .fold(0.0, f64::max);
+
+        let c = covariance;
+        let trace = c[0][0] + c[1][1] + c[2][2];
+        let minors = c[0][0] * c[1][1] - c[0][1] * c[1][0] + c[0][0] * c[2][2] - c[0][2] * c[2][0]
+            + c[1][1] * c[2][2]
+            - c[1][2] * c[2][1];
+        let determinant = c[0][0] * (c[1][1] * c[2][2] - c[1][2] * c[2][1])
+            - c[0][1] * (c[1][0] * c[2][2] - c[1][2] * c[2][0])
+            + c[0][2] * (c[1][0] * c[2][1] - c[1][1] * c[2][0]);
+
+        // Express every component as a length so one tolerance fits all
+        let components = [
+            radius,
+            stats.surface_area.sqrt(),
+            stats.volume.cbrt(),
+            trace.max(0.0).sqrt(),
+            minors.max(0.0).powf(0.25),
+            determinant.max(0.0).powf(1.0 / 6.0),
+        ];
+
+        components
+            .iter()
+            .map(|&value| quantize(value, radius))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+/// Components of a fingerprint made by `Mesh::fingerprint`; None for fingerprints
+/// stored in another format
+pub fn parse_fingerprint(fingerprint: &str) -> Option<[f64; 6]> {
+    let mut components = [0.0; 6];
+    let mut parts = fingerprint.split(':');
+    for component in components.iter_mut() {
+        *component = parts.next()?.parse().ok()?;
+    }
+    parts.next().is_none().then_some(components)
+}
+
+/// Whether two fingerprints describe the same shape, i.e. every component is
+/// within `FINGERPRINT_TOLERANCE` of the other. Unlike comparing the strings,
+/// values rounded to either side of a digit still match.
+pub fn fingerprints_match(a: &[f64; 6], b: &[f64; 6]) -> bool {
+    // The radius is the largest component
+    let tolerance = a[0].max(b[0]) * FINGERPRINT_TOLERANCE;
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() <= tolerance)
+}
+
+/// Round a length to the fingerprint precision, flushing values that are
+/// negligible next to the model size to zero
+fn quantize(value: f64, scale: f64) -> String {
+    if value.abs() <= scale * 1e-6 {
+        return "0".to_string();
+    }
+    format!("{:.*e}", FINGERPRINT_DIGITS - 1, value)
+}
+
+fn triangle_area(triangle: &Triangle) -> f64 {
+    let [a, b, c] = triangle.map(|v| v.map(f64::from));
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let cross = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt()
+}
+
+/// Unit normal of a triangle (zero vector for degenerate triangles)
+pub fn face_normal(triangle: &Triangle) -> Vertex {
+    let [a, b, c] = triangle;
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if len > 0.0 {
+        [n[0] / len, n[1] / len, n[2] / len]
+    } else {
+        [0.0, 0.0, 0.0]
+    }
+}
+
+/// Find companion files of an OBJ mesh: material libraries referenced via `mtllib`
+/// and the texture maps those libraries reference. Only files that exist are returned,
+/// as paths relative to the OBJ's directory.
+pub fn obj_companion_files(obj_path: &Path) -> Vec<PathBuf> {
+    let base_dir = match obj_path.parent() {
+        Some(dir) => dir,
+        None => return Vec::new(),
+    };
+
+    let mut companions = Vec::new();
+
+    // `mtllib` takes one or more libraries, or a single name containing spaces
+    let mut libraries = Vec::new();
+    for arguments in directive_arguments(obj_path, &["mtllib"]) {
+        let whole = relative_reference(&arguments);
+        let names = match whole.filter(|name| base_dir.join(name).is_file()) {
+            Some(whole) => vec![whole],
+            None => arguments
+                .split_whitespace()
+                .filter_map(relative_reference)
+                .filter(|name| base_dir.join(name).is_file())
+                .collect(),
+        };
+        for name in names {
+            if !libraries.contains(&name) {
+                libraries.push(name);
+            }
+        }
+    }
+
+    for mtl in libraries {
+        // Texture paths in an MTL file are relative to the MTL's own directory
+        let mtl_dir = mtl.parent().map(Path::to_path_buf).unwrap_or_default();
+        for arguments in directive_arguments(&base_dir.join(&mtl), MTL_TEXTURE_DIRECTIVES) {
+            // Options like `-s 1 1 1` come before the name, which may contain spaces:
+            // take the longest tail of the line that names an existing file
+            let texture = token_starts(&arguments)
+                .filter_map(|start| relative_reference(&arguments[start..]))
+                .map(|name| mtl_dir.join(name))
+                .find(|relative| base_dir.join(relative).is_file());
+            if let Some(texture) = texture.filter(|t| !companions.contains(t)) {
+                companions.push(texture);
+            }
+        }
+
+        companions.insert(0, mtl);
+    }
+
+    companions
+}
+
+const MTL_TEXTURE_DIRECTIVES: &[&str] = &[
+    "map_Ka", "map_Kd", "map_Ks", "map_Ns", "map_d", "map_bump", "bump", "disp", "norm",
+];
+
+/// The rest of each line starting with one of the given directives, trimmed
+fn directive_arguments(path: &Path, directives: &[&str]) -> Vec<String> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => return Vec::new(),
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            let line = line.trim();
+            let directive = line.split_whitespace().next()?;
+            if !directives.contains(&directive) {
+                return None;
+            }
+            let arguments = line[directive.len()..].trim();
+            (!arguments.is_empty()).then(|| arguments.to_string())
+        })
+        .collect()
+}
+
+/// Byte offsets at which the whitespace-separated tokens of `text` start
+fn token_starts(text: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut previous = ' ';
+    text.char_indices().filter_map(move |(i, c)| {
+        let starts = previous.is_whitespace() && !c.is_whitespace();
+        previous = c;
+        starts.then_some(i)
+    })
+}
+
+/// A file name from a model or material file as a relative path. Never follows
+/// references outside the model's folder.
+fn relative_reference(name: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(name.trim().replace('\\', "/"));
+    if relative.as_os_str().is_empty()
+        || relative.is_absolute()
+        || relative
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return None;
+    }
+    Some(relative)
+}
+
+/// Fill `buf` as far as the reader allows, returning the number of bytes read
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, AppError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+fn obj_error(line_no: usize, message: &str) -> AppError {
+    AppError::ValidationError(format!(
+        "OBJ parse error on line {}: {}",
+        line_no + 1,
+        message
+    ))
+}
+
+/// Resolve an OBJ face token (`v`, `v/vt`, `v//vn`, `v/vt/vn`) to a zero-based index.
+/// Negative indices are relative to the end of the current vertex list.
+fn resolve_obj_index(token: &str, vertex_count: usize) -> Option<usize> {
+    let index: i64 = token.split('/').next()?.parse().ok()?;
+    let resolved = if index < 0 {
+        vertex_count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved >= 0 && (resolved as usize) < vertex_count {
+        Some(resolved as usize)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyEncoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(PlyType::Int8),
+            "uchar" | "uint8" => Some(PlyType::UInt8),
+            "short" | "int16" => Some(PlyType::Int16),
+            "ushort" | "uint16" => Some(PlyType::UInt16),
+            "int" | "int32" => Some(PlyType::Int32),
+            "uint" | "uint32" => Some(PlyType::UInt32),
+            "float" | "float32" => Some(PlyType::Float32),
+            "double" | "float64" => Some(PlyType::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            PlyType::Int8 | PlyType::UInt8 => 1,
+            PlyType::Int16 | PlyType::UInt16 => 2,
+            PlyType::Int32 | PlyType::UInt32 | PlyType::Float32 => 4,
+            PlyType::Float64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum PlyProperty {
+    Scalar {
+        kind: PlyType,
+        name: String,
+    },
+    List {
+        count_kind: PlyType,
+        item_kind: PlyType,
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+#[derive(Debug)]
+struct PlyHeader {
+    encoding: PlyEncoding,
+    elements: Vec<PlyElement>,
+}
+
+impl PlyHeader {
+    fn read<R: BufRead>(reader: &mut R) -> Result<Self, AppError> {
+        let invalid = |msg: &str| AppError::ValidationError(format!("Invalid PLY header: {}", msg));
+
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim() != "ply" {
+            return Err(invalid("missing 'ply' magic"));
+        }
+
+        let mut encoding = None;
+        let mut elements: Vec<PlyElement> = Vec::new();
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(invalid("missing end_header"));
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+
+            match parts.as_slice() {
+                ["end_header"] => break,
+                ["format", "ascii", ..] => encoding = Some(PlyEncoding::Ascii),
+                ["format", "binary_little_endian", ..] => {
+                    encoding = Some(PlyEncoding::BinaryLittleEndian)
+                }
+                ["format", "binary_big_endian", ..] => {
+                    encoding = Some(PlyEncoding::BinaryBigEndian)
+                }
+                ["element", name, count] => elements.push(PlyElement {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| invalid("bad element count"))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count_kind, item_kind, name] => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| invalid("property before element"))?;
+                    element.properties.push(PlyProperty::List {
+                        count_kind: PlyType::parse(count_kind)
+                            .ok_or_else(|| invalid("unknown list count type"))?,
+                        item_kind: PlyType::parse(item_kind)
+                            .ok_or_else(|| invalid("unknown list item type"))?,
+                        name: name.to_string(),
+                    });
+                }
+                ["property", kind, name] => {
+                    let element = elements
+                        .last_mut()
+                        .ok_or_else(|| invalid("property before element"))?;
+                    element.properties.push(PlyProperty::Scalar {
+                        kind: PlyType::parse(kind)
+                            .ok_or_else(|| invalid("unknown property type"))?,
+                        name: name.to_string(),
+                    });
+                }
+                _ => {} // comment, obj_info, blank lines
+            }
+        }
+
+        Ok(Self {
+            encoding: encoding.ok_or_else(|| invalid("missing format"))?,
+            elements,
+        })
+    }
+}
+
+trait PlyValueReader {
+    fn read(&mut self, kind: PlyType) -> Result<f64, AppError>;
+}
+
+struct AsciiValues<R: BufRead> {
+    reader: R,
+    tokens: std::vec::IntoIter<String>,
+}
+
+impl<R: BufRead> AsciiValues<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            tokens: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<R: BufRead> PlyValueReader for AsciiValues<R> {
+    fn read(&mut self, _kind: PlyType) -> Result<f64, AppError> {
+        loop {
+            if let Some(token) = self.tokens.next() {
+                return token.parse::<f64>().map_err(|_| {
+                    AppError::ValidationError(format!("Invalid PLY value: {}", token))
+                });
+            }
+
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(AppError::ValidationError(
+                    "Unexpected end of PLY data".to_string(),
+                ));
+            }
+            self.tokens = line
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+struct BinaryValues<R: Read> {
+    reader: R,
+    big_endian: bool,
+}
+
+impl<R: Read> BinaryValues<R> {
+    fn new(reader: R, big_endian: bool) -> Self {
+        Self { reader, big_endian }
+    }
+}
+
+impl<R: Read> PlyValueReader for BinaryValues<R> {
+    fn read(&mut self, kind: PlyType) -> Result<f64, AppError> {
+        let mut buf = [0u8; 8];
+        let bytes = &mut buf[..kind.size()];
+        self.reader
+            .read_exact(bytes)
+            .map_err(|_| AppError::ValidationError("Unexpected end of PLY data".to_string()))?;
+        if self.big_endian {
+            bytes.reverse();
+        }
+
+        Ok(match kind {
+            PlyType::Int8 => bytes[0] as i8 as f64,
+            PlyType::UInt8 => bytes[0] as f64,
+            PlyType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyType::Int32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyType::UInt32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyType::Float32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyType::Float64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn unit_cube() -> Mesh {
+        let obj = "v 0 0 0\nv 10 0 0\nv 10 10 0\nv 0 10 0\n\
+                   v 0 0 10\nv 10 0 10\nv 10 10 10\nv 0 10 10\n\
+                   f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+        Mesh::from_obj(Cursor::new(obj)).unwrap()
+    }
+
+    #[test]
+    fn test_stats_of_cube() {
+        let stats = unit_cube().stats();
+
+        assert_eq!(stats.triangle_count, 12);
+        assert_eq!(stats.size(), [10.0, 10.0, 10.0]);
+        assert!((stats.surface_area - 600.0).abs() < 1e-6);
+        assert!((stats.volume - 1000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_validate_closed_cube() {
+        let validation = unit_cube().validate();
+
+        assert_eq!(
+            validation,
+            MeshValidation {
+                shells: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_detects_problems() {
+        let mut mesh = unit_cube();
+        // Flip one face, open a hole, add a degenerate sliver and a separate shell
+        mesh.triangles[0].swap(1, 2);
+        mesh.triangles.pop();
+        mesh.triangles
+            .push([[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]]);
+        mesh.triangles
+            .push([[50.0, 0.0, 0.0], [51.0, 0.0, 0.0], [50.0, 1.0, 0.0]]);
+
+        let validation = mesh.validate();
+
+        assert_eq!(validation.degenerate_triangles, 1);
+        assert!(validation.inconsistent_edges > 0);
+        assert!(validation.open_edges > 0);
+        assert_eq!(validation.shells, 2);
+        assert!(!validation.inverted);
+    }
+
+    #[test]
+    fn test_validate_detects_inverted_mesh() {
+        let mut mesh = unit_cube();
+        for triangle in &mut mesh.triangles {
+            triangle.swap(1, 2);
+        }
+
+        assert!(mesh.validate().inverted);
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_order_and_winding() {
+        let cube = unit_cube();
+        let mut shuffled = unit_cube();
+        shuffled.triangles.reverse();
+        for triangle in &mut shuffled.triangles {
+            triangle.rotate_left(1);
+        }
+        // Float noise from an ASCII export round trip
+        shuffled.triangles[0][0][0] += 1e-6;
+
+        assert_eq!(cube.fingerprint(), shuffled.fingerprint());
+    }
+
+    fn fingerprint(mesh: &Mesh) -> [f64; 6] {
+        parse_fingerprint(&mesh.fingerprint()).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_rotation() {
+        let cube = unit_cube();
+        let mut rotated = unit_cube();
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        for triangle in &mut rotated.triangles {
+            for vertex in triangle.iter_mut() {
+                let [x, y, z] = *vertex;
+                *vertex = [x * cos - y * sin + 100.0, x * sin + y * cos, z];
+            }
+        }
+
+        assert_ne!(cube.stats().size(), rotated.stats().size());
+        assert!(fingerprints_match(
+            &fingerprint(&cube),
+            &fingerprint(&rotated)
+        ));
+    }
+
+    #[test]
+    fn test_fingerprints_match_across_rounding() {
+        // Just either side of a rounding step of the stored digits
+        let a = [1.0000049, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let b = [1.0000051, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let stored = |c: &[f64; 6]| {
+            let text = c.map(|v| quantize(v, c[0])).join(":");
+            parse_fingerprint(&text).unwrap()
+        };
+
+        assert_ne!(stored(&a), stored(&b));
+        assert!(fingerprints_match(&stored(&a), &stored(&b)));
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_shapes() {
+        let cube = unit_cube();
+        let mut stretched = unit_cube();
+        for triangle in &mut stretched.triangles {
+            for vertex in triangle.iter_mut() {
+                vertex[2] *= 1.5;
+            }
+        }
+        let mut moved_corner = unit_cube();
+        for triangle in &mut moved_corner.triangles {
+            for vertex in triangle.iter_mut() {
+                if *vertex == [10.0, 10.0, 10.0] {
+                    *vertex = [9.0, 9.0, 10.0];
+                }
+            }
+        }
+
+        assert!(!fingerprints_match(
+            &fingerprint(&cube),
+            &fingerprint(&stretched)
+        ));
+        assert!(!fingerprints_match(
+            &fingerprint(&cube),
+            &fingerprint(&moved_corner)
+        ));
+    }
+
+    #[test]
+    fn test_binary_stl_round_trip() {
+        let cube = unit_cube();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cube.stl");
+        cube.write_binary_stl(&path).unwrap();
+
+        let loaded = Mesh::load(&path).unwrap();
+
+        assert_eq!(loaded.triangles, cube.triangles);
+    }
+
+    #[test]
+    fn test_binary_stl_with_wrong_triangle_count_is_rejected() {
+        let mut stl = vec![0u8; 84 + 50];
+        stl[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let result = Mesh::from_stl(Cursor::new(&stl), stl.len() as u64);
+
+        assert!(matches!(result, Err(AppError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_obj_companions_with_several_libraries_and_spaces() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let obj = dir.path().join("bust.obj");
+        std::fs::write(
+            &obj,
+            "mtllib a.mtl b.mtl\nmtllib painted bust.mtl\nv 0 0 0\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("a.mtl"), "map_Kd -s 1 1 1 skin tone.png\n").unwrap();
+        std::fs::write(dir.path().join("b.mtl"), "map_Kd a.png\n").unwrap();
+        std::fs::write(dir.path().join("painted bust.mtl"), "bump ../outside.png\n").unwrap();
+        for texture in ["skin tone.png", "a.png"] {
+            std::fs::write(dir.path().join(texture), "png").unwrap();
+        }
+
+        let mut companions = obj_companion_files(&obj);
+        companions.sort();
+        let expected: Vec<PathBuf> = [
+            "a.mtl",
+            "a.png",
+            "b.mtl",
+            "painted bust.mtl",
+            "skin tone.png",
+        ]
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+        assert_eq!(companions, expected);
+    }
+
+    #[test]
+    fn test_parse_ascii_stl() {
+        let stl = "solid tri\n\
+                   facet normal 0 0 1\n outer loop\n\
+                   vertex 0 0 0\n vertex 4 0 0\n vertex 0 3 0\n\
+                   endloop\nendfacet\nendsolid tri\n";
+        let mesh = Mesh::from_stl(Cursor::new(stl), stl.len() as u64).unwrap();
+
+        assert_eq!(mesh.triangles.len(), 1);
+        assert!((mesh.stats().surface_area - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parse_obj_with_quad_and_negative_indices() {
+        let obj = "# cube face\n\
+                   v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vn 0 0 1\n\
+                   f 1//1 2//1 3//1 4//1\n\
+                   f -4 -3 -2\n";
+        let mesh = Mesh::from_obj(Cursor::new(obj)).unwrap();
+
+        // Quad is split into two triangles, plus one triangle with relative indices
+        assert_eq!(mesh.triangles.len(), 3);
+        assert_eq!(mesh.triangles[0][2], [1.0, 1.0, 0.0]);
+        assert_eq!(mesh.triangles[2][0], [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_parse_obj_rejects_bad_index() {
+        let obj = "v 0 0 0\nf 1 2 3\n";
+        assert!(Mesh::from_obj(Cursor::new(obj)).is_err());
+    }
+
+    #[test]
+    fn test_parse_ascii_ply() {
+        let ply = "ply\nformat ascii 1.0\ncomment test\n\
+                   element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                   element face 1\nproperty list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+        let mesh = Mesh::from_ply(Cursor::new(ply)).unwrap();
+
+        assert_eq!(mesh.triangles.len(), 1);
+        assert_eq!(mesh.triangles[0][1], [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_parse_binary_ply() {
+        let mut data = b"ply\nformat binary_little_endian 1.0\n\
+                         element vertex 3\nproperty float x\nproperty float y\nproperty float z\n\
+                         property uchar red\n\
+                         element face 1\nproperty list uchar uint vertex_index\nend_header\n"
+            .to_vec();
+        for v in [[0f32, 0., 0.], [2., 0., 0.], [0., 2., 0.]] {
+            for c in v {
+                data.extend_from_slice(&c.to_le_bytes());
+            }
+            data.push(255);
+        }
+        data.push(3);
+        for i in [0u32, 1, 2] {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+
+        let mesh = Mesh::from_ply(Cursor::new(data)).unwrap();
+
+        assert_eq!(mesh.triangles.len(), 1);
+        assert_eq!(mesh.triangles[0][2], [0.0, 2.0, 0.0]);
+    }
+}
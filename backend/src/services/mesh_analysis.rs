@@ -0,0 +1,258 @@
+use crate::db::connection::DbPool;
+use crate::models::mesh_analysis::{MeshAnalysis, MeshIssue};
+use crate::models::stl_file::ModelFormat;
+use crate::services::mesh::{Mesh, MeshValidation, MAX_MESH_FILE_SIZE};
+use crate::utils::error::AppError;
+use crate::utils::fs::file_signature;
+use rusqlite::{params, OptionalExtension};
+use std::path::Path;
+use tracing::debug;
+
+/// Default material density in g/cm³ (PLA), used for mass estimates
+pub const DEFAULT_MATERIAL_DENSITY: f64 = 1.24;
+
+#[derive(Clone)]
+pub struct MeshAnalysisService {
+    pool: DbPool,
+    material_density: f64,
+}
+
+impl MeshAnalysisService {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            material_density: DEFAULT_MATERIAL_DENSITY,
+        }
+    }
+
+    /// Set the material density (g/cm³) used for mass estimates
+    pub fn with_material_density(mut self, density: f64) -> Self {
+        self.material_density = density;
+        self
+    }
+
+    /// Whether the file's geometry can be analyzed (3MF packages are skipped)
+    pub fn supports(path: &Path) -> bool {
+        matches!(
+            ModelFormat::from_path(path),
+            Some(ModelFormat::Stl) | Some(ModelFormat::Obj) | Some(ModelFormat::Ply)
+        )
+    }
+
+    /// Analyze a model file unless a stored analysis, with a fingerprint, matches its
+    /// current size and mtime. Returns true if the file was (re)analyzed.
+    pub fn analyze_if_changed(&self, stl_file_id: i64, path: &Path) -> Result<bool, AppError> {
+        if !Self::supports(path) {
+            return Ok(false);
+        }
+
+        let (source_size, source_mtime) = file_signature(path)?;
+        if source_size as u64 > MAX_MESH_FILE_SIZE {
+            debug!("Not analyzing {} (>100MB)", path.display());
+            return Ok(false);
+        }
+
+        if let Some(existing) = self.get_analysis(stl_file_id)? {
+            if existing.source_size == source_size
+                && existing.source_mtime == source_mtime
+                && existing.geometry_fingerprint.is_some()
+            {
+                debug!("Mesh analysis up to date for {}", path.display());
+                return Ok(false);
+            }
+        }
+
+        self.analyze(stl_file_id, path)?;
+        Ok(true)
+    }
+
+    /// Parse the mesh, store its measurements and printability issues,
+    /// replacing any previous analysis
+    pub fn analyze(&self, stl_file_id: i64, path: &Path) -> Result<MeshAnalysis, AppError> {
+        let (source_size, source_mtime) = file_signature(path)?;
+        if source_size as u64 > MAX_MESH_FILE_SIZE {
+            return Err(AppError::ValidationError(format!(
+                "{} is too large to analyze (>100MB)",
+                path.display()
+            )));
+        }
+        let mesh = Mesh::load(path)?;
+        let stats = mesh.stats();
+        let issues = issues_from_validation(&mesh.validate(), stats.triangle_count);
+        let [size_x, size_y, size_z] = stats.size();
+
+        let analysis = MeshAnalysis {
+            stl_file_id,
+            size_x,
+            size_y,
+            size_z,
+            triangle_count: stats.triangle_count as i64,
+            surface_area: stats.surface_area,
+            volume: stats.volume,
+            // mm³ -> cm³, times density in g/cm³
+            estimated_mass: stats.volume / 1000.0 * self.material_density,
+            geometry_fingerprint: Some(mesh.fingerprint()),
+            source_size,
+            source_mtime,
+            analyzed_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs() as i64,
+        };
+
+        let mut conn = self.pool.get()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO mesh_analysis (stl_file_id, size_x, size_y, size_z, triangle_count,
+                                                   surface_area, volume, estimated_mass, geometry_fingerprint,
+                                                   source_size, source_mtime, analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                analysis.stl_file_id,
+                analysis.size_x,
+                analysis.size_y,
+                analysis.size_z,
+                analysis.triangle_count,
+                analysis.surface_area,
+                analysis.volume,
+                analysis.estimated_mass,
+                analysis.geometry_fingerprint,
+                analysis.source_size,
+                analysis.source_mtime,
+                analysis.analyzed_at
+            ],
+        )?;
+
+        tx.execute(
+            "DELETE FROM mesh_issues WHERE stl_file_id = ?1",
+            params![stl_file_id],
+        )?;
+        for issue in &issues {
+            tx.execute(
+                "INSERT INTO mesh_issues (stl_file_id, issue_type, count, message)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![stl_file_id, issue.issue_type, issue.count, issue.message],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(analysis)
+    }
+
+    pub fn get_analysis(&self, stl_file_id: i64) -> Result<Option<MeshAnalysis>, AppError> {
+        let conn = self.pool.get()?;
+        let analysis = conn
+            .query_row(
+                "SELECT stl_file_id, size_x, size_y, size_z, triangle_count, surface_area, volume,
+                        estimated_mass, geometry_fingerprint, source_size, source_mtime, analyzed_at
+                 FROM mesh_analysis WHERE stl_file_id = ?1",
+                params![stl_file_id],
+                |row| {
+                    Ok(MeshAnalysis {
+                        stl_file_id: row.get(0)?,
+                        size_x: row.get(1)?,
+                        size_y: row.get(2)?,
+                        size_z: row.get(3)?,
+                        triangle_count: row.get(4)?,
+                        surface_area: row.get(5)?,
+                        volume: row.get(6)?,
+                        estimated_mass: row.get(7)?,
+                        geometry_fingerprint: row.get(8)?,
+                        source_size: row.get(9)?,
+                        source_mtime: row.get(10)?,
+                        analyzed_at: row.get(11)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(analysis)
+    }
+
+    pub fn get_issues(&self, stl_file_id: i64) -> Result<Vec<MeshIssue>, AppError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT issue_type, count, message FROM mesh_issues
+             WHERE stl_file_id = ?1 ORDER BY issue_type",
+        )?;
+
+        let issues = stmt
+            .query_map(params![stl_file_id], |row| {
+                Ok(MeshIssue {
+                    issue_type: row.get(0)?,
+                    count: row.get(1)?,
+                    message: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(issues)
+    }
+}
+
+/// Turn validation counts into the issue rows stored per file
+fn issues_from_validation(validation: &MeshValidation, triangle_count: usize) -> Vec<MeshIssue> {
+    let mut issues = Vec::new();
+    let mut push = |issue_type: &str, count: usize, message: String| {
+        issues.push(MeshIssue {
+            issue_type: issue_type.to_string(),
+            count: count as i64,
+            message,
+        });
+    };
+
+    if validation.non_manifold_edges > 0 {
+        push(
+            "non_manifold_edges",
+            validation.non_manifold_edges,
+            format!(
+                "{} edges are shared by more than two triangles",
+                validation.non_manifold_edges
+            ),
+        );
+    }
+    if validation.open_edges > 0 {
+        push(
+            "open_edges",
+            validation.open_edges,
+            format!(
+                "{} open edges; the mesh is not watertight",
+                validation.open_edges
+            ),
+        );
+    }
+    if validation.inconsistent_edges > 0 {
+        push(
+            "flipped_normals",
+            validation.inconsistent_edges,
+            format!(
+                "{} edges join triangles with opposite winding",
+                validation.inconsistent_edges
+            ),
+        );
+    } else if validation.inverted {
+        push(
+            "flipped_normals",
+            triangle_count,
+            "All normals point inwards; the mesh is inside out".to_string(),
+        );
+    }
+    if validation.degenerate_triangles > 0 {
+        push(
+            "degenerate_triangles",
+            validation.degenerate_triangles,
+            format!(
+                "{} triangles have zero area",
+                validation.degenerate_triangles
+            ),
+        );
+    }
+    if validation.shells > 1 {
+        push(
+            "multiple_shells",
+            validation.shells,
+            format!("Mesh consists of {} disconnected shells", validation.shells),
+        );
+    }
+
+    issues
+}
@@ -1,8 +1,14 @@
 pub mod composite_preview;
 pub mod download;
+pub mod duplicates;
 pub mod folder_service;
 pub mod image_cache;
+pub mod mesh;
+pub mod mesh_analysis;
 pub mod rescan;
+pub mod scan_progress;
+pub mod scan_schedule;
 pub mod scanner;
 pub mod search;
+pub mod sidecar;
 pub mod stl_preview;
@@ -1,16 +1,29 @@
+use crate::config::ConfigService;
 use crate::db::connection::DbPool;
+use crate::db::repositories::creator_repo::CreatorRepository;
 use crate::db::repositories::file_repo::FileRepository;
 use crate::db::repositories::project_repo::ProjectRepository;
+use crate::db::repositories::release_repo::ReleaseRepository;
 use crate::models::project::CreateProject;
+use crate::models::scan_progress::ScanPhase;
+use crate::models::stl_file::ModelFormat;
+use crate::services::duplicates::DuplicateService;
 use crate::services::image_cache::ImageCacheService;
+use crate::services::mesh_analysis::MeshAnalysisService;
+use crate::services::scan_progress::{ScanCancellation, ScanProgressTracker};
+use crate::services::sidecar::SidecarService;
+use crate::utils::archive;
 use crate::utils::error::AppError;
+use crate::utils::fs::{file_signature, image_files_in};
+use crate::utils::ignore::IgnoreRules;
+use rusqlite::params;
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RescanResult {
     pub projects_found: usize,
     pub projects_added: usize,
@@ -23,17 +36,51 @@ pub struct RescanResult {
     pub stl_previews_regenerated: usize,
     pub stl_previews_cached: usize,
     pub errors: Vec<String>,
+    /// The rescan was cancelled before it finished
+    pub cancelled: bool,
+}
+
+/// A file as recorded by a previous scan
+struct IndexedFile {
+    id: i64,
+    file_size: i64,
+    file_mtime: Option<i64>,
+    /// Whether a preview has been rendered (model files only)
+    has_preview: bool,
+}
+
+impl IndexedFile {
+    /// Whether the file on disk still matches this row. Rows indexed before
+    /// mtimes were stored only compare sizes.
+    fn matches(&self, file_size: i64, file_mtime: i64) -> bool {
+        self.file_size == file_size && self.file_mtime.is_none_or(|m| m == file_mtime)
+    }
+}
+
+/// What a rescan did with a file found on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileChange {
+    Added,
+    Updated,
+    Unchanged,
 }
 
 pub struct RescanService {
     project_repo: ProjectRepository,
     file_repo: FileRepository,
+    creator_repo: CreatorRepository,
+    release_repo: ReleaseRepository,
     image_cache_service: Option<ImageCacheService>,
     preview_repo: crate::db::repositories::preview_repo::PreviewRepository,
     composite_service: Option<crate::services::composite_preview::CompositePreviewService>,
     stl_preview_service: Option<crate::services::stl_preview::StlPreviewService>,
     preview_queue: Option<std::sync::Arc<crate::services::stl_preview::PreviewQueue>>,
+    mesh_analysis_service: Option<MeshAnalysisService>,
+    duplicate_service: DuplicateService,
+    sidecar_service: SidecarService,
     ignored_keywords: Vec<String>,
+    progress: ScanProgressTracker,
+    cancellation: ScanCancellation,
 }
 
 impl RescanService {
@@ -41,12 +88,21 @@ impl RescanService {
         Self {
             project_repo: ProjectRepository::new(pool.clone()),
             file_repo: FileRepository::new(pool.clone()),
+            creator_repo: CreatorRepository::new(pool.clone()),
+            release_repo: ReleaseRepository::new(pool.clone()),
             image_cache_service: None,
-            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(pool),
+            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(
+                pool.clone(),
+            ),
             composite_service: None,
             stl_preview_service: None,
             preview_queue: None,
+            mesh_analysis_service: None,
+            duplicate_service: DuplicateService::new(pool.clone()),
+            sidecar_service: SidecarService::new(pool),
             ignored_keywords: Vec::new(),
+            progress: ScanProgressTracker::new(),
+            cancellation: ScanCancellation::new(),
         }
     }
 
@@ -54,12 +110,21 @@ impl RescanService {
         Self {
             project_repo: ProjectRepository::new(pool.clone()),
             file_repo: FileRepository::new(pool.clone()),
+            creator_repo: CreatorRepository::new(pool.clone()),
+            release_repo: ReleaseRepository::new(pool.clone()),
             image_cache_service: Some(cache_service),
-            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(pool),
+            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(
+                pool.clone(),
+            ),
             composite_service: None,
             stl_preview_service: None,
             preview_queue: None,
+            mesh_analysis_service: None,
+            duplicate_service: DuplicateService::new(pool.clone()),
+            sidecar_service: SidecarService::new(pool),
             ignored_keywords: Vec::new(),
+            progress: ScanProgressTracker::new(),
+            cancellation: ScanCancellation::new(),
         }
     }
 
@@ -79,11 +144,28 @@ impl RescanService {
         self
     }
 
+    pub fn with_mesh_analysis(mut self, mesh_analysis_service: MeshAnalysisService) -> Self {
+        self.mesh_analysis_service = Some(mesh_analysis_service);
+        self
+    }
+
     pub fn with_ignored_keywords(mut self, keywords: Vec<String>) -> Self {
         self.ignored_keywords = keywords;
         self
     }
 
+    /// Report progress of full and subtree rescans into a shared tracker
+    pub fn with_progress(mut self, progress: ScanProgressTracker) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Stop full and subtree rescans between folders once the flag is set
+    pub fn with_cancellation(mut self, cancellation: ScanCancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     /// Check if a folder name contains any ignored keyword (case-insensitive substring match)
     fn is_stl_category_folder(&self, folder_name: &str) -> bool {
         let normalized_name = folder_name.trim().to_lowercase();
@@ -120,6 +202,17 @@ impl RescanService {
     }
 
     pub fn rescan(&self, root_path: &str) -> Result<RescanResult, AppError> {
+        self.rescan_subtree(root_path, Path::new(root_path))
+    }
+
+    /// Rescan only the folders below `subtree`, which must lie inside `root_path`.
+    /// Projects elsewhere in the library are left untouched, while the ancestors of
+    /// the subtree keep their images and `is_leaf` flags up to date.
+    pub fn rescan_subtree(
+        &self,
+        root_path: &str,
+        subtree: &Path,
+    ) -> Result<RescanResult, AppError> {
         let root = Path::new(root_path);
 
         if !root.exists() {
@@ -136,52 +229,62 @@ impl RescanService {
             )));
         }
 
-        let mut result = RescanResult {
-            projects_found: 0,
-            projects_added: 0,
-            projects_updated: 0,
-            projects_removed: 0,
-            files_processed: 0,
-            files_added: 0,
-            files_updated: 0,
-            files_removed: 0,
-            stl_previews_regenerated: 0,
-            stl_previews_cached: 0,
-            errors: Vec::new(),
-        };
+        let mut result = RescanResult::default();
 
-        // Clear all inherited images before rescanning (they will be regenerated)
-        if let Err(e) = self.clear_inherited_images() {
-            let error_msg = format!("Error clearing inherited images: {}", e);
-            warn!("{}", error_msg);
-            result.errors.push(error_msg);
-        } else {
-            info!("Cleared inherited images for rebuild");
+        if !subtree.starts_with(root) || !subtree.is_dir() {
+            return Err(AppError::ValidationError(format!(
+                "Not a directory inside the library root: {}",
+                subtree.display()
+            )));
         }
 
-        // Get all existing projects from database
-        let existing_projects = self.get_all_projects_map()?;
+        // A category folder is reconciled together with the project it belongs to
+        let scope = self.find_project_folder(subtree, root);
+        let ignore = ConfigService::new(self.file_repo.pool.clone()).ignore_rules(root);
+
+        // Get existing projects from database, limited to the scanned subtree.
+        // Other library roots are never touched.
+        let mut existing_projects = self.get_all_projects_map()?;
+        existing_projects.retain(|path, _| Path::new(path).starts_with(&scope));
         let mut found_project_paths = HashSet::new();
 
         // Scan file system for current state
         let mut project_folders = HashMap::new();
 
-        for entry in WalkDir::new(root).follow_links(false) {
+        self.progress.begin_phase(ScanPhase::Walking, None);
+        for entry in ignore.walk(&scope) {
             match entry {
                 Ok(e) => {
-                    if e.file_type().is_file() {
-                        if let Some(ext) = e.path().extension() {
-                            if ext.eq_ignore_ascii_case("stl") {
-                                if let Some(parent) = e.path().parent() {
-                                    // Find the actual project folder by traversing up
-                                    // past any STL category folders
-                                    let project_folder = self.find_project_folder(parent, root);
-                                    project_folders
-                                        .entry(project_folder)
-                                        .or_insert_with(Vec::new)
-                                        .push(e.path().to_path_buf());
-                                }
-                            }
+                    if e.file_type().is_dir() {
+                        if self.cancellation.is_cancelled() {
+                            result.cancelled = true;
+                            break;
+                        }
+                        self.progress.visit_folder(e.path());
+                    }
+                    // Any recognised model format (STL, 3MF, OBJ, PLY) marks a project folder.
+                    // Archives are indexed as folders holding the files they contain.
+                    let model_files = if !e.file_type().is_file() {
+                        Vec::new()
+                    } else if ModelFormat::from_path(e.path()).is_some() {
+                        vec![e.path().to_path_buf()]
+                    } else if archive::is_archive(e.path()) {
+                        self.archive_model_files(e.path(), &ignore, &mut result)
+                    } else if archive::is_unsupported_archive(e.path()) {
+                        report_unsupported_archive(e.path(), &mut result);
+                        Vec::new()
+                    } else {
+                        Vec::new()
+                    };
+                    for model_file in model_files {
+                        if let Some(parent) = model_file.parent() {
+                            // Find the actual project folder by traversing up
+                            // past any STL category folders
+                            let project_folder = self.find_project_folder(parent, root);
+                            project_folders
+                                .entry(project_folder)
+                                .or_insert_with(Vec::new)
+                                .push(model_file);
                         }
                     }
                 }
@@ -193,12 +296,35 @@ impl RescanService {
             }
         }
 
+        if !result.cancelled {
+            if let Err(e) = self.project_repo.replace_folder_links(
+                &scope,
+                &ignore.folder_links(),
+                &ignore.external_links(),
+            ) {
+                result
+                    .errors
+                    .push(format!("Error saving folder links: {}", e));
+            }
+        }
+
         // Track all processed paths
         let mut path_to_id = HashMap::new();
         let mut processed_paths = HashSet::new();
+        // Projects whose files changed, so their composite previews are rebuilt
+        let mut changed_projects = HashSet::new();
+
+        let model_file_count = project_folders.values().map(Vec::len).sum();
+        self.progress
+            .begin_phase(ScanPhase::Indexing, Some(model_file_count));
 
         // Process each project folder
         for (folder, stl_files) in project_folders.iter() {
+            if result.cancelled || self.cancellation.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+
             let full_path = folder.to_str().unwrap_or("").to_string();
             found_project_paths.insert(full_path.clone());
 
@@ -212,14 +338,15 @@ impl RescanService {
             ) {
                 Ok(project_id) => {
                     result.projects_found += 1;
+                    let changes_before = change_count(&result);
 
                     // Get existing STL files for this project
                     let existing_stl_files = self.get_existing_stl_files(project_id)?;
                     let mut found_stl_paths = HashSet::new();
+                    let mut needs_preview = Vec::new();
 
                     // Process STL files
-                    let stl_files_vec: Vec<_> = stl_files.to_vec();
-                    for stl_file in &stl_files_vec {
+                    for stl_file in stl_files {
                         let file_path = stl_file.to_str().unwrap_or("");
                         found_stl_paths.insert(file_path.to_string());
 
@@ -229,16 +356,25 @@ impl RescanService {
                             &existing_stl_files,
                             &mut result,
                         ) {
-                            Ok(_) => result.files_processed += 1,
+                            Ok(change) => {
+                                result.files_processed += 1;
+                                let has_preview = existing_stl_files
+                                    .get(file_path)
+                                    .is_some_and(|f| f.has_preview);
+                                if change != FileChange::Unchanged || !has_preview {
+                                    needs_preview.push(stl_file);
+                                }
+                            }
                             Err(e) => result
                                 .errors
                                 .push(format!("Error processing STL file: {}", e)),
                         }
+                        self.progress.process_file(stl_file);
                     }
 
-                    // T040-T042: Queue STL previews for generation with smart caching (US4)
-                    if self.preview_queue.is_some() && !stl_files_vec.is_empty() {
-                        for stl_file in &stl_files_vec {
+                    // T040-T042: Queue previews for new and changed files only (US4)
+                    if self.preview_queue.is_some() {
+                        for stl_file in needs_preview {
                             match self.queue_stl_preview_generation(
                                 project_id,
                                 stl_file,
@@ -257,9 +393,9 @@ impl RescanService {
                     }
 
                     // T043: Remove deleted STL files and their previews
-                    for (file_path, file_id) in existing_stl_files.iter() {
+                    for (file_path, indexed) in existing_stl_files.iter() {
                         if !found_stl_paths.contains(file_path) {
-                            if let Err(e) = self.file_repo.delete_stl_file(*file_id) {
+                            if let Err(e) = self.file_repo.delete_stl_file(indexed.id) {
                                 result
                                     .errors
                                     .push(format!("Error removing deleted STL file: {}", e));
@@ -276,6 +412,11 @@ impl RescanService {
                             .errors
                             .push(format!("Error processing images: {}", e));
                     }
+                    self.import_sidecar(project_id, folder, &mut result);
+
+                    if change_count(&result) != changes_before {
+                        changed_projects.insert(project_id);
+                    }
                 }
                 Err(e) => {
                     result
@@ -285,13 +426,28 @@ impl RescanService {
             }
         }
 
+        if result.cancelled {
+            // Folders indexed so far stay; removals and inheritance wait for the next rescan
+            self.refresh_leaf_flags(path_to_id.values().copied(), &mut result);
+            info!(
+                "Rescan cancelled after {} project folders",
+                result.projects_found
+            );
+            return Ok(result);
+        }
+
         // Remove projects that no longer exist
         for (project_path, project_id) in existing_projects.iter() {
             if !found_project_paths.contains(project_path)
                 && !processed_paths.contains(Path::new(project_path))
             {
-                // Check if path still exists in filesystem
-                if !Path::new(project_path).exists() {
+                // Check if path still exists in filesystem or in its archive, and
+                // drop projects that an ignore pattern or a symlink now hides
+                let path = Path::new(project_path);
+                if !archive::exists(path)
+                    || ignore.is_excluded(path, true)
+                    || ignore.behind_symlink(path)
+                {
                     if let Err(e) = self.project_repo.delete(*project_id) {
                         result
                             .errors
@@ -303,7 +459,20 @@ impl RescanService {
             }
         }
 
+        // Clear inherited images before rebuilding them. This is not interrupted by
+        // cancellation, so no project is left without its inherited images.
+        let cleared = self.clear_inherited_images(&scope);
+        if let Err(e) = cleared {
+            let error_msg = format!("Error clearing inherited images: {}", e);
+            warn!("{}", error_msg);
+            result.errors.push(error_msg);
+        } else {
+            info!("Cleared inherited images for rebuild");
+        }
+
         // First-and-a-half pass: Scan parent folders for images
+        self.progress
+            .begin_phase(ScanPhase::Inheritance, Some(project_folders.len()));
         info!("Scanning parent folders for images during rescan");
         let mut scanned_parent_folders = HashSet::new();
 
@@ -324,7 +493,8 @@ impl RescanService {
                 // Ensure parent project exists
                 match self.ensure_project_exists(parent_folder, root, &path_to_id) {
                     Ok(parent_id) => {
-                        // Process images in parent folder (checks for new/deleted images)
+                        // Process images in parent folder (checks for new/changed/deleted images)
+                        let changes_before = change_count(&result);
                         if let Err(e) =
                             self.process_images_for_project(parent_id, parent_folder, &mut result)
                         {
@@ -336,6 +506,10 @@ impl RescanService {
                             warn!("{}", error_msg);
                             result.errors.push(error_msg);
                         }
+                        self.import_sidecar(parent_id, parent_folder, &mut result);
+                        if change_count(&result) != changes_before {
+                            changed_projects.insert(parent_id);
+                        }
                         scanned_parent_folders.insert(parent_folder.to_path_buf());
                     }
                     Err(e) => {
@@ -356,6 +530,7 @@ impl RescanService {
         // Second pass: Propagate images from parent folders to children
         info!("Propagating images from parent folders to children");
         for (folder, _) in project_folders.iter() {
+            self.progress.advance(folder);
             if let Some(&project_id) = path_to_id.get(folder) {
                 if let Err(e) =
                     self.inherit_images_from_parents(project_id, folder, root, &path_to_id)
@@ -371,14 +546,42 @@ impl RescanService {
             }
         }
 
-        // Third pass: Generate/update composite previews for ALL projects
+        // Folders that gained or lost their own model files, and the ancestors of the
+        // subtree, may have changed between project and parent folder
+        let ancestor_ids = scope
+            .ancestors()
+            .skip(1)
+            .take_while(|folder| *folder >= root)
+            .filter_map(|folder| {
+                self.project_repo
+                    .get_by_path(folder.to_str().unwrap_or(""))
+                    .ok()
+                    .flatten()
+            })
+            .map(|project| project.id);
+        let leaf_candidates: HashSet<i64> =
+            path_to_id.values().copied().chain(ancestor_ids).collect();
+        self.refresh_leaf_flags(leaf_candidates, &mut result);
+        self.assign_creators(&mut result);
+
+        // Third pass: Generate/update composite previews for changed projects
         if let Some(ref composite_service) = self.composite_service {
-            info!("Generating composite previews for all projects during rescan");
+            info!("Updating composite previews for changed projects during rescan");
+            self.progress
+                .begin_phase(ScanPhase::Previews, Some(path_to_id.len()));
             // Iterate over all projects, not just folders with STL files
-            for (_folder, &project_id) in path_to_id.iter() {
-                if let Err(e) =
-                    self.generate_preview_for_project(project_id, composite_service, &mut result)
+            for (folder, &project_id) in path_to_id.iter() {
+                if self.cancellation.is_cancelled() {
+                    result.cancelled = true;
+                    break;
+                }
+                self.progress.advance(folder);
+                if !changed_projects.contains(&project_id)
+                    && self.preview_repo.get_preview(project_id)?.is_some()
                 {
+                    continue;
+                }
+                if let Err(e) = self.generate_preview_for_project(project_id, composite_service) {
                     let error_msg =
                         format!("Error generating preview for project {}: {}", project_id, e);
                     warn!("{}", error_msg);
@@ -406,6 +609,440 @@ impl RescanService {
         Ok(result)
     }
 
+    /// Apply filesystem changes reported by the watcher without walking the whole library.
+    /// `changed` holds created, modified, removed or renamed paths under `root_path`; only
+    /// the project folders containing them are reconciled with the database.
+    pub fn rescan_paths(
+        &self,
+        root_path: &str,
+        changed: &[PathBuf],
+    ) -> Result<RescanResult, AppError> {
+        let root = Path::new(root_path);
+        let mut result = RescanResult::default();
+        let ignore = ConfigService::new(self.file_repo.pool.clone()).ignore_rules(root);
+
+        // Project folders whose files need to be reconciled
+        let mut folders = HashSet::new();
+        for path in changed {
+            if !path.starts_with(root) || ignore.is_excluded(path, path.is_dir()) {
+                continue;
+            }
+
+            if path.is_dir() {
+                // A new or renamed folder may contain whole releases
+                for entry in ignore.walk(path).flatten() {
+                    if !entry.file_type().is_file() {
+                        continue;
+                    }
+                    let model_files = if ModelFormat::from_path(entry.path()).is_some() {
+                        vec![entry.into_path()]
+                    } else if archive::is_archive(entry.path()) {
+                        self.archive_model_files(entry.path(), &ignore, &mut result)
+                    } else if archive::is_unsupported_archive(entry.path()) {
+                        report_unsupported_archive(entry.path(), &mut result);
+                        Vec::new()
+                    } else {
+                        Vec::new()
+                    };
+                    for model_file in model_files {
+                        if let Some(parent) = model_file.parent() {
+                            folders.insert(self.find_project_folder(parent, root));
+                        }
+                    }
+                }
+                folders.insert(self.find_project_folder(path, root));
+            } else {
+                if archive::is_archive(path) && path.is_file() {
+                    // Archives are indexed as folders holding the files they contain
+                    if let Err(e) = self.remove_missing_archive_projects(path, &mut result) {
+                        result.errors.push(format!(
+                            "Error removing projects under {}: {}",
+                            path.display(),
+                            e
+                        ));
+                    }
+                    for model_file in self.archive_model_files(path, &ignore, &mut result) {
+                        if let Some(parent) = model_file.parent() {
+                            folders.insert(self.find_project_folder(parent, root));
+                        }
+                    }
+                    folders.insert(path.to_path_buf());
+                    for (_, folder) in self.get_model_projects_under(path)? {
+                        folders.insert(folder);
+                    }
+                }
+                if archive::is_unsupported_archive(path) && path.is_file() {
+                    report_unsupported_archive(path, &mut result);
+                }
+                if !path.exists() {
+                    // The path may have been a project folder that was removed or renamed
+                    if let Err(e) = self.remove_projects_under(path, &mut result) {
+                        result.errors.push(format!(
+                            "Error removing projects under {}: {}",
+                            path.display(),
+                            e
+                        ));
+                    }
+                }
+                if let Some(parent) = path.parent() {
+                    if parent >= root {
+                        folders.insert(self.find_project_folder(parent, root));
+                    }
+                }
+            }
+        }
+
+        let existing_projects = HashMap::new();
+        let mut path_to_id = HashMap::new();
+        let mut processed_paths = HashSet::new();
+        let mut refreshed = Vec::new();
+
+        self.progress
+            .begin_phase(ScanPhase::Indexing, Some(folders.len()));
+        for folder in &folders {
+            if self.cancellation.is_cancelled() {
+                result.cancelled = true;
+                break;
+            }
+            self.progress.advance(folder);
+            if !archive::exists(folder) {
+                continue;
+            }
+
+            let model_files = self.project_model_files(folder, root, &ignore, &mut result);
+            let project_id = if model_files.is_empty() {
+                // Without model files only an already indexed project needs updating,
+                // which may no longer be a leaf
+                match self
+                    .project_repo
+                    .get_by_path(folder.to_str().unwrap_or(""))?
+                {
+                    Some(project) => {
+                        path_to_id.insert(folder.clone(), project.id);
+                        project.id
+                    }
+                    None => continue,
+                }
+            } else {
+                match self.create_or_update_project_hierarchy(
+                    folder,
+                    root,
+                    &mut path_to_id,
+                    &mut processed_paths,
+                    &existing_projects,
+                    &mut result,
+                ) {
+                    Ok(project_id) => project_id,
+                    Err(e) => {
+                        result
+                            .errors
+                            .push(format!("Error processing project: {}", e));
+                        continue;
+                    }
+                }
+            };
+            result.projects_found += 1;
+            let changes_before = change_count(&result);
+
+            let existing_stl_files = self.get_existing_stl_files(project_id)?;
+            let mut found_stl_paths = HashSet::new();
+            for model_file in &model_files {
+                let file_path = model_file.to_str().unwrap_or("");
+                found_stl_paths.insert(file_path.to_string());
+
+                match self.process_stl_file(
+                    project_id,
+                    model_file,
+                    &existing_stl_files,
+                    &mut result,
+                ) {
+                    Ok(change) => {
+                        result.files_processed += 1;
+                        let has_preview = existing_stl_files
+                            .get(file_path)
+                            .is_some_and(|f| f.has_preview);
+                        if change != FileChange::Unchanged || !has_preview {
+                            if let Err(e) = self.queue_stl_preview_generation(
+                                project_id,
+                                model_file,
+                                &mut result,
+                            ) {
+                                warn!(
+                                    "Failed to queue preview for {}: {}",
+                                    model_file.display(),
+                                    e
+                                );
+                            }
+                        }
+                    }
+                    Err(e) => result
+                        .errors
+                        .push(format!("Error processing STL file: {}", e)),
+                }
+            }
+
+            for (file_path, indexed) in existing_stl_files.iter() {
+                if !found_stl_paths.contains(file_path) {
+                    if let Err(e) = self.file_repo.delete_stl_file(indexed.id) {
+                        result
+                            .errors
+                            .push(format!("Error removing deleted STL file: {}", e));
+                    } else {
+                        result.files_removed += 1;
+                    }
+                }
+            }
+
+            if let Err(e) = self.process_images_for_project(project_id, folder, &mut result) {
+                result
+                    .errors
+                    .push(format!("Error processing images: {}", e));
+            }
+            self.import_sidecar(project_id, folder, &mut result);
+
+            refreshed.push((
+                folder.clone(),
+                project_id,
+                change_count(&result) != changes_before,
+            ));
+        }
+
+        // Newly created parent folders need their own images indexed
+        for folder in &processed_paths {
+            if folders.contains(folder) {
+                continue;
+            }
+            if let Some(&project_id) = path_to_id.get(folder) {
+                if let Err(e) = self.process_images_for_project(project_id, folder, &mut result) {
+                    result.errors.push(format!(
+                        "Error processing images for parent folder {}: {}",
+                        folder.display(),
+                        e
+                    ));
+                }
+                self.import_sidecar(project_id, folder, &mut result);
+            }
+        }
+
+        // Image changes in a folder affect what every project below it inherits
+        let mut inheriting = HashMap::new();
+        for (folder, project_id, _) in &refreshed {
+            inheriting.insert(*project_id, folder.clone());
+            for (id, path) in self.get_model_projects_under(folder)? {
+                inheriting.insert(id, path);
+            }
+        }
+        for (project_id, folder) in &inheriting {
+            let inherited = self
+                .file_repo
+                .delete_inherited_images(*project_id)
+                .and_then(|_| {
+                    self.inherit_images_from_parents(*project_id, folder, root, &path_to_id)
+                });
+            if let Err(e) = inherited {
+                let error_msg = format!(
+                    "Error inheriting images for project {}: {}",
+                    folder.display(),
+                    e
+                );
+                warn!("{}", error_msg);
+                result.errors.push(error_msg);
+            }
+        }
+
+        // Parent folders created above are not projects of their own
+        self.refresh_leaf_flags(path_to_id.values().copied(), &mut result);
+        self.assign_creators(&mut result);
+
+        if let Some(ref composite_service) = self.composite_service {
+            for (_, project_id, changed) in &refreshed {
+                if !changed && self.preview_repo.get_preview(*project_id)?.is_some() {
+                    continue;
+                }
+                if let Err(e) = self.generate_preview_for_project(*project_id, composite_service) {
+                    let error_msg =
+                        format!("Error generating preview for project {}: {}", project_id, e);
+                    warn!("{}", error_msg);
+                    result.errors.push(error_msg);
+                }
+            }
+        }
+
+        info!(
+            "Applied changes to {} project folders: {} files added, {} removed",
+            refreshed.len(),
+            result.files_added,
+            result.files_removed
+        );
+
+        Ok(result)
+    }
+
+    /// Model files stored in an archive, as virtual paths
+    fn archive_model_files(
+        &self,
+        archive_path: &Path,
+        ignore: &IgnoreRules,
+        result: &mut RescanResult,
+    ) -> Vec<PathBuf> {
+        match archive::list_entries(archive_path) {
+            Ok(entries) => entries
+                .into_iter()
+                .map(|entry| entry.path)
+                .filter(|path| {
+                    ModelFormat::from_path(path).is_some() && !ignore.is_excluded(path, false)
+                })
+                .collect(),
+            Err(err) => {
+                warn!("{}", err);
+                result.errors.push(err.to_string());
+                Vec::new()
+            }
+        }
+    }
+
+    /// Model files that belong to a project folder: those directly inside it and
+    /// those in its STL category subfolders. The folder may be an archive or lie
+    /// inside one.
+    fn project_model_files(
+        &self,
+        folder: &Path,
+        root: &Path,
+        ignore: &IgnoreRules,
+        result: &mut RescanResult,
+    ) -> Vec<PathBuf> {
+        let archive_path = if archive::is_archive(folder) && folder.is_file() {
+            Some(folder)
+        } else {
+            archive::split_path(folder).map(|(archive_path, _)| archive_path)
+        };
+        if let Some(archive_path) = archive_path {
+            let mut model_files = self.archive_model_files(archive_path, ignore, result);
+            model_files.retain(|path| {
+                path.parent()
+                    .is_some_and(|parent| self.find_project_folder(parent, root) == folder)
+            });
+            return model_files;
+        }
+
+        WalkDir::new(folder)
+            .follow_links(ignore.follows_symlinks())
+            .into_iter()
+            .filter_entry(|e| {
+                let is_dir = e.file_type().is_dir();
+                if e.path() != folder
+                    && (ignore.is_ignored(e.path(), is_dir)
+                        || (e.path_is_symlink() && !ignore.leads_outside(e.path())))
+                {
+                    return false;
+                }
+                e.path() == folder
+                    || !is_dir
+                    || e.file_name()
+                        .to_str()
+                        .map(|name| self.is_stl_category_folder(name))
+                        .unwrap_or(false)
+            })
+            .flatten()
+            .filter(|e| e.file_type().is_file() && ModelFormat::from_path(e.path()).is_some())
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    /// Delete the project at `path` and every project below it
+    fn remove_projects_under(
+        &self,
+        path: &Path,
+        result: &mut RescanResult,
+    ) -> Result<(), AppError> {
+        let full_path = path.to_str().unwrap_or("");
+        let ids: Vec<i64> = {
+            let conn = self.project_repo.pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT id FROM projects WHERE full_path = ?1 OR full_path LIKE ?2 ESCAPE '\\'
+                 ORDER BY LENGTH(full_path) DESC",
+            )?;
+            let ids = stmt
+                .query_map(params![full_path, like_prefix(full_path)], |row| row.get(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+
+        for id in ids {
+            self.project_repo.delete(id)?;
+            result.projects_removed += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Delete the projects inside `archive_path` whose folders the archive no longer holds
+    fn remove_missing_archive_projects(
+        &self,
+        archive_path: &Path,
+        result: &mut RescanResult,
+    ) -> Result<(), AppError> {
+        let projects: Vec<(i64, String)> = {
+            let conn = self.project_repo.pool.get()?;
+            let mut stmt = conn.prepare(
+                "SELECT id, full_path FROM projects WHERE full_path LIKE ?1 ESCAPE '\\'
+                 ORDER BY LENGTH(full_path) DESC",
+            )?;
+            let projects = stmt
+                .query_map(
+                    params![like_prefix(archive_path.to_str().unwrap_or(""))],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?
+                .collect::<Result<Vec<_>, _>>()?;
+            projects
+        };
+
+        for (id, full_path) in projects {
+            if !archive::exists(Path::new(&full_path)) {
+                self.project_repo.delete(id)?;
+                result.projects_removed += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Projects below `folder` that hold model files, with their folder paths
+    fn get_model_projects_under(&self, folder: &Path) -> Result<Vec<(i64, PathBuf)>, AppError> {
+        let conn = self.project_repo.pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.full_path FROM projects p
+             WHERE p.full_path LIKE ?1 ESCAPE '\\'
+               AND EXISTS (SELECT 1 FROM stl_files s WHERE s.project_id = p.id)",
+        )?;
+
+        let projects = stmt
+            .query_map(params![like_prefix(folder.to_str().unwrap_or(""))], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    PathBuf::from(row.get::<_, String>(1)?),
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(projects)
+    }
+
+    /// Recompute is_leaf for projects whose model files may have changed
+    fn refresh_leaf_flags(
+        &self,
+        project_ids: impl IntoIterator<Item = i64>,
+        result: &mut RescanResult,
+    ) {
+        for project_id in project_ids {
+            if let Err(e) = self.project_repo.refresh_is_leaf(project_id) {
+                result
+                    .errors
+                    .push(format!("Error updating project {}: {}", project_id, e));
+            }
+        }
+    }
+
     fn create_or_update_project_hierarchy(
         &self,
         folder: &Path,
@@ -490,13 +1127,27 @@ impl RescanService {
         Ok(projects)
     }
 
-    fn get_existing_stl_files(&self, project_id: i64) -> Result<HashMap<String, i64>, AppError> {
+    fn get_existing_stl_files(
+        &self,
+        project_id: i64,
+    ) -> Result<HashMap<String, IndexedFile>, AppError> {
         let conn = self.file_repo.pool.get()?;
-        let mut stmt = conn.prepare("SELECT id, file_path FROM stl_files WHERE project_id = ?1")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_path, file_size, file_mtime, preview_generated_at IS NOT NULL
+             FROM stl_files WHERE project_id = ?1",
+        )?;
 
         let files = stmt
             .query_map([project_id], |row| {
-                Ok((row.get::<_, String>(1)?, row.get::<_, i64>(0)?))
+                Ok((
+                    row.get::<_, String>(1)?,
+                    IndexedFile {
+                        id: row.get(0)?,
+                        file_size: row.get(2)?,
+                        file_mtime: row.get(3)?,
+                        has_preview: row.get(4)?,
+                    },
+                ))
             })?
             .collect::<Result<HashMap<_, _>, _>>()?;
 
@@ -507,17 +1158,16 @@ impl RescanService {
         &self,
         project_id: i64,
         stl_file: &Path,
-        existing_files: &HashMap<String, i64>,
+        existing_files: &HashMap<String, IndexedFile>,
         result: &mut RescanResult,
-    ) -> Result<(), AppError> {
+    ) -> Result<FileChange, AppError> {
         let file_path = stl_file.to_str().unwrap_or("");
         let filename = stl_file
             .file_name()
             .unwrap_or_default()
             .to_str()
             .unwrap_or("");
-        let metadata = fs::metadata(stl_file)?;
-        let file_size = metadata.len() as i64;
+        let (file_size, file_mtime) = file_signature(stl_file)?;
 
         // Determine category from parent folder name
         let parent_folder_name = stl_file
@@ -538,13 +1188,29 @@ impl RescanService {
 
         info!("   Final category: {:?}", category);
 
-        if let Some(&_file_id) = existing_files.get(file_path) {
-            // File exists - check if modified (size or timestamp changed)
-            // For now, just count as processed
-            // TODO: Could add modification detection based on size/mtime
+        let (file_id, change) = if let Some(indexed) = existing_files.get(file_path) {
+            if indexed.matches(file_size, file_mtime) {
+                if indexed.file_mtime.is_none() {
+                    // Indexed before mtimes were stored; record it without counting a change
+                    self.file_repo
+                        .update_stl_file_signature(indexed.id, file_size, file_mtime)?;
+                }
+                (indexed.id, FileChange::Unchanged)
+            } else {
+                // Replaced in place: record the new size/mtime and drop the stale preview
+                self.file_repo
+                    .update_stl_file_signature(indexed.id, file_size, file_mtime)?;
+                self.file_repo.reset_stl_preview(indexed.id)?;
+                if let Some(ref cache_service) = self.image_cache_service {
+                    cache_service.invalidate(file_path)?;
+                }
+                info!("🔄 Updated STL file '{}'", filename);
+                result.files_updated += 1;
+                (indexed.id, FileChange::Updated)
+            }
         } else {
             // New file - add it
-            self.file_repo.add_stl_file_with_category(
+            let file_id = self.file_repo.add_stl_file_with_category(
                 project_id,
                 filename,
                 file_path,
@@ -556,9 +1222,60 @@ impl RescanService {
                 filename, category
             );
             result.files_added += 1;
+            (file_id, FileChange::Added)
+        };
+
+        // Content hash and geometry are only recomputed when the file's size or mtime changed
+        if let Err(e) = self.duplicate_service.hash_if_changed(file_id, stl_file) {
+            let error_msg = format!("Error hashing model file {}: {}", stl_file.display(), e);
+            warn!("{}", error_msg);
+            result.errors.push(error_msg);
         }
 
-        Ok(())
+        if let Some(ref analysis_service) = self.mesh_analysis_service {
+            if let Err(e) = analysis_service.analyze_if_changed(file_id, stl_file) {
+                let error_msg = format!("Error analyzing mesh {}: {}", stl_file.display(), e);
+                warn!("{}", error_msg);
+                result.errors.push(error_msg);
+            }
+        }
+
+        Ok(change)
+    }
+
+    /// Derive project creators and detected releases again, after folders and sidecar
+    /// authors changed
+    fn assign_creators(&self, result: &mut RescanResult) {
+        let assigned = ConfigService::new(self.file_repo.pool.clone())
+            .get_config()
+            .and_then(|config| {
+                self.creator_repo
+                    .assign_creators(config.creator_folder_level)?;
+                self.release_repo
+                    .assign_releases(config.release_pattern().as_ref())
+            });
+        if let Err(e) = assigned {
+            let error_msg = format!("Error assigning creators and releases: {}", e);
+            warn!("{}", error_msg);
+            result.errors.push(error_msg);
+        }
+    }
+
+    /// Import the project's sidecar files if they changed, counting the project as updated
+    fn import_sidecar(&self, project_id: i64, folder: &Path, result: &mut RescanResult) {
+        match self.sidecar_service.import_if_changed(project_id, folder) {
+            Ok(true) => result.projects_updated += 1,
+            Ok(false) => {}
+            Err(e) => {
+                let error_msg = format!(
+                    "Error importing metadata for project {}: {}",
+                    folder.display(),
+                    e
+                );
+                warn!("{}", error_msg);
+                result.errors.push(error_msg);
+            }
+        }
     }
 
     fn process_images_for_project(
@@ -567,45 +1284,30 @@ impl RescanService {
         folder: &Path,
         result: &mut RescanResult,
     ) -> Result<(), AppError> {
-        let image_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-
         // Get existing images
         let existing_images = self.get_existing_image_files(project_id)?;
         let mut found_image_paths = HashSet::new();
 
-        if let Ok(entries) = fs::read_dir(folder) {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_file() {
-                        if let Some(ext) = entry.path().extension() {
-                            if image_extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
-                                let file_path = entry.path().to_str().unwrap_or("").to_string();
-                                found_image_paths.insert(file_path.clone());
-
-                                if !existing_images.contains_key(&file_path) {
-                                    let filename =
-                                        entry.file_name().to_str().unwrap_or("").to_string();
-                                    let file_size = fs::metadata(entry.path())
-                                        .map(|m| m.len() as i64)
-                                        .unwrap_or(0);
-
-                                    self.file_repo.add_image_file(
-                                        project_id, &filename, &file_path, file_size, "direct",
-                                        None, 0,
-                                    )?;
-                                    result.files_added += 1;
-                                }
-                            }
-                        }
-                    }
-                }
+        for (path, file_size) in image_files_in(folder) {
+            let file_path = path.to_str().unwrap_or("").to_string();
+            found_image_paths.insert(file_path.clone());
+
+            if let Some(indexed) = existing_images.get(&file_path) {
+                self.check_image_file(indexed, &path, result)?;
+            } else {
+                let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+                self.file_repo.add_image_file(
+                    project_id, filename, &file_path, file_size, "direct", None, 0,
+                )?;
+                result.files_added += 1;
             }
         }
 
         // Remove deleted images
-        for (image_path, image_id) in existing_images.iter() {
+        for (image_path, indexed) in existing_images.iter() {
             if !found_image_paths.contains(image_path) {
-                if let Err(e) = self.file_repo.delete_image_file(*image_id) {
+                if let Err(e) = self.file_repo.delete_image_file(indexed.id) {
                     result
                         .errors
                         .push(format!("Error removing deleted image: {}", e));
@@ -618,28 +1320,72 @@ impl RescanService {
         Ok(())
     }
 
-    fn get_existing_image_files(&self, project_id: i64) -> Result<HashMap<String, i64>, AppError> {
+    /// Update an indexed image that was replaced in place and drop its cached copy
+    fn check_image_file(
+        &self,
+        indexed: &IndexedFile,
+        path: &Path,
+        result: &mut RescanResult,
+    ) -> Result<(), AppError> {
+        let (file_size, file_mtime) = file_signature(path)?;
+
+        if indexed.matches(file_size, file_mtime) {
+            if indexed.file_mtime.is_none() {
+                self.file_repo
+                    .update_image_file_signature(indexed.id, file_size, file_mtime)?;
+            }
+            return Ok(());
+        }
+
+        self.file_repo
+            .update_image_file_signature(indexed.id, file_size, file_mtime)?;
+        if let Some(ref cache_service) = self.image_cache_service {
+            cache_service.invalidate(path.to_str().unwrap_or(""))?;
+        }
+        result.files_updated += 1;
+
+        Ok(())
+    }
+
+    fn get_existing_image_files(
+        &self,
+        project_id: i64,
+    ) -> Result<HashMap<String, IndexedFile>, AppError> {
         let conn = self.file_repo.pool.get()?;
         let mut stmt = conn.prepare(
-            "SELECT id, file_path FROM image_files WHERE project_id = ?1 AND source_type = 'direct'"
+            "SELECT id, file_path, file_size, file_mtime FROM image_files
+             WHERE project_id = ?1 AND source_type = 'direct'",
         )?;
 
         let files = stmt
             .query_map([project_id], |row| {
-                Ok((row.get::<_, String>(1)?, row.get::<_, i64>(0)?))
+                Ok((
+                    row.get::<_, String>(1)?,
+                    IndexedFile {
+                        id: row.get(0)?,
+                        file_size: row.get(2)?,
+                        file_mtime: row.get(3)?,
+                        has_preview: false,
+                    },
+                ))
             })?
             .collect::<Result<HashMap<_, _>, _>>()?;
 
         Ok(files)
     }
 
-    /// Clear all inherited images from the database.
-    /// This is done at the start of rescan to rebuild inheritance fresh.
-    fn clear_inherited_images(&self) -> Result<(), AppError> {
+    /// Clear the inherited images of every project below `scope`.
+    /// This is done before the inheritance pass to rebuild inheritance fresh.
+    fn clear_inherited_images(&self, scope: &Path) -> Result<(), AppError> {
         let conn = self.file_repo.pool.get()?;
         conn.execute(
-            "DELETE FROM image_files WHERE source_type = 'inherited'",
-            [],
+            "DELETE FROM image_files
+             WHERE source_type = 'inherited'
+               AND project_id IN (
+                   SELECT id FROM projects
+                   WHERE full_path = ?1 OR substr(full_path, 1, length(?1) + 1) = ?1 || '/'
+               )",
+            [scope.to_string_lossy()],
         )?;
         Ok(())
     }
@@ -700,7 +1446,6 @@ impl RescanService {
         root: &Path,
         path_to_id: &HashMap<PathBuf, i64>,
     ) -> Result<(), AppError> {
-        let image_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
         let mut inherited_images = Vec::new();
 
         // Walk up the tree from current folder to root
@@ -715,40 +1460,22 @@ impl RescanService {
             depth += 1;
 
             // Scan parent folder for images
-            if let Ok(entries) = fs::read_dir(parent_folder) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type() {
-                        if file_type.is_file() {
-                            if let Some(ext) = entry.path().extension() {
-                                if image_extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
-                                    // Get the parent project ID
-                                    let source_project_id =
-                                        path_to_id.get(parent_folder).copied().or_else(|| {
-                                            self.ensure_project_exists(
-                                                parent_folder,
-                                                root,
-                                                path_to_id,
-                                            )
-                                            .ok()
-                                        });
-
-                                    if let Some(source_id) = source_project_id {
-                                        let filename =
-                                            entry.file_name().to_str().unwrap_or("").to_string();
-                                        let file_path =
-                                            entry.path().to_str().unwrap_or("").to_string();
-                                        let file_size = fs::metadata(entry.path())
-                                            .map(|m| m.len() as i64)
-                                            .unwrap_or(0);
-
-                                        inherited_images.push((
-                                            filename, file_path, file_size, source_id, depth,
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    }
+            for (path, file_size) in image_files_in(parent_folder) {
+                // Get the parent project ID
+                let source_project_id = path_to_id.get(parent_folder).copied().or_else(|| {
+                    self.ensure_project_exists(parent_folder, root, path_to_id)
+                        .ok()
+                });
+
+                if let Some(source_id) = source_project_id {
+                    let filename = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let file_path = path.to_str().unwrap_or("").to_string();
+
+                    inherited_images.push((filename, file_path, file_size, source_id, depth));
                 }
             }
 
@@ -777,7 +1504,6 @@ impl RescanService {
         &self,
         project_id: i64,
         composite_service: &crate::services::composite_preview::CompositePreviewService,
-        result: &mut RescanResult,
     ) -> Result<(), AppError> {
         // Get first 4 direct images for this project (priority-sorted)
         let conn = self.file_repo.pool.get()?;
@@ -820,12 +1546,6 @@ impl RescanService {
 
         self.preview_repo.store_preview(&create_preview)?;
 
-        if !had_preview {
-            result.files_added += 1; // Count new previews
-        } else {
-            result.files_updated += 1; // Count updated previews
-        }
-
         Ok(())
     }
 
@@ -878,3 +1598,24 @@ impl RescanService {
         Ok(())
     }
 }
+
+/// LIKE pattern matching every path below `path`
+fn like_prefix(path: &str) -> String {
+    let escaped = path
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("{}{}%", escaped, std::path::MAIN_SEPARATOR)
+}
+
+/// Number of file rows a rescan has added, updated or removed so far
+fn change_count(result: &RescanResult) -> usize {
+    result.files_added + result.files_updated + result.files_removed
+}
+
+/// Record an archive the scanner cannot look into
+fn report_unsupported_archive(path: &Path, result: &mut RescanResult) {
+    let error_msg = archive::unsupported_message(path);
+    warn!("{}", error_msg);
+    result.errors.push(error_msg);
+}
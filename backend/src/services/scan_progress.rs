@@ -0,0 +1,147 @@
+use crate::models::scan_progress::{ScanPhase, ScanProgress};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Debug, Default)]
+struct ProgressState {
+    phase: Option<ScanPhase>,
+    folders_visited: usize,
+    files_processed: usize,
+    current_path: Option<String>,
+    phase_completed: usize,
+    phase_total: Option<usize>,
+    started_at: Option<Instant>,
+    phase_started_at: Option<Instant>,
+}
+
+/// Shared progress of the running scan. The scanner and rescan services report
+/// into it while the API reads snapshots; clones share the same state.
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgressTracker {
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl ScanProgressTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reset all counters for a new scan
+    pub fn start(&self) {
+        self.update(|state| {
+            *state = ProgressState {
+                started_at: Some(Instant::now()),
+                ..ProgressState::default()
+            };
+        });
+    }
+
+    /// Enter a new phase; `total` is the number of work items if known
+    pub fn begin_phase(&self, phase: ScanPhase, total: Option<usize>) {
+        self.update(|state| {
+            state.phase = Some(phase);
+            state.phase_completed = 0;
+            state.phase_total = total;
+            state.phase_started_at = Some(Instant::now());
+        });
+    }
+
+    pub fn visit_folder(&self, path: &Path) {
+        self.update(|state| {
+            state.folders_visited += 1;
+            state.current_path = Some(path.to_string_lossy().to_string());
+        });
+    }
+
+    /// Count a model file as processed; files are the work items of the indexing phase
+    pub fn process_file(&self, path: &Path) {
+        self.update(|state| {
+            state.files_processed += 1;
+            state.phase_completed += 1;
+            state.current_path = Some(path.to_string_lossy().to_string());
+        });
+    }
+
+    /// Finish one work item of the current phase
+    pub fn advance(&self, path: &Path) {
+        self.update(|state| {
+            state.phase_completed += 1;
+            state.current_path = Some(path.to_string_lossy().to_string());
+        });
+    }
+
+    pub fn finish(&self) {
+        self.update(|state| {
+            state.phase = None;
+            state.current_path = None;
+        });
+    }
+
+    pub fn snapshot(&self) -> ScanProgress {
+        let state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        let elapsed_seconds = state
+            .started_at
+            .map(|started| started.elapsed().as_secs())
+            .unwrap_or(0);
+
+        let estimated_remaining_seconds = match (state.phase_total, state.phase_started_at) {
+            (Some(total), Some(phase_started)) if state.phase_completed > 0 => {
+                let per_item = phase_started.elapsed().as_secs_f64() / state.phase_completed as f64;
+                let remaining = total.saturating_sub(state.phase_completed);
+                Some((per_item * remaining as f64).round() as u64)
+            }
+            _ => None,
+        };
+
+        ScanProgress {
+            phase: state.phase,
+            folders_visited: state.folders_visited,
+            files_processed: state.files_processed,
+            current_path: state.current_path.clone(),
+            phase_completed: state.phase_completed,
+            phase_total: state.phase_total,
+            elapsed_seconds,
+            estimated_remaining_seconds,
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut ProgressState)) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        apply(&mut state);
+    }
+}
+
+/// Cancellation flag for the running scan, shared between the API and the scan services.
+/// Services check it between folders and stop early, leaving finished folders indexed.
+#[derive(Debug, Clone, Default)]
+pub struct ScanCancellation {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ScanCancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Clear a previous cancellation before a new scan starts
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
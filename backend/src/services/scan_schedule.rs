@@ -0,0 +1,247 @@
+/// When automatic rescans run, as configured in `config`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanSchedule {
+    /// Every given number of seconds
+    Interval(i64),
+    /// At the times matched by a cron expression
+    Cron(CronExpression),
+}
+
+impl ScanSchedule {
+    /// Schedule stored in `config`. A cron expression takes precedence over an interval;
+    /// `None` means automatic rescans are disabled.
+    pub fn from_config(
+        scan_schedule: Option<&str>,
+        scan_interval_minutes: Option<i64>,
+    ) -> Result<Option<Self>, String> {
+        if let Some(expression) = scan_schedule.filter(|s| !s.trim().is_empty()) {
+            return CronExpression::parse(expression).map(|cron| Some(ScanSchedule::Cron(cron)));
+        }
+
+        match scan_interval_minutes {
+            Some(minutes) if minutes > 0 => Ok(Some(ScanSchedule::Interval(minutes * 60))),
+            _ => Ok(None),
+        }
+    }
+
+    /// First run strictly after `now` (seconds since epoch)
+    pub fn next_after(&self, now: i64) -> Option<i64> {
+        match self {
+            ScanSchedule::Interval(seconds) => Some(now + seconds),
+            ScanSchedule::Cron(cron) => cron.next_after(now),
+        }
+    }
+}
+
+/// Five-field cron expression (`minute hour day-of-month month day-of-week`), evaluated in UTC.
+/// Fields accept `*`, single values, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// comma-separated lists. Day of week runs from 0 (Sunday) to 6, with 7 also meaning Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether day-of-month and day-of-week were restricted; when both are,
+    /// a day matching either one matches (standard cron behaviour)
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Cron expression must have 5 fields (minute hour day month weekday): {}",
+                expression
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "day of week")?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minute")?,
+            hours: parse_field(fields[1], 0, 23, "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, "day of month")?,
+            months: parse_field(fields[3], 1, 12, "month")?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+
+    /// First matching minute strictly after `now` (seconds since epoch)
+    pub fn next_after(&self, now: i64) -> Option<i64> {
+        let start_minute = now.div_euclid(60) + 1;
+        let first_day = start_minute.div_euclid(MINUTES_PER_DAY);
+
+        // Every valid expression matches within a leap-year cycle
+        for day in first_day..first_day + 4 * 366 {
+            if !self.matches_day(day) {
+                continue;
+            }
+
+            let from = if day == first_day {
+                start_minute.rem_euclid(MINUTES_PER_DAY)
+            } else {
+                0
+            };
+            for minute_of_day in from..MINUTES_PER_DAY {
+                let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                if has_bit(self.hours, hour) && has_bit(self.minutes, minute) {
+                    return Some((day * MINUTES_PER_DAY + minute_of_day) * 60);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn matches_day(&self, days_since_epoch: i64) -> bool {
+        let (_, month, day) = civil_from_days(days_since_epoch);
+        if !has_bit(self.months, month) {
+            return false;
+        }
+
+        // 1970-01-01 was a Thursday
+        let weekday = (days_since_epoch + 4).rem_euclid(7);
+        let dom = has_bit(self.days_of_month, day);
+        let dow = has_bit(self.days_of_week, weekday);
+
+        if self.dom_restricted && self.dow_restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+}
+
+const MINUTES_PER_DAY: i64 = 24 * 60;
+
+fn has_bit(mask: u64, value: i64) -> bool {
+    (0..64).contains(&value) && mask & (1 << value) != 0
+}
+
+/// Parse one cron field into a bitmask of allowed values
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid {} in cron expression: {}", name, field);
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                start.parse::<u32>().map_err(|_| invalid())?,
+                end.parse::<u32>().map_err(|_| invalid())?,
+            )
+        } else {
+            let value = range.parse::<u32>().map_err(|_| invalid())?;
+            // `5/15` means from 5 to the end of the range in steps of 15
+            if part.contains('/') {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-03-15 10:20:30 UTC, a Friday
+    const NOW: i64 = 1_710_498_030;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(NOW / 86_400), (2024, 3, 15));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // Every night at 03:30
+        let nightly = CronExpression::parse("30 3 * * *").unwrap();
+        assert_eq!(nightly.next_after(NOW), Some(1_710_559_800)); // 2024-03-16 03:30
+
+        // Every 15 minutes
+        let quarterly = CronExpression::parse("*/15 * * * *").unwrap();
+        assert_eq!(quarterly.next_after(NOW), Some(1_710_498_600)); // 10:30
+
+        // Weekdays at 02:00; the next one after Friday morning is Monday
+        let weekdays = CronExpression::parse("0 2 * * 1-5").unwrap();
+        assert_eq!(weekdays.next_after(NOW), Some(1_710_727_200)); // 2024-03-18 02:00
+
+        // Sunday written as 7
+        let sunday = CronExpression::parse("0 0 * * 7").unwrap();
+        assert_eq!(sunday.next_after(NOW), Some(1_710_633_600)); // 2024-03-17 00:00
+
+        // Leap days only
+        let leap = CronExpression::parse("0 0 29 2 *").unwrap();
+        assert_eq!(leap.next_after(NOW), Some(1_835_395_200)); // 2028-02-29 00:00
+    }
+
+    #[test]
+    fn test_cron_rejects_invalid_expressions() {
+        assert!(CronExpression::parse("* * * *").is_err());
+        assert!(CronExpression::parse("60 * * * *").is_err());
+        assert!(CronExpression::parse("*/0 * * * *").is_err());
+        assert!(CronExpression::parse("5-1 * * * *").is_err());
+        assert!(CronExpression::parse("0 3 * * mon").is_err());
+    }
+
+    #[test]
+    fn test_schedule_from_config() {
+        assert_eq!(ScanSchedule::from_config(None, None), Ok(None));
+        assert_eq!(ScanSchedule::from_config(Some(""), Some(0)), Ok(None));
+        assert_eq!(
+            ScanSchedule::from_config(None, Some(90)),
+            Ok(Some(ScanSchedule::Interval(5400)))
+        );
+        assert!(matches!(
+            ScanSchedule::from_config(Some("0 3 * * *"), Some(90)),
+            Ok(Some(ScanSchedule::Cron(_)))
+        ));
+        assert!(ScanSchedule::from_config(Some("nightly"), None).is_err());
+    }
+}
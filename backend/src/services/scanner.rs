@@ -1,15 +1,29 @@
+use crate::config::ConfigService;
 use crate::db::connection::DbPool;
+use crate::db::repositories::creator_repo::CreatorRepository;
 use crate::db::repositories::file_repo::FileRepository;
 use crate::db::repositories::project_repo::ProjectRepository;
+use crate::db::repositories::release_repo::ReleaseRepository;
 use crate::models::project::CreateProject;
+use crate::models::scan_progress::ScanPhase;
+use crate::models::stl_file::{CreateStlFile, ModelFormat};
+use crate::services::duplicates::DuplicateService;
+use crate::services::mesh_analysis::MeshAnalysisService;
+use crate::services::scan_progress::{ScanCancellation, ScanProgressTracker};
+use crate::services::sidecar::SidecarService;
+use crate::utils::archive;
 use crate::utils::error::AppError;
+use crate::utils::fs::{file_signature, image_files_in};
+use crate::utils::walk::{default_threads, walk_parallel, WalkEntry};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tracing::{error, info, warn};
-use walkdir::WalkDir;
+
+/// Project folders whose projects and model files are inserted in one transaction
+const INDEX_BATCH_SIZE: usize = 256;
 
 #[derive(Debug, Clone)]
 pub struct ScanResult {
@@ -18,17 +32,27 @@ pub struct ScanResult {
     pub stl_previews_generated: usize,
     pub stl_previews_queued: usize,
     pub errors: Vec<String>,
+    /// The scan was cancelled before it finished
+    pub cancelled: bool,
 }
 
 pub struct ScannerService {
     project_repo: ProjectRepository,
     file_repo: FileRepository,
+    creator_repo: CreatorRepository,
+    release_repo: ReleaseRepository,
     preview_repo: crate::db::repositories::preview_repo::PreviewRepository,
     composite_service: Option<crate::services::composite_preview::CompositePreviewService>,
     stl_preview_service: Option<crate::services::stl_preview::StlPreviewService>,
     preview_queue: Option<std::sync::Arc<crate::services::stl_preview::PreviewQueue>>,
+    mesh_analysis_service: Option<MeshAnalysisService>,
+    duplicate_service: DuplicateService,
+    sidecar_service: SidecarService,
     ignored_keywords: Vec<String>,
+    walk_threads: usize,
     preview_semaphore: Arc<Semaphore>,
+    progress: ScanProgressTracker,
+    cancellation: ScanCancellation,
 }
 
 impl ScannerService {
@@ -38,12 +62,22 @@ impl ScannerService {
         Self {
             project_repo: ProjectRepository::new(pool.clone()),
             file_repo: FileRepository::new(pool.clone()),
-            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(pool),
+            creator_repo: CreatorRepository::new(pool.clone()),
+            release_repo: ReleaseRepository::new(pool.clone()),
+            preview_repo: crate::db::repositories::preview_repo::PreviewRepository::new(
+                pool.clone(),
+            ),
             composite_service: None,
             stl_preview_service: None,
             preview_queue: None,
+            mesh_analysis_service: None,
+            duplicate_service: DuplicateService::new(pool.clone()),
+            sidecar_service: SidecarService::new(pool),
             ignored_keywords: Vec::new(),
+            walk_threads: default_threads(),
             preview_semaphore,
+            progress: ScanProgressTracker::new(),
+            cancellation: ScanCancellation::new(),
         }
     }
 
@@ -52,6 +86,12 @@ impl ScannerService {
         self
     }
 
+    /// Number of threads listing folders while walking the library
+    pub fn with_walk_threads(mut self, threads: usize) -> Self {
+        self.walk_threads = threads;
+        self
+    }
+
     pub fn with_composite_preview(mut self, cache_dir: std::path::PathBuf) -> Self {
         self.composite_service =
             Some(crate::services::composite_preview::CompositePreviewService::new(cache_dir));
@@ -68,6 +108,23 @@ impl ScannerService {
         self
     }
 
+    pub fn with_mesh_analysis(mut self, mesh_analysis_service: MeshAnalysisService) -> Self {
+        self.mesh_analysis_service = Some(mesh_analysis_service);
+        self
+    }
+
+    /// Report scan progress into a shared tracker
+    pub fn with_progress(mut self, progress: ScanProgressTracker) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Stop scans between folders once the flag is set
+    pub fn with_cancellation(mut self, cancellation: ScanCancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
     /// Check if a folder name contains any ignored keyword (case-insensitive substring match)
     fn is_stl_category_folder(&self, folder_name: &str) -> bool {
         let normalized_name = folder_name.trim().to_lowercase();
@@ -77,9 +134,15 @@ impl ScannerService {
     }
 
     pub fn scan(&self, root_path: &str) -> Result<ScanResult, AppError> {
+        self.scan_subtree(root_path, Path::new(root_path))
+    }
+
+    /// Scan only the folders below `subtree`, which must lie inside `root_path`.
+    /// Parent folders up to the root are still indexed so inheritance stays intact.
+    pub fn scan_subtree(&self, root_path: &str, subtree: &Path) -> Result<ScanResult, AppError> {
         let root = Path::new(root_path);
 
-        info!("Starting scan of directory: {}", root_path);
+        info!("Starting scan of directory: {}", subtree.display());
 
         if !root.exists() {
             error!("Scan failed: Root path does not exist: {}", root_path);
@@ -97,40 +160,104 @@ impl ScannerService {
             )));
         }
 
+        if !subtree.starts_with(root) || !subtree.is_dir() {
+            return Err(AppError::ValidationError(format!(
+                "Not a directory inside the library root: {}",
+                subtree.display()
+            )));
+        }
+
+        // A category folder is scanned together with the project it belongs to
+        let scope = self.find_project_folder(subtree, root);
+        let ignore = Arc::new(ConfigService::new(self.file_repo.pool.clone()).ignore_rules(root));
+
         let mut projects_found = 0;
         let mut files_processed = 0;
         let mut errors = Vec::new();
+        let mut cancelled = false;
 
         // Find all folders containing STL files
-        let mut project_folders = HashMap::new();
-
-        for entry in WalkDir::new(root).follow_links(false) {
-            match entry {
-                Ok(e) => {
-                    if e.file_type().is_file() {
-                        if let Some(ext) = e.path().extension() {
-                            if ext.eq_ignore_ascii_case("stl") {
-                                if let Some(parent) = e.path().parent() {
-                                    // Find the actual project folder by traversing up
-                                    // past any STL category folders
-                                    let project_folder = self.find_project_folder(parent, root);
-                                    project_folders
-                                        .entry(project_folder)
-                                        .or_insert_with(Vec::new)
-                                        .push(e.path().to_path_buf());
-                                }
-                            }
-                        }
+        let mut project_folders: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+
+        self.progress.begin_phase(ScanPhase::Walking, None);
+        for entry in walk_parallel(&scope, ignore.clone(), self.walk_threads) {
+            let path = match entry {
+                WalkEntry::Folder(folder) => {
+                    if self.cancellation.is_cancelled() {
+                        // Dropping the receiver stops the walker threads
+                        cancelled = true;
+                        break;
                     }
+                    self.progress.visit_folder(&folder);
+                    continue;
                 }
-                Err(e) => {
+                WalkEntry::File(path) => path,
+                WalkEntry::Error(e) => {
                     let error_msg = format!("Error walking directory: {}", e);
                     error!("{}", error_msg);
                     errors.push(error_msg);
+                    continue;
+                }
+            };
+
+            // Any recognised model format (STL, 3MF, OBJ, PLY) marks a project folder.
+            // Archives are indexed as folders holding the files they contain.
+            let model_files = if ModelFormat::from_path(&path).is_some() {
+                vec![path]
+            } else if archive::is_archive(&path) {
+                match archive::list_entries(&path) {
+                    Ok(entries) => entries
+                        .into_iter()
+                        .map(|entry| entry.path)
+                        .filter(|path| {
+                            ModelFormat::from_path(path).is_some()
+                                && !ignore.is_excluded(path, false)
+                        })
+                        .collect(),
+                    Err(err) => {
+                        warn!("{}", err);
+                        errors.push(err.to_string());
+                        Vec::new()
+                    }
+                }
+            } else if archive::is_unsupported_archive(&path) {
+                let error_msg = archive::unsupported_message(&path);
+                warn!("{}", error_msg);
+                errors.push(error_msg);
+                Vec::new()
+            } else {
+                Vec::new()
+            };
+            for model_file in model_files {
+                if let Some(parent) = model_file.parent() {
+                    // Find the actual project folder by traversing up
+                    // past any STL category folders
+                    let project_folder = self.find_project_folder(parent, root);
+                    project_folders
+                        .entry(project_folder)
+                        .or_default()
+                        .push(model_file);
                 }
             }
         }
 
+        // Folders are walked concurrently; keep the indexing order stable
+        for model_files in project_folders.values_mut() {
+            model_files.sort();
+        }
+
+        if !cancelled {
+            if let Err(e) = self.project_repo.replace_folder_links(
+                &scope,
+                &ignore.folder_links(),
+                &ignore.external_links(),
+            ) {
+                let error_msg = format!("Error saving folder links: {}", e);
+                error!("{}", error_msg);
+                errors.push(error_msg);
+            }
+        }
+
         info!(
             "Directory walk complete. Found {} project folders",
             project_folders.len()
@@ -146,7 +273,7 @@ impl ScannerService {
         }
 
         // Migrate projects that became category folders due to keyword changes
-        if !self.ignored_keywords.is_empty() {
+        if !self.ignored_keywords.is_empty() && !cancelled {
             info!("Checking for projects that should become category folders");
             if let Err(e) = self.migrate_category_folder_projects(root, &project_folders) {
                 let error_msg = format!("Error migrating category folder projects: {}", e);
@@ -155,96 +282,152 @@ impl ScannerService {
             }
         }
 
+        let model_file_count = project_folders.values().map(Vec::len).sum();
+        self.progress
+            .begin_phase(ScanPhase::Indexing, Some(model_file_count));
+
         // Build project hierarchy
         let mut path_to_id = HashMap::new();
-        let mut processed_paths = HashSet::new();
-
-        // Process each project folder
-        for (folder, stl_files) in project_folders.iter() {
-            match self.create_project_hierarchy(folder, root, &mut path_to_id, &mut processed_paths)
-            {
-                Ok(project_id) => {
-                    projects_found += 1;
-
-                    // Add STL files and generate previews
-                    let stl_files_vec: Vec<_> = stl_files.to_vec();
-                    for stl_file in &stl_files_vec {
-                        // Extract category from the immediate parent folder if it's an STL category folder
-                        let category = stl_file
-                            .parent()
-                            .and_then(|parent| parent.file_name())
-                            .and_then(|name| name.to_str())
-                            .and_then(|name_str| {
-                                if self.is_stl_category_folder(name_str) {
-                                    Some(name_str)
-                                } else {
-                                    None
-                                }
-                            });
 
-                        match self.file_repo.add_stl_file_with_category(
-                            project_id,
-                            stl_file.file_name().unwrap().to_str().unwrap(),
-                            stl_file.to_str().unwrap(),
-                            fs::metadata(stl_file).map(|m| m.len() as i64).unwrap_or(0),
-                            category,
-                        ) {
-                            Ok(_) => files_processed += 1,
-                            Err(e) => {
+        // Sorted so that a batch mostly shares its parent folders
+        let mut folders: Vec<(&PathBuf, &Vec<PathBuf>)> = project_folders.iter().collect();
+        folders.sort();
+
+        for batch in folders.chunks(INDEX_BATCH_SIZE) {
+            if cancelled || self.cancellation.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+
+            // Create the batch's projects and their parent folders in one transaction
+            let batch_folders: Vec<&Path> =
+                batch.iter().map(|(folder, _)| folder.as_path()).collect();
+            match self.project_repo.ensure_hierarchy(root, &batch_folders) {
+                Ok(ids) => path_to_id.extend(ids),
+                Err(e) => {
+                    let error_msg =
+                        format!("Error creating projects for {} folders: {}", batch.len(), e);
+                    error!("{}", error_msg);
+                    errors.push(error_msg);
+                    continue;
+                }
+            }
+
+            // Then insert all of the batch's model files in one transaction
+            let rows: Vec<CreateStlFile> = batch
+                .iter()
+                .flat_map(|(folder, stl_files)| {
+                    let project_id = path_to_id[*folder];
+                    stl_files
+                        .iter()
+                        .map(move |stl_file| self.model_file_row(project_id, stl_file))
+                })
+                .collect();
+            let mut file_ids = match self.file_repo.create_stl_files(&rows) {
+                Ok(ids) => ids.into_iter(),
+                Err(e) => {
+                    let error_msg = format!("Error adding {} model files: {}", rows.len(), e);
+                    error!("{}", error_msg);
+                    errors.push(error_msg);
+                    Vec::new().into_iter()
+                }
+            };
+
+            for (folder, stl_files) in batch {
+                if self.cancellation.is_cancelled() {
+                    cancelled = true;
+                    break;
+                }
+                let project_id = path_to_id[*folder];
+                projects_found += 1;
+
+                for stl_file in stl_files.iter() {
+                    if let Some(file_id) = file_ids.next() {
+                        files_processed += 1;
+
+                        if let Err(e) = self.duplicate_service.hash_if_changed(file_id, stl_file) {
+                            let error_msg =
+                                format!("Error hashing model file {}: {}", stl_file.display(), e);
+                            warn!("{}", error_msg);
+                            errors.push(error_msg);
+                        }
+
+                        if let Some(ref analysis_service) = self.mesh_analysis_service {
+                            if let Err(e) = analysis_service.analyze_if_changed(file_id, stl_file) {
                                 let error_msg =
-                                    format!("Error adding STL file {}: {}", stl_file.display(), e);
-                                error!("{}", error_msg);
+                                    format!("Error analyzing mesh {}: {}", stl_file.display(), e);
+                                warn!("{}", error_msg);
                                 errors.push(error_msg);
                             }
                         }
                     }
+                    self.progress.process_file(stl_file);
+                }
 
-                    // Generate STL previews if service available (US1)
-                    if self.stl_preview_service.is_some() && !stl_files_vec.is_empty() {
-                        // Split: first 2 sync, rest async
-                        let (sync_files, async_files) =
-                            stl_files_vec.split_at(std::cmp::min(2, stl_files_vec.len()));
-
-                        // Generate first 2 synchronously
-                        for stl_file in sync_files {
-                            if let Err(e) = self.generate_stl_preview_sync(project_id, stl_file) {
-                                warn!(
-                                    "Failed to generate preview for {}: {}",
-                                    stl_file.display(),
-                                    e
-                                );
-                            }
-                        }
+                // Generate STL previews if service available (US1)
+                if self.stl_preview_service.is_some() && !stl_files.is_empty() {
+                    // Split: first 2 sync, rest async
+                    let (sync_files, async_files) =
+                        stl_files.split_at(std::cmp::min(2, stl_files.len()));
 
-                        // Queue remaining for async generation
-                        for stl_file in async_files {
-                            if let Err(e) = self.queue_stl_preview(project_id, stl_file) {
-                                warn!("Failed to queue preview for {}: {}", stl_file.display(), e);
-                            }
+                    // Generate first 2 synchronously
+                    for stl_file in sync_files {
+                        if let Err(e) = self.generate_stl_preview_sync(project_id, stl_file) {
+                            warn!(
+                                "Failed to generate preview for {}: {}",
+                                stl_file.display(),
+                                e
+                            );
                         }
                     }
 
-                    // Find and add images
-                    if let Err(e) = self.add_images_for_project(project_id, folder) {
-                        let error_msg = format!(
-                            "Error adding images for project {}: {}",
-                            folder.display(),
-                            e
-                        );
-                        warn!("{}", error_msg);
-                        errors.push(error_msg);
+                    // Queue remaining for async generation
+                    for stl_file in async_files {
+                        if let Err(e) = self.queue_stl_preview(project_id, stl_file) {
+                            warn!("Failed to queue preview for {}: {}", stl_file.display(), e);
+                        }
                     }
                 }
-                Err(e) => {
-                    let error_msg =
-                        format!("Error creating project for {}: {}", folder.display(), e);
-                    error!("{}", error_msg);
+
+                // Find and add images
+                if let Err(e) = self.add_images_for_project(project_id, folder) {
+                    let error_msg = format!(
+                        "Error adding images for project {}: {}",
+                        folder.display(),
+                        e
+                    );
+                    warn!("{}", error_msg);
+                    errors.push(error_msg);
+                }
+
+                if let Err(e) = self.sidecar_service.import_if_changed(project_id, folder) {
+                    let error_msg = format!(
+                        "Error importing metadata for project {}: {}",
+                        folder.display(),
+                        e
+                    );
+                    warn!("{}", error_msg);
                     errors.push(error_msg);
                 }
             }
         }
 
+        if cancelled {
+            // Projects indexed so far stay; parent images and previews wait for the next scan
+            info!("Scan cancelled after {} project folders", projects_found);
+            return Ok(ScanResult {
+                projects_found,
+                files_processed,
+                stl_previews_generated: 0,
+                stl_previews_queued: 0,
+                errors,
+                cancelled,
+            });
+        }
+
         // NEW: Scan parent folders for images
+        self.progress
+            .begin_phase(ScanPhase::Inheritance, Some(project_folders.len()));
         info!("Scanning parent folders for images");
         let mut scanned_folders = HashSet::new();
 
@@ -275,6 +458,18 @@ impl ScannerService {
                             warn!("{}", error_msg);
                             errors.push(error_msg);
                         }
+                        if let Err(e) = self
+                            .sidecar_service
+                            .import_if_changed(parent_id, parent_folder)
+                        {
+                            let error_msg = format!(
+                                "Error importing metadata for parent folder {}: {}",
+                                parent_folder.display(),
+                                e
+                            );
+                            warn!("{}", error_msg);
+                            errors.push(error_msg);
+                        }
                         scanned_folders.insert(parent_folder.to_path_buf());
                     }
                     Err(e) => {
@@ -292,13 +487,58 @@ impl ScannerService {
             }
         }
 
+        // Ancestors of the scanned subtree may no longer hold model files of their own
+        for folder in scope
+            .ancestors()
+            .skip(1)
+            .take_while(|folder| *folder >= root)
+        {
+            let project_id = match path_to_id.get(folder) {
+                Some(&id) => Some(id),
+                None => self
+                    .project_repo
+                    .get_by_path(folder.to_str().unwrap_or(""))?
+                    .map(|project| project.id),
+            };
+            if let Some(project_id) = project_id {
+                if let Err(e) = self.project_repo.refresh_is_leaf(project_id) {
+                    errors.push(format!(
+                        "Error updating parent folder {}: {}",
+                        folder.display(),
+                        e
+                    ));
+                }
+            }
+        }
+
+        // Creators follow the folder hierarchy and the authors imported above, and
+        // detected releases the folders and their creators
+        let assigned = ConfigService::new(self.file_repo.pool.clone())
+            .get_config()
+            .and_then(|config| {
+                self.creator_repo
+                    .assign_creators(config.creator_folder_level)?;
+                self.release_repo
+                    .assign_releases(config.release_pattern().as_ref())
+            });
+        if let Err(e) = assigned {
+            let error_msg = format!("Error assigning creators and releases: {}", e);
+            warn!("{}", error_msg);
+            errors.push(error_msg);
+        }
+
         // Second pass: Propagate images from parent folders to children
         info!("Propagating images from parent folders to children");
         for (folder, _) in project_folders.iter() {
+            self.progress.advance(folder);
             if let Some(&project_id) = path_to_id.get(folder) {
-                if let Err(e) =
-                    self.inherit_images_from_parents(project_id, folder, root, &mut path_to_id)
-                {
+                let inherited = self
+                    .file_repo
+                    .delete_inherited_images(project_id)
+                    .and_then(|_| {
+                        self.inherit_images_from_parents(project_id, folder, root, &mut path_to_id)
+                    });
+                if let Err(e) = inherited {
                     let error_msg = format!(
                         "Error inheriting images for project {}: {}",
                         folder.display(),
@@ -313,8 +553,15 @@ impl ScannerService {
         // Third pass: Generate composite previews for ALL projects
         if let Some(ref composite_service) = self.composite_service {
             info!("Generating composite previews for all projects");
+            self.progress
+                .begin_phase(ScanPhase::Previews, Some(path_to_id.len()));
             // Iterate over all projects, not just folders with STL files
-            for (_folder, &project_id) in path_to_id.iter() {
+            for (folder, &project_id) in path_to_id.iter() {
+                if self.cancellation.is_cancelled() {
+                    cancelled = true;
+                    break;
+                }
+                self.progress.advance(folder);
                 if let Err(e) = self.generate_preview_for_project(project_id, composite_service) {
                     let error_msg =
                         format!("Error generating preview for project {}: {}", project_id, e);
@@ -325,12 +572,13 @@ impl ScannerService {
         }
 
         // Fourth pass: Backfill missing STL previews
-        let (stl_previews_generated, stl_previews_queued) = if self.stl_preview_service.is_some() {
-            info!("Backfilling missing STL previews");
-            self.backfill_stl_previews(&mut errors)?
-        } else {
-            (0, 0)
-        };
+        let (stl_previews_generated, stl_previews_queued) =
+            if self.stl_preview_service.is_some() && !cancelled {
+                info!("Backfilling missing STL previews");
+                self.backfill_stl_previews(&mut errors)?
+            } else {
+                (0, 0)
+            };
 
         info!(
             "Scan complete: {} projects found, {} files processed, {} STL previews generated, {} queued, {} errors",
@@ -351,6 +599,7 @@ impl ScannerService {
             stl_previews_generated,
             stl_previews_queued,
             errors,
+            cancelled,
         })
     }
 
@@ -375,80 +624,39 @@ impl ScannerService {
         current.to_path_buf()
     }
 
-    fn create_project_hierarchy(
-        &self,
-        folder: &Path,
-        root: &Path,
-        path_to_id: &mut HashMap<PathBuf, i64>,
-        processed_paths: &mut HashSet<PathBuf>,
-    ) -> Result<i64, AppError> {
-        let full_path = folder.to_str().unwrap().to_string();
-
-        if let Some(&existing_id) = path_to_id.get(folder) {
-            // Ensure is_leaf is true since this folder has STL files
-            self.project_repo.update_is_leaf(existing_id, true)?;
-            return Ok(existing_id);
-        }
-
-        if processed_paths.contains(folder) {
-            if let Some(project) = self.project_repo.get_by_path(&full_path)? {
-                path_to_id.insert(folder.to_path_buf(), project.id);
-                // Ensure is_leaf is true
-                if !project.is_leaf {
-                    self.project_repo.update_is_leaf(project.id, true)?;
-                }
-                return Ok(project.id);
-            }
-        }
-
-        processed_paths.insert(folder.to_path_buf());
-
-        let parent_id = if folder != root {
-            if let Some(parent) = folder.parent() {
-                if parent >= root {
-                    // Use ensure_project_exists for parent (creates with is_leaf=false)
-                    Some(self.ensure_project_exists(parent, root, path_to_id)?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let name = folder
-            .file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or("Unknown")
-            .to_string();
-
-        let create_project = CreateProject {
-            name,
-            full_path: full_path.clone(),
-            parent_id,
-            is_leaf: true,
-        };
-
-        let project_id = if let Some(existing) = self.project_repo.get_by_path(&full_path)? {
-            if !existing.is_leaf {
-                self.project_repo.update_is_leaf(existing.id, true)?;
-            }
-            existing.id
-        } else {
-            self.project_repo.create(&create_project)?
+    /// Row for a model file of a project. The category is the immediate parent
+    /// folder when that is an STL category folder.
+    fn model_file_row(&self, project_id: i64, stl_file: &Path) -> CreateStlFile {
+        let category = stl_file
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .filter(|name| self.is_stl_category_folder(name));
+        let (file_size, file_mtime) = match file_signature(stl_file) {
+            Ok((size, mtime)) => (size, Some(mtime)),
+            Err(_) => (0, None),
         };
 
-        path_to_id.insert(folder.to_path_buf(), project_id);
-
-        Ok(project_id)
+        CreateStlFile {
+            project_id,
+            filename: stl_file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("")
+                .to_string(),
+            file_path: stl_file.to_str().unwrap_or("").to_string(),
+            file_size,
+            // Only recognised formats reach this point
+            format: ModelFormat::from_path(stl_file)
+                .unwrap_or(ModelFormat::Stl)
+                .as_str()
+                .to_string(),
+            category: category.map(str::to_string),
+            file_mtime,
+        }
     }
 
     fn add_images_for_project(&self, project_id: i64, folder: &Path) -> Result<(), AppError> {
-        let image_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
-
         // Get existing images for this project to avoid duplicates
         let conn = self.file_repo.pool.get()?;
         let mut stmt = conn.prepare(
@@ -458,32 +666,18 @@ impl ScannerService {
             .query_map([project_id], |row| row.get::<_, String>(0))?
             .collect::<Result<HashSet<_>, _>>()?;
 
-        if let Ok(entries) = fs::read_dir(folder) {
-            for entry in entries.flatten() {
-                if let Ok(file_type) = entry.file_type() {
-                    if file_type.is_file() {
-                        if let Some(ext) = entry.path().extension() {
-                            if image_extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
-                                let filename = entry.file_name().to_str().unwrap_or("").to_string();
-                                let file_path = entry.path().to_str().unwrap_or("").to_string();
-
-                                // Skip if already exists
-                                if existing_images.contains(&file_path) {
-                                    continue;
-                                }
-
-                                let file_size = fs::metadata(entry.path())
-                                    .map(|m| m.len() as i64)
-                                    .unwrap_or(0);
+        for (path, file_size) in image_files_in(folder) {
+            let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            let file_path = path.to_str().unwrap_or("");
 
-                                self.file_repo.add_image_file(
-                                    project_id, &filename, &file_path, file_size, "direct", None, 0,
-                                )?;
-                            }
-                        }
-                    }
-                }
+            // Skip if already exists
+            if existing_images.contains(file_path) {
+                continue;
             }
+
+            self.file_repo.add_image_file(
+                project_id, filename, file_path, file_size, "direct", None, 0,
+            )?;
         }
 
         Ok(())
@@ -557,7 +751,6 @@ impl ScannerService {
         root: &Path,
         path_to_id: &mut HashMap<PathBuf, i64>,
     ) -> Result<(), AppError> {
-        let image_extensions = ["jpg", "jpeg", "png", "gif", "webp"];
         let mut inherited_images = Vec::new();
 
         // Walk up the tree from current folder to root
@@ -572,40 +765,22 @@ impl ScannerService {
             depth += 1;
 
             // Scan parent folder for images
-            if let Ok(entries) = fs::read_dir(parent_folder) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type() {
-                        if file_type.is_file() {
-                            if let Some(ext) = entry.path().extension() {
-                                if image_extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)) {
-                                    // Get the parent project ID
-                                    let source_project_id =
-                                        path_to_id.get(parent_folder).copied().or_else(|| {
-                                            self.ensure_project_exists(
-                                                parent_folder,
-                                                root,
-                                                path_to_id,
-                                            )
-                                            .ok()
-                                        });
-
-                                    if let Some(source_id) = source_project_id {
-                                        let filename =
-                                            entry.file_name().to_str().unwrap_or("").to_string();
-                                        let file_path =
-                                            entry.path().to_str().unwrap_or("").to_string();
-                                        let file_size = fs::metadata(entry.path())
-                                            .map(|m| m.len() as i64)
-                                            .unwrap_or(0);
-
-                                        inherited_images.push((
-                                            filename, file_path, file_size, source_id, depth,
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-                    }
+            for (path, file_size) in image_files_in(parent_folder) {
+                // Get the parent project ID
+                let source_project_id = path_to_id.get(parent_folder).copied().or_else(|| {
+                    self.ensure_project_exists(parent_folder, root, path_to_id)
+                        .ok()
+                });
+
+                if let Some(source_id) = source_project_id {
+                    let filename = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let file_path = path.to_str().unwrap_or("").to_string();
+
+                    inherited_images.push((filename, file_path, file_size, source_id, depth));
                 }
             }
 
@@ -1,20 +1,57 @@
+use crate::config::ConfigService;
 use crate::db::connection::DbPool;
+use crate::db::repositories::creator_repo::CreatorRepository;
 use crate::db::repositories::file_repo::FileRepository;
 use crate::models::project::{Project, SearchResultProject};
 use crate::utils::error::AppError;
+use rusqlite::types::Value;
+use rusqlite::ToSql;
 
 pub struct SearchService {
     pool: DbPool,
     ignored_keywords: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct SearchParams {
     pub query: Option<String>,
     pub tags: Vec<String>,
     pub page: usize,
     pub per_page: usize,
     pub leaf_only: bool,
+    /// Maximum part width (mm); every analyzed part of a project must fit
+    pub max_x: Option<f64>,
+    /// Maximum part depth (mm)
+    pub max_y: Option<f64>,
+    /// Maximum part height (mm)
+    pub max_z: Option<f64>,
+    /// Minimum height (mm) of the project's tallest part
+    pub min_z: Option<f64>,
+    /// Printer profile name; its build volume fills any max_x/max_y/max_z not given
+    pub printer: Option<String>,
+    /// Only projects with (true) or without (false) mesh printability issues
+    pub has_issues: Option<bool>,
+    /// Only projects from this library root
+    pub root_id: Option<i64>,
+    /// Only projects by this creator (name, case-insensitive)
+    pub creator: Option<String>,
+}
+
+/// Extra SQL conditions on `p`, with the values for their `?` placeholders in order
+struct Filters {
+    sql: String,
+    values: Vec<Value>,
+}
+
+impl Filters {
+    fn push(&mut self, sql: &str, values: impl IntoIterator<Item = Value>) {
+        self.sql.push_str(sql);
+        self.values.extend(values);
+    }
+
+    fn params(&self) -> impl Iterator<Item = &dyn ToSql> {
+        self.values.iter().map(|value| value as &dyn ToSql)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -62,20 +99,21 @@ impl SearchService {
     pub fn search(&self, params: &SearchParams) -> Result<SearchResult, AppError> {
         let conn = self.pool.get()?;
         let offset = (params.page.saturating_sub(1)) * params.per_page;
+        let filters = self.build_filters(params)?;
 
         // Build query based on search parameters
         let (mut projects, total) = if params.query.is_some() && !params.tags.is_empty() {
             // Search by both name and tags
-            self.search_combined(&conn, params, offset)?
+            self.search_combined(&conn, params, &filters, offset)?
         } else if params.query.is_some() {
             // Search by name only using FTS5
-            self.search_fts(&conn, params, offset)?
+            self.search_fts(&conn, params, &filters, offset)?
         } else if !params.tags.is_empty() {
             // Filter by tags only
-            self.search_by_tags(&conn, params, offset)?
+            self.search_by_tags(&conn, params, &filters, offset)?
         } else {
             // No filters - return all leaf projects
-            self.search_all(&conn, params, offset)?
+            self.search_all(&conn, params, &filters, offset)?
         };
 
         // Populate images for each project
@@ -105,71 +143,194 @@ impl SearchService {
         })
     }
 
+    /// Extra SQL conditions on `p` (leaf-only, library root, creator, mesh issues and build-volume
+    /// fit) shared by all search queries. Values are bound as parameters.
+    fn build_filters(&self, params: &SearchParams) -> Result<Filters, AppError> {
+        let mut filters = Filters {
+            sql: String::new(),
+            values: Vec::new(),
+        };
+        if params.leaf_only {
+            filters.push(" AND p.is_leaf = 1", []);
+        }
+        if let Some(root_id) = params.root_id {
+            filters.push(" AND p.root_id = ?", [Value::Integer(root_id)]);
+        }
+        if let Some(ref name) = params.creator {
+            let creator = CreatorRepository::new(self.pool.clone())
+                .find_by_name(name)?
+                .ok_or_else(|| AppError::ValidationError(format!("Unknown creator: {}", name)))?;
+            filters.push(" AND p.creator_id = ?", [Value::Integer(creator.id)]);
+        }
+
+        let limits = [
+            ("max_x", params.max_x),
+            ("max_y", params.max_y),
+            ("max_z", params.max_z),
+            ("min_z", params.min_z),
+        ];
+        for (name, value) in limits {
+            if let Some(v) = value {
+                if !v.is_finite() || v < 0.0 {
+                    return Err(AppError::ValidationError(format!(
+                        "{} must be a non-negative number",
+                        name
+                    )));
+                }
+            }
+        }
+
+        let (mut max_x, mut max_y, mut max_z) = (params.max_x, params.max_y, params.max_z);
+        if let Some(ref printer) = params.printer {
+            let profile = ConfigService::new(self.pool.clone())
+                .get_printer_profile(printer)?
+                .ok_or_else(|| {
+                    AppError::ValidationError(format!("Unknown printer profile: {}", printer))
+                })?;
+            max_x = max_x.or(Some(profile.build_x));
+            max_y = max_y.or(Some(profile.build_y));
+            max_z = max_z.or(Some(profile.build_z));
+        }
+
+        // A part fits if it fits as-is or rotated 90° on the build plate
+        let mut fit_conditions = Vec::new();
+        let mut fit_values = Vec::new();
+        match (max_x, max_y) {
+            (Some(x), Some(y)) => {
+                fit_conditions.push(
+                    "((ma.size_x <= ? AND ma.size_y <= ?) OR (ma.size_x <= ? AND ma.size_y <= ?))",
+                );
+                fit_values.extend([x, y, y, x]);
+            }
+            (Some(x), None) => {
+                fit_conditions.push("ma.size_x <= ?");
+                fit_values.push(x);
+            }
+            (None, Some(y)) => {
+                fit_conditions.push("ma.size_y <= ?");
+                fit_values.push(y);
+            }
+            (None, None) => {}
+        }
+        if let Some(z) = max_z {
+            fit_conditions.push("ma.size_z <= ?");
+            fit_values.push(z);
+        }
+
+        if let Some(has_issues) = params.has_issues {
+            filters.push(
+                &format!(
+                    " AND {}EXISTS (SELECT 1 FROM stl_files sf
+                       INNER JOIN mesh_issues mi ON mi.stl_file_id = sf.id
+                       WHERE sf.project_id = p.id)",
+                    if has_issues { "" } else { "NOT " }
+                ),
+                [],
+            );
+        }
+
+        if fit_conditions.is_empty() && params.min_z.is_none() {
+            return Ok(filters);
+        }
+
+        // Projects without analyzed parts can't be checked, so they are excluded
+        let analyzed_parts = "SELECT 1 FROM stl_files sf
+             INNER JOIN mesh_analysis ma ON ma.stl_file_id = sf.id
+             WHERE sf.project_id = p.id";
+        filters.push(&format!(" AND EXISTS ({})", analyzed_parts), []);
+
+        if !fit_conditions.is_empty() {
+            filters.push(
+                &format!(
+                    " AND NOT EXISTS ({} AND NOT ({}))",
+                    analyzed_parts,
+                    fit_conditions.join(" AND ")
+                ),
+                fit_values.into_iter().map(Value::Real),
+            );
+        }
+
+        if let Some(min_z) = params.min_z {
+            filters.push(
+                " AND (SELECT MAX(ma.size_z) FROM stl_files sf
+                   INNER JOIN mesh_analysis ma ON ma.stl_file_id = sf.id
+                   WHERE sf.project_id = p.id) >= ?",
+                [Value::Real(min_z)],
+            );
+        }
+
+        Ok(filters)
+    }
+
     fn search_fts(
         &self,
         conn: &rusqlite::Connection,
         params: &SearchParams,
+        filters: &Filters,
         offset: usize,
     ) -> Result<(Vec<SearchResultProject>, usize), AppError> {
         let search_query = params.query.as_ref().unwrap();
         // Add wildcard for partial matching
         let fts_query = format!("{}*", search_query);
-        let leaf_filter = if params.leaf_only {
-            "AND p.is_leaf = 1"
-        } else {
-            ""
-        };
 
         // Get total count
         let count_sql = format!(
             "SELECT COUNT(DISTINCT p.id)
              FROM projects p
              INNER JOIN projects_fts fts ON p.id = fts.project_id
-             WHERE projects_fts MATCH ?1 {}",
-            leaf_filter
+             WHERE projects_fts MATCH ? {}",
+            filters.sql
         );
 
-        let total: usize = conn.query_row(&count_sql, [&fts_query], |row| row.get(0))?;
+        let mut count_params: Vec<&dyn ToSql> = vec![&fts_query];
+        count_params.extend(filters.params());
+        let total: usize = conn.query_row(
+            &count_sql,
+            rusqlite::params_from_iter(count_params),
+            |row| row.get(0),
+        )?;
 
         // Get projects
         let per_page_i64 = params.per_page as i64;
         let offset_i64 = offset as i64;
 
         let sql = format!(
-            "SELECT DISTINCT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at,
+            "SELECT DISTINCT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at, p.root_id,
              (SELECT COUNT(*) FROM stl_files WHERE project_id = p.id) as stl_count
              FROM projects p
              INNER JOIN projects_fts fts ON p.id = fts.project_id
-             WHERE projects_fts MATCH ?1 {}
+             WHERE projects_fts MATCH ? {}
              ORDER BY p.name
-             LIMIT ?2 OFFSET ?3",
-            leaf_filter
+             LIMIT ? OFFSET ?",
+            filters.sql
         );
 
         let mut stmt = conn.prepare(&sql)?;
 
+        let mut query_params: Vec<&dyn ToSql> = vec![&fts_query];
+        query_params.extend(filters.params());
+        query_params.push(&per_page_i64);
+        query_params.push(&offset_i64);
         let mut projects = stmt
-            .query_map(
-                rusqlite::params![&fts_query, per_page_i64, offset_i64],
-                |row| {
-                    Ok(SearchResultProject {
-                        project: Project {
-                            id: row.get(0)?,
-                            name: row.get(1)?,
-                            full_path: row.get(2)?,
-                            parent_id: row.get(3)?,
-                            is_leaf: row.get(4)?,
-                            description: row.get(5)?,
-                            folder_level: row.get(6)?,
-                            created_at: row.get(7)?,
-                            updated_at: row.get(8)?,
-                        },
-                        stl_count: row.get(9)?,
-                        image_count: 0,
-                        images: vec![],
-                    })
-                },
-            )?
+            .query_map(rusqlite::params_from_iter(query_params), |row| {
+                Ok(SearchResultProject {
+                    project: Project {
+                        id: row.get(0)?,
+                        name: row.get(1)?,
+                        full_path: row.get(2)?,
+                        parent_id: row.get(3)?,
+                        is_leaf: row.get(4)?,
+                        description: row.get(5)?,
+                        folder_level: row.get(6)?,
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                        root_id: row.get(9)?,
+                    },
+                    stl_count: row.get(10)?,
+                    image_count: 0,
+                    images: vec![],
+                })
+            })?
             .collect::<Result<Vec<_>, _>>()?;
 
         // Update display names based on ignored keywords
@@ -184,6 +345,7 @@ impl SearchService {
         &self,
         conn: &rusqlite::Connection,
         params: &SearchParams,
+        filters: &Filters,
         offset: usize,
     ) -> Result<(Vec<SearchResultProject>, usize), AppError> {
         // For simplicity with multiple tags, we'll filter projects that have ALL specified tags
@@ -194,12 +356,6 @@ impl SearchService {
             .collect::<Vec<_>>()
             .join(", ");
 
-        let leaf_filter = if params.leaf_only {
-            "AND p.is_leaf = 1"
-        } else {
-            ""
-        };
-
         let count_query = format!(
             "SELECT COUNT(*)
              FROM (
@@ -212,11 +368,11 @@ impl SearchService {
                  GROUP BY p.id
                  HAVING COUNT(DISTINCT t.id) = ?
              )",
-            placeholders, leaf_filter
+            placeholders, filters.sql
         );
 
         let query = format!(
-            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at,
+            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at, p.root_id,
              (SELECT COUNT(*) FROM stl_files WHERE project_id = p.id) as stl_count
              FROM projects p
              INNER JOIN project_tags pt ON p.id = pt.project_id
@@ -227,15 +383,16 @@ impl SearchService {
              HAVING COUNT(DISTINCT t.id) = ?
              ORDER BY p.name
              LIMIT ? OFFSET ?",
-            placeholders, leaf_filter
+            placeholders, filters.sql
         );
 
         // Build params for count
         let tag_count = params.tags.len() as i64;
-        let mut count_params: Vec<&dyn rusqlite::ToSql> = Vec::new();
+        let mut count_params: Vec<&dyn ToSql> = Vec::new();
         for tag in &params.tags {
             count_params.push(tag);
         }
+        count_params.extend(filters.params());
         count_params.push(&tag_count);
 
         let mut stmt = conn.prepare(&count_query)?;
@@ -247,10 +404,11 @@ impl SearchService {
         // Build params for query
         let per_page_i64 = params.per_page as i64;
         let offset_i64 = offset as i64;
-        let mut query_params: Vec<&dyn rusqlite::ToSql> = Vec::new();
+        let mut query_params: Vec<&dyn ToSql> = Vec::new();
         for tag in &params.tags {
             query_params.push(tag);
         }
+        query_params.extend(filters.params());
         query_params.push(&tag_count);
         query_params.push(&per_page_i64);
         query_params.push(&offset_i64);
@@ -269,8 +427,9 @@ impl SearchService {
                         folder_level: row.get(6)?,
                         created_at: row.get(7)?,
                         updated_at: row.get(8)?,
+                        root_id: row.get(9)?,
                     },
-                    stl_count: row.get(9)?,
+                    stl_count: row.get(10)?,
                     image_count: 0,
                     images: vec![],
                 })
@@ -289,6 +448,7 @@ impl SearchService {
         &self,
         conn: &rusqlite::Connection,
         params: &SearchParams,
+        filters: &Filters,
         offset: usize,
     ) -> Result<(Vec<SearchResultProject>, usize), AppError> {
         let search_query = params.query.as_ref().unwrap();
@@ -299,12 +459,6 @@ impl SearchService {
             .collect::<Vec<_>>()
             .join(", ");
 
-        let leaf_filter = if params.leaf_only {
-            "AND p.is_leaf = 1"
-        } else {
-            ""
-        };
-
         let count_query = format!(
             "SELECT COUNT(*)
              FROM (
@@ -319,11 +473,11 @@ impl SearchService {
                  GROUP BY p.id
                  HAVING COUNT(DISTINCT t.id) = ?
              )",
-            placeholders, leaf_filter
+            placeholders, filters.sql
         );
 
         let query = format!(
-            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at,
+            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at, p.root_id,
              (SELECT COUNT(*) FROM stl_files WHERE project_id = p.id) as stl_count
              FROM projects p
              INNER JOIN projects_fts fts ON p.id = fts.project_id
@@ -336,15 +490,16 @@ impl SearchService {
              HAVING COUNT(DISTINCT t.id) = ?
              ORDER BY p.name
              LIMIT ? OFFSET ?",
-            placeholders, leaf_filter
+            placeholders, filters.sql
         );
 
         // Build params for count
         let tag_count = params.tags.len() as i64;
-        let mut count_params: Vec<&dyn rusqlite::ToSql> = vec![search_query];
+        let mut count_params: Vec<&dyn ToSql> = vec![search_query];
         for tag in &params.tags {
             count_params.push(tag);
         }
+        count_params.extend(filters.params());
         count_params.push(&tag_count);
 
         let mut stmt = conn.prepare(&count_query)?;
@@ -356,10 +511,11 @@ impl SearchService {
         // Build params for query
         let per_page_i64 = params.per_page as i64;
         let offset_i64 = offset as i64;
-        let mut query_params: Vec<&dyn rusqlite::ToSql> = vec![search_query];
+        let mut query_params: Vec<&dyn ToSql> = vec![search_query];
         for tag in &params.tags {
             query_params.push(tag);
         }
+        query_params.extend(filters.params());
         query_params.push(&tag_count);
         query_params.push(&per_page_i64);
         query_params.push(&offset_i64);
@@ -378,8 +534,9 @@ impl SearchService {
                         folder_level: row.get(6)?,
                         created_at: row.get(7)?,
                         updated_at: row.get(8)?,
+                        root_id: row.get(9)?,
                     },
-                    stl_count: row.get(9)?,
+                    stl_count: row.get(10)?,
                     image_count: 0,
                     images: vec![],
                 })
@@ -398,26 +555,28 @@ impl SearchService {
         &self,
         conn: &rusqlite::Connection,
         params: &SearchParams,
+        filters: &Filters,
         offset: usize,
     ) -> Result<(Vec<SearchResultProject>, usize), AppError> {
-        let leaf_filter = if params.leaf_only {
-            "WHERE is_leaf = 1"
-        } else {
-            ""
-        };
-
-        let count_sql = format!("SELECT COUNT(*) FROM projects {}", leaf_filter);
+        let count_sql = format!(
+            "SELECT COUNT(*) FROM projects p WHERE 1 = 1 {}",
+            filters.sql
+        );
 
-        let total: usize = conn.query_row(&count_sql, [], |row| row.get(0))?;
+        let total: usize = conn.query_row(
+            &count_sql,
+            rusqlite::params_from_iter(filters.params()),
+            |row| row.get(0),
+        )?;
 
         let sql = format!(
-            "SELECT id, name, full_path, parent_id, is_leaf, description, folder_level, created_at, updated_at,
-             (SELECT COUNT(*) FROM stl_files WHERE project_id = projects.id) as stl_count
-             FROM projects
-             {}
-             ORDER BY name
-             LIMIT ?1 OFFSET ?2",
-            leaf_filter
+            "SELECT p.id, p.name, p.full_path, p.parent_id, p.is_leaf, p.description, p.folder_level, p.created_at, p.updated_at, p.root_id,
+             (SELECT COUNT(*) FROM stl_files WHERE project_id = p.id) as stl_count
+             FROM projects p
+             WHERE 1 = 1 {}
+             ORDER BY p.name
+             LIMIT ? OFFSET ?",
+            filters.sql
         );
 
         let mut stmt = conn.prepare(&sql)?;
@@ -425,8 +584,11 @@ impl SearchService {
         let per_page_i64 = params.per_page as i64;
         let offset_i64 = offset as i64;
 
+        let mut query_params: Vec<&dyn ToSql> = filters.params().collect();
+        query_params.push(&per_page_i64);
+        query_params.push(&offset_i64);
         let mut projects = stmt
-            .query_map([per_page_i64, offset_i64], |row| {
+            .query_map(rusqlite::params_from_iter(query_params), |row| {
                 Ok(SearchResultProject {
                     project: Project {
                         id: row.get(0)?,
@@ -438,8 +600,9 @@ impl SearchService {
                         folder_level: row.get(6)?,
                         created_at: row.get(7)?,
                         updated_at: row.get(8)?,
+                        root_id: row.get(9)?,
                     },
-                    stl_count: row.get(9)?,
+                    stl_count: row.get(10)?,
                     image_count: 0,
                     images: vec![],
                 })
@@ -0,0 +1,503 @@
+//! Per-project sidecar files.
+//!
+//! A project folder may hold a `glyptotheka.json` (or `.yaml` / `.yml`) file with its
+//! description, tags, author, license, source URL, release date, scale and print
+//! settings, and a README whose text is used as the description when the metadata file
+//! has none.
+//! Sidecar files are imported again whenever one of them changes. With `write_sidecars`
+//! enabled, tags and metadata edited through the API are written back to the metadata
+//! file, so they survive a clean scan or a lost database.
+
+use crate::config::ConfigService;
+use crate::db::connection::DbPool;
+use crate::db::repositories::project_repo::ProjectRepository;
+use crate::models::project::ProjectMetadata;
+use crate::utils::archive;
+use crate::utils::error::AppError;
+use crate::utils::fs::file_signature;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Deserializer};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Metadata file names, in order of preference
+pub const METADATA_FILE_NAMES: [&str; 3] =
+    ["glyptotheka.json", "glyptotheka.yaml", "glyptotheka.yml"];
+
+/// README file names (matched case-insensitively), in order of preference
+const README_FILE_NAMES: [&str; 4] = ["readme.md", "readme.markdown", "readme.txt", "readme"];
+
+/// Longest README imported as a description
+const MAX_README_BYTES: u64 = 64 * 1024;
+
+/// Contents of a `glyptotheka.json` / `.yaml` file. Unknown keys are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SidecarMetadata {
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(alias = "designer")]
+    pub author: Option<String>,
+    pub license: Option<String>,
+    #[serde(alias = "source", alias = "url")]
+    pub source_url: Option<String>,
+    /// `YYYY-MM-DD`
+    pub release_date: Option<String>,
+    /// Written as text ("32mm", "1:10") or as a number
+    #[serde(deserialize_with = "scale_text")]
+    pub scale: Option<String>,
+    pub print_settings: Option<serde_json::Value>,
+}
+
+impl SidecarMetadata {
+    /// Parse a metadata file, as YAML unless it is named `.json`
+    pub fn parse(file_name: &str, contents: &str) -> Result<Self, String> {
+        if file_name.to_lowercase().ends_with(".json") {
+            serde_json::from_str(contents).map_err(|e| e.to_string())
+        } else if contents.trim().is_empty() {
+            Ok(Self::default())
+        } else {
+            serde_yaml::from_str(contents).map_err(|e| e.to_string())
+        }
+    }
+
+    fn project_metadata(&self) -> ProjectMetadata {
+        ProjectMetadata {
+            author: non_empty(&self.author),
+            license: non_empty(&self.license),
+            source_url: non_empty(&self.source_url),
+            release_date: non_empty(&self.release_date),
+            scale: non_empty(&self.scale),
+            print_settings: self.print_settings.clone().filter(|s| !s.is_null()),
+        }
+    }
+}
+
+fn scale_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(scale)) => Some(scale),
+            Some(serde_json::Value::Null) | None => None,
+            Some(scale) => Some(scale.to_string()),
+        },
+    )
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Sidecar files found in a project folder
+#[derive(Debug, Default)]
+struct SidecarFiles {
+    metadata: Option<PathBuf>,
+    readme: Option<PathBuf>,
+}
+
+impl SidecarFiles {
+    /// Files directly inside `folder`, on disk or inside an archive
+    fn find(folder: &Path) -> Self {
+        let mut files: Vec<PathBuf> = match std::fs::read_dir(folder) {
+            Ok(entries) => entries
+                .flatten()
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+                .map(|entry| entry.path())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        files.extend(
+            archive::folder_entries(folder)
+                .into_iter()
+                .map(|entry| entry.path),
+        );
+
+        let find = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                files
+                    .iter()
+                    .find(|file| {
+                        file.file_name()
+                            .and_then(|n| n.to_str())
+                            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+                    })
+                    .cloned()
+            })
+        };
+
+        Self {
+            metadata: find(&METADATA_FILE_NAMES),
+            readme: find(&README_FILE_NAMES),
+        }
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.metadata.iter().chain(self.readme.iter())
+    }
+
+    /// Names, sizes and modification times of the files, to detect changes
+    fn signature(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .paths()
+            .map(|path| {
+                let (size, mtime) = file_signature(path).unwrap_or((0, 0));
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                format!("{}:{}:{}", name, size, mtime)
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(";"))
+    }
+}
+
+#[derive(Clone)]
+pub struct SidecarService {
+    pool: DbPool,
+}
+
+impl SidecarService {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Import the sidecar files of a project folder if they changed since the last
+    /// import. When they are removed, the imported metadata is removed as well.
+    /// Returns whether the project was updated.
+    pub fn import_if_changed(&self, project_id: i64, folder: &Path) -> Result<bool, AppError> {
+        let files = SidecarFiles::find(folder);
+        let signature = files.signature();
+
+        let stored: Option<Option<String>> = {
+            let conn = self.pool.get()?;
+            conn.query_row(
+                "SELECT sidecar_signature FROM project_metadata WHERE project_id = ?1",
+                params![project_id],
+                |row| row.get(0),
+            )
+            .optional()?
+        };
+        if stored.clone().flatten() == signature && (stored.is_some() || signature.is_none()) {
+            return Ok(false);
+        }
+
+        match signature {
+            Some(signature) => {
+                let (metadata, description) = Self::read(&files)?;
+                debug!("Importing sidecar metadata for {}", folder.display());
+                self.store(project_id, &metadata, description.as_deref(), &signature)?;
+            }
+            None => self.clear(project_id)?,
+        }
+        Ok(true)
+    }
+
+    /// Write the project's description, tags and metadata to its metadata file, if
+    /// `write_sidecars` is enabled. Keys the file holds for other tools are kept.
+    /// Returns whether the file was written.
+    pub fn write_back(&self, project_id: i64) -> Result<bool, AppError> {
+        if !ConfigService::new(self.pool.clone())
+            .get_config()?
+            .write_sidecars
+        {
+            return Ok(false);
+        }
+
+        let project_repo = ProjectRepository::new(self.pool.clone());
+        let project = project_repo
+            .get_by_id(project_id)?
+            .ok_or_else(|| AppError::NotFound(format!("Project {} not found", project_id)))?;
+        let folder = PathBuf::from(&project.full_path);
+        if archive::split_path(&folder).is_some() || archive::is_archive(&folder) {
+            return Err(AppError::ValidationError(format!(
+                "Cannot write metadata into archive {}",
+                folder.display()
+            )));
+        }
+
+        let files = SidecarFiles::find(&folder);
+        let path = files
+            .metadata
+            .clone()
+            .unwrap_or_else(|| folder.join(METADATA_FILE_NAMES[0]));
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+        // Start from the current file so unknown keys survive; a file that does not
+        // parse is left alone rather than overwritten
+        let mut document = match &files.metadata {
+            Some(path) => {
+                let contents = read_text(path, None)?;
+                let parsed: Result<serde_json::Value, String> = if is_json {
+                    serde_json::from_str(&contents).map_err(|e| e.to_string())
+                } else if contents.trim().is_empty() {
+                    Ok(serde_json::Value::Null)
+                } else {
+                    serde_yaml::from_str(&contents).map_err(|e| e.to_string())
+                };
+                parsed.map_err(|e| {
+                    AppError::ValidationError(format!(
+                        "Invalid metadata file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            }
+            None => serde_json::Value::Null,
+        };
+        if !document.is_object() {
+            document = serde_json::json!({});
+        }
+        let fields = document.as_object_mut().expect("document is an object");
+
+        // A description read from the README stays there
+        let readme = match &files.readme {
+            Some(path) => non_empty(&Some(read_text(path, Some(MAX_README_BYTES))?)),
+            None => None,
+        };
+        let description = non_empty(&project.description)
+            .filter(|d| fields.contains_key("description") || Some(d) != readme.as_ref());
+
+        let conn = self.pool.get()?;
+        let tags: Vec<String> = conn
+            .prepare(
+                "SELECT t.name FROM tags t
+                 INNER JOIN project_tags pt ON t.id = pt.tag_id
+                 WHERE pt.project_id = ?1
+                 ORDER BY t.name",
+            )?
+            .query_map(params![project_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        drop(conn);
+        let metadata = project_repo.get_metadata(project_id)?.unwrap_or_default();
+
+        // Aliases are replaced by the canonical keys
+        for alias in ["designer", "source", "url"] {
+            fields.remove(alias);
+        }
+        let mut set = |key: &str, value: Option<serde_json::Value>| match value {
+            Some(value) => {
+                fields.insert(key.to_string(), value);
+            }
+            None => {
+                fields.remove(key);
+            }
+        };
+        set("description", description.map(Into::into));
+        set("tags", (!tags.is_empty()).then(|| tags.into()));
+        set("author", metadata.author.clone().map(Into::into));
+        set("license", metadata.license.clone().map(Into::into));
+        set("source_url", metadata.source_url.clone().map(Into::into));
+        set(
+            "release_date",
+            metadata.release_date.clone().map(Into::into),
+        );
+        set("scale", metadata.scale.clone().map(Into::into));
+        set("print_settings", metadata.print_settings.clone());
+
+        let contents = if is_json {
+            serde_json::to_string_pretty(&document)
+                .map(|json| json + "\n")
+                .map_err(|e| AppError::InternalServer(e.to_string()))?
+        } else {
+            serde_yaml::to_string(&document).map_err(|e| AppError::InternalServer(e.to_string()))?
+        };
+
+        // Write next to the file and rename, so a scan never reads half a file
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = folder.join(format!(".{}.tmp", name));
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, &path)?;
+        debug!("Wrote sidecar metadata to {}", path.display());
+
+        // What was written is now imported: record the new signature so the next scan
+        // does not import it again, and let the file own the project's tags
+        let signature = SidecarFiles::find(&folder).signature().unwrap_or_default();
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO project_metadata (project_id, sidecar_signature, updated_at)
+             VALUES (?1, ?2, ?3)
+             ON CONFLICT(project_id) DO UPDATE
+             SET sidecar_signature = excluded.sidecar_signature, updated_at = excluded.updated_at",
+            params![project_id, signature, now],
+        )?;
+        tx.execute(
+            "UPDATE project_tags SET from_sidecar = 1 WHERE project_id = ?1",
+            params![project_id],
+        )?;
+        tx.commit()?;
+
+        Ok(true)
+    }
+
+    /// Parse the metadata file and pick the description
+    fn read(files: &SidecarFiles) -> Result<(SidecarMetadata, Option<String>), AppError> {
+        let metadata = match &files.metadata {
+            Some(path) => {
+                let contents = read_text(path, None)?;
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                SidecarMetadata::parse(&name, &contents).map_err(|e| {
+                    AppError::ValidationError(format!(
+                        "Invalid metadata file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+            }
+            None => SidecarMetadata::default(),
+        };
+
+        let description = match non_empty(&metadata.description) {
+            Some(description) => Some(description),
+            None => match &files.readme {
+                Some(path) => non_empty(&Some(read_text(path, Some(MAX_README_BYTES))?)),
+                None => None,
+            },
+        };
+
+        Ok((metadata, description))
+    }
+
+    fn store(
+        &self,
+        project_id: i64,
+        metadata: &SidecarMetadata,
+        description: Option<&str>,
+        signature: &str,
+    ) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+        let project_metadata = metadata.project_metadata();
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE projects SET description = ?1, updated_at = ?2 WHERE id = ?3",
+            params![description, now, project_id],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO project_metadata
+                 (project_id, author, license, source_url, release_date, scale, print_settings,
+                  sidecar_signature, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                project_id,
+                project_metadata.author,
+                project_metadata.license,
+                project_metadata.source_url,
+                project_metadata.release_date,
+                project_metadata.scale,
+                project_metadata
+                    .print_settings
+                    .as_ref()
+                    .map(|settings| settings.to_string()),
+                signature,
+                now
+            ],
+        )?;
+
+        // Tags added by hand stay; imported ones follow the file
+        tx.execute(
+            "DELETE FROM project_tags WHERE project_id = ?1 AND from_sidecar = 1",
+            params![project_id],
+        )?;
+        for name in &metadata.tags {
+            let name = name.trim();
+            if name.is_empty() || name.chars().count() > 50 {
+                continue;
+            }
+            tx.execute(
+                "INSERT OR IGNORE INTO tags (name, created_at, usage_count) VALUES (?1, ?2, 0)",
+                params![name, now],
+            )?;
+            tx.execute(
+                "INSERT OR IGNORE INTO project_tags (project_id, tag_id, created_at, from_sidecar)
+                 SELECT ?1, id, ?3, 1 FROM tags WHERE name = ?2 COLLATE NOCASE",
+                params![project_id, name, now],
+            )?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn clear(&self, project_id: i64) -> Result<(), AppError> {
+        let mut conn = self.pool.get()?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs() as i64;
+
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE projects SET description = NULL, updated_at = ?1 WHERE id = ?2",
+            params![now, project_id],
+        )?;
+        tx.execute(
+            "DELETE FROM project_metadata WHERE project_id = ?1",
+            params![project_id],
+        )?;
+        tx.execute(
+            "DELETE FROM project_tags WHERE project_id = ?1 AND from_sidecar = 1",
+            params![project_id],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+/// Read a text file on disk or inside an archive, up to `limit` bytes
+fn read_text(path: &Path, limit: Option<u64>) -> Result<String, AppError> {
+    archive::read_file(path, |file, size| {
+        let mut bytes = Vec::new();
+        file.take(limit.unwrap_or(size)).read_to_end(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sidecar_metadata() {
+        let json = r#"{
+            "description": "A knight",
+            "tags": ["fantasy", "hero"],
+            "designer": "Cast'N'Play",
+            "url": "https://example.com/knight",
+            "scale": 32,
+            "print_settings": { "layer_height": 0.05 },
+            "unknown": true
+        }"#;
+        let metadata = SidecarMetadata::parse("glyptotheka.json", json).unwrap();
+        assert_eq!(metadata.description.as_deref(), Some("A knight"));
+        assert_eq!(metadata.tags, vec!["fantasy", "hero"]);
+        assert_eq!(metadata.author.as_deref(), Some("Cast'N'Play"));
+        assert_eq!(
+            metadata.source_url.as_deref(),
+            Some("https://example.com/knight")
+        );
+        assert_eq!(metadata.scale.as_deref(), Some("32"));
+
+        let yaml = "license: CC-BY-NC\nscale: 1:10\ntags:\n  - bust\n";
+        let metadata = SidecarMetadata::parse("glyptotheka.yaml", yaml).unwrap();
+        assert_eq!(metadata.license.as_deref(), Some("CC-BY-NC"));
+        assert_eq!(metadata.scale.as_deref(), Some("1:10"));
+        assert_eq!(metadata.tags, vec!["bust"]);
+
+        assert!(SidecarMetadata::parse("glyptotheka.json", "{").is_err());
+        assert_eq!(
+            SidecarMetadata::parse("glyptotheka.yml", "").unwrap(),
+            SidecarMetadata::default()
+        );
+    }
+}
@@ -1,7 +1,12 @@
 use crate::db::connection::DbPool;
+use crate::models::stl_file::ModelFormat;
 use crate::services::image_cache::ImageCacheService;
+use crate::services::mesh::{Mesh, MAX_MESH_FILE_SIZE};
+use crate::utils::archive;
 use crate::utils::error::AppError;
+use crate::utils::fs::file_mtime;
 use rusqlite::params;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use stl_thumb::config::Config as StlConfig;
@@ -9,6 +14,12 @@ use tokio::sync::{mpsc, Semaphore};
 use tokio::time::{timeout, Duration};
 use tracing::{info, warn};
 
+/// Location of the embedded preview image inside a 3MF package
+const THREE_MF_THUMBNAIL_PATH: &str = "Metadata/thumbnail.png";
+
+/// Largest embedded 3MF thumbnail used; larger ones are ignored and the model rendered
+const MAX_THUMBNAIL_BYTES: u64 = 16 * 1024 * 1024;
+
 // T009: PreviewResult struct
 #[derive(Debug)]
 pub enum PreviewResult {
@@ -58,7 +69,7 @@ impl StlPreviewService {
 
         // T008: Validate file size (100MB limit)
         let stl_path_buf = PathBuf::from(stl_path);
-        if !stl_path_buf.exists() {
+        if !archive::exists(&stl_path_buf) {
             warn!("STL file not found: {}", stl_path);
             return Err(AppError::NotFound(format!(
                 "STL file not found: {}",
@@ -66,19 +77,6 @@ impl StlPreviewService {
             )));
         }
 
-        let metadata = std::fs::metadata(&stl_path_buf)?;
-        let file_size = metadata.len();
-        if file_size > 100 * 1024 * 1024 {
-            warn!(
-                "Skipping STL file (>100MB): {} ({}MB)",
-                stl_path,
-                file_size / (1024 * 1024)
-            );
-            return Ok(PreviewResult::Skipped(
-                "File too large (>100MB)".to_string(),
-            ));
-        }
-
         // T050: Basic disk space check (ensure at least 100MB free)
         // Note: Full implementation would check actual free space
         // For now, we rely on the cache directory being writable
@@ -91,31 +89,83 @@ impl StlPreviewService {
             }
         }
 
-        // Generate new preview with timeout
-        let preview_data = match timeout(
-            Duration::from_secs(30), // T011: 30 second timeout
-            self.render_stl_preview(&stl_path_buf),
-        )
-        .await
-        {
-            Ok(Ok(data)) => data,
-            Ok(Err(e)) => {
-                // T012, T048, T049: Graceful error handling with detailed logging
-                warn!(
-                    "Failed to render STL preview for {}: {} (possibly corrupted STL file)",
-                    stl_path, e
-                );
-                return Err(e);
+        // Files inside an archive are streamed out to a temporary copy for rendering
+        let extracted = match archive::split_path(&stl_path_buf) {
+            Some(_) => {
+                let virtual_path = stl_path_buf.clone();
+                let extracted =
+                    tokio::task::spawn_blocking(move || ExtractedFile::extract(&virtual_path))
+                        .await
+                        .map_err(|e| {
+                            AppError::InternalServer(format!("Extraction task failed: {}", e))
+                        })??;
+                match extracted {
+                    Some(file) => Some(file),
+                    None => {
+                        warn!("Skipping archived model file (>100MB): {}", stl_path);
+                        return Ok(PreviewResult::Skipped(
+                            "File too large (>100MB)".to_string(),
+                        ));
+                    }
+                }
             }
-            Err(_) => {
+            None => None,
+        };
+        let model_path = extracted
+            .as_ref()
+            .map_or(stl_path_buf.as_path(), |file| file.path.as_path());
+
+        // 3MF files usually ship their own thumbnail; use it instead of rendering
+        let embedded_thumbnail = if ModelFormat::from_path(model_path) == Some(ModelFormat::ThreeMf)
+        {
+            Self::extract_3mf_thumbnail(model_path)
+        } else {
+            None
+        };
+
+        let preview_data = if let Some(data) = embedded_thumbnail {
+            info!("Using embedded 3MF thumbnail for {}", stl_path);
+            data
+        } else {
+            let metadata = std::fs::metadata(model_path)?;
+            let file_size = metadata.len();
+            if file_size > MAX_MESH_FILE_SIZE {
                 warn!(
-                    "STL preview generation timed out after 30s for {}",
-                    stl_path
+                    "Skipping STL file (>100MB): {} ({}MB)",
+                    stl_path,
+                    file_size / (1024 * 1024)
                 );
-                return Err(AppError::InternalServer(
-                    "Preview generation timed out".to_string(),
+                return Ok(PreviewResult::Skipped(
+                    "File too large (>100MB)".to_string(),
                 ));
             }
+
+            // Generate new preview with timeout
+            match timeout(
+                Duration::from_secs(30), // T011: 30 second timeout
+                self.render_stl_preview(model_path),
+            )
+            .await
+            {
+                Ok(Ok(data)) => data,
+                Ok(Err(e)) => {
+                    // T012, T048, T049: Graceful error handling with detailed logging
+                    warn!(
+                        "Failed to render STL preview for {}: {} (possibly corrupted STL file)",
+                        stl_path, e
+                    );
+                    return Err(e);
+                }
+                Err(_) => {
+                    warn!(
+                        "STL preview generation timed out after 30s for {}",
+                        stl_path
+                    );
+                    return Err(AppError::InternalServer(
+                        "Preview generation timed out".to_string(),
+                    ));
+                }
+            }
         };
 
         // Cache the preview
@@ -130,17 +180,11 @@ impl StlPreviewService {
 
     // T006: Check if preview is valid (mtime comparison)
     pub async fn is_preview_valid(&self, stl_path: &str) -> Result<bool, AppError> {
-        // Get STL file modification time
-        let stl_path_buf = PathBuf::from(stl_path);
-        if !stl_path_buf.exists() {
-            return Ok(false);
-        }
-
-        let stl_metadata = std::fs::metadata(&stl_path_buf)?;
-        let stl_mtime = stl_metadata
-            .modified()?
-            .duration_since(std::time::UNIX_EPOCH)?
-            .as_secs() as i64;
+        // Get STL file modification time (that of the archive for files inside one)
+        let stl_mtime = match file_mtime(Path::new(stl_path)) {
+            Some(mtime) => mtime,
+            None => return Ok(false),
+        };
 
         // Get preview timestamp from database
         if let Some(preview_timestamp) = self.get_preview_timestamp(stl_path)? {
@@ -178,7 +222,60 @@ impl StlPreviewService {
         }
     }
 
-    /// Render STL file to PNG using stl-thumb library
+    /// Read the thumbnail embedded in a 3MF package (`Metadata/thumbnail.png`).
+    /// Returns None if the archive cannot be opened or has no thumbnail.
+    fn extract_3mf_thumbnail(model_path: &Path) -> Option<Vec<u8>> {
+        let file = match std::fs::File::open(model_path) {
+            Ok(f) => f,
+            Err(e) => {
+                warn!("Failed to open 3MF file {}: {}", model_path.display(), e);
+                return None;
+            }
+        };
+
+        let mut archive = match zip::ZipArchive::new(file) {
+            Ok(a) => a,
+            Err(e) => {
+                warn!("Failed to read 3MF package {}: {}", model_path.display(), e);
+                return None;
+            }
+        };
+
+        // Entry names are case-sensitive in ZIP, but exporters disagree on casing
+        let entry_name = archive
+            .file_names()
+            .find(|name| {
+                name.trim_start_matches('/')
+                    .eq_ignore_ascii_case(THREE_MF_THUMBNAIL_PATH)
+            })?
+            .to_string();
+
+        let entry = archive.by_name(&entry_name).ok()?;
+        // The size in the ZIP header is only a claim; read at most one byte past the limit
+        let mut data = Vec::with_capacity(entry.size().min(MAX_THUMBNAIL_BYTES) as usize);
+        if let Err(e) = entry.take(MAX_THUMBNAIL_BYTES + 1).read_to_end(&mut data) {
+            warn!(
+                "Failed to read 3MF thumbnail from {}: {}",
+                model_path.display(),
+                e
+            );
+            return None;
+        }
+        if data.len() as u64 > MAX_THUMBNAIL_BYTES {
+            warn!(
+                "Ignoring 3MF thumbnail larger than {}MB in {}",
+                MAX_THUMBNAIL_BYTES / (1024 * 1024),
+                model_path.display()
+            );
+            return None;
+        }
+
+        Some(data)
+    }
+
+    /// Render a model file to PNG using stl-thumb library.
+    /// OBJ and PLY meshes are loaded by our own parser and converted to a
+    /// temporary binary STL first, so every format goes through the same renderer.
     async fn render_stl_preview(&self, stl_path: &Path) -> Result<Vec<u8>, AppError> {
         let stl_path = stl_path.to_path_buf();
         let stl_path_str = stl_path.to_string_lossy().to_string();
@@ -197,19 +294,34 @@ impl StlPreviewService {
             let result = (|| {
                 // Generate temporary output path
                 let temp_dir = std::env::temp_dir();
-                let temp_filename = format!(
-                    "stl_preview_{}_{}.png",
+                let temp_id = format!(
+                    "{}_{}",
                     std::process::id(),
                     std::time::SystemTime::now()
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap()
-                        .as_millis()
+                        .as_nanos()
                 );
-                let output_path = temp_dir.join(temp_filename);
+                let output_path = temp_dir.join(format!("stl_preview_{}.png", temp_id));
+
+                // Convert meshes stl-thumb can't read into a temporary STL
+                let converted_path = match ModelFormat::from_path(&stl_path) {
+                    Some(ModelFormat::Obj) | Some(ModelFormat::Ply) => {
+                        let converted = temp_dir.join(format!("mesh_preview_{}.stl", temp_id));
+                        Mesh::load(&stl_path)
+                            .and_then(|mesh| mesh.write_binary_stl(&converted))
+                            .map_err(|e| format!("Mesh conversion failed: {}", e))?;
+                        Some(converted)
+                    }
+                    _ => None,
+                };
 
                 // Configure stl-thumb to render at 512x512
                 let config = StlConfig {
-                    model_filename: stl_path_str.clone(),
+                    model_filename: converted_path
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| stl_path_str.clone()),
                     img_filename: output_path.to_string_lossy().to_string(),
                     width: 512,
                     height: 512,
@@ -219,8 +331,13 @@ impl StlPreviewService {
                 };
 
                 // Render directly to file
-                stl_thumb::render_to_file(&config)
-                    .map_err(|e| format!("STL rendering failed: {}", e))?;
+                let rendered = stl_thumb::render_to_file(&config)
+                    .map_err(|e| format!("STL rendering failed: {}", e));
+
+                if let Some(converted) = &converted_path {
+                    let _ = std::fs::remove_file(converted);
+                }
+                rendered?;
 
                 // Read the generated file
                 let data = std::fs::read(&output_path)
@@ -272,6 +389,56 @@ impl StlPreviewService {
     }
 }
 
+/// Temporary copy of a file stored inside an archive, removed when dropped
+struct ExtractedFile {
+    path: PathBuf,
+}
+
+impl ExtractedFile {
+    /// Copy an archive member to a temporary file. Returns None without extracting
+    /// anything when the archive lists the member as larger than `MAX_MESH_FILE_SIZE`.
+    fn extract(virtual_path: &Path) -> Result<Option<Self>, AppError> {
+        if archive::member_size(virtual_path)? > MAX_MESH_FILE_SIZE {
+            return Ok(None);
+        }
+
+        // Keep the extension so the renderer recognises the format
+        let extension = virtual_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("stl");
+        let path = std::env::temp_dir().join(format!(
+            "archive_member_{}_{}.{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_nanos(),
+            extension
+        ));
+
+        let file = Self { path };
+        archive::read_file(virtual_path, |reader, _| {
+            let mut output = std::fs::File::create(&file.path)?;
+            // The size in the archive's index is not checked against the data itself
+            let copied = std::io::copy(&mut reader.take(MAX_MESH_FILE_SIZE + 1), &mut output)?;
+            if copied > MAX_MESH_FILE_SIZE {
+                return Err(AppError::ValidationError(format!(
+                    "{} is larger than its archive lists it",
+                    virtual_path.display()
+                )));
+            }
+            Ok(())
+        })?;
+        Ok(Some(file))
+    }
+}
+
+impl Drop for ExtractedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 /// Background job queue for preview generation
 pub struct PreviewQueue {
     pub sender: mpsc::Sender<String>,
@@ -0,0 +1,519 @@
+//! Read-only access to files stored inside ZIP and 7z archives.
+//!
+//! Files inside an archive are indexed under a virtual path: the path of the archive
+//! followed by the path of the member, e.g. `/library/Release.zip/Heroes/knight.stl`.
+//! Nothing is extracted to the library; members are read straight from the archive.
+//!
+//! The index of an archive is read once and kept while the archive is unchanged, so
+//! listing and reading the members of an archive during a scan does not parse it
+//! again for every member. RAR archives cannot be read, as no RAR decoder is
+//! available; scans report them instead of indexing them.
+
+use crate::utils::error::AppError;
+use crate::utils::sevenz::SevenZArchive;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+use zip::ZipArchive;
+
+/// Extensions of the archives the scanner looks into
+const ARCHIVE_EXTENSIONS: [&str; 2] = ["zip", "7z"];
+
+/// Extensions of archives that cannot be read
+const UNSUPPORTED_ARCHIVE_EXTENSIONS: [&str; 1] = ["rar"];
+
+/// Archives whose index is kept open at a time
+const MAX_OPEN_ARCHIVES: usize = 64;
+
+/// A file stored in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Virtual path: the archive path joined with the member path
+    pub path: PathBuf,
+    /// Uncompressed size in bytes
+    pub size: u64,
+}
+
+/// Whether a file is an archive the scanner indexes, judged by its extension
+pub fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ARCHIVE_EXTENSIONS
+            .iter()
+            .any(|e| ext.eq_ignore_ascii_case(e))
+    })
+}
+
+/// Whether a file is an archive the scanner cannot look into, judged by its extension
+pub fn is_unsupported_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        UNSUPPORTED_ARCHIVE_EXTENSIONS
+            .iter()
+            .any(|e| ext.eq_ignore_ascii_case(e))
+    })
+}
+
+/// Error reported for an archive the scanner cannot look into
+pub fn unsupported_message(path: &Path) -> String {
+    format!(
+        "Cannot index {}: RAR archives are not supported",
+        path.display()
+    )
+}
+
+/// Split a virtual path into the archive file holding it and the member path inside it.
+/// Returns None for paths that do not lie inside an archive.
+pub fn split_path(path: &Path) -> Option<(&Path, &Path)> {
+    let archive = path
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor) && ancestor.is_file())?;
+    Some((archive, path.strip_prefix(archive).ok()?))
+}
+
+/// Files stored in an archive. Folders, macOS resource forks and member names
+/// that would escape the archive (absolute paths, `..`) are skipped.
+pub fn list_entries(archive: &Path) -> Result<Vec<ArchiveEntry>, AppError> {
+    Ok(index(archive)?.entries.clone())
+}
+
+/// Files directly inside `folder`, which is an archive or a folder within one.
+/// Returns nothing for folders on disk.
+pub fn folder_entries(folder: &Path) -> Vec<ArchiveEntry> {
+    let archive = if is_archive(folder) && folder.is_file() {
+        folder
+    } else {
+        match split_path(folder) {
+            Some((archive, _)) => archive,
+            None => return Vec::new(),
+        }
+    };
+
+    match list_entries(archive) {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|entry| entry.path.parent() == Some(folder))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Whether a path exists on disk or as a file or folder inside an archive
+pub fn exists(path: &Path) -> bool {
+    if path.exists() {
+        return true;
+    }
+
+    match split_path(path) {
+        Some((archive, _)) => index(archive)
+            .map(|index| {
+                index
+                    .entries
+                    .iter()
+                    .any(|entry| entry.path.starts_with(path))
+            })
+            .unwrap_or(false),
+        None => false,
+    }
+}
+
+/// Uncompressed size of an archive member, given its virtual path, as recorded in
+/// the archive's index; nothing is decompressed
+pub fn member_size(path: &Path) -> Result<u64, AppError> {
+    let (archive, member) = split_path(path)
+        .ok_or_else(|| AppError::NotFound(format!("Not in an archive: {}", path.display())))?;
+    let (_, size) = index(archive)?
+        .member(member)
+        .ok_or_else(|| not_found(path))?;
+    Ok(size)
+}
+
+/// Run `read` on the contents of a file, along with its size. Files inside an
+/// archive are decompressed on the fly; anything else is read from disk.
+pub fn read_file<T>(
+    path: &Path,
+    read: impl FnOnce(&mut dyn Read, u64) -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    let (archive, member) = match split_path(path) {
+        Some(split) => split,
+        None => {
+            let mut file = File::open(path)?;
+            let size = file.metadata()?.len();
+            return read(&mut file, size);
+        }
+    };
+
+    let index = index(archive)?;
+    let (member_index, size) = index.member(member).ok_or_else(|| not_found(path))?;
+    match index.reader {
+        ArchiveReader::Zip(ref zip) => {
+            // Clones share the parsed central directory
+            let mut zip = zip.clone();
+            let mut entry = zip
+                .by_index(member_index)
+                .map_err(|e| archive_error(archive, e))?;
+            let size = entry.size();
+            read(&mut entry, size)
+        }
+        ArchiveReader::SevenZ(ref sevenz) => {
+            let mut entry = sevenz
+                .entry_reader(member_index)
+                .map_err(|e| archive_error(archive, e))?;
+            read(&mut entry, size)
+        }
+    }
+}
+
+/// The parsed index of an archive, from which members are read
+enum ArchiveReader {
+    Zip(ZipArchive<SharedFile>),
+    SevenZ(SevenZArchive<SharedFile>),
+}
+
+/// The index of an archive, valid while the archive keeps its size and modification time
+struct ArchiveIndex {
+    signature: (u64, Option<SystemTime>),
+    reader: ArchiveReader,
+    entries: Vec<ArchiveEntry>,
+    /// Index in the archive and uncompressed size of each member, by member path
+    members: HashMap<PathBuf, (usize, u64)>,
+}
+
+impl ArchiveIndex {
+    fn read(archive: &Path, signature: (u64, Option<SystemTime>)) -> Result<Self, AppError> {
+        let file = SharedFile::new(File::open(archive)?);
+        let is_sevenz = archive
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("7z"));
+
+        // Path, index and uncompressed size of each file in the archive
+        let mut files = Vec::new();
+        let reader = if is_sevenz {
+            let sevenz = SevenZArchive::new(file).map_err(|e| archive_error(archive, e))?;
+            for (index, entry) in sevenz.entries().iter().enumerate() {
+                if !entry.is_dir {
+                    files.push((enclosed_name(&entry.name), index, entry.size));
+                }
+            }
+            ArchiveReader::SevenZ(sevenz)
+        } else {
+            let mut zip = ZipArchive::new(file).map_err(|e| archive_error(archive, e))?;
+            for index in 0..zip.len() {
+                let entry = zip
+                    .by_index_raw(index)
+                    .map_err(|e| archive_error(archive, e))?;
+                if !entry.is_dir() {
+                    files.push((entry.enclosed_name(), index, entry.size()));
+                }
+            }
+            ArchiveReader::Zip(zip)
+        };
+
+        let mut entries = Vec::new();
+        let mut members = HashMap::new();
+        for (member, index, size) in files {
+            let member = match member {
+                Some(member) if !is_resource_fork(&member) => member,
+                _ => continue,
+            };
+
+            entries.push(ArchiveEntry {
+                path: archive.join(&member),
+                size,
+            });
+            members.entry(member).or_insert((index, size));
+        }
+
+        Ok(Self {
+            signature,
+            reader,
+            entries,
+            members,
+        })
+    }
+
+    fn member(&self, member: &Path) -> Option<(usize, u64)> {
+        self.members.get(member).copied()
+    }
+}
+
+/// Indexes of recently read archives, with the use count at which each was last used
+#[derive(Default)]
+struct IndexCache {
+    indexes: HashMap<PathBuf, (Arc<ArchiveIndex>, u64)>,
+    uses: u64,
+}
+
+static INDEXES: LazyLock<Mutex<IndexCache>> = LazyLock::new(Mutex::default);
+
+/// The index of an archive, read from the archive the first time and whenever it
+/// has changed since. The least recently used index is dropped once
+/// `MAX_OPEN_ARCHIVES` are kept.
+fn index(archive: &Path) -> Result<Arc<ArchiveIndex>, AppError> {
+    let metadata = std::fs::metadata(archive)?;
+    let signature = (metadata.len(), metadata.modified().ok());
+
+    {
+        let mut cache = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+        cache.uses += 1;
+        let uses = cache.uses;
+        if let Some((index, last_used)) = cache.indexes.get_mut(archive) {
+            if index.signature == signature {
+                *last_used = uses;
+                return Ok(index.clone());
+            }
+        }
+    }
+
+    // Read without holding the lock so that other archives can be read meanwhile
+    let index = Arc::new(ArchiveIndex::read(archive, signature)?);
+    let mut cache = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    cache.uses += 1;
+    let uses = cache.uses;
+    if cache.indexes.len() >= MAX_OPEN_ARCHIVES && !cache.indexes.contains_key(archive) {
+        let oldest = cache
+            .indexes
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(path, _)| path.clone());
+        if let Some(oldest) = oldest {
+            cache.indexes.remove(&oldest);
+        }
+    }
+    cache
+        .indexes
+        .insert(archive.to_path_buf(), (index.clone(), uses));
+    Ok(index)
+}
+
+/// An open archive file that several readers can read at once, each at its own position
+#[derive(Clone)]
+struct SharedFile {
+    file: Arc<Mutex<File>>,
+    position: u64,
+}
+
+impl SharedFile {
+    fn new(file: File) -> Self {
+        Self {
+            file: Arc::new(Mutex::new(file)),
+            position: 0,
+        }
+    }
+}
+
+impl Read for SharedFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.seek(SeekFrom::Start(self.position))?;
+        let read = file.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for SharedFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => {
+                let len = self
+                    .file
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .metadata()?
+                    .len();
+                len.checked_add_signed(offset)
+            }
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seek before start of file")
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// `__MACOSX/` folders and `._` files hold Finder metadata, not the files they are named after
+fn is_resource_fork(member: &Path) -> bool {
+    member.components().any(|c| c.as_os_str() == "__MACOSX")
+        || member
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("._"))
+}
+
+fn not_found(path: &Path) -> AppError {
+    AppError::NotFound(format!("File not found in archive: {}", path.display()))
+}
+
+/// A member name as a relative path, or None for names that would escape the archive.
+/// Both `/` and `\` separate folders.
+fn enclosed_name(name: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(name.replace('\\', "/"));
+    if path.as_os_str().is_empty()
+        || !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return None;
+    }
+    Some(path)
+}
+
+fn archive_error(archive: &Path, e: impl std::fmt::Display) -> AppError {
+    AppError::InternalServer(format!(
+        "Failed to read archive {}: {}",
+        archive.display(),
+        e
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_archive_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("Release.zip");
+        write_zip(
+            &archive,
+            &[
+                ("Heroes/knight.stl", b"solid knight"),
+                ("Heroes/render.png", b"png"),
+                ("__MACOSX/Heroes/._knight.stl", b"fork"),
+                ("../escape.stl", b"evil"),
+            ],
+        );
+
+        let entries = list_entries(&archive).unwrap();
+        let paths: Vec<_> = entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                archive.join("Heroes/knight.stl"),
+                archive.join("Heroes/render.png")
+            ]
+        );
+
+        let knight = archive.join("Heroes/knight.stl");
+        assert_eq!(
+            split_path(&knight),
+            Some((archive.as_path(), Path::new("Heroes/knight.stl")))
+        );
+        assert_eq!(split_path(temp_dir.path()), None);
+        assert_eq!(member_size(&knight).unwrap(), 12);
+
+        let contents = read_file(&knight, |reader, _| {
+            let mut data = String::new();
+            reader.read_to_string(&mut data)?;
+            Ok(data)
+        })
+        .unwrap();
+        assert_eq!(contents, "solid knight");
+
+        assert!(exists(&archive.join("Heroes")));
+        assert!(!exists(&archive.join("Villains")));
+        assert_eq!(folder_entries(&archive.join("Heroes")).len(), 2);
+        assert!(folder_entries(&archive).is_empty());
+
+        // A rewritten archive is read again
+        write_zip(&archive, &[("Heroes/knight.stl", b"solid knight v2")]);
+        assert_eq!(list_entries(&archive).unwrap().len(), 1);
+        assert_eq!(member_size(&knight).unwrap(), 15);
+        assert!(member_size(&archive.join("Heroes/render.png")).is_err());
+    }
+
+    fn read_string(path: &Path) -> Result<String, AppError> {
+        read_file(path, |reader, _| {
+            let mut data = String::new();
+            reader.read_to_string(&mut data)?;
+            Ok(data)
+        })
+    }
+
+    #[test]
+    fn test_7z_archive_members() {
+        let temp_dir = TempDir::new().unwrap();
+        for (name, data) in [
+            (
+                "lzma2.7z",
+                &include_bytes!("../../tests/fixtures/release_lzma2.7z")[..],
+            ),
+            (
+                "lzma.7z",
+                &include_bytes!("../../tests/fixtures/release_lzma.7z")[..],
+            ),
+        ] {
+            let archive = temp_dir.path().join(name);
+            fs::write(&archive, data).unwrap();
+
+            let paths: Vec<_> = list_entries(&archive)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.path)
+                .collect();
+            assert_eq!(
+                paths,
+                vec![
+                    archive.join("Heroes/knight.stl"),
+                    archive.join("Heroes/render.png"),
+                    archive.join("Heroes/STL/shield.stl"),
+                    archive.join("Heroes/empty.txt"),
+                ]
+            );
+
+            // Members of a solid block are read from anywhere in it
+            let shield = archive.join("Heroes/STL/shield.stl");
+            assert_eq!(member_size(&shield).unwrap(), 29);
+            assert_eq!(
+                read_string(&shield).unwrap(),
+                "solid shield\nendsolid shield\n"
+            );
+            assert_eq!(
+                read_string(&archive.join("Heroes/knight.stl")).unwrap(),
+                "solid knight\nendsolid knight\n"
+            );
+            assert_eq!(read_string(&archive.join("Heroes/empty.txt")).unwrap(), "");
+        }
+    }
+
+    #[test]
+    fn test_7z_unsupported_method() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("ppmd.7z");
+        fs::write(
+            &archive,
+            include_bytes!("../../tests/fixtures/release_ppmd.7z"),
+        )
+        .unwrap();
+
+        // The listing is readable, the PPMd-compressed data is not
+        assert_eq!(list_entries(&archive).unwrap().len(), 4);
+        let err = read_string(&archive.join("Heroes/knight.stl")).unwrap_err();
+        assert!(err.to_string().contains("not supported"), "{}", err);
+    }
+
+    #[test]
+    fn test_unsupported_archives() {
+        assert!(is_unsupported_archive(Path::new("Release.RAR")));
+        assert!(!is_unsupported_archive(Path::new("Release.7z")));
+        assert!(is_archive(Path::new("Release.7Z")));
+    }
+}
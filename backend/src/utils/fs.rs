@@ -0,0 +1,116 @@
+use crate::utils::archive;
+use crate::utils::error::AppError;
+use std::path::{Component, Path, PathBuf};
+
+/// File size and modification time (seconds since epoch), used to detect changed files.
+/// Files inside an archive report their own size and the modification time of the archive.
+pub fn file_signature(path: &Path) -> Result<(i64, i64), AppError> {
+    if let Some((archive_path, _)) = archive::split_path(path) {
+        let (_, mtime) = file_signature(archive_path)?;
+        return Ok((archive::member_size(path)? as i64, mtime));
+    }
+
+    let metadata = std::fs::metadata(path)?;
+    let mtime = metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64;
+    Ok((metadata.len() as i64, mtime))
+}
+
+/// Modification time of a file, or None if it cannot be read
+pub fn file_mtime(path: &Path) -> Option<i64> {
+    file_signature(path).ok().map(|(_, mtime)| mtime)
+}
+
+/// Extensions of the image files indexed alongside model files
+const IMAGE_EXTENSIONS: [&str; 5] = ["jpg", "jpeg", "png", "gif", "webp"];
+
+/// Image files directly inside a folder on disk or inside an archive, with their sizes
+pub fn image_files_in(folder: &Path) -> Vec<(PathBuf, i64)> {
+    let is_image = |path: &Path| {
+        path.extension()
+            .is_some_and(|ext| IMAGE_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+    };
+
+    let mut images: Vec<(PathBuf, i64)> = match std::fs::read_dir(folder) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_file()))
+            .map(|entry| entry.path())
+            .filter(|path| is_image(path))
+            .map(|path| {
+                let size = std::fs::metadata(&path)
+                    .map(|m| m.len() as i64)
+                    .unwrap_or(0);
+                (path, size)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    images.extend(
+        archive::folder_entries(folder)
+            .into_iter()
+            .filter(|entry| is_image(&entry.path))
+            .map(|entry| (entry.path, entry.size as i64)),
+    );
+
+    images
+}
+
+/// Resolve a path relative to the library root, rejecting absolute paths, `..`
+/// components and symlinks that lead outside the root. The result must be a directory.
+pub fn resolve_library_dir(root: &Path, relative: &str) -> Result<PathBuf, AppError> {
+    let mut resolved = root.to_path_buf();
+    for component in Path::new(relative).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(AppError::ValidationError(format!(
+                    "Path must be relative to the library root: {}",
+                    relative
+                )))
+            }
+        }
+    }
+
+    if !resolved.is_dir() {
+        return Err(AppError::ValidationError(format!(
+            "Path is not a directory: {}",
+            relative
+        )));
+    }
+
+    let canonical_root = root.canonicalize()?;
+    if !resolved.canonicalize()?.starts_with(&canonical_root) {
+        return Err(AppError::ValidationError(format!(
+            "Path escapes the library root: {}",
+            relative
+        )));
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_library_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("library");
+        std::fs::create_dir_all(root.join("Creator/Release")).unwrap();
+
+        assert_eq!(
+            resolve_library_dir(&root, "Creator/./Release").unwrap(),
+            root.join("Creator").join("Release")
+        );
+        assert!(resolve_library_dir(&root, "Creator/../..").is_err());
+        assert!(resolve_library_dir(&root, temp_dir.path().to_str().unwrap()).is_err());
+        assert!(resolve_library_dir(&root, "Missing").is_err());
+    }
+}
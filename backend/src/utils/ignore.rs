@@ -0,0 +1,492 @@
+//! Gitignore-style exclusion rules for the library.
+//!
+//! Rules come from the global patterns in the config, which are relative to the
+//! library root, and from `.glyptoignore` files, which apply to the folder they
+//! are in and everything below it. The last matching pattern wins, so deeper
+//! files can re-include (`!pattern`) what the config or a parent excluded.
+//! Patterns are matched case-insensitively, like `IGNORED_KEYWORDS`.
+//!
+//! The rules also decide which symlinks a walk follows. Symlinks are only followed
+//! when enabled in the config. A link to a folder inside the library is never walked
+//! twice: it is recorded as a folder link and the target is indexed under its real
+//! path. Links leading outside the library are walked once, under the link path.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use walkdir::{DirEntry, WalkDir};
+
+/// Name of the per-folder ignore file
+pub const IGNORE_FILE_NAME: &str = ".glyptoignore";
+
+/// One line of an ignore file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnorePattern {
+    /// Path segments to match; `**` matches any number of folders
+    segments: Vec<String>,
+    /// `!pattern` re-includes what an earlier pattern excluded
+    negated: bool,
+    /// `pattern/` only matches folders
+    dir_only: bool,
+}
+
+impl IgnorePattern {
+    /// Parse a line of an ignore file. Blank lines and `#` comments yield None.
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let mut pattern = line.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return Ok(None);
+        }
+
+        // `\!` and `\#` escape a leading `!` or `#`
+        let negated = pattern.starts_with('!');
+        if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#") {
+            pattern = &pattern[1..];
+        }
+
+        let dir_only = pattern.ends_with('/');
+        let pattern = pattern.trim_end_matches('/');
+        if pattern.is_empty() {
+            return Err(format!("Invalid ignore pattern: {}", line));
+        }
+
+        // Without a slash the pattern matches a name at any depth; with one it is
+        // relative to the folder of the ignore file (or the library root)
+        let anchored = pattern.contains('/');
+        let mut segments: Vec<String> = pattern
+            .trim_start_matches('/')
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        if !anchored {
+            segments.insert(0, "**".to_string());
+        }
+
+        for segment in &segments {
+            if !brackets_balanced(segment) {
+                return Err(format!(
+                    "Unclosed character class in ignore pattern: {}",
+                    line
+                ));
+            }
+        }
+
+        Ok(Some(Self {
+            segments,
+            negated,
+            dir_only,
+        }))
+    }
+
+    /// Whether the pattern matches a path relative to the pattern's base folder
+    fn matches(&self, relative: &[String], is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && match_segments(&self.segments, relative)
+    }
+}
+
+/// Parse every line of an ignore file, skipping lines that are not valid patterns
+pub fn parse_patterns<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<IgnorePattern> {
+    lines
+        .into_iter()
+        .filter_map(|line| IgnorePattern::parse(line).ok().flatten())
+        .collect()
+}
+
+/// Exclusion rules for one library root. `.glyptoignore` files are read once and cached.
+pub struct IgnoreRules {
+    root: PathBuf,
+    global: Vec<IgnorePattern>,
+    folders: Mutex<HashMap<PathBuf, Arc<Vec<IgnorePattern>>>>,
+    /// Set when symlinks are followed
+    symlinks: Option<Symlinks>,
+}
+
+/// Symlinks met while walking
+struct Symlinks {
+    canonical_root: PathBuf,
+    /// Canonical targets outside the library already walked, so that loops end
+    visited: Mutex<HashSet<PathBuf>>,
+    /// Symlinked folders leading into the library, with the library path of their target
+    links: Mutex<Vec<(PathBuf, PathBuf)>>,
+    /// Followed symlinks leading outside the library, with their canonical target
+    external: Mutex<Vec<(PathBuf, PathBuf)>>,
+}
+
+impl IgnoreRules {
+    pub fn new(root: &Path, global_patterns: &[String]) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            global: parse_patterns(global_patterns.iter().map(String::as_str)),
+            folders: Mutex::new(HashMap::new()),
+            symlinks: None,
+        }
+    }
+
+    /// Follow symlinks while walking, with loop detection
+    pub fn with_follow_symlinks(mut self, follow: bool) -> Self {
+        self.symlinks = if follow {
+            match self.root.canonicalize() {
+                Ok(canonical_root) => Some(Symlinks {
+                    canonical_root,
+                    visited: Mutex::new(HashSet::new()),
+                    links: Mutex::new(Vec::new()),
+                    external: Mutex::new(Vec::new()),
+                }),
+                Err(e) => {
+                    tracing::warn!("Not following symlinks in {}: {}", self.root.display(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        self
+    }
+
+    pub fn follows_symlinks(&self) -> bool {
+        self.symlinks.is_some()
+    }
+
+    /// Whether a walk should follow the symlink at `path`. Links into the library are
+    /// not followed; folder links among them are recorded for `folder_links`. Links
+    /// leading outside are followed the first time their target is met and recorded
+    /// for `external_links`.
+    pub fn follow_symlink(&self, path: &Path) -> bool {
+        let symlinks = match &self.symlinks {
+            Some(symlinks) => symlinks,
+            None => return false,
+        };
+        // Broken links are skipped
+        let target = match path.canonicalize() {
+            Ok(target) => target,
+            Err(_) => return false,
+        };
+
+        match target.strip_prefix(&symlinks.canonical_root) {
+            Ok(relative) => {
+                if target.is_dir() {
+                    let target = self.root.join(relative);
+                    symlinks
+                        .links
+                        .lock()
+                        .unwrap()
+                        .push((path.to_path_buf(), target));
+                }
+                false
+            }
+            Err(_) => {
+                let follow = symlinks.visited.lock().unwrap().insert(target.clone());
+                if follow && target.is_dir() {
+                    symlinks
+                        .external
+                        .lock()
+                        .unwrap()
+                        .push((path.to_path_buf(), target));
+                }
+                follow
+            }
+        }
+    }
+
+    /// Whether `path` is a symlink a walk may follow because it leads outside the
+    /// library. Unlike `follow_symlink`, this does not remember the target.
+    pub fn leads_outside(&self, path: &Path) -> bool {
+        match (&self.symlinks, path.canonicalize()) {
+            (Some(symlinks), Ok(target)) => !target.starts_with(&symlinks.canonical_root),
+            _ => false,
+        }
+    }
+
+    /// Whether `path` can only be reached through a symlink that is not followed
+    pub fn behind_symlink(&self, path: &Path) -> bool {
+        !self.follows_symlinks()
+            && path
+                .ancestors()
+                .take_while(|folder| *folder != self.root && folder.starts_with(&self.root))
+                .any(|folder| folder.is_symlink())
+    }
+
+    /// Symlinked folders found while walking that lead into the library, as
+    /// (link path, target path) pairs
+    pub fn folder_links(&self) -> Vec<(PathBuf, PathBuf)> {
+        let mut links = match &self.symlinks {
+            Some(symlinks) => symlinks.links.lock().unwrap().clone(),
+            None => Vec::new(),
+        };
+        links.sort();
+        links.dedup();
+        links
+    }
+
+    /// Symlinked folders followed while walking that lead outside the library, as
+    /// (link path, canonical target) pairs
+    pub fn external_links(&self) -> Vec<(PathBuf, PathBuf)> {
+        let mut links = match &self.symlinks {
+            Some(symlinks) => symlinks.external.lock().unwrap().clone(),
+            None => Vec::new(),
+        };
+        links.sort();
+        links
+    }
+
+    /// Whether `path` itself is excluded. Used while walking, where the contents
+    /// of excluded folders are never visited. The library root is never excluded.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => return false,
+        };
+
+        let mut ignored = apply(&self.global, &components(relative), is_dir, false);
+
+        // Ignore files from the root down to the folder holding `path`
+        let mut folders: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|folder| folder.starts_with(&self.root))
+            .collect();
+        folders.reverse();
+        for folder in folders {
+            let patterns = self.folder_patterns(folder);
+            if patterns.is_empty() {
+                continue;
+            }
+            let relative = path.strip_prefix(folder).unwrap_or(path);
+            ignored = apply(&patterns, &components(relative), is_dir, ignored);
+        }
+
+        ignored
+    }
+
+    /// Whether `path` or any folder above it (up to the root) is excluded
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        self.is_ignored(path, is_dir)
+            || path
+                .ancestors()
+                .skip(1)
+                .take_while(|folder| folder.starts_with(&self.root))
+                .any(|folder| self.is_ignored(folder, true))
+    }
+
+    /// Walk `folder` without descending into excluded folders or yielding excluded files
+    pub fn walk<'a>(
+        &'a self,
+        folder: &Path,
+    ) -> impl Iterator<Item = walkdir::Result<DirEntry>> + 'a {
+        WalkDir::new(folder)
+            .follow_links(self.follows_symlinks())
+            .into_iter()
+            .filter_entry(move |entry| {
+                let is_dir = entry.file_type().is_dir();
+                if entry.depth() == 0 {
+                    !self.is_excluded(entry.path(), is_dir)
+                } else {
+                    !self.is_ignored(entry.path(), is_dir)
+                        && (!entry.path_is_symlink() || self.follow_symlink(entry.path()))
+                }
+            })
+            // Links back into the walked tree and broken links are skipped, not errors
+            .filter(move |entry| match entry {
+                Err(e) if e.loop_ancestor().is_some() => {
+                    if let Some(path) = e.path() {
+                        self.follow_symlink(path);
+                    }
+                    false
+                }
+                Err(e) => !e
+                    .path()
+                    .is_some_and(|path| path.is_symlink() && !path.exists()),
+                Ok(_) => true,
+            })
+    }
+
+    fn folder_patterns(&self, folder: &Path) -> Arc<Vec<IgnorePattern>> {
+        let mut folders = self.folders.lock().unwrap();
+        folders
+            .entry(folder.to_path_buf())
+            .or_insert_with(|| {
+                let contents =
+                    std::fs::read_to_string(folder.join(IGNORE_FILE_NAME)).unwrap_or_default();
+                Arc::new(parse_patterns(contents.lines()))
+            })
+            .clone()
+    }
+}
+
+fn apply(patterns: &[IgnorePattern], relative: &[String], is_dir: bool, ignored: bool) -> bool {
+    patterns
+        .iter()
+        .rev()
+        .find(|pattern| pattern.matches(relative, is_dir))
+        .map_or(ignored, |pattern| !pattern.negated)
+}
+
+fn components(relative: &Path) -> Vec<String> {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+        .collect()
+}
+
+fn match_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((segment, rest)) if segment == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((segment, rest)) => {
+            !path.is_empty()
+                && glob_match(
+                    &segment.chars().collect::<Vec<_>>(),
+                    &path[0].chars().collect::<Vec<_>>(),
+                )
+                && match_segments(rest, &path[1..])
+        }
+    }
+}
+
+/// Match one path segment against `*`, `?`, `[a-z]` / `[!a-z]` and `\` escapes
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| glob_match(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && glob_match(&pattern[1..], &text[1..]),
+        Some('[') => {
+            let end = match pattern.iter().skip(2).position(|&c| c == ']') {
+                Some(position) => position + 2,
+                None => return false,
+            };
+            let (negated, class) = match pattern[1] {
+                '!' | '^' => (true, &pattern[2..end]),
+                _ => (false, &pattern[1..end]),
+            };
+            !text.is_empty()
+                && class_contains(class, text[0]) != negated
+                && glob_match(&pattern[end + 1..], &text[1..])
+        }
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && glob_match(&pattern[1..], &text[1..]),
+    }
+}
+
+fn class_contains(class: &[char], c: char) -> bool {
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if (class[i]..=class[i + 2]).contains(&c) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
+}
+
+fn brackets_balanced(segment: &str) -> bool {
+    let chars: Vec<char> = segment.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            // The first character of a class may be `]` (or `!]`)
+            '[' => match chars.iter().skip(i + 2).position(|&c| c == ']') {
+                Some(position) => i += position + 2,
+                None => return false,
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(IgnorePattern::parse("# comment"), Ok(None));
+        assert_eq!(IgnorePattern::parse("   "), Ok(None));
+        assert!(IgnorePattern::parse("/").is_err());
+        assert!(IgnorePattern::parse("test[").is_err());
+
+        let pattern = IgnorePattern::parse("!Support-Tests/").unwrap().unwrap();
+        assert!(pattern.negated);
+        assert!(pattern.dir_only);
+        assert_eq!(pattern.segments, vec!["**", "support-tests"]);
+        assert_eq!(
+            IgnorePattern::parse("/Creator/*_old")
+                .unwrap()
+                .unwrap()
+                .segments,
+            vec!["creator", "*_old"]
+        );
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("Creator/Release")).unwrap();
+        std::fs::write(
+            root.join("Creator").join(IGNORE_FILE_NAME),
+            "# Test prints\nsupport-tests/\n!keep_old\n",
+        )
+        .unwrap();
+
+        let rules = IgnoreRules::new(root, &["*_old".to_string(), "/tmp*/".to_string()]);
+
+        // Global patterns match at any depth unless anchored to the root
+        assert!(rules.is_ignored(&root.join("Dragon_OLD"), true));
+        assert!(rules.is_ignored(&root.join("tmp-lychee"), true));
+        assert!(!rules.is_ignored(&root.join("Creator/tmp-lychee"), true));
+        assert!(!rules.is_ignored(&root.join("tmp.stl"), false));
+
+        // Folder rules apply below their folder and override the global ones
+        assert!(rules.is_ignored(&root.join("Creator/Release/Support-Tests"), true));
+        assert!(!rules.is_ignored(&root.join("Creator/Release/support-tests"), false));
+        assert!(!rules.is_ignored(&root.join("Support-Tests"), true));
+        assert!(!rules.is_ignored(&root.join("Creator/keep_old"), true));
+        assert!(rules.is_ignored(&root.join("Creator/other_old"), true));
+
+        assert!(rules.is_excluded(&root.join("Dragon_old/base/base.stl"), false));
+        assert!(!rules.is_excluded(&root.join("Creator/Release/base.stl"), false));
+        assert!(!rules.is_ignored(root, true));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        let matches = |pattern: &str, text: &str| {
+            glob_match(
+                &pattern.chars().collect::<Vec<_>>(),
+                &text.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(matches("*.stl", "knight.stl"));
+        assert!(!matches("*.stl", "knight.stl.bak"));
+        assert!(matches("v?", "v2"));
+        assert!(matches("v[0-9]", "v7"));
+        assert!(!matches("v[!0-9]", "v7"));
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+        assert!(match_segments(
+            &["a".to_string(), "**".to_string(), "b".to_string()],
+            &[
+                "a".to_string(),
+                "x".to_string(),
+                "y".to_string(),
+                "b".to_string()
+            ]
+        ));
+    }
+}
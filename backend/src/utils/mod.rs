@@ -1,2 +1,8 @@
+pub mod archive;
 pub mod error;
+pub mod fs;
+pub mod ignore;
 pub mod pagination;
+pub mod release_pattern;
+pub mod sevenz;
+pub mod walk;
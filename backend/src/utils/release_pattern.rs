@@ -0,0 +1,144 @@
+//! Folder name patterns that mark release folders, e.g. monthly Patreon drops.
+//!
+//! `YYYY`, `MM` and `DD` match the digits of a date and `*` matches any text;
+//! everything else matches itself, case-insensitively. A pattern must match the
+//! whole folder name, so `YYYY-MM*` matches `2024-03 Dragons` but not `Dragons`.
+
+/// One element of a release folder pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Year,
+    Month,
+    Day,
+    Any,
+    Char(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleasePattern {
+    tokens: Vec<Token>,
+}
+
+/// Date parts captured while matching a folder name
+#[derive(Debug, Clone, Copy, Default)]
+struct DateParts {
+    year: Option<u32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+impl ReleasePattern {
+    /// Parse a pattern; it needs at least one of `YYYY`, `MM` or `DD`
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        let mut tokens = Vec::new();
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            let (token, len) = if rest.starts_with("YYYY") {
+                (Token::Year, 4)
+            } else if rest.starts_with("MM") {
+                (Token::Month, 2)
+            } else if rest.starts_with("DD") {
+                (Token::Day, 2)
+            } else if c == '*' {
+                (Token::Any, 1)
+            } else {
+                (Token::Char(c.to_ascii_lowercase()), c.len_utf8())
+            };
+            // Consecutive wildcards match the same as one
+            if !(token == Token::Any && tokens.last() == Some(&Token::Any)) {
+                tokens.push(token);
+            }
+            rest = &rest[len..];
+        }
+
+        if !tokens
+            .iter()
+            .any(|t| matches!(t, Token::Year | Token::Month | Token::Day))
+        {
+            return Err(format!(
+                "Release folder pattern needs YYYY, MM or DD: {}",
+                pattern
+            ));
+        }
+
+        Ok(Self { tokens })
+    }
+
+    /// Whether a folder name matches the pattern. The release date is returned as
+    /// `YYYY-MM-DD` when the pattern has a year, with a missing month or day as 01.
+    pub fn match_name(&self, name: &str) -> Option<Option<String>> {
+        let chars: Vec<char> = name
+            .trim()
+            .chars()
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let parts = match_tokens(&self.tokens, &chars, DateParts::default())?;
+
+        Some(parts.year.map(|year| {
+            format!(
+                "{:04}-{:02}-{:02}",
+                year,
+                parts.month.unwrap_or(1),
+                parts.day.unwrap_or(1)
+            )
+        }))
+    }
+}
+
+fn match_tokens(tokens: &[Token], chars: &[char], parts: DateParts) -> Option<DateParts> {
+    let Some((&token, rest)) = tokens.split_first() else {
+        return chars.is_empty().then_some(parts);
+    };
+
+    match token {
+        Token::Any => (0..=chars.len()).find_map(|skip| match_tokens(rest, &chars[skip..], parts)),
+        Token::Char(c) => match chars.split_first() {
+            Some((&first, remaining)) if first == c => match_tokens(rest, remaining, parts),
+            _ => None,
+        },
+        Token::Year | Token::Month | Token::Day => {
+            let len = if token == Token::Year { 4 } else { 2 };
+            if chars.len() < len || !chars[..len].iter().all(char::is_ascii_digit) {
+                return None;
+            }
+            let value: u32 = chars[..len].iter().collect::<String>().parse().ok()?;
+            let mut parts = parts;
+            match token {
+                Token::Year => parts.year = Some(value),
+                Token::Month if (1..=12).contains(&value) => parts.month = Some(value),
+                Token::Day if (1..=31).contains(&value) => parts.day = Some(value),
+                _ => return None,
+            }
+            match_tokens(rest, &chars[len..], parts)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_pattern() {
+        let pattern = ReleasePattern::parse("YYYY-MM*").unwrap();
+        assert_eq!(
+            pattern.match_name("2024-03"),
+            Some(Some("2024-03-01".to_string()))
+        );
+        assert_eq!(
+            pattern.match_name("2024-11 Dragons of the North"),
+            Some(Some("2024-11-01".to_string()))
+        );
+        assert_eq!(pattern.match_name("2024-13"), None);
+        assert_eq!(pattern.match_name("Dragons 2024-03"), None);
+        assert_eq!(pattern.match_name("24-03"), None);
+
+        let pattern = ReleasePattern::parse("Release MM").unwrap();
+        assert_eq!(pattern.match_name("release 07"), Some(None));
+        assert_eq!(pattern.match_name("Release 7"), None);
+
+        assert!(ReleasePattern::parse("Releases *").is_err());
+        assert!(ReleasePattern::parse("").is_err());
+    }
+}
@@ -0,0 +1,753 @@
+//! Reading files stored in 7z archives.
+//!
+//! Only what indexing a library needs is supported: the archive header, which may
+//! itself be compressed, and files packed with LZMA, LZMA2 or no compression at all,
+//! optionally behind a BCJ or delta filter. These cover the archives 7-Zip and p7zip
+//! create with their default settings. Encrypted archives and the other methods
+//! (PPMd, BZip2, Deflate, BCJ2) are reported as unsupported when a file is read.
+
+use lzma_rust2::filter::bcj::BcjReader;
+use lzma_rust2::filter::delta::DeltaReader;
+use lzma_rust2::{Lzma2Reader, LzmaReader};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+const SIGNATURE: [u8; 6] = [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+
+/// Size of the signature header in front of the packed streams
+const SIGNATURE_HEADER_SIZE: u64 = 32;
+
+/// Largest archive header read into memory, compressed or not
+const MAX_HEADER_SIZE: u64 = 64 * 1024 * 1024;
+
+// Property ids of the archive header
+const END: u8 = 0x00;
+const HEADER: u8 = 0x01;
+const ARCHIVE_PROPERTIES: u8 = 0x02;
+const ADDITIONAL_STREAMS_INFO: u8 = 0x03;
+const MAIN_STREAMS_INFO: u8 = 0x04;
+const FILES_INFO: u8 = 0x05;
+const PACK_INFO: u8 = 0x06;
+const UNPACK_INFO: u8 = 0x07;
+const SUBSTREAMS_INFO: u8 = 0x08;
+const SIZE: u8 = 0x09;
+const CRC: u8 = 0x0A;
+const FOLDER: u8 = 0x0B;
+const CODERS_UNPACK_SIZE: u8 = 0x0C;
+const NUM_UNPACK_STREAM: u8 = 0x0D;
+const EMPTY_STREAM: u8 = 0x0E;
+const EMPTY_FILE: u8 = 0x0F;
+const NAME: u8 = 0x11;
+const WIN_ATTRIBUTES: u8 = 0x15;
+const ENCODED_HEADER: u8 = 0x17;
+
+/// Windows attribute bit of folders
+const ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+/// A file or folder stored in a 7z archive
+#[derive(Debug, Clone)]
+pub struct SevenZEntry {
+    /// Path inside the archive, as stored
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    pub is_dir: bool,
+    /// Folder (compressed block) holding the data and the offset of the data in it
+    location: Option<(usize, u64)>,
+}
+
+/// A compression method applied to a folder's data
+#[derive(Debug, Clone)]
+struct Coder {
+    method: Vec<u8>,
+    properties: Vec<u8>,
+    num_in_streams: usize,
+    num_out_streams: usize,
+}
+
+/// A block of data compressed as one stream, holding one or more files
+#[derive(Debug, Clone, Default)]
+struct Folder {
+    coders: Vec<Coder>,
+    /// Input stream bound to the output stream of another coder
+    bind_pairs: Vec<(usize, usize)>,
+    /// Input streams read from packed streams, in packed stream order
+    packed_streams: Vec<usize>,
+    /// Size of every coder output stream
+    unpack_sizes: Vec<u64>,
+    crc_defined: bool,
+    /// Index of the folder's first packed stream in the archive
+    first_packed_stream: usize,
+}
+
+impl Folder {
+    /// Size of the folder's decoded data: the output stream no other coder reads
+    fn unpack_size(&self) -> u64 {
+        (0..self.unpack_sizes.len())
+            .find(|out| !self.bind_pairs.iter().any(|(_, bound)| bound == out))
+            .map(|out| self.unpack_sizes[out])
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Default)]
+struct StreamsInfo {
+    pack_pos: u64,
+    pack_sizes: Vec<u64>,
+    folders: Vec<Folder>,
+    /// Sizes of the files stored in each folder, in order
+    substream_sizes: Vec<Vec<u64>>,
+}
+
+/// The index of a 7z archive, read once, from which files are decompressed on demand
+pub struct SevenZArchive<R> {
+    reader: R,
+    /// Absolute offset and size of every packed stream
+    packed_streams: Vec<(u64, u64)>,
+    folders: Vec<Folder>,
+    entries: Vec<SevenZEntry>,
+}
+
+impl<R: Read + Seek + Clone + 'static> SevenZArchive<R> {
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut start = [0u8; SIGNATURE_HEADER_SIZE as usize];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut start)?;
+        if start[..6] != SIGNATURE {
+            return Err(invalid("not a 7z archive"));
+        }
+
+        let next_header_offset = u64::from_le_bytes(start[12..20].try_into().unwrap());
+        let next_header_size = u64::from_le_bytes(start[20..28].try_into().unwrap());
+        let mut archive = Self {
+            reader,
+            packed_streams: Vec::new(),
+            folders: Vec::new(),
+            entries: Vec::new(),
+        };
+        if next_header_size == 0 {
+            return Ok(archive);
+        }
+        if next_header_size > MAX_HEADER_SIZE {
+            return Err(invalid("archive header too large"));
+        }
+
+        let header_start = SIGNATURE_HEADER_SIZE
+            .checked_add(next_header_offset)
+            .ok_or_else(|| invalid("bad header offset"))?;
+        archive.reader.seek(SeekFrom::Start(header_start))?;
+        let mut header = vec![0u8; next_header_size as usize];
+        archive.reader.read_exact(&mut header)?;
+
+        loop {
+            let mut r = HeaderReader::new(&header);
+            match r.byte()? {
+                HEADER => {
+                    archive.read_header(&mut r)?;
+                    return Ok(archive);
+                }
+                // The header is compressed, as the first folder of these streams
+                ENCODED_HEADER => {
+                    let streams = read_streams_info(&mut r)?;
+                    archive.set_streams(&streams)?;
+                    let size = archive
+                        .folders
+                        .first()
+                        .ok_or_else(|| invalid("empty encoded header"))?
+                        .unpack_size();
+                    if size > MAX_HEADER_SIZE {
+                        return Err(invalid("archive header too large"));
+                    }
+                    let mut decoded = Vec::with_capacity(size as usize);
+                    archive
+                        .folder_reader(0)?
+                        .take(size)
+                        .read_to_end(&mut decoded)?;
+                    header = decoded;
+                }
+                _ => return Err(invalid("unknown header")),
+            }
+        }
+    }
+
+    /// Files and folders in the order they are stored
+    pub fn entries(&self) -> &[SevenZEntry] {
+        &self.entries
+    }
+
+    /// Decompress the entry at `index`. Files in a solid block are stored one after the
+    /// other, so the data in front of the entry is decompressed and skipped.
+    pub fn entry_reader(&self, index: usize) -> io::Result<Box<dyn Read>> {
+        let entry = self
+            .entries
+            .get(index)
+            .ok_or_else(|| invalid("no such entry"))?;
+        let Some((folder, offset)) = entry.location else {
+            return Ok(Box::new(io::empty()));
+        };
+
+        let mut reader = self.folder_reader(folder)?;
+        let skipped = io::copy(&mut (&mut reader).take(offset), &mut io::sink())?;
+        if skipped < offset {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        Ok(Box::new(reader.take(entry.size)))
+    }
+
+    fn read_header(&mut self, r: &mut HeaderReader) -> io::Result<()> {
+        let mut id = r.byte()?;
+        if id == ARCHIVE_PROPERTIES {
+            while r.byte()? != END {
+                r.skip_data()?;
+            }
+            id = r.byte()?;
+        }
+        if id == ADDITIONAL_STREAMS_INFO {
+            read_streams_info(r)?;
+            id = r.byte()?;
+        }
+        let streams = if id == MAIN_STREAMS_INFO {
+            let streams = read_streams_info(r)?;
+            id = r.byte()?;
+            streams
+        } else {
+            StreamsInfo::default()
+        };
+        self.set_streams(&streams)?;
+        if id == FILES_INFO {
+            self.entries = read_files_info(r, &streams)?;
+            id = r.byte()?;
+        }
+        if id != END {
+            return Err(invalid("unexpected property in header"));
+        }
+        Ok(())
+    }
+
+    fn set_streams(&mut self, streams: &StreamsInfo) -> io::Result<()> {
+        let mut offset = SIGNATURE_HEADER_SIZE
+            .checked_add(streams.pack_pos)
+            .ok_or_else(|| invalid("bad packed stream offset"))?;
+        self.packed_streams = Vec::with_capacity(streams.pack_sizes.len());
+        for &size in &streams.pack_sizes {
+            self.packed_streams.push((offset, size));
+            offset = offset
+                .checked_add(size)
+                .ok_or_else(|| invalid("bad packed stream size"))?;
+        }
+
+        self.folders = streams.folders.clone();
+        let mut first_packed_stream = 0;
+        for folder in &mut self.folders {
+            folder.first_packed_stream = first_packed_stream;
+            first_packed_stream += folder.packed_streams.len();
+        }
+        if first_packed_stream > self.packed_streams.len() {
+            return Err(invalid("missing packed streams"));
+        }
+        Ok(())
+    }
+
+    /// The decoded data of a folder
+    fn folder_reader(&self, index: usize) -> io::Result<Box<dyn Read>> {
+        let folder = self
+            .folders
+            .get(index)
+            .ok_or_else(|| invalid("no such folder"))?;
+        if let Some(coder) = folder
+            .coders
+            .iter()
+            .find(|c| c.num_in_streams != 1 || c.num_out_streams != 1)
+        {
+            return Err(unsupported(&coder.method));
+        }
+
+        // With one input and one output per coder, stream indexes are coder indexes
+        let main = (0..folder.coders.len())
+            .find(|out| !folder.bind_pairs.iter().any(|(_, bound)| bound == out))
+            .ok_or_else(|| invalid("folder without output"))?;
+        self.coder_reader(folder, main, folder.coders.len())
+    }
+
+    /// The output of a coder, reading its input from a packed stream or another coder
+    fn coder_reader(
+        &self,
+        folder: &Folder,
+        coder: usize,
+        depth: usize,
+    ) -> io::Result<Box<dyn Read>> {
+        let input: Box<dyn Read> =
+            if let Some(packed) = folder.packed_streams.iter().position(|&i| i == coder) {
+                let (offset, size) = self.packed_streams[folder.first_packed_stream + packed];
+                let mut reader = self.reader.clone();
+                reader.seek(SeekFrom::Start(offset))?;
+                Box::new(BufReader::new(reader.take(size)))
+            } else {
+                let &(_, bound) = folder
+                    .bind_pairs
+                    .iter()
+                    .find(|(input, _)| *input == coder)
+                    .ok_or_else(|| invalid("coder without input"))?;
+                if depth == 0 {
+                    return Err(invalid("cyclic coders"));
+                }
+                self.coder_reader(folder, bound, depth - 1)?
+            };
+
+        decoder(&folder.coders[coder], input, folder.unpack_sizes[coder])
+    }
+}
+
+/// Wrap `input` in the decoder of a coder producing `size` bytes
+fn decoder(coder: &Coder, input: Box<dyn Read>, size: u64) -> io::Result<Box<dyn Read>> {
+    let properties = &coder.properties;
+    Ok(match coder.method.as_slice() {
+        [0x00] => input,
+        [0x21] => {
+            let dict_size = match properties.first() {
+                Some(&bits) if bits < 40 => (2 | (bits as u32 & 1)) << (bits / 2 + 11),
+                Some(40) => u32::MAX,
+                _ => return Err(invalid("bad LZMA2 properties")),
+            };
+            // The dictionary never needs to hold more than the data
+            let dict_size = dict_size.min(size.clamp(4096, 1 << 31) as u32);
+            Box::new(Lzma2Reader::new(input, dict_size, None))
+        }
+        [0x03, 0x01, 0x01] => {
+            if properties.len() < 5 {
+                return Err(invalid("bad LZMA properties"));
+            }
+            let dict_size = u32::from_le_bytes(properties[1..5].try_into().unwrap());
+            Box::new(LzmaReader::new_with_props(
+                input,
+                size,
+                properties[0],
+                dict_size,
+                None,
+            )?)
+        }
+        [0x03, 0x03, 0x01, 0x03] | [0x04] => Box::new(BcjReader::new_x86(input, 0)),
+        [0x03, 0x03, 0x02, 0x05] | [0x05] => Box::new(BcjReader::new_ppc(input, 0)),
+        [0x03, 0x03, 0x04, 0x01] | [0x06] => Box::new(BcjReader::new_ia64(input, 0)),
+        [0x03, 0x03, 0x05, 0x01] | [0x07] => Box::new(BcjReader::new_arm(input, 0)),
+        [0x03, 0x03, 0x07, 0x01] | [0x08] => Box::new(BcjReader::new_arm_thumb(input, 0)),
+        [0x03, 0x03, 0x08, 0x05] | [0x09] => Box::new(BcjReader::new_sparc(input, 0)),
+        [0x0A] => Box::new(BcjReader::new_arm64(input, 0)),
+        [0x0B] => Box::new(BcjReader::new_riscv(input, 0)),
+        [0x03] => {
+            let distance = properties.first().map_or(1, |&d| d as usize + 1);
+            Box::new(DeltaReader::new(input, distance))
+        }
+        method => return Err(unsupported(method)),
+    })
+}
+
+fn read_streams_info(r: &mut HeaderReader) -> io::Result<StreamsInfo> {
+    let mut info = StreamsInfo::default();
+    let mut id = r.byte()?;
+
+    if id == PACK_INFO {
+        info.pack_pos = r.number()?;
+        let count = r.count()?;
+        loop {
+            match r.byte()? {
+                END => break,
+                SIZE => {
+                    info.pack_sizes = (0..count).map(|_| r.number()).collect::<io::Result<_>>()?
+                }
+                CRC => {
+                    r.digests(count)?;
+                }
+                _ => r.skip_data()?,
+            }
+        }
+        if info.pack_sizes.len() != count {
+            return Err(invalid("missing packed stream sizes"));
+        }
+        id = r.byte()?;
+    }
+
+    if id == UNPACK_INFO {
+        if r.byte()? != FOLDER {
+            return Err(invalid("missing folders"));
+        }
+        let count = r.count()?;
+        if r.byte()? != 0 {
+            return Err(invalid("external folders are not supported"));
+        }
+        info.folders = (0..count)
+            .map(|_| read_folder(r))
+            .collect::<io::Result<_>>()?;
+
+        if r.byte()? != CODERS_UNPACK_SIZE {
+            return Err(invalid("missing folder sizes"));
+        }
+        for folder in &mut info.folders {
+            let outputs = folder.coders.iter().map(|c| c.num_out_streams).sum();
+            folder.unpack_sizes = (0..outputs)
+                .map(|_| r.number())
+                .collect::<io::Result<_>>()?;
+        }
+
+        loop {
+            match r.byte()? {
+                END => break,
+                CRC => {
+                    let defined = r.digests(count)?;
+                    for (folder, defined) in info.folders.iter_mut().zip(defined) {
+                        folder.crc_defined = defined;
+                    }
+                }
+                _ => r.skip_data()?,
+            }
+        }
+        id = r.byte()?;
+    }
+
+    if id == SUBSTREAMS_INFO {
+        read_substreams_info(r, &mut info)?;
+        id = r.byte()?;
+    } else {
+        info.substream_sizes = info
+            .folders
+            .iter()
+            .map(|folder| vec![folder.unpack_size()])
+            .collect();
+    }
+
+    if id != END {
+        return Err(invalid("unexpected property in streams"));
+    }
+    Ok(info)
+}
+
+fn read_folder(r: &mut HeaderReader) -> io::Result<Folder> {
+    let mut folder = Folder::default();
+    let num_coders = r.count()?;
+    for _ in 0..num_coders {
+        let flags = r.byte()?;
+        if flags & 0x80 != 0 {
+            return Err(invalid("alternative coder methods are not supported"));
+        }
+        let method = r.bytes((flags & 0x0F) as usize)?.to_vec();
+        let (num_in_streams, num_out_streams) = if flags & 0x10 != 0 {
+            (r.count()?, r.count()?)
+        } else {
+            (1, 1)
+        };
+        let properties = if flags & 0x20 != 0 {
+            let size = r.count()?;
+            r.bytes(size)?.to_vec()
+        } else {
+            Vec::new()
+        };
+        folder.coders.push(Coder {
+            method,
+            properties,
+            num_in_streams,
+            num_out_streams,
+        });
+    }
+
+    let inputs: usize = folder.coders.iter().map(|c| c.num_in_streams).sum();
+    let outputs: usize = folder.coders.iter().map(|c| c.num_out_streams).sum();
+    let num_bind_pairs = outputs
+        .checked_sub(1)
+        .ok_or_else(|| invalid("folder without output"))?;
+    for _ in 0..num_bind_pairs {
+        let pair = (r.count()?, r.count()?);
+        if pair.0 >= inputs || pair.1 >= outputs {
+            return Err(invalid("bad bind pair"));
+        }
+        folder.bind_pairs.push(pair);
+    }
+
+    let num_packed_streams = inputs
+        .checked_sub(num_bind_pairs)
+        .ok_or_else(|| invalid("bad folder streams"))?;
+    if num_packed_streams == 1 {
+        let input = (0..inputs)
+            .find(|input| !folder.bind_pairs.iter().any(|(bound, _)| bound == input))
+            .ok_or_else(|| invalid("folder without input"))?;
+        folder.packed_streams.push(input);
+    } else {
+        for _ in 0..num_packed_streams {
+            folder.packed_streams.push(r.count()?);
+        }
+    }
+
+    Ok(folder)
+}
+
+fn read_substreams_info(r: &mut HeaderReader, info: &mut StreamsInfo) -> io::Result<()> {
+    let mut counts = vec![1; info.folders.len()];
+    let mut id = r.byte()?;
+    if id == NUM_UNPACK_STREAM {
+        for count in &mut counts {
+            *count = r.count()?;
+        }
+        id = r.byte()?;
+    }
+
+    let has_sizes = id == SIZE;
+    for (folder, &count) in info.folders.iter().zip(&counts) {
+        let mut sizes = Vec::new();
+        if count > 0 {
+            let mut total = 0u64;
+            if has_sizes {
+                for _ in 1..count {
+                    let size = r.number()?;
+                    total = total
+                        .checked_add(size)
+                        .ok_or_else(|| invalid("bad file size"))?;
+                    sizes.push(size);
+                }
+            } else if count > 1 {
+                return Err(invalid("missing file sizes"));
+            }
+            sizes.push(
+                folder
+                    .unpack_size()
+                    .checked_sub(total)
+                    .ok_or_else(|| invalid("bad file size"))?,
+            );
+        }
+        info.substream_sizes.push(sizes);
+    }
+    if has_sizes {
+        id = r.byte()?;
+    }
+
+    while id != END {
+        if id == CRC {
+            // Folders holding a single file with a known CRC have no digest of their own
+            let digests = info
+                .folders
+                .iter()
+                .zip(&counts)
+                .map(|(folder, &count)| {
+                    if count == 1 && folder.crc_defined {
+                        0
+                    } else {
+                        count
+                    }
+                })
+                .sum();
+            r.digests(digests)?;
+        } else {
+            r.skip_data()?;
+        }
+        id = r.byte()?;
+    }
+    Ok(())
+}
+
+fn read_files_info(r: &mut HeaderReader, streams: &StreamsInfo) -> io::Result<Vec<SevenZEntry>> {
+    let count = r.count()?;
+    let mut names = Vec::new();
+    let mut empty_streams = vec![false; count];
+    let mut empty_files = Vec::new();
+    let mut attributes = vec![None; count];
+
+    loop {
+        let id = r.byte()?;
+        if id == END {
+            break;
+        }
+        let size = r.count()?;
+        let mut data = HeaderReader::new(r.bytes(size)?);
+        match id {
+            EMPTY_STREAM => empty_streams = data.bits(count)?,
+            EMPTY_FILE => {
+                let empty = empty_streams.iter().filter(|&&empty| empty).count();
+                empty_files = data.bits(empty)?;
+            }
+            NAME => {
+                if data.byte()? != 0 {
+                    return Err(invalid("external file names are not supported"));
+                }
+                let units: Vec<u16> = data
+                    .rest()
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .collect();
+                names = units
+                    .split(|&unit| unit == 0)
+                    .take(count)
+                    .map(String::from_utf16_lossy)
+                    .collect();
+            }
+            WIN_ATTRIBUTES => {
+                let defined = data.defined_bits(count)?;
+                if data.byte()? != 0 {
+                    return Err(invalid("external attributes are not supported"));
+                }
+                for (attribute, defined) in attributes.iter_mut().zip(defined) {
+                    if defined {
+                        *attribute = Some(data.u32()?);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if names.len() != count {
+        return Err(invalid("missing file names"));
+    }
+
+    // Files with data take the folders' streams in order
+    let mut substreams = streams
+        .substream_sizes
+        .iter()
+        .enumerate()
+        .flat_map(|(folder, sizes)| {
+            sizes.iter().scan(0u64, move |offset, &size| {
+                let location = (folder, *offset);
+                *offset += size;
+                Some((location, size))
+            })
+        });
+
+    let mut entries = Vec::with_capacity(count);
+    let mut empty_index = 0;
+    for (index, name) in names.into_iter().enumerate() {
+        let is_directory_attribute =
+            attributes[index].is_some_and(|attributes| attributes & ATTRIBUTE_DIRECTORY != 0);
+        let entry = if empty_streams[index] {
+            let is_empty_file = empty_files.get(empty_index).copied().unwrap_or(false);
+            empty_index += 1;
+            SevenZEntry {
+                name,
+                size: 0,
+                is_dir: !is_empty_file || is_directory_attribute,
+                location: None,
+            }
+        } else {
+            let (location, size) = substreams
+                .next()
+                .ok_or_else(|| invalid("more files than streams"))?;
+            SevenZEntry {
+                name,
+                size,
+                is_dir: is_directory_attribute,
+                location: Some(location),
+            }
+        };
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Reads the fields of an archive header
+struct HeaderReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> HeaderReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn byte(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(count)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| invalid("truncated header"))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position..];
+        self.position = self.data.len();
+        rest
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    /// A variable-length number: the leading one bits of the first byte count the
+    /// bytes that follow, and the remaining bits are its most significant part
+    fn number(&mut self) -> io::Result<u64> {
+        let first = self.byte()?;
+        let mut value = 0u64;
+        for i in 0..8 {
+            let mask = 0x80u8 >> i;
+            if first & mask == 0 {
+                let high = (first & mask.wrapping_sub(1)) as u64;
+                return Ok(value | (high << (8 * i)));
+            }
+            value |= (self.byte()? as u64) << (8 * i);
+        }
+        Ok(value)
+    }
+
+    /// A number of items, each taking at least a byte of the header
+    fn count(&mut self) -> io::Result<usize> {
+        let count = self.number()?;
+        if count > self.data.len() as u64 {
+            return Err(invalid("bad item count"));
+        }
+        Ok(count as usize)
+    }
+
+    fn skip_data(&mut self) -> io::Result<()> {
+        let size = self.count()?;
+        self.bytes(size)?;
+        Ok(())
+    }
+
+    fn bits(&mut self, count: usize) -> io::Result<Vec<bool>> {
+        let bytes = self.bytes(count.div_ceil(8))?;
+        Ok((0..count)
+            .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+            .collect())
+    }
+
+    /// A bit vector preceded by a flag that sets every bit
+    fn defined_bits(&mut self, count: usize) -> io::Result<Vec<bool>> {
+        if self.byte()? != 0 {
+            Ok(vec![true; count])
+        } else {
+            self.bits(count)
+        }
+    }
+
+    /// Skip the CRCs of `count` streams, returning which are defined
+    fn digests(&mut self, count: usize) -> io::Result<Vec<bool>> {
+        let defined = self.defined_bits(count)?;
+        for _ in defined.iter().filter(|&&defined| defined) {
+            self.u32()?;
+        }
+        Ok(defined)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid 7z archive: {}", message),
+    )
+}
+
+fn unsupported(method: &[u8]) -> io::Error {
+    let message = match method {
+        [0x06, 0xF1, 0x07, 0x01] => "encrypted 7z archives are not supported".to_string(),
+        _ => format!(
+            "7z compression method {} is not supported",
+            method
+                .iter()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<String>()
+        ),
+    };
+    io::Error::new(io::ErrorKind::Unsupported, message)
+}
@@ -0,0 +1,158 @@
+//! Parallel directory walking.
+//!
+//! Listing a folder on a network share is dominated by round trips, so folders are
+//! read concurrently on a small thread pool. Entries are streamed back over a bounded
+//! channel in no particular order; the consumer stops the walk by dropping the receiver.
+
+use crate::utils::ignore::IgnoreRules;
+use rayon::{Scope, ThreadPoolBuilder};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::Arc;
+
+/// Entries buffered before walker threads wait for the consumer
+const CHANNEL_CAPACITY: usize = 4096;
+
+/// An entry found while walking
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalkEntry {
+    Folder(PathBuf),
+    File(PathBuf),
+    /// A folder or entry that could not be read; the walk goes on without it
+    Error(String),
+}
+
+/// Number of walker threads used when none is configured
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// Walk `folder` on `threads` threads, skipping what `ignore` excludes. Symlinks are
+/// followed only when `ignore` follows them. `folder` itself is yielded first unless
+/// it is excluded.
+pub fn walk_parallel(
+    folder: &Path,
+    ignore: Arc<IgnoreRules>,
+    threads: usize,
+) -> Receiver<WalkEntry> {
+    let (sender, receiver) = sync_channel(CHANNEL_CAPACITY);
+    let folder = folder.to_path_buf();
+
+    std::thread::spawn(move || {
+        if ignore.is_excluded(&folder, true) {
+            return;
+        }
+
+        let pool = match ThreadPoolBuilder::new().num_threads(threads.max(1)).build() {
+            Ok(pool) => pool,
+            Err(e) => {
+                let _ = sender.send(WalkEntry::Error(format!(
+                    "Failed to start directory walker: {}",
+                    e
+                )));
+                return;
+            }
+        };
+
+        pool.scope(|scope| visit_folder(scope, folder, &ignore, &sender));
+    });
+
+    receiver
+}
+
+fn visit_folder<'s>(
+    scope: &Scope<'s>,
+    folder: PathBuf,
+    ignore: &'s IgnoreRules,
+    sender: &'s SyncSender<WalkEntry>,
+) {
+    let entries = match std::fs::read_dir(&folder) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = sender.send(WalkEntry::Error(format!(
+                "Error reading directory {}: {}",
+                folder.display(),
+                e
+            )));
+            return;
+        }
+    };
+    // A closed channel means the consumer has stopped the walk
+    if sender.send(WalkEntry::Folder(folder.clone())).is_err() {
+        return;
+    }
+
+    for entry in entries {
+        let (path, file_type) = match entry.and_then(|e| Ok((e.path(), e.file_type()?))) {
+            Ok(entry) => entry,
+            Err(e) => {
+                let _ = sender.send(WalkEntry::Error(format!(
+                    "Error reading entry in {}: {}",
+                    folder.display(),
+                    e
+                )));
+                continue;
+            }
+        };
+
+        // Symlinks are followed only where the ignore rules allow it
+        let file_type = if file_type.is_symlink() && ignore.follows_symlinks() {
+            match std::fs::metadata(&path) {
+                Ok(metadata) if !ignore.is_ignored(&path, metadata.is_dir()) => {
+                    if !ignore.follow_symlink(&path) {
+                        continue;
+                    }
+                    metadata.file_type()
+                }
+                _ => continue,
+            }
+        } else {
+            file_type
+        };
+
+        if file_type.is_dir() {
+            if !ignore.is_ignored(&path, true) {
+                scope.spawn(move |scope| visit_folder(scope, path, ignore, sender));
+            }
+        } else if file_type.is_file()
+            && !ignore.is_ignored(&path, false)
+            && sender.send(WalkEntry::File(path)).is_err()
+        {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_walk_parallel() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for folder in ["a/b", "a/c", "skip/d"] {
+            std::fs::create_dir_all(root.join(folder)).unwrap();
+            std::fs::write(root.join(folder).join("model.stl"), "solid").unwrap();
+        }
+
+        let ignore = Arc::new(IgnoreRules::new(root, &["skip/".to_string()]));
+        let mut entries: Vec<_> = walk_parallel(root, ignore, 3).into_iter().collect();
+        entries.sort_by(|a, b| format!("{:?}", a).cmp(&format!("{:?}", b)));
+
+        assert_eq!(
+            entries,
+            vec![
+                WalkEntry::File(root.join("a/b/model.stl")),
+                WalkEntry::File(root.join("a/c/model.stl")),
+                WalkEntry::Folder(root.to_path_buf()),
+                WalkEntry::Folder(root.join("a")),
+                WalkEntry::Folder(root.join("a/b")),
+                WalkEntry::Folder(root.join("a/c")),
+            ]
+        );
+    }
+}
@@ -3,16 +3,24 @@ use axum::{
     http::{Request, StatusCode},
     middleware,
 };
+use glyptotheka_backend::api::browse_routes::BrowseState;
+use glyptotheka_backend::api::handlers::scan;
 use glyptotheka_backend::api::middleware::cors::cors_middleware;
+use glyptotheka_backend::api::routes::{create_state, router, AppState};
 use glyptotheka_backend::config::Config;
 use glyptotheka_backend::db::connection::create_pool;
+use glyptotheka_backend::models::scan_session::{ScanMode, ScanSessionStatus, ScanTrigger};
+use glyptotheka_backend::services::scanner::ScannerService;
+use glyptotheka_backend::utils::pagination::PaginationParams;
 use serde_json::Value;
 use std::fs;
+use std::io::Write;
 
 use tempfile::TempDir;
 use tower::util::ServiceExt;
+use zip::write::{SimpleFileOptions, ZipWriter};
 
-async fn setup_test_app() -> (axum::Router, TempDir, Config) {
+async fn setup_test_state() -> (AppState, BrowseState, TempDir, Config) {
     let temp_dir = TempDir::new().unwrap();
     let db_path = temp_dir.path().join("test.db");
     let cache_dir = temp_dir.path().join("cache");
@@ -32,13 +40,14 @@ async fn setup_test_app() -> (axum::Router, TempDir, Config) {
 
     let ignored_keywords = vec!["STL".to_string(), "PRESUPPORTED_STL".to_string()];
     let root_path = temp_dir.path().join("projects");
-    let app = glyptotheka_backend::api::routes::create_router(
-        pool,
-        cache_dir,
-        ignored_keywords,
-        root_path,
-    )
-    .layer(middleware::from_fn(cors_middleware));
+    let (state, browse_state) = create_state(pool, cache_dir, ignored_keywords, root_path);
+
+    (state, browse_state, temp_dir, config)
+}
+
+async fn setup_test_app() -> (axum::Router, TempDir, Config) {
+    let (state, browse_state, temp_dir, config) = setup_test_state().await;
+    let app = router(state, browse_state).layer(middleware::from_fn(cors_middleware));
 
     (app, temp_dir, config)
 }
@@ -93,6 +102,129 @@ async fn test_update_config() {
     assert_eq!(response.status(), StatusCode::OK);
 }
 
+#[tokio::test]
+async fn test_update_printer_profiles() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let request_body = serde_json::json!({
+        "printer_profiles": [
+            { "name": "Voron 2.4", "build_x": 350.0, "build_y": 350.0, "build_z": 340.0 }
+        ]
+    });
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/config")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_vec(&request_body).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    let profiles = json["printer_profiles"].as_array().unwrap();
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0]["name"], "Voron 2.4");
+
+    // Searching by a profile that was removed is a client error
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/search?printer=Prusa%20MK4")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_update_ignore_patterns() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let post_patterns = |patterns: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/config")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&serde_json::json!({ "ignore_patterns": patterns })).unwrap(),
+            ))
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(post_patterns(serde_json::json!([
+            "*_old/", "# drafts", "tmp*"
+        ])))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    // Comments and blank lines are not stored
+    assert_eq!(
+        json["ignore_patterns"],
+        serde_json::json!(["*_old/", "tmp*"])
+    );
+
+    // Malformed patterns are rejected
+    let response = app
+        .oneshot(post_patterns(serde_json::json!(["test["])))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_update_config_is_all_or_nothing() {
+    let (app, temp_dir, _config) = setup_test_app().await;
+    let (_, before) = request_json(&app, "GET", "/api/config", None).await;
+
+    // A valid root path and printer profile are not saved when the schedule is invalid
+    let new_root = temp_dir.path().join("new_root");
+    fs::create_dir_all(&new_root).unwrap();
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({
+            "root_path": new_root.to_str().unwrap(),
+            "printer_profiles": [
+                { "name": "Voron 2.4", "build_x": 350.0, "build_y": 350.0, "build_z": 340.0 }
+            ],
+            "scan_schedule": "not a schedule"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, after) = request_json(&app, "GET", "/api/config", None).await;
+    assert_eq!(after["root_path"], before["root_path"]);
+    assert_eq!(after["printer_profiles"], before["printer_profiles"]);
+    let (_, roots) = request_json(&app, "GET", "/api/roots", None).await;
+    assert!(roots
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|root| root["path"] != new_root.to_str().unwrap()));
+}
+
 #[tokio::test]
 async fn test_get_root_projects() {
     let (app, _temp_dir, _config) = setup_test_app().await;
@@ -218,3 +350,1605 @@ async fn test_cors_headers() {
 
     assert!(response.status() == StatusCode::OK || response.status() == StatusCode::NO_CONTENT);
 }
+
+#[tokio::test]
+async fn test_get_file_analysis_not_found() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/files/999/analysis")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_scan_events_complete_when_idle() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"].to_str().unwrap(),
+        "text/event-stream"
+    );
+
+    // Without a running scan the stream sends the final status and ends
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = String::from_utf8(body.to_vec()).unwrap();
+    assert!(body.starts_with("event: complete\n"));
+    assert!(body.contains("\"is_scanning\":false"));
+}
+
+#[tokio::test]
+async fn test_cancel_scan_without_running_scan() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/scan/cancel")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_scan_records_history() {
+    let (app, temp_dir, config) = setup_test_app().await;
+
+    let project = temp_dir.path().join("projects").join("project1");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("model.stl"), "solid test\nendsolid test\n").unwrap();
+
+    let pool = create_pool(&config.database_path).unwrap();
+    pool.get()
+        .unwrap()
+        .execute(
+            "INSERT INTO library_roots (name, path, created_at) VALUES ('default', ?1, 1)",
+            [temp_dir.path().join("projects").to_str().unwrap()],
+        )
+        .unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/scan")
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The event stream ends once the scan has finished
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/history?per_page=10")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["meta"]["total"], 1);
+    let session = &json["data"][0];
+    assert_eq!(session["status"], "completed");
+    assert_eq!(session["mode"], "full");
+    assert_eq!(session["projects_found"], 1);
+    assert_eq!(session["files_added"], 1);
+    assert!(session["completed_at"].is_i64());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/scan/history/{}", session["id"]))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let detail: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(detail["id"], session["id"]);
+    assert!(detail["errors"].is_array());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/history/999")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_update_scan_schedule() {
+    let (app, _temp_dir, _config) = setup_test_app().await;
+
+    let update = |body: Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/config")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    };
+
+    let response = app
+        .clone()
+        .oneshot(update(
+            serde_json::json!({ "scan_schedule": "every night" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .clone()
+        .oneshot(update(serde_json::json!({ "scan_interval_minutes": -5 })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(update(serde_json::json!({
+            "scan_schedule": "30 3 * * *",
+            "scan_interval_minutes": 120
+        })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["scan_schedule"], "30 3 * * *");
+    assert_eq!(json["scan_interval_minutes"], 120);
+}
+
+#[tokio::test]
+async fn test_scheduled_rescan_recorded_in_history() {
+    let (state, _browse_state, temp_dir, _config) = setup_test_state().await;
+
+    // Nothing to scan until a library root is configured
+    assert!(!scan::start_scheduled_rescan(&state).await.unwrap());
+
+    let project = temp_dir.path().join("projects").join("project1");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("model.stl"), "solid test\nendsolid test\n").unwrap();
+    state
+        .pool
+        .get()
+        .unwrap()
+        .execute(
+            "INSERT INTO library_roots (name, path, created_at) VALUES ('default', ?1, 1)",
+            [temp_dir.path().join("projects").to_str().unwrap()],
+        )
+        .unwrap();
+
+    // The scheduler never starts a scan while another one is running
+    state.scan_state.lock().await.is_scanning = true;
+    assert!(!scan::start_scheduled_rescan(&state).await.unwrap());
+    state.scan_state.lock().await.is_scanning = false;
+
+    assert!(scan::start_scheduled_rescan(&state).await.unwrap());
+    while state.scan_state.lock().await.is_scanning {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+
+    let (sessions, total) = state
+        .scan_session_repo
+        .list(&PaginationParams {
+            page: 1,
+            per_page: 10,
+        })
+        .unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(sessions[0].triggered_by, ScanTrigger::Scheduled);
+    assert_eq!(sessions[0].mode, ScanMode::Incremental);
+    assert_eq!(sessions[0].status, ScanSessionStatus::Completed);
+    assert_eq!(sessions[0].counts.files_added, 1);
+}
+
+#[tokio::test]
+async fn test_library_watcher_rescans_changed_folders() {
+    use glyptotheka_backend::api::watcher::LibraryWatcher;
+    use std::time::{Duration, Instant};
+
+    let (state, _browse_state, temp_dir, _config) = setup_test_state().await;
+    let roots = [
+        temp_dir.path().join("projects"),
+        temp_dir.path().join("more"),
+    ];
+    for (name, root) in ["default", "more"].iter().zip(&roots) {
+        fs::create_dir_all(root).unwrap();
+        state
+            .pool
+            .get()
+            .unwrap()
+            .execute(
+                "INSERT INTO library_roots (name, path, created_at) VALUES (?1, ?2, 1)",
+                [*name, root.to_str().unwrap()],
+            )
+            .unwrap();
+    }
+    let _watcher = LibraryWatcher::start(state.clone(), Duration::from_millis(200)).unwrap();
+    let indexed = || -> i64 {
+        state
+            .pool
+            .get()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM stl_files", [], |row| row.get(0))
+            .unwrap()
+    };
+    let wait_for = |count: i64| async move {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while indexed() != count && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(indexed(), count);
+    };
+
+    // Changes seen while another scan runs wait for it to finish
+    state.scan_state.lock().await.is_scanning = true;
+    for root in &roots {
+        let release = root.join("new_release");
+        fs::create_dir_all(&release).unwrap();
+        fs::write(release.join("model.stl"), "solid test\nendsolid test\n").unwrap();
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(indexed(), 0);
+    state.scan_state.lock().await.is_scanning = false;
+    wait_for(2).await;
+    while state.scan_state.lock().await.is_scanning {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Each root the changes were under is recorded as a scan of its own
+    let (sessions, total) = state
+        .scan_session_repo
+        .list(&PaginationParams {
+            page: 1,
+            per_page: 10,
+        })
+        .unwrap();
+    assert_eq!(total, 2);
+    for session in &sessions {
+        assert_eq!(session.triggered_by, ScanTrigger::Watcher);
+        assert_eq!(session.mode, ScanMode::Incremental);
+        assert_eq!(session.status, ScanSessionStatus::Completed);
+        assert_eq!(session.counts.files_added, 1);
+    }
+}
+
+/// Send a request with an optional JSON body and parse the JSON response, if any
+async fn request_json(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let body = match body {
+        Some(body) => Body::from(serde_json::to_vec(&body).unwrap()),
+        None => Body::empty(),
+    };
+    let response = app
+        .clone()
+        .oneshot(request.body(body).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+    )
+}
+
+/// Download a ZIP and list its entries, sorted
+async fn download_zip(
+    app: &axum::Router,
+    uri: &str,
+) -> (Vec<String>, zip::ZipArchive<std::io::Cursor<Vec<u8>>>) {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(body.to_vec())).unwrap();
+    let mut entries: Vec<String> = (0..archive.len())
+        .map(|i| archive.by_index(i).unwrap().name().to_string())
+        .collect();
+    entries.sort();
+    (entries, archive)
+}
+
+#[tokio::test]
+async fn test_library_roots() {
+    let (app, temp_dir, _config) = setup_test_app().await;
+
+    let nas = temp_dir.path().join("nas");
+    let ssd = temp_dir.path().join("ssd");
+    for project in [nas.join("dragon"), ssd.join("tower")] {
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("model.stl"), "solid test\nendsolid test\n").unwrap();
+    }
+
+    let (status, nas_root) = request_json(
+        &app,
+        "POST",
+        "/api/roots",
+        Some(serde_json::json!({ "name": "nas", "path": nas.to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, ssd_root) = request_json(
+        &app,
+        "POST",
+        "/api/roots",
+        Some(serde_json::json!({ "name": "ssd", "path": ssd.to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Names are unique and roots may not nest
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/roots",
+        Some(serde_json::json!({ "name": "NAS", "path": temp_dir.path().join("projects").to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/roots",
+        Some(
+            serde_json::json!({ "name": "dragons", "path": nas.join("dragon").to_str().unwrap() }),
+        ),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, roots) = request_json(&app, "GET", "/api/roots", None).await;
+    assert_eq!(roots.as_array().unwrap().len(), 2);
+
+    // Without a root id every root is scanned, each recorded on its own
+    let (status, _) = request_json(&app, "POST", "/api/scan", Some(serde_json::json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    let (_, history) = request_json(&app, "GET", "/api/scan/history", None).await;
+    assert_eq!(history["meta"]["total"], 2);
+
+    // Browsing is namespaced per root
+    let (status, contents) = request_json(
+        &app,
+        "GET",
+        &format!("/api/roots/{}/browse", nas_root["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let projects = contents["projects"].as_array().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0]["project"]["name"], "dragon");
+    assert_eq!(projects[0]["project"]["root_id"], nas_root["id"]);
+
+    let (_, breadcrumb) = request_json(
+        &app,
+        "GET",
+        &format!("/api/roots/{}/browse/breadcrumb/dragon", nas_root["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(breadcrumb[0]["name"], "nas");
+    assert_eq!(breadcrumb[1]["path"], "dragon");
+
+    let (status, _) = request_json(&app, "GET", "/api/roots/999/browse", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Search can be limited to one root
+    let (_, results) = request_json(
+        &app,
+        "GET",
+        &format!("/api/search?root_id={}", ssd_root["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(results["meta"]["total"], 1);
+    assert_eq!(results["data"][0]["name"], "tower");
+
+    // Removing a root drops everything indexed from it
+    let (status, _) = request_json(
+        &app,
+        "DELETE",
+        &format!("/api/roots/{}", nas_root["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, results) = request_json(&app, "GET", "/api/search", None).await;
+    assert_eq!(results["meta"]["total"], 1);
+    assert_eq!(results["data"][0]["name"], "tower");
+
+    // The configured root path is the default root, which may not overlap the others
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "root_path": ssd.join("tower").to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let hdd = temp_dir.path().join("hdd");
+    fs::create_dir_all(hdd.join("keep")).unwrap();
+    fs::write(hdd.join("keep/model.stl"), "solid test\nendsolid test\n").unwrap();
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "root_path": hdd.to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = request_json(&app, "POST", "/api/scan", Some(serde_json::json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/scan/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+
+    // Browsing without a root id follows the default root
+    let (_, contents) = request_json(&app, "GET", "/api/browse", None).await;
+    let projects = contents["projects"].as_array().unwrap();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0]["project"]["name"], "keep");
+
+    // Moving the default root drops what was indexed from its old path
+    let attic = temp_dir.path().join("attic");
+    fs::create_dir_all(&attic).unwrap();
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "root_path": attic.to_str().unwrap() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, results) = request_json(&app, "GET", "/api/search", None).await;
+    assert_eq!(results["meta"]["total"], 1);
+    assert_eq!(results["data"][0]["name"], "tower");
+    let (_, roots) = request_json(&app, "GET", "/api/roots", None).await;
+    let default_root = roots
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|root| root["name"] == "default")
+        .unwrap();
+    assert_eq!(
+        default_root["path"],
+        attic.canonicalize().unwrap().to_str().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_download_file_from_zip_archive() {
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let root = temp_dir.path().join("projects");
+    let mut zip = ZipWriter::new(fs::File::create(root.join("Release.zip")).unwrap());
+    zip.start_file("Knight/knight.stl", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"solid knight\nendsolid knight\n").unwrap();
+    zip.start_file("Knight/render.png", SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(b"FAKE PNG DATA").unwrap();
+    zip.finish().unwrap();
+
+    ScannerService::new(pool.clone())
+        .scan(root.to_str().unwrap())
+        .unwrap();
+    let (stl_id, image_id): (i64, i64) = {
+        let conn = pool.get().unwrap();
+        (
+            conn.query_row("SELECT id FROM stl_files", [], |row| row.get(0))
+                .unwrap(),
+            conn.query_row("SELECT id FROM image_files", [], |row| row.get(0))
+                .unwrap(),
+        )
+    };
+
+    // Members are served straight out of the archive
+    for (uri, expected) in [
+        (
+            format!("/api/files/{}?type=stl", stl_id),
+            &b"solid knight\nendsolid knight\n"[..],
+        ),
+        (
+            format!("/api/files/images/{}", image_id),
+            &b"FAKE PNG DATA"[..],
+        ),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], expected);
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_follow_symlinks() {
+    use glyptotheka_backend::services::rescan::RescanService;
+    use std::os::unix::fs::symlink;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    // A favourites folder linking into the library, and a shared folder outside
+    // it whose contents link back to themselves
+    let root = temp_dir.path().join("projects");
+    let external = temp_dir.path().join("external");
+    fs::create_dir_all(root.join("Creator/Knight")).unwrap();
+    fs::create_dir_all(root.join("Favourites")).unwrap();
+    fs::create_dir_all(external.join("Dragon")).unwrap();
+    fs::write(root.join("Creator/Knight/knight.stl"), "solid knight").unwrap();
+    fs::write(external.join("Dragon/dragon.stl"), "solid dragon").unwrap();
+    symlink(root.join("Creator/Knight"), root.join("Favourites/Knight")).unwrap();
+    symlink(&root, root.join("Favourites/Everything")).unwrap();
+    symlink(&external, root.join("Shared")).unwrap();
+    symlink(&external, external.join("Dragon/Loop")).unwrap();
+
+    let scan = || {
+        ScannerService::new(pool.clone())
+            .scan(root.to_str().unwrap())
+            .unwrap()
+    };
+    let indexed_files = || -> Vec<String> {
+        let conn = pool.get().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT file_path FROM stl_files ORDER BY file_path")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+
+    // Symlinks are not followed by default
+    scan();
+    assert_eq!(indexed_files().len(), 1);
+
+    let (status, config) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "follow_symlinks": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(config["follow_symlinks"], true);
+
+    // Linked projects are indexed once, under their real path
+    let result = scan();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(
+        indexed_files(),
+        vec![
+            root.join("Creator/Knight/knight.stl")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            root.join("Shared/Dragon/dragon.stl")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        ]
+    );
+
+    // Browsing shows the link where it is and the project it leads to
+    let (_, favourites) = request_json(&app, "GET", "/api/browse/Favourites", None).await;
+    let folders = favourites["folders"].as_array().unwrap();
+    assert_eq!(folders.len(), 2);
+    assert_eq!(folders[1]["path"], "Favourites/Knight");
+    assert_eq!(folders[1]["link_target"], "Creator/Knight");
+
+    let (status, knight) = request_json(&app, "GET", "/api/browse/Favourites/Knight", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(knight["current_path"], "Favourites/Knight");
+    assert_eq!(knight["link_target"], "Creator/Knight");
+    assert_eq!(knight["is_leaf_project"], true);
+    assert_eq!(
+        knight["project_details"]["project"]["full_path"],
+        root.join("Creator/Knight").to_str().unwrap()
+    );
+
+    let (status, dragon) = request_json(&app, "GET", "/api/browse/Shared/Dragon", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(dragon["is_leaf_project"], true);
+
+    // Following symlinks does not open the rest of the filesystem
+    for uri in [
+        "/api/browse//etc",
+        "/api/browse/Shared/../../..",
+        "/api/browse/./Shared",
+    ] {
+        let (status, _) = request_json(&app, "GET", uri, None).await;
+        assert_ne!(status, StatusCode::OK, "{}", uri);
+    }
+    fs::create_dir_all(temp_dir.path().join("private")).unwrap();
+    symlink(
+        temp_dir.path().join("private"),
+        external.join("Dragon/Private"),
+    )
+    .unwrap();
+    let (status, _) = request_json(&app, "GET", "/api/browse/Shared/Dragon/Private", None).await;
+    assert_ne!(status, StatusCode::OK);
+
+    // Rescans walk the same links, including the one back to the library root, and
+    // record the new link outside
+    let result = RescanService::new(pool.clone())
+        .rescan(root.to_str().unwrap())
+        .unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_added + result.files_removed, 0);
+    let links: Vec<(bool, i64)> = {
+        let conn = pool.get().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT external, COUNT(*) FROM folder_links GROUP BY external")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+    assert_eq!(links, vec![(false, 2), (true, 2)]);
+    let (status, _) = request_json(&app, "GET", "/api/browse/Shared/Dragon/Private", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Turning it off again drops what was only reachable through links
+    request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "follow_symlinks": false })),
+    )
+    .await;
+    let result = RescanService::new(pool.clone())
+        .rescan(root.to_str().unwrap())
+        .unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(indexed_files().len(), 1);
+    let links: i64 = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM folder_links", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(links, 0);
+}
+
+#[tokio::test]
+async fn test_sidecar_write_back() {
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let knight = temp_dir.path().join("projects/Knight");
+    fs::create_dir_all(&knight).unwrap();
+    fs::write(knight.join("knight.stl"), "solid knight").unwrap();
+    let sidecar = knight.join("glyptotheka.json");
+    fs::write(
+        &sidecar,
+        r#"{"tags": ["fantasy"], "designer": "Cast'N'Play", "painter_notes": "keep"}"#,
+    )
+    .unwrap();
+
+    let scan = || {
+        let result = ScannerService::new(pool.clone())
+            .scan(temp_dir.path().join("projects").to_str().unwrap())
+            .unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        ProjectRepository::new(pool.clone())
+            .get_by_path(knight.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .id
+    };
+    let read_sidecar =
+        || -> Value { serde_json::from_slice(&fs::read(&sidecar).unwrap()).unwrap() };
+    let project_id = scan();
+
+    // Without write-back the file is left alone
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        &format!("/api/projects/{}/tags", project_id),
+        Some(serde_json::json!({ "tagName": "hero" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(read_sidecar()["tags"], serde_json::json!(["fantasy"]));
+
+    let (status, config) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "write_sidecars": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(config["write_sidecars"], true);
+
+    request_json(
+        &app,
+        "POST",
+        &format!("/api/projects/{}/tags", project_id),
+        Some(serde_json::json!({ "tagName": "painted" })),
+    )
+    .await;
+    request_json(
+        &app,
+        "DELETE",
+        &format!("/api/projects/{}/tags?tagName=fantasy", project_id),
+        None,
+    )
+    .await;
+    assert_eq!(
+        read_sidecar(),
+        serde_json::json!({
+            "tags": ["hero", "painted"],
+            "author": "Cast'N'Play",
+            "painter_notes": "keep"
+        })
+    );
+
+    // The tags come back from the file after the database is wiped
+    ProjectRepository::new(pool.clone()).clear_all().unwrap();
+    let project_id = scan();
+    let (_, project) =
+        request_json(&app, "GET", &format!("/api/projects/{}", project_id), None).await;
+    let tags: Vec<&str> = project["tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| tag["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(tags, vec!["hero", "painted"]);
+    assert_eq!(project["metadata"]["author"], "Cast'N'Play");
+}
+
+#[tokio::test]
+async fn test_update_project_metadata() {
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+    use glyptotheka_backend::services::rescan::RescanService;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let root = temp_dir.path().join("projects");
+    let knight = root.join("Knight");
+    fs::create_dir_all(&knight).unwrap();
+    fs::write(knight.join("knight.stl"), "solid knight").unwrap();
+    fs::write(knight.join("README.md"), "Imported description").unwrap();
+    ScannerService::new(pool.clone())
+        .scan(root.to_str().unwrap())
+        .unwrap();
+    let project_id = ProjectRepository::new(pool.clone())
+        .get_by_path(knight.to_str().unwrap())
+        .unwrap()
+        .unwrap()
+        .id;
+    let uri = format!("/api/projects/{}", project_id);
+
+    let (status, project) = request_json(
+        &app,
+        "PATCH",
+        &uri,
+        Some(serde_json::json!({
+            "description": "A **paladin** on foot",
+            "designer": "Cast'N'Play",
+            "license": "CC-BY-NC",
+            "source_url": "https://example.com/knight",
+            "release_date": "2024-02-29",
+            "scale": "32mm"
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(project["description"], "A **paladin** on foot");
+    assert_eq!(project["metadata"]["author"], "Cast'N'Play");
+    assert_eq!(project["metadata"]["release_date"], "2024-02-29");
+    assert_eq!(project["metadata"]["scale"], "32mm");
+
+    // Invalid values are rejected without changing anything
+    for invalid in [
+        serde_json::json!({ "source_url": "ftp://example.com/knight" }),
+        serde_json::json!({ "release_date": "2023-02-29" }),
+        serde_json::json!({ "license": "x".repeat(201) }),
+    ] {
+        let (status, _) = request_json(&app, "PATCH", &uri, Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+    let (status, _) = request_json(
+        &app,
+        "PATCH",
+        "/api/projects/999999",
+        Some(serde_json::json!({ "scale": "1:10" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The description is searchable
+    let (_, results) = request_json(&app, "GET", "/api/search?q=paladin", None).await;
+    assert_eq!(results["data"].as_array().unwrap().len(), 1);
+    assert_eq!(results["data"][0]["id"], project_id);
+
+    // Edits outlive rescans while the sidecar files are unchanged
+    RescanService::new(pool.clone())
+        .rescan(root.to_str().unwrap())
+        .unwrap();
+    let (_, project) = request_json(&app, "GET", &uri, None).await;
+    assert_eq!(project["description"], "A **paladin** on foot");
+    assert_eq!(project["metadata"]["license"], "CC-BY-NC");
+
+    // Empty strings clear fields; missing ones are kept
+    let (_, project) = request_json(
+        &app,
+        "PATCH",
+        &uri,
+        Some(serde_json::json!({ "description": "", "license": "" })),
+    )
+    .await;
+    assert_eq!(project["description"], Value::Null);
+    assert_eq!(project["metadata"]["license"], Value::Null);
+    assert_eq!(project["metadata"]["author"], "Cast'N'Play");
+    let (_, results) = request_json(&app, "GET", "/api/search?q=paladin", None).await;
+    assert!(results["data"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_creators() {
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+    use glyptotheka_backend::services::rescan::RescanService;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let root = temp_dir.path().join("projects");
+    for (folder, file) in [
+        ("Creator A/Release 1/Knight", "knight.stl"),
+        ("Creator A/Release 1/Archer", "archer.stl"),
+        ("Creator B/Dragon", "dragon.stl"),
+    ] {
+        fs::create_dir_all(root.join(folder)).unwrap();
+        fs::write(root.join(folder).join(file), "solid model").unwrap();
+    }
+    fs::write(
+        root.join("Creator B/Dragon/glyptotheka.json"),
+        r#"{"author": "Guest Sculptor"}"#,
+    )
+    .unwrap();
+    ScannerService::new(pool.clone())
+        .scan(root.to_str().unwrap())
+        .unwrap();
+    let project_id = |folder: &str| {
+        ProjectRepository::new(pool.clone())
+            .get_by_path(root.join(folder).to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .id
+    };
+    let creators = |list: &Value| -> Vec<(String, u64)> {
+        list.as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["name"].as_str().unwrap().to_string(),
+                    c["project_count"].as_u64().unwrap(),
+                )
+            })
+            .collect()
+    };
+
+    // Without a creator folder level only sidecar authors name creators
+    let (_, list) = request_json(&app, "GET", "/api/creators", None).await;
+    assert_eq!(creators(&list), vec![("Guest Sculptor".to_string(), 1)]);
+
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "creator_folder_level": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, list) = request_json(&app, "GET", "/api/creators", None).await;
+    assert_eq!(
+        creators(&list),
+        vec![
+            ("Creator A".to_string(), 2),
+            ("Creator B".to_string(), 0),
+            ("Guest Sculptor".to_string(), 1),
+        ]
+    );
+
+    // Latest releases come first
+    let knight = project_id("Creator A/Release 1/Knight");
+    request_json(
+        &app,
+        "PATCH",
+        &format!("/api/projects/{}", knight),
+        Some(serde_json::json!({ "release_date": "2024-05-01" })),
+    )
+    .await;
+    let creator_id = list[0]["id"].as_i64().unwrap();
+    let (status, creator) =
+        request_json(&app, "GET", &format!("/api/creators/{}", creator_id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(creator["latest_projects"][0]["name"], "Knight");
+    assert_eq!(creator["latest_projects"].as_array().unwrap().len(), 2);
+
+    let (_, results) = request_json(&app, "GET", "/api/search?creator=creator%20a", None).await;
+    assert_eq!(results["meta"]["total"], 2);
+    let (status, _) = request_json(&app, "GET", "/api/search?creator=Nobody", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A creator set by hand passes on to subfolders and outlives rescans
+    let release = format!("/api/projects/{}", project_id("Creator A/Release 1"));
+    let (status, _) = request_json(
+        &app,
+        "PATCH",
+        &release,
+        Some(serde_json::json!({ "creator": "Studio X" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    RescanService::new(pool.clone())
+        .rescan(root.to_str().unwrap())
+        .unwrap();
+    let (_, project) = request_json(&app, "GET", &format!("/api/projects/{}", knight), None).await;
+    assert_eq!(project["creator"]["name"], "Studio X");
+    let (_, list) = request_json(&app, "GET", "/api/creators?q=studio", None).await;
+    assert_eq!(creators(&list), vec![("Studio X".to_string(), 2)]);
+
+    // Clearing it goes back to the folder's creator
+    request_json(
+        &app,
+        "PATCH",
+        &release,
+        Some(serde_json::json!({ "creator": "" })),
+    )
+    .await;
+    let (_, project) = request_json(&app, "GET", &format!("/api/projects/{}", knight), None).await;
+    assert_eq!(project["creator"]["name"], "Creator A");
+    let (_, list) = request_json(&app, "GET", "/api/creators?q=studio", None).await;
+    assert!(list.as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_releases() {
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+    use glyptotheka_backend::services::rescan::RescanService;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let root = temp_dir.path().join("projects");
+    for (folder, file) in [
+        ("Creator A/2024-03 Dragons/Red Dragon", "red.stl"),
+        ("Creator A/2024-03 Dragons/Wyrmling", "wyrmling.stl"),
+        ("Creator A/2024-04/Knight", "knight.stl"),
+        ("Creator B/Orc", "orc.stl"),
+    ] {
+        fs::create_dir_all(root.join(folder)).unwrap();
+        fs::write(root.join(folder).join(file), "solid model").unwrap();
+    }
+    image::RgbaImage::from_pixel(16, 16, image::Rgba([200, 30, 30, 255]))
+        .save(root.join("Creator A/2024-03 Dragons/Red Dragon/render.png"))
+        .unwrap();
+    ScannerService::new(pool.clone())
+        .scan(root.to_str().unwrap())
+        .unwrap();
+    let project_id = |folder: &str| {
+        ProjectRepository::new(pool.clone())
+            .get_by_path(root.join(folder).to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .id
+    };
+    let names = |list: &Value, key: &str| -> Vec<String> {
+        list[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Release folders are detected once a pattern is set, newest first
+    let (_, list) = request_json(&app, "GET", "/api/releases", None).await;
+    assert!(list.as_array().unwrap().is_empty());
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({ "release_folder_pattern": "Releases *" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/config",
+        Some(serde_json::json!({
+            "release_folder_pattern": "YYYY-MM*",
+            "creator_folder_level": 1
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, list) = request_json(&app, "GET", "/api/releases", None).await;
+    let list = list.as_array().unwrap();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0]["name"], "2024-04");
+    assert_eq!(list[1]["name"], "2024-03 Dragons");
+    assert_eq!(list[1]["release_date"], "2024-03-01");
+    assert_eq!(list[1]["creator"]["name"], "Creator A");
+    assert_eq!(list[1]["project_count"], 2);
+    assert_eq!(list[1]["auto_detected"], true);
+    let dragons = format!("/api/releases/{}", list[1]["id"]);
+    let april = format!("/api/releases/{}", list[0]["id"]);
+    let (_, by_creator) = request_json(
+        &app,
+        "GET",
+        &format!("/api/releases?creator_id={}", list[1]["creator"]["id"]),
+        None,
+    )
+    .await;
+    assert_eq!(by_creator.as_array().unwrap().len(), 2);
+
+    // An author set on a release folder names the creator of that release only
+    let (status, _) = request_json(
+        &app,
+        "PATCH",
+        &format!("/api/projects/{}", project_id("Creator A/2024-04")),
+        Some(serde_json::json!({ "author": "Guest Sculptor" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, release) = request_json(&app, "GET", &april, None).await;
+    assert_eq!(release["creator"]["name"], "Guest Sculptor");
+    let (_, release) = request_json(&app, "GET", &dragons, None).await;
+    assert_eq!(release["creator"]["name"], "Creator A");
+
+    let (_, release) = request_json(&app, "GET", &dragons, None).await;
+    assert_eq!(names(&release, "projects"), vec!["Red Dragon", "Wyrmling"]);
+
+    // The cover is composed from the projects' images
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{}/cover", dragons))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let (status, _) = request_json(&app, "GET", &format!("{}/cover", april), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The ZIP has a folder per project
+    let (entries, _) = download_zip(&app, &format!("{}/download", dragons)).await;
+    assert_eq!(
+        entries,
+        vec![
+            "Red Dragon/red.stl",
+            "Red Dragon/render.png",
+            "Wyrmling/wyrmling.stl"
+        ]
+    );
+
+    // Releases made by hand, with projects added, moved and removed
+    let orc = project_id("Creator B/Orc");
+    let knight = project_id("Creator A/2024-04/Knight");
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/releases",
+        Some(serde_json::json!({ "name": "Gifts", "release_date": "2024-13-01" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, release) = request_json(
+        &app,
+        "POST",
+        "/api/releases",
+        Some(serde_json::json!({
+            "name": "Gifts",
+            "creator": "Creator B",
+            "project_ids": [orc, knight]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(release["auto_detected"], false);
+    assert_eq!(names(&release, "projects"), vec!["Orc", "Knight"]);
+    let gifts = format!("/api/releases/{}", release["id"]);
+
+    let (_, release) = request_json(
+        &app,
+        "POST",
+        &format!("{}/projects", gifts),
+        Some(serde_json::json!({ "project_id": project_id("Creator A/2024-03 Dragons/Wyrmling"), "position": 0 })),
+    )
+    .await;
+    assert_eq!(
+        names(&release, "projects"),
+        vec!["Wyrmling", "Orc", "Knight"]
+    );
+    let (_, release) = request_json(
+        &app,
+        "POST",
+        &format!("{}/projects", gifts),
+        Some(serde_json::json!({ "project_id": orc })),
+    )
+    .await;
+    assert_eq!(
+        names(&release, "projects"),
+        vec!["Wyrmling", "Knight", "Orc"]
+    );
+    let (_, release) = request_json(
+        &app,
+        "PUT",
+        &format!("{}/projects", gifts),
+        Some(serde_json::json!({ "project_ids": [knight, orc] })),
+    )
+    .await;
+    assert_eq!(names(&release, "projects"), vec!["Knight", "Orc"]);
+    let (status, _) = request_json(
+        &app,
+        "PUT",
+        &format!("{}/projects", gifts),
+        Some(serde_json::json!({ "project_ids": [knight, 999_999] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, release) =
+        request_json(&app, "DELETE", &format!("{}/projects/{}", gifts, orc), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(names(&release, "projects"), vec!["Knight"]);
+    let (status, _) =
+        request_json(&app, "DELETE", &format!("{}/projects/{}", gifts, orc), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // An edited release is no longer synced with its folder; the others follow theirs
+    let (_, release) = request_json(
+        &app,
+        "PATCH",
+        &dragons,
+        Some(serde_json::json!({ "name": "March Dragons" })),
+    )
+    .await;
+    assert_eq!(release["auto_detected"], false);
+    fs::remove_dir_all(root.join("Creator A/2024-04")).unwrap();
+    RescanService::new(pool.clone())
+        .rescan(root.to_str().unwrap())
+        .unwrap();
+    let (_, list) = request_json(&app, "GET", "/api/releases", None).await;
+    let release_names: Vec<&str> = list
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(release_names, vec!["March Dragons", "Gifts"]);
+    let (status, _) = request_json(&app, "GET", &april, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, release) = request_json(&app, "GET", &gifts, None).await;
+    assert!(release["projects"].as_array().unwrap().is_empty());
+
+    let (status, _) = request_json(&app, "DELETE", &gifts, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = request_json(&app, "GET", &gifts, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Any release name makes a valid download header
+    let (_, release) = request_json(
+        &app,
+        "POST",
+        "/api/releases",
+        Some(serde_json::json!({
+            "name": "Élan \"Vital\"\nPart 2",
+            "project_ids": [project_id("Creator A/2024-03 Dragons/Wyrmling")]
+        })),
+    )
+    .await;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/releases/{}/download", release["id"]))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"_lan _Vital__Part 2.zip\"; \
+         filename*=UTF-8''%C3%89lan%20%22Vital%22%0APart%202.zip"
+    );
+}
+
+#[tokio::test]
+async fn test_collections() {
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+
+    let (state, browse_state, temp_dir, _config) = setup_test_state().await;
+    let pool = state.pool.clone();
+    let app = router(state, browse_state);
+
+    let root = temp_dir.path().join("projects");
+    for (folder, files) in [
+        ("Heroes/Knight", &["knight.stl", "shield.stl"][..]),
+        ("Heroes/Knight/PRESUPPORTED_STL", &["knight.stl"][..]),
+        ("Monsters/Orc", &["orc.stl", "axe.stl"][..]),
+    ] {
+        fs::create_dir_all(root.join(folder)).unwrap();
+        for file in files {
+            fs::write(root.join(folder).join(file), format!("solid {}", folder)).unwrap();
+        }
+    }
+    ScannerService::new(pool.clone())
+        .scan(root.to_str().unwrap())
+        .unwrap();
+    let project_id = |folder: &str| {
+        ProjectRepository::new(pool.clone())
+            .get_by_path(root.join(folder).to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .id
+    };
+    let knight = project_id("Heroes/Knight");
+    let orc = project_id("Monsters/Orc");
+    let axe: i64 = pool
+        .get()
+        .unwrap()
+        .query_row(
+            "SELECT id FROM stl_files WHERE filename = 'axe.stl'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    let items = |collection: &Value| -> Vec<(String, i64)> {
+        collection["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| {
+                let name = match item["stl_file"]["filename"].as_str() {
+                    Some(filename) => filename,
+                    None => item["project"]["name"].as_str().unwrap(),
+                };
+                (name.to_string(), item["quantity"].as_i64().unwrap())
+            })
+            .collect()
+    };
+
+    let (status, _) = request_json(
+        &app,
+        "POST",
+        "/api/collections",
+        Some(serde_json::json!({ "name": " " })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, collection) = request_json(
+        &app,
+        "POST",
+        "/api/collections",
+        Some(serde_json::json!({ "name": "Next D&D session" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let uri = format!("/api/collections/{}", collection["id"]);
+    let items_uri = format!("{}/items", uri);
+
+    // Items are whole projects or single model files
+    request_json(
+        &app,
+        "POST",
+        &items_uri,
+        Some(serde_json::json!({ "project_id": knight, "quantity": 4 })),
+    )
+    .await;
+    let (status, collection) = request_json(
+        &app,
+        "POST",
+        &items_uri,
+        Some(serde_json::json!({ "stl_file_id": axe, "notes": "print in PETG", "position": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        items(&collection),
+        vec![("axe.stl".to_string(), 1), ("Knight".to_string(), 4)]
+    );
+    assert_eq!(collection["items"][0]["project"]["name"], "Orc");
+    assert_eq!(collection["items"][0]["notes"], "print in PETG");
+    for body in [
+        serde_json::json!({ "project_id": knight }),
+        serde_json::json!({ "project_id": knight, "stl_file_id": axe }),
+        serde_json::json!({ "project_id": 999_999 }),
+        serde_json::json!({ "project_id": orc, "quantity": 0 }),
+    ] {
+        let (status, _) = request_json(&app, "POST", &items_uri, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    }
+
+    // Items are edited and reordered
+    let axe_item = collection["items"][0]["id"].as_i64().unwrap();
+    let knight_item = collection["items"][1]["id"].as_i64().unwrap();
+    let (_, collection) = request_json(
+        &app,
+        "PATCH",
+        &format!("{}/{}", items_uri, axe_item),
+        Some(serde_json::json!({ "quantity": 2, "position": 5 })),
+    )
+    .await;
+    assert_eq!(
+        items(&collection),
+        vec![("Knight".to_string(), 4), ("axe.stl".to_string(), 2)]
+    );
+    let (_, collection) = request_json(
+        &app,
+        "PUT",
+        &items_uri,
+        Some(serde_json::json!({ "item_ids": [axe_item, knight_item] })),
+    )
+    .await;
+    assert_eq!(
+        items(&collection),
+        vec![("axe.stl".to_string(), 2), ("Knight".to_string(), 4)]
+    );
+    let (status, _) = request_json(
+        &app,
+        "PUT",
+        &items_uri,
+        Some(serde_json::json!({ "item_ids": [axe_item] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Project detail counts the collections holding the project or its files
+    let (_, project) = request_json(&app, "GET", &format!("/api/projects/{}", orc), None).await;
+    assert_eq!(project["collection_count"], 1);
+    let (_, list) = request_json(&app, "GET", "/api/collections", None).await;
+    assert_eq!(list[0]["item_count"], 2);
+
+    // The ZIP has the referenced files and a list of what to print
+    let (entries, mut archive) = download_zip(&app, &format!("{}/download", uri)).await;
+    assert_eq!(
+        entries,
+        vec![
+            "Knight/knight.stl",
+            "Knight/shield.stl",
+            "Orc/axe.stl",
+            "collection.txt"
+        ]
+    );
+    let mut manifest = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name("collection.txt").unwrap(),
+        &mut manifest,
+    )
+    .unwrap();
+    assert_eq!(
+        manifest,
+        "Next D&D session\n\n2 x Orc/axe.stl - print in PETG\n4 x Knight/\n"
+    );
+
+    // A file picked from a project that is in the collection too is written once
+    let (status, collection) = request_json(
+        &app,
+        "POST",
+        &items_uri,
+        Some(serde_json::json!({ "project_id": orc })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let orc_item = collection["items"][2]["id"].as_i64().unwrap();
+
+    // ...and listed under the entry it was written to
+    let supported: i64 = pool
+        .get()
+        .unwrap()
+        .query_row(
+            "SELECT id FROM stl_files WHERE file_path LIKE '%PRESUPPORTED_STL%'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    let (status, collection) = request_json(
+        &app,
+        "POST",
+        &items_uri,
+        Some(serde_json::json!({ "stl_file_id": supported })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let supported_item = collection["items"][3]["id"].as_i64().unwrap();
+    let (entries, mut archive) = download_zip(&app, &format!("{}/download", uri)).await;
+    assert_eq!(
+        entries,
+        vec![
+            "Knight/knight.stl",
+            "Knight/shield.stl",
+            "Orc/axe.stl",
+            "Orc/orc.stl",
+            "PRESUPPORTED_STL/knight.stl",
+            "collection.txt"
+        ]
+    );
+    let mut manifest = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name("collection.txt").unwrap(),
+        &mut manifest,
+    )
+    .unwrap();
+    let supported_entry = manifest
+        .lines()
+        .last()
+        .unwrap()
+        .strip_prefix("1 x ")
+        .unwrap()
+        .to_string();
+    let mut contents = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name(&supported_entry).unwrap(),
+        &mut contents,
+    )
+    .unwrap();
+    assert_eq!(contents, "solid Heroes/Knight/PRESUPPORTED_STL");
+    for item in [orc_item, supported_item] {
+        let (status, _) =
+            request_json(&app, "DELETE", &format!("{}/{}", items_uri, item), None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{}/download", uri))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"Next D&D session.zip\"; \
+         filename*=UTF-8''Next%20D&D%20session.zip"
+    );
+
+    let (status, collection) =
+        request_json(&app, "DELETE", &format!("{}/{}", items_uri, axe_item), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(items(&collection), vec![("Knight".to_string(), 4)]);
+    let (_, project) = request_json(&app, "GET", &format!("/api/projects/{}", orc), None).await;
+    assert_eq!(project["collection_count"], 0);
+
+    let (status, _) = request_json(&app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = request_json(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -1,10 +1,19 @@
 #[cfg(test)]
 mod rescan_tests {
+    use glyptotheka_backend::config::{ConfigService, UpdateConfigRequest};
     use glyptotheka_backend::db::connection::create_pool;
+    use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+    use glyptotheka_backend::models::scan_progress::ScanPhase;
+    use glyptotheka_backend::services::image_cache::ImageCacheService;
+    use glyptotheka_backend::services::mesh_analysis::MeshAnalysisService;
     use glyptotheka_backend::services::rescan::RescanService;
+    use glyptotheka_backend::services::scan_progress::{ScanCancellation, ScanProgressTracker};
     use std::fs;
+    use std::io::Write;
     use std::path::Path;
+    use std::time::{Duration, SystemTime};
     use tempfile::TempDir;
+    use zip::write::{SimpleFileOptions, ZipWriter};
 
     fn setup_test_env() -> (TempDir, String) {
         let temp_dir = TempDir::new().unwrap();
@@ -82,4 +91,675 @@ mod rescan_tests {
         let scan_result = result.unwrap();
         assert!(scan_result.projects_found > 0);
     }
+
+    #[test]
+    fn test_rescan_only_reanalyzes_changed_meshes() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone())
+            .with_mesh_analysis(MeshAnalysisService::new(pool.clone()));
+
+        let project1 = Path::new(&test_root).join("project1");
+        fs::create_dir_all(&project1).unwrap();
+        let model = project1.join("plate.stl");
+        fs::write(
+            &model,
+            "solid plate\nfacet normal 0 0 1\nouter loop\n\
+             vertex 0 0 0\nvertex 20 0 0\nvertex 0 10 0\n\
+             endloop\nendfacet\nendsolid plate\n",
+        )
+        .unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_added, 1);
+
+        let conn = pool.get().unwrap();
+        let (size_x, size_y, triangles): (f64, f64, i64) = conn
+            .query_row(
+                "SELECT size_x, size_y, triangle_count FROM mesh_analysis",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((size_x, size_y, triangles), (20.0, 10.0, 1));
+
+        // Unchanged files keep their stored analysis
+        conn.execute("UPDATE mesh_analysis SET triangle_count = -1", [])
+            .unwrap();
+        rescan_service.rescan(&test_root).unwrap();
+        let triangles: i64 = conn
+            .query_row("SELECT triangle_count FROM mesh_analysis", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(triangles, -1);
+
+        // A modified file is analyzed again
+        fs::write(
+            &model,
+            "solid plate\nfacet normal 0 0 1\nouter loop\n\
+             vertex 0 0 0\nvertex 30 0 0\nvertex 0 10 0\n\
+             endloop\nendfacet\n\
+             facet normal 0 0 1\nouter loop\n\
+             vertex 0 0 0\nvertex 30 0 0\nvertex 0 0 5\n\
+             endloop\nendfacet\nendsolid plate\n",
+        )
+        .unwrap();
+        rescan_service.rescan(&test_root).unwrap();
+        let (size_x, triangles): (f64, i64) = conn
+            .query_row(
+                "SELECT size_x, triangle_count FROM mesh_analysis",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((size_x, triangles), (30.0, 2));
+    }
+
+    #[test]
+    fn test_rescan_paths_applies_targeted_changes() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+
+        let root = Path::new(&test_root);
+        let project1 = root.join("project1");
+        let project2 = root.join("project2");
+        fs::create_dir_all(&project1).unwrap();
+        fs::create_dir_all(&project2).unwrap();
+        create_test_stl(&project1, "model1.stl");
+        create_test_stl(&project2, "model1.stl");
+        rescan_service.rescan(&test_root).unwrap();
+
+        // A file added to one project, a new release folder and a removed project
+        create_test_stl(&project1, "model2.stl");
+        let project3 = root.join("project3");
+        fs::create_dir_all(&project3).unwrap();
+        create_test_stl(&project3, "model1.stl");
+        fs::remove_dir_all(&project2).unwrap();
+
+        let result = rescan_service
+            .rescan_paths(
+                &test_root,
+                &[
+                    project1.join("model2.stl"),
+                    project3.clone(),
+                    project2.clone(),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(result.files_added, 2);
+        assert_eq!(result.projects_added, 1);
+        assert_eq!(result.projects_removed, 1);
+
+        let conn = pool.get().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT p.name, COUNT(s.id) FROM projects p
+                 LEFT JOIN stl_files s ON s.project_id = p.id
+                 WHERE p.parent_id IS NOT NULL
+                 GROUP BY p.id ORDER BY p.name",
+            )
+            .unwrap();
+        let projects: Vec<(String, i64)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            projects,
+            vec![("project1".to_string(), 2), ("project3".to_string(), 1)]
+        );
+
+        // Removing a single file only touches that project
+        fs::remove_file(project1.join("model1.stl")).unwrap();
+        let result = rescan_service
+            .rescan_paths(&test_root, &[project1.join("model1.stl")])
+            .unwrap();
+        assert_eq!(result.files_removed, 1);
+        assert_eq!(result.projects_removed, 0);
+
+        // A folder left without model files is no longer a leaf project
+        fs::remove_file(project1.join("model2.stl")).unwrap();
+        rescan_service
+            .rescan_paths(&test_root, &[project1.join("model2.stl")])
+            .unwrap();
+        let is_leaf: bool = pool
+            .get()
+            .unwrap()
+            .query_row(
+                "SELECT is_leaf FROM projects WHERE name = 'project1'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!is_leaf);
+    }
+
+    #[test]
+    fn test_rescan_detects_modified_files() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let cache = ImageCacheService::new(temp_dir.path().join("cache"), pool.clone());
+        let rescan_service = RescanService::with_cache(pool.clone(), cache.clone());
+
+        let project1 = Path::new(&test_root).join("project1");
+        fs::create_dir_all(&project1).unwrap();
+        create_test_stl(&project1, "model1.stl");
+        create_test_stl(&project1, "model2.stl");
+        let image = project1.join("photo.jpg");
+        fs::write(&image, b"FAKE IMAGE DATA").unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_added, 3);
+
+        let image_path = image.to_str().unwrap();
+        cache.cache_image(image_path).unwrap();
+
+        // Unchanged files are not counted as updates
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!((result.files_added, result.files_updated), (0, 0));
+
+        // One model grows, the other keeps its size but gets a new mtime,
+        // and the image is replaced in place
+        fs::write(
+            project1.join("model1.stl"),
+            "solid replaced\nendsolid replaced\n",
+        )
+        .unwrap();
+        let touched = fs::File::options()
+            .write(true)
+            .open(project1.join("model2.stl"))
+            .unwrap();
+        touched
+            .set_modified(SystemTime::now() + Duration::from_secs(120))
+            .unwrap();
+        fs::write(&image, b"OTHER IMAGE BYTES").unwrap();
+        let touched = fs::File::options().write(true).open(&image).unwrap();
+        touched
+            .set_modified(SystemTime::now() + Duration::from_secs(120))
+            .unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_added, 0);
+        assert_eq!(result.files_updated, 3);
+        assert_eq!(result.files_removed, 0);
+
+        let conn = pool.get().unwrap();
+        let size: i64 = conn
+            .query_row(
+                "SELECT file_size FROM stl_files WHERE filename = 'model1.stl'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(size, 33);
+        drop(conn);
+
+        // The stale cached copy of the image was dropped
+        assert!(cache.get_cached_image(image_path).unwrap().is_none());
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_updated, 0);
+    }
+
+    #[test]
+    fn test_rescan_subtree_leaves_siblings_untouched() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+
+        let root = Path::new(&test_root);
+        let creator = root.join("Creator");
+        let release_a = creator.join("ReleaseA");
+        let other = root.join("Other");
+        fs::create_dir_all(&release_a).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        fs::write(creator.join("cover.jpg"), "jpg").unwrap();
+        create_test_stl(&release_a, "model1.stl");
+        create_test_stl(&other, "model1.stl");
+        rescan_service.rescan(&test_root).unwrap();
+
+        // A new release below a new folder, and a change outside the subtree
+        let release_b = creator.join("Series").join("ReleaseB");
+        fs::create_dir_all(&release_b).unwrap();
+        create_test_stl(&release_b, "model1.stl");
+        create_test_stl(&other, "model2.stl");
+
+        let result = rescan_service
+            .rescan_subtree(&test_root, &creator.join("Series"))
+            .unwrap();
+        assert_eq!(result.files_added, 1);
+        assert_eq!(result.projects_removed, 0);
+
+        let conn = pool.get().unwrap();
+        let project = |path: &Path| -> (i64, bool) {
+            conn.query_row(
+                "SELECT id, is_leaf FROM projects WHERE full_path = ?1",
+                [path.to_str().unwrap()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+        };
+        let count =
+            |sql: &str, id: i64| -> i64 { conn.query_row(sql, [id], |row| row.get(0)).unwrap() };
+
+        // Folders above the release are not projects of their own
+        assert!(!project(&creator.join("Series")).1);
+        assert!(!project(&creator).1);
+        assert!(project(&release_b).1);
+
+        // The new release inherits the creator's cover, the sibling keeps its copy
+        let inherited = "SELECT COUNT(*) FROM image_files
+                         WHERE project_id = ?1 AND source_type = 'inherited'";
+        assert_eq!(count(inherited, project(&release_b).0), 1);
+        assert_eq!(count(inherited, project(&release_a).0), 1);
+
+        // Files outside the subtree are picked up by the next full rescan only
+        let models = "SELECT COUNT(*) FROM stl_files WHERE project_id = ?1";
+        assert_eq!(count(models, project(&other).0), 1);
+
+        // Paths outside the library root are rejected
+        assert!(rescan_service
+            .rescan_subtree(&test_root, temp_dir.path())
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescan_reports_progress() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let progress = ScanProgressTracker::new();
+        let rescan_service = RescanService::new(pool.clone()).with_progress(progress.clone());
+
+        let root = Path::new(&test_root);
+        for name in ["project1", "project2"] {
+            let project = root.join(name);
+            fs::create_dir_all(&project).unwrap();
+            create_test_stl(&project, "model1.stl");
+            create_test_stl(&project, "model2.stl");
+        }
+
+        progress.start();
+        rescan_service.rescan(&test_root).unwrap();
+
+        let snapshot = progress.snapshot();
+        assert_eq!(snapshot.phase, Some(ScanPhase::Inheritance));
+        assert_eq!(snapshot.folders_visited, 3);
+        assert_eq!(snapshot.files_processed, 4);
+        assert_eq!(snapshot.phase_completed, 2);
+        assert_eq!(snapshot.phase_total, Some(2));
+        assert_eq!(snapshot.estimated_remaining_seconds, Some(0));
+
+        progress.finish();
+        assert_eq!(progress.snapshot().phase, None);
+    }
+
+    #[test]
+    fn test_rescan_cancelled_keeps_index_consistent() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let cancellation = ScanCancellation::new();
+        let rescan_service =
+            RescanService::new(pool.clone()).with_cancellation(cancellation.clone());
+
+        let root = Path::new(&test_root);
+        let project1 = root.join("Creator").join("project1");
+        fs::create_dir_all(&project1).unwrap();
+        fs::write(root.join("Creator").join("cover.jpg"), "jpg").unwrap();
+        create_test_stl(&project1, "model1.stl");
+        rescan_service.rescan(&test_root).unwrap();
+
+        // A cancelled rescan changes nothing, including inherited images
+        fs::remove_dir_all(&project1).unwrap();
+        cancellation.cancel();
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.projects_removed, 0);
+
+        let conn = pool.get().unwrap();
+        let inherited: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM image_files WHERE source_type = 'inherited'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(inherited, 1);
+
+        cancellation.reset();
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert!(!result.cancelled);
+        assert_eq!(result.projects_removed, 1);
+    }
+
+    fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, data) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_rescan_reconciles_zip_archives() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+
+        let archive = Path::new(&test_root).join("Release.zip");
+        write_zip(
+            &archive,
+            &[
+                ("Knight/knight.stl", b"solid knight"),
+                ("Knight/shield.stl", b"solid shield"),
+                ("Archer/archer.stl", b"solid archer"),
+            ],
+        );
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.files_added, 3);
+
+        // Unchanged archives are left alone
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_added + result.files_removed, 0);
+        assert_eq!(result.projects_removed, 0);
+
+        // Members and folders removed from the archive are removed from the index
+        write_zip(&archive, &[("Knight/knight.stl", b"solid knight")]);
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.files_removed, 1);
+        assert_eq!(result.projects_removed, 1);
+
+        let conn = pool.get().unwrap();
+        let paths: Vec<String> = conn
+            .prepare("SELECT file_path FROM stl_files")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            paths,
+            vec![archive
+                .join("Knight/knight.stl")
+                .to_str()
+                .unwrap()
+                .to_string()]
+        );
+        drop(conn);
+
+        // Deleting the archive removes everything it held
+        fs::remove_file(&archive).unwrap();
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.projects_removed, 2);
+    }
+
+    #[test]
+    fn test_rescan_paths_applies_archive_changes() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+        let stl_files = || -> Vec<String> {
+            pool.get()
+                .unwrap()
+                .prepare("SELECT file_path FROM stl_files ORDER BY file_path")
+                .unwrap()
+                .query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+
+        // An archive copied into the library
+        let archive = Path::new(&test_root).join("Release.zip");
+        write_zip(
+            &archive,
+            &[
+                ("Knight/knight.stl", b"solid knight"),
+                ("Archer/archer.stl", b"solid archer"),
+            ],
+        );
+        let result = rescan_service
+            .rescan_paths(&test_root, std::slice::from_ref(&archive))
+            .unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.files_added, 2);
+        assert_eq!(
+            stl_files(),
+            vec![
+                archive.join("Archer/archer.stl").to_str().unwrap(),
+                archive.join("Knight/knight.stl").to_str().unwrap(),
+            ]
+        );
+
+        // An archive replaced with a new release
+        write_zip(
+            &archive,
+            &[
+                ("Knight/knight.stl", b"solid knight"),
+                ("Knight/shield.stl", b"solid shield"),
+            ],
+        );
+        let result = rescan_service
+            .rescan_paths(&test_root, std::slice::from_ref(&archive))
+            .unwrap();
+        assert_eq!(result.files_added, 1);
+        assert_eq!(result.projects_removed, 1);
+        assert_eq!(
+            stl_files(),
+            vec![
+                archive.join("Knight/knight.stl").to_str().unwrap(),
+                archive.join("Knight/shield.stl").to_str().unwrap(),
+            ]
+        );
+
+        // A folder holding an archive moved into the library
+        let release = Path::new(&test_root).join("Orcs");
+        fs::create_dir_all(&release).unwrap();
+        write_zip(&release.join("Orcs.zip"), &[("orc.stl", b"solid orc")]);
+        let result = rescan_service
+            .rescan_paths(&test_root, std::slice::from_ref(&release))
+            .unwrap();
+        assert_eq!(result.files_added, 1);
+
+        // A removed archive takes its projects with it
+        fs::remove_file(&archive).unwrap();
+        let result = rescan_service
+            .rescan_paths(&test_root, std::slice::from_ref(&archive))
+            .unwrap();
+        assert_eq!(result.projects_removed, 2);
+        assert_eq!(
+            stl_files(),
+            vec![release.join("Orcs.zip/orc.stl").to_str().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_rescan_honors_ignore_patterns() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+        let root = Path::new(&test_root);
+
+        for folder in ["Knight", "Knight/Support-Tests", "Archer", "Dragon_old"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+            create_test_stl(&root.join(folder), "model.stl");
+        }
+        fs::write(root.join("Knight/.glyptoignore"), "support-tests/\n").unwrap();
+
+        ConfigService::new(pool.clone())
+            .update_config(&UpdateConfigRequest {
+                root_path: None,
+                cache_max_size_mb: None,
+                images_per_page: None,
+                printer_profiles: None,
+                scan_schedule: None,
+                scan_interval_minutes: None,
+                ignore_patterns: Some(vec!["*_old/".to_string()]),
+                follow_symlinks: None,
+                write_sidecars: None,
+                creator_folder_level: None,
+                release_folder_pattern: None,
+            })
+            .unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.files_added, 2);
+
+        // Projects that a new pattern excludes are removed on the next rescan
+        fs::write(root.join(".glyptoignore"), "archer\n").unwrap();
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.projects_removed, 1);
+
+        let conn = pool.get().unwrap();
+        let paths: Vec<String> = conn
+            .prepare("SELECT file_path FROM stl_files")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            paths,
+            vec![root.join("Knight/model.stl").to_str().unwrap().to_string()]
+        );
+    }
+
+    #[test]
+    fn test_rescan_imports_sidecar_metadata() {
+        let (temp_dir, test_root) = setup_test_env();
+        let pool = create_pool(temp_dir.path().join("test.db")).unwrap();
+        glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+        let rescan_service = RescanService::new(pool.clone());
+        let project_repo = ProjectRepository::new(pool.clone());
+        let root = Path::new(&test_root);
+
+        let knight = root.join("Knight");
+        fs::create_dir_all(&knight).unwrap();
+        create_test_stl(&knight, "model.stl");
+        fs::write(
+            knight.join("glyptotheka.json"),
+            r#"{
+                "tags": ["fantasy", "Hero"],
+                "author": "Cast'N'Play",
+                "license": "CC-BY-NC",
+                "scale": "32mm",
+                "print_settings": { "layer_height": 0.05, "supports": true }
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            knight.join("README.md"),
+            "# Knight\n\nA knight in armour.\n",
+        )
+        .unwrap();
+
+        let broken = root.join("Broken");
+        fs::create_dir_all(&broken).unwrap();
+        create_test_stl(&broken, "model.stl");
+        fs::write(broken.join("glyptotheka.yaml"), "tags: [unclosed\n").unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.errors.len(), 1, "{:?}", result.errors);
+        assert!(result.errors[0].contains("glyptotheka.yaml"));
+
+        let project_id = project_repo
+            .get_by_path(knight.to_str().unwrap())
+            .unwrap()
+            .unwrap()
+            .id;
+        let project = project_repo
+            .get_with_relations(project_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            project.project.description.as_deref(),
+            Some("# Knight\n\nA knight in armour.")
+        );
+        let metadata = project.metadata.unwrap();
+        assert_eq!(metadata.author.as_deref(), Some("Cast'N'Play"));
+        assert_eq!(metadata.license.as_deref(), Some("CC-BY-NC"));
+        assert_eq!(metadata.scale.as_deref(), Some("32mm"));
+        assert_eq!(
+            metadata.print_settings.unwrap()["layer_height"],
+            serde_json::json!(0.05)
+        );
+        let mut tags: Vec<_> = project.tags.iter().map(|t| t.name.clone()).collect();
+        tags.sort();
+        assert_eq!(tags, vec!["Hero", "fantasy"]);
+
+        // A tag added by hand survives re-imports
+        let conn = pool.get().unwrap();
+        conn.execute(
+            "INSERT INTO tags (name, created_at, usage_count) VALUES ('painted', 0, 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO project_tags (project_id, tag_id, created_at)
+             SELECT ?1, id, 0 FROM tags WHERE name = 'painted'",
+            [project_id],
+        )
+        .unwrap();
+        drop(conn);
+
+        // Unchanged sidecar files are not imported again
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.projects_updated, 0);
+
+        let sidecar = knight.join("glyptotheka.json");
+        fs::write(
+            &sidecar,
+            r#"{"description": "Knight on foot", "tags": ["fantasy"]}"#,
+        )
+        .unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&sidecar)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(120))
+            .unwrap();
+
+        let result = rescan_service.rescan(&test_root).unwrap();
+        assert_eq!(result.projects_updated, 1);
+        let project = project_repo
+            .get_with_relations(project_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            project.project.description.as_deref(),
+            Some("Knight on foot")
+        );
+        assert_eq!(project.metadata.unwrap().author, None);
+        let mut tags: Vec<_> = project.tags.iter().map(|t| t.name.clone()).collect();
+        tags.sort();
+        assert_eq!(tags, vec!["fantasy", "painted"]);
+
+        // Removing the sidecar files removes what was imported from them
+        fs::remove_file(&sidecar).unwrap();
+        fs::remove_file(knight.join("README.md")).unwrap();
+        rescan_service.rescan(&test_root).unwrap();
+        let project = project_repo
+            .get_with_relations(project_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.project.description, None);
+        assert!(project.metadata.is_none());
+        let tags: Vec<_> = project.tags.iter().map(|t| t.name.clone()).collect();
+        assert_eq!(tags, vec!["painted"]);
+    }
 }
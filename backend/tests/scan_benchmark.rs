@@ -0,0 +1,149 @@
+//! Scanner throughput over a generated library.
+//!
+//! The benchmark is ignored by default; run it with
+//! `cargo test --release --test scan_benchmark -- --ignored --nocapture`.
+//! `SCAN_BENCH_PROJECTS` sets the number of project folders (default 5000).
+
+use glyptotheka_backend::db::connection::{create_pool, DbPool};
+use glyptotheka_backend::services::scanner::ScannerService;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use tempfile::TempDir;
+
+/// Creators / releases / projects, each project holding `files` models, a
+/// category folder and a render, with a cover image on every release
+fn generate_library(root: &Path, projects: usize, files: usize) {
+    let per_release = 25;
+    let per_creator = 10 * per_release;
+
+    for project in 0..projects {
+        let release = root
+            .join(format!("Creator {:03}", project / per_creator))
+            .join(format!("Release {:03}", project / per_release));
+        let folder = release.join(format!("Project {:05}", project));
+        let category = folder.join("Supported");
+        fs::create_dir_all(&category).unwrap();
+
+        for file in 0..files {
+            fs::write(folder.join(format!("part{}.stl", file)), b"solid part").unwrap();
+        }
+        fs::write(category.join("part0_supported.stl"), b"solid supported").unwrap();
+        fs::write(folder.join("render.png"), b"png").unwrap();
+        fs::write(release.join("cover.jpg"), b"jpg").unwrap();
+    }
+}
+
+fn setup_pool(temp_dir: &TempDir, name: &str) -> DbPool {
+    let pool = create_pool(temp_dir.path().join(name)).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    pool
+}
+
+/// (model file path, project path, category) for every indexed model file
+fn indexed_files(pool: &DbPool) -> Vec<(String, String, Option<String>)> {
+    let conn = pool.get().unwrap();
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.file_path, p.full_path, s.category
+             FROM stl_files s JOIN projects p ON p.id = s.project_id
+             ORDER BY s.file_path",
+        )
+        .unwrap();
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn scanner(pool: &DbPool, threads: usize) -> ScannerService {
+    ScannerService::new(pool.clone())
+        .with_ignored_keywords(vec!["supported".to_string()])
+        .with_walk_threads(threads)
+}
+
+#[test]
+fn test_parallel_scan_matches_single_threaded() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("library");
+    generate_library(&root, 60, 3);
+    let root = root.to_str().unwrap();
+
+    let sequential = setup_pool(&temp_dir, "sequential.db");
+    let result = scanner(&sequential, 1).scan(root).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.projects_found, 60);
+    assert_eq!(result.files_processed, 240);
+
+    let parallel = setup_pool(&temp_dir, "parallel.db");
+    let result = scanner(&parallel, 8).scan(root).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(indexed_files(&parallel), indexed_files(&sequential));
+
+    // Releases and creators are indexed as parents with their images
+    let conn = parallel.get().unwrap();
+    let parents: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM projects WHERE is_leaf = 0",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(parents, 1 + 1 + 3);
+    let inherited: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM image_files WHERE source_type = 'inherited'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(inherited, 60);
+
+    // A second scan of an unchanged library adds nothing
+    drop(conn);
+    let result = scanner(&parallel, 8).scan(root).unwrap();
+    assert_eq!(result.files_processed, 240);
+    assert_eq!(indexed_files(&parallel), indexed_files(&sequential));
+}
+
+#[test]
+#[ignore = "benchmark; run with --ignored --nocapture"]
+fn benchmark_scan_generated_library() {
+    let projects = std::env::var("SCAN_BENCH_PROJECTS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(5000);
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("library");
+    let started = Instant::now();
+    generate_library(&root, projects, 4);
+    println!(
+        "Generated {} projects ({} model files) in {:?}",
+        projects,
+        projects * 5,
+        started.elapsed()
+    );
+    let root = root.to_str().unwrap();
+
+    let threads = [
+        1,
+        std::thread::available_parallelism().map_or(4, |n| n.get()) * 2,
+    ];
+    for (run, threads) in threads.into_iter().enumerate() {
+        let pool = setup_pool(&temp_dir, &format!("bench{}.db", run));
+        let started = Instant::now();
+        let result = scanner(&pool, threads).scan(root).unwrap();
+        let elapsed = started.elapsed();
+
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.files_processed, projects * 5);
+        println!(
+            "{} walker threads: {} files in {:?} ({:.0} files/s)",
+            threads,
+            result.files_processed,
+            elapsed,
+            result.files_processed as f64 / elapsed.as_secs_f64()
+        );
+    }
+}
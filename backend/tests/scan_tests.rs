@@ -1,8 +1,19 @@
 use glyptotheka_backend::config::Config;
 use glyptotheka_backend::db::connection::create_pool;
+use glyptotheka_backend::db::repositories::file_repo::FileRepository;
+use glyptotheka_backend::db::repositories::project_repo::ProjectRepository;
+use glyptotheka_backend::models::duplicate::DuplicateMode;
+use glyptotheka_backend::services::download::DownloadService;
+use glyptotheka_backend::services::duplicates::DuplicateService;
+use glyptotheka_backend::services::mesh::Mesh;
+use glyptotheka_backend::services::mesh_analysis::MeshAnalysisService;
+use glyptotheka_backend::services::scan_progress::ScanCancellation;
 use glyptotheka_backend::services::scanner::ScannerService;
+use glyptotheka_backend::utils::pagination::PaginationParams;
 use std::fs;
+use std::io::Write;
 use tempfile::TempDir;
+use zip::write::{SimpleFileOptions, ZipWriter};
 
 fn setup_test_env() -> (TempDir, Config) {
     let temp_dir = TempDir::new().unwrap();
@@ -19,6 +30,15 @@ fn setup_test_env() -> (TempDir, Config) {
     (temp_dir, config)
 }
 
+fn write_zip(path: &std::path::Path, files: &[(&str, &[u8])]) {
+    let mut zip = ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, data) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
 fn create_test_project(
     base_path: &std::path::Path,
     name: &str,
@@ -116,6 +136,522 @@ fn test_scan_multiple_projects() {
     assert_eq!(result.files_processed, 6); // 1 + 2 + 3 STL files
 }
 
+#[test]
+fn test_scan_indexes_3mf_files() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone());
+
+    let scan_path = temp_dir.path().join("projects");
+    let project_path = scan_path.join("project3mf");
+    fs::create_dir_all(&project_path).unwrap();
+
+    // A project that ships only 3MF files still becomes a project
+    fs::write(project_path.join("model.3mf"), b"FAKE 3MF DATA").unwrap();
+    fs::write(project_path.join("other.3MF"), b"FAKE 3MF DATA").unwrap();
+
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    assert_eq!(result.projects_found, 1);
+    assert_eq!(result.files_processed, 2);
+
+    let conn = pool.get().unwrap();
+    let count_3mf: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM stl_files WHERE format = '3mf'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(count_3mf, 2);
+}
+
+#[tokio::test]
+async fn test_scan_indexes_obj_and_ply_with_companions() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone());
+
+    let scan_path = temp_dir.path().join("projects");
+    let project_path = scan_path.join("meshes");
+    fs::create_dir_all(project_path.join("textures")).unwrap();
+
+    fs::write(
+        project_path.join("bust.obj"),
+        "mtllib bust.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+    )
+    .unwrap();
+    fs::write(
+        project_path.join("bust.mtl"),
+        "newmtl stone\nmap_Kd textures/stone.jpg\n",
+    )
+    .unwrap();
+    fs::write(project_path.join("textures/stone.jpg"), b"FAKE JPG").unwrap();
+    fs::write(
+        project_path.join("base.ply"),
+        "ply\nformat ascii 1.0\nelement vertex 0\nproperty float x\nend_header\n",
+    )
+    .unwrap();
+
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    assert_eq!(result.projects_found, 1);
+    assert_eq!(result.files_processed, 2);
+
+    let conn = pool.get().unwrap();
+    let formats: Vec<String> = conn
+        .prepare("SELECT format FROM stl_files ORDER BY format")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(formats, vec!["obj", "ply"]);
+
+    let project_id: i64 = conn
+        .query_row("SELECT id FROM projects WHERE name = 'meshes'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    drop(conn);
+
+    // The material library and its texture travel with the OBJ in downloads
+    let zip_path = temp_dir.path().join("meshes.zip");
+    DownloadService::new(pool)
+        .create_project_zip(project_id, &zip_path)
+        .await
+        .unwrap();
+
+    let archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["base.ply", "bust.mtl", "bust.obj", "textures/stone.jpg"]
+    );
+}
+
+#[tokio::test]
+async fn test_project_zip_keeps_images_with_the_same_name() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone());
+
+    // The project's own render and the one it inherits from its creator folder
+    let scan_path = temp_dir.path().join("projects");
+    let project_path = scan_path.join("Creator/Orc");
+    fs::create_dir_all(&project_path).unwrap();
+    fs::write(project_path.join("orc.stl"), "solid orc").unwrap();
+    fs::write(project_path.join("render.jpg"), b"ORC RENDER").unwrap();
+    fs::write(scan_path.join("Creator/render.jpg"), b"CREATOR RENDER").unwrap();
+
+    scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    let project_id: i64 = pool
+        .get()
+        .unwrap()
+        .query_row("SELECT id FROM projects WHERE name = 'Orc'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    let zip_path = temp_dir.path().join("orc.zip");
+    DownloadService::new(pool)
+        .create_project_zip(project_id, &zip_path)
+        .await
+        .unwrap();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+    let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+    names.sort();
+    assert_eq!(names, vec!["orc.stl", "render (2).jpg", "render.jpg"]);
+    let mut renders = Vec::new();
+    for name in ["render.jpg", "render (2).jpg"] {
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name(name).unwrap(), &mut contents).unwrap();
+        renders.push(contents);
+    }
+    renders.sort();
+    assert_eq!(renders, vec!["CREATOR RENDER", "ORC RENDER"]);
+}
+
+#[test]
+fn test_scan_analyzes_mesh_geometry() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone())
+        .with_mesh_analysis(MeshAnalysisService::new(pool.clone()));
+
+    let scan_path = temp_dir.path().join("projects");
+    let project_path = scan_path.join("cube");
+    fs::create_dir_all(&project_path).unwrap();
+
+    // 10mm cube
+    fs::write(
+        project_path.join("cube.obj"),
+        "v 0 0 0\nv 10 0 0\nv 10 10 0\nv 0 10 0\n\
+         v 0 0 10\nv 10 0 10\nv 10 10 10\nv 0 10 10\n\
+         f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n",
+    )
+    .unwrap();
+
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+    let conn = pool.get().unwrap();
+    let project_id: i64 = conn
+        .query_row("SELECT id FROM projects WHERE name = 'cube'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    drop(conn);
+
+    let files = FileRepository::new(pool)
+        .get_stl_files_by_project(project_id)
+        .unwrap();
+    let analysis = files[0].analysis.as_ref().expect("mesh should be analyzed");
+
+    assert_eq!(
+        (analysis.size_x, analysis.size_y, analysis.size_z),
+        (10.0, 10.0, 10.0)
+    );
+    assert_eq!(analysis.triangle_count, 12);
+    assert!((analysis.surface_area - 600.0).abs() < 1e-6);
+    assert!((analysis.volume - 1000.0).abs() < 1e-6);
+    // 1 cm³ of PLA
+    assert!((analysis.estimated_mass - 1.24).abs() < 1e-6);
+}
+
+#[test]
+fn test_scan_records_mesh_issues() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let analysis_service = MeshAnalysisService::new(pool.clone());
+    let scanner = ScannerService::new(pool.clone()).with_mesh_analysis(analysis_service.clone());
+
+    let scan_path = temp_dir.path().join("projects");
+    let project_path = scan_path.join("broken");
+    fs::create_dir_all(&project_path).unwrap();
+
+    // A single open triangle plus a zero-area sliver
+    fs::write(
+        project_path.join("broken.obj"),
+        "v 0 0 0\nv 10 0 0\nv 0 10 0\nv 20 0 0\n\
+         f 1 2 3\nf 1 2 4\n",
+    )
+    .unwrap();
+
+    scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    let conn = pool.get().unwrap();
+    let file_id: i64 = conn
+        .query_row("SELECT id FROM stl_files", [], |row| row.get(0))
+        .unwrap();
+    drop(conn);
+
+    let issues = analysis_service.get_issues(file_id).unwrap();
+    let types: Vec<&str> = issues.iter().map(|i| i.issue_type.as_str()).collect();
+    assert_eq!(types, vec!["degenerate_triangles", "open_edges"]);
+    assert_eq!(issues[1].count, 3);
+}
+
+#[test]
+fn test_scan_detects_duplicate_files() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone());
+
+    let scan_path = temp_dir.path().join("projects");
+    let original = scan_path.join("dragon");
+    let copy = scan_path.join("dragon_copy");
+    fs::create_dir_all(&original).unwrap();
+    fs::create_dir_all(&copy).unwrap();
+
+    fs::write(original.join("dragon.stl"), b"SAME STL DATA").unwrap();
+    fs::write(copy.join("dragon_renamed.stl"), b"SAME STL DATA").unwrap();
+    fs::write(copy.join("base.stl"), b"DIFFERENT STL DATA").unwrap();
+
+    scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    let service = DuplicateService::new(pool.clone());
+    let report = service
+        .find_duplicates(
+            DuplicateMode::Exact,
+            &PaginationParams {
+                page: 1,
+                per_page: 20,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(report.total_groups, 1);
+    assert_eq!(report.total_wasted_bytes, 13);
+    let group = &report.groups[0];
+    assert_eq!(group.copies, 2);
+    assert_eq!(group.file_size, 13);
+    let filenames: Vec<&str> = group.files.iter().map(|f| f.filename.as_str()).collect();
+    assert_eq!(filenames, vec!["dragon.stl", "dragon_renamed.stl"]);
+
+    let project_repo = ProjectRepository::new(pool);
+    let dragon = project_repo
+        .get_by_path(original.to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    let relations = project_repo.get_with_relations(dragon.id).unwrap().unwrap();
+    assert_eq!(relations.also_appears_in.len(), 1);
+    assert_eq!(relations.also_appears_in[0].name, "dragon_copy");
+    assert_eq!(relations.also_appears_in[0].shared_files, 1);
+}
+
+#[test]
+fn test_scan_detects_similar_models() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone())
+        .with_mesh_analysis(MeshAnalysisService::new(pool.clone()));
+
+    let scan_path = temp_dir.path().join("projects");
+    let binary = scan_path.join("binary");
+    let ascii = scan_path.join("ascii");
+    fs::create_dir_all(&binary).unwrap();
+    fs::create_dir_all(&ascii).unwrap();
+
+    // The same tetrahedron as binary STL and as ASCII STL with reordered facets
+    let tetrahedron = "v 0 0 0\nv 10 0 0\nv 0 10 0\nv 0 0 10\n\
+                       f 1 3 2\nf 1 2 4\nf 1 4 3\nf 2 3 4\n";
+    let mesh = Mesh::from_obj(std::io::Cursor::new(tetrahedron)).unwrap();
+    mesh.write_binary_stl(&binary.join("tetra.stl")).unwrap();
+
+    let mut ascii_stl = String::from("solid exported_again\n");
+    for triangle in mesh.triangles.iter().rev() {
+        ascii_stl.push_str("facet normal 0 0 0\nouter loop\n");
+        for vertex in triangle {
+            ascii_stl.push_str(&format!(
+                "vertex {:e} {:e} {:e}\n",
+                vertex[0], vertex[1], vertex[2]
+            ));
+        }
+        ascii_stl.push_str("endloop\nendfacet\n");
+    }
+    ascii_stl.push_str("endsolid exported_again\n");
+    fs::write(ascii.join("tetra_ascii.stl"), ascii_stl).unwrap();
+
+    // And rotated, which changes its bounding box
+    let rotated = scan_path.join("rotated");
+    fs::create_dir_all(&rotated).unwrap();
+    let mut turned = mesh.clone();
+    let (sin, cos) = 45f32.to_radians().sin_cos();
+    for triangle in &mut turned.triangles {
+        for vertex in triangle.iter_mut() {
+            let [x, y, z] = *vertex;
+            *vertex = [x * cos - y * sin, x * sin + y * cos, z];
+        }
+    }
+    turned
+        .write_binary_stl(&rotated.join("tetra_rotated.stl"))
+        .unwrap();
+
+    // A different shape must not be grouped with them
+    let cube = "v 0 0 0\nv 10 0 0\nv 10 10 0\nv 0 10 0\n\
+                v 0 0 10\nv 10 0 10\nv 10 10 10\nv 0 10 10\n\
+                f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+    fs::write(ascii.join("cube.obj"), cube).unwrap();
+
+    scanner.scan(scan_path.to_str().unwrap()).unwrap();
+
+    let service = DuplicateService::new(pool);
+    let pagination = PaginationParams {
+        page: 1,
+        per_page: 20,
+    };
+
+    let exact = service
+        .find_duplicates(DuplicateMode::Exact, &pagination)
+        .unwrap();
+    assert_eq!(exact.total_groups, 0);
+
+    let similar = service
+        .find_duplicates(DuplicateMode::Similar, &pagination)
+        .unwrap();
+    assert_eq!(similar.total_groups, 1);
+    let filenames: Vec<&str> = similar.groups[0]
+        .files
+        .iter()
+        .map(|f| f.filename.as_str())
+        .collect();
+    assert_eq!(
+        filenames,
+        vec!["tetra_ascii.stl", "tetra.stl", "tetra_rotated.stl"]
+    );
+    assert_eq!(similar.groups[0].file_size, 284);
+    assert!(similar.groups[0].content_hash.is_none());
+}
+
+#[test]
+fn test_scan_stops_when_cancelled() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let cancellation = ScanCancellation::new();
+    let scanner = ScannerService::new(pool.clone()).with_cancellation(cancellation.clone());
+
+    let scan_path = temp_dir.path().join("projects");
+    fs::create_dir_all(&scan_path).unwrap();
+    create_test_project(&scan_path, "project1", 2, 1);
+
+    cancellation.cancel();
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+    assert!(result.cancelled);
+    assert_eq!(result.projects_found, 0);
+
+    let project_repo = ProjectRepository::new(pool);
+    assert!(project_repo
+        .get_by_path(scan_path.join("project1").to_str().unwrap())
+        .unwrap()
+        .is_none());
+
+    // The next scan runs to completion once the flag is reset
+    cancellation.reset();
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+    assert!(!result.cancelled);
+    assert_eq!(result.files_processed, 2);
+}
+
+#[test]
+fn test_scan_indexes_zip_archives() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone()).with_ignored_keywords(vec!["STL".to_string()]);
+
+    let scan_path = temp_dir.path().join("projects");
+    fs::create_dir_all(&scan_path).unwrap();
+    let archive = scan_path.join("Release.zip");
+    write_zip(
+        &archive,
+        &[
+            ("cover.jpg", b"FAKE IMAGE DATA"),
+            ("Knight/knight.stl", b"SAME STL DATA"),
+            ("Knight/STL/sword.stl", b"SWORD STL DATA"),
+            ("Knight/render.png", b"FAKE IMAGE DATA"),
+            ("__MACOSX/Knight/._knight.stl", b"FORK"),
+        ],
+    );
+    // An identical copy on disk is found as a duplicate of the archived file
+    create_test_project(&scan_path, "loose", 0, 0);
+    fs::write(scan_path.join("loose").join("knight.stl"), b"SAME STL DATA").unwrap();
+
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+    assert_eq!(result.files_processed, 3);
+
+    // The archive is a folder whose subfolders mirror its contents
+    let project_repo = ProjectRepository::new(pool.clone());
+    let release = project_repo
+        .get_by_path(archive.to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    assert_eq!(release.name, "Release.zip");
+    assert!(!release.is_leaf);
+    let knight = project_repo
+        .get_by_path(archive.join("Knight").to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(knight.is_leaf);
+    assert_eq!(knight.parent_id, Some(release.id));
+
+    let file_repo = FileRepository::new(pool.clone());
+    let mut files = file_repo.get_stl_files_by_project(knight.id).unwrap();
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let names: Vec<(&str, Option<&str>)> = files
+        .iter()
+        .map(|f| (f.filename.as_str(), f.category.as_deref()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("knight.stl", None), ("sword.stl", Some("STL"))]
+    );
+    assert_eq!(files[0].file_size, 13);
+
+    // Images inside the archive are indexed directly and inherited
+    let images = file_repo
+        .get_image_files_by_project(knight.id, 10, 0)
+        .unwrap();
+    let mut sources: Vec<(&str, &str)> = images
+        .iter()
+        .map(|i| (i.filename.as_str(), i.source_type.as_str()))
+        .collect();
+    sources.sort();
+    assert_eq!(
+        sources,
+        vec![("cover.jpg", "inherited"), ("render.png", "direct")]
+    );
+
+    let report = DuplicateService::new(pool)
+        .find_duplicates(
+            DuplicateMode::Exact,
+            &PaginationParams {
+                page: 1,
+                per_page: 20,
+            },
+        )
+        .unwrap();
+    assert_eq!(report.total_groups, 1);
+    assert_eq!(report.groups[0].copies, 2);
+}
+
+#[test]
+fn test_scan_indexes_7z_archives_and_reports_rar() {
+    let (temp_dir, config) = setup_test_env();
+    let pool = create_pool(&config.database_path).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+    let scanner = ScannerService::new(pool.clone()).with_ignored_keywords(vec!["STL".to_string()]);
+
+    let scan_path = temp_dir.path().join("projects");
+    fs::create_dir_all(&scan_path).unwrap();
+    let archive = scan_path.join("Release.7z");
+    fs::write(&archive, include_bytes!("fixtures/release_lzma2.7z")).unwrap();
+    let rar = scan_path.join("Villains.rar");
+    fs::write(&rar, b"Rar!").unwrap();
+
+    let result = scanner.scan(scan_path.to_str().unwrap()).unwrap();
+    assert_eq!(result.files_processed, 2);
+    // RAR archives cannot be read, so the scan says so instead of skipping them
+    assert_eq!(
+        result.errors,
+        vec![format!(
+            "Cannot index {}: RAR archives are not supported",
+            rar.display()
+        )]
+    );
+
+    let project_repo = ProjectRepository::new(pool.clone());
+    let heroes = project_repo
+        .get_by_path(archive.join("Heroes").to_str().unwrap())
+        .unwrap()
+        .unwrap();
+    assert!(heroes.is_leaf);
+
+    let file_repo = FileRepository::new(pool);
+    let mut files = file_repo.get_stl_files_by_project(heroes.id).unwrap();
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let names: Vec<(&str, Option<&str>)> = files
+        .iter()
+        .map(|f| (f.filename.as_str(), f.category.as_deref()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("knight.stl", None), ("shield.stl", Some("STL"))]
+    );
+    assert_eq!(files[0].file_size, 29);
+}
+
 #[test]
 fn test_scan_invalid_path() {
     let (_temp_dir, config) = setup_test_env();
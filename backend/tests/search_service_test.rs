@@ -1,11 +1,18 @@
 use glyptotheka_backend::config::Config;
-use glyptotheka_backend::db::connection::create_pool;
+use glyptotheka_backend::db::connection::{create_pool, DbPool};
 use glyptotheka_backend::db::migrations::run_migrations;
 use glyptotheka_backend::services::search::{SearchParams, SearchService};
 use std::fs;
 use tempfile::TempDir;
 
 fn setup_test_env() -> (TempDir, SearchService) {
+    let (temp_dir, pool) = setup_test_pool();
+    let ignored_keywords = vec!["STL".to_string(), "PRESUPPORTED_STL".to_string()];
+    let service = SearchService::new(pool, ignored_keywords);
+    (temp_dir, service)
+}
+
+fn setup_test_pool() -> (TempDir, DbPool) {
     let temp_dir = TempDir::new().unwrap();
     let db_path = temp_dir.path().join("test.db");
     let cache_dir = temp_dir.path().join("cache");
@@ -23,10 +30,21 @@ fn setup_test_env() -> (TempDir, SearchService) {
     let conn = pool.get().unwrap();
     let fixture = include_str!("fixtures/hierarchical_projects.sql");
     conn.execute_batch(fixture).unwrap();
+    drop(conn);
 
-    let ignored_keywords = vec!["STL".to_string(), "PRESUPPORTED_STL".to_string()];
-    let service = SearchService::new(pool, ignored_keywords);
-    (temp_dir, service)
+    (temp_dir, pool)
+}
+
+fn insert_analysis(pool: &DbPool, filename: &str, size: [f64; 3]) {
+    let conn = pool.get().unwrap();
+    conn.execute(
+        "INSERT INTO mesh_analysis (stl_file_id, size_x, size_y, size_z, triangle_count,
+                                    surface_area, volume, estimated_mass, source_size,
+                                    source_mtime, analyzed_at)
+         SELECT id, ?2, ?3, ?4, 12, 0, 0, 0, 0, 0, 0 FROM stl_files WHERE filename = ?1",
+        rusqlite::params![filename, size[0], size[1], size[2]],
+    )
+    .unwrap();
 }
 
 #[test]
@@ -40,6 +58,7 @@ fn test_search_fts_leaf_only() {
         page: 1,
         per_page: 10,
         leaf_only: true,
+        ..Default::default()
     };
 
     let result = service.search(&params).unwrap();
@@ -61,6 +80,7 @@ fn test_search_fts_all() {
         page: 1,
         per_page: 10,
         leaf_only: false,
+        ..Default::default()
     };
 
     let result = service.search(&params).unwrap();
@@ -79,6 +99,7 @@ fn test_search_all_leaf_only() {
         page: 1,
         per_page: 10,
         leaf_only: true,
+        ..Default::default()
     };
 
     let result = service.search(&params).unwrap();
@@ -89,3 +110,149 @@ fn test_search_all_leaf_only() {
         assert!(p.project.is_leaf);
     }
 }
+
+#[test]
+fn test_search_by_build_volume() {
+    let (_temp_dir, pool) = setup_test_pool();
+    insert_analysis(&pool, "body.stl", [240.0, 100.0, 50.0]);
+    insert_analysis(&pool, "cab.stl", [120.0, 120.0, 230.0]);
+    let service = SearchService::new(pool, vec![]);
+
+    let search = |params: SearchParams| {
+        service
+            .search(&SearchParams {
+                page: 1,
+                per_page: 10,
+                leaf_only: true,
+                ..params
+            })
+            .unwrap()
+    };
+
+    // The cab is too tall for the MK4 (250 x 210 x 220)
+    let result = search(SearchParams {
+        printer: Some("prusa mk4".to_string()),
+        ..Default::default()
+    });
+    assert_eq!(result.total, 1);
+    assert_eq!(result.projects[0].project.name, "Sports Car");
+
+    // The car body only fits the Y limit when rotated on the plate
+    let result = search(SearchParams {
+        max_x: Some(110.0),
+        max_y: Some(245.0),
+        ..Default::default()
+    });
+    assert_eq!(result.total, 1);
+    assert_eq!(result.projects[0].project.name, "Sports Car");
+
+    let result = search(SearchParams {
+        min_z: Some(100.0),
+        ..Default::default()
+    });
+    assert_eq!(result.total, 1);
+    assert_eq!(result.projects[0].project.name, "Truck");
+
+    // Explicit limits override the printer profile
+    let result = search(SearchParams {
+        printer: Some("Prusa MK4".to_string()),
+        max_z: Some(300.0),
+        ..Default::default()
+    });
+    assert_eq!(result.total, 2);
+}
+
+#[test]
+fn test_search_filters_with_text_and_tags() {
+    let (_temp_dir, pool) = setup_test_pool();
+    insert_analysis(&pool, "body.stl", [240.0, 100.0, 50.0]);
+    insert_analysis(&pool, "cab.stl", [120.0, 120.0, 230.0]);
+    pool.get()
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO tags (name, created_at) VALUES ('red', 0);
+             INSERT INTO project_tags (project_id, tag_id, created_at)
+             SELECT id, (SELECT id FROM tags WHERE name = 'red'), 0
+             FROM projects WHERE is_leaf = 1;",
+        )
+        .unwrap();
+    let service = SearchService::new(pool, vec![]);
+
+    // Filter values are bound after the text and tag parameters of each query
+    let search = |query: Option<&str>, tags: &[&str]| {
+        service
+            .search(&SearchParams {
+                query: query.map(str::to_string),
+                tags: tags.iter().map(|t| t.to_string()).collect(),
+                page: 1,
+                per_page: 10,
+                max_x: Some(110.0),
+                max_y: Some(245.0),
+                min_z: Some(10.0),
+                ..Default::default()
+            })
+            .unwrap()
+    };
+    let names = |result: glyptotheka_backend::services::search::SearchResult| -> Vec<String> {
+        result
+            .projects
+            .into_iter()
+            .map(|p| p.project.name)
+            .collect()
+    };
+
+    assert_eq!(names(search(None, &[])), vec!["Sports Car"]);
+    assert_eq!(names(search(Some("Sports"), &[])), vec!["Sports Car"]);
+    assert_eq!(names(search(None, &["red"])), vec!["Sports Car"]);
+    assert_eq!(names(search(Some("Sports"), &["red"])), vec!["Sports Car"]);
+    assert!(names(search(Some("Truck"), &["red"])).is_empty());
+}
+
+#[test]
+fn test_search_unknown_printer_is_rejected() {
+    let (_temp_dir, service) = setup_test_env();
+
+    let params = SearchParams {
+        page: 1,
+        per_page: 10,
+        printer: Some("Imaginary 9000".to_string()),
+        ..Default::default()
+    };
+
+    assert!(service.search(&params).is_err());
+}
+
+#[test]
+fn test_search_by_mesh_issues() {
+    let (_temp_dir, pool) = setup_test_pool();
+    {
+        let conn = pool.get().unwrap();
+        conn.execute(
+            "INSERT INTO mesh_issues (stl_file_id, issue_type, count, message)
+             SELECT id, 'open_edges', 3, '3 open edges' FROM stl_files WHERE filename = 'cab.stl'",
+            [],
+        )
+        .unwrap();
+    }
+    let service = SearchService::new(pool, vec![]);
+
+    let params = SearchParams {
+        page: 1,
+        per_page: 10,
+        leaf_only: true,
+        has_issues: Some(true),
+        ..Default::default()
+    };
+    let result = service.search(&params).unwrap();
+    assert_eq!(result.total, 1);
+    assert_eq!(result.projects[0].project.name, "Truck");
+
+    let result = service
+        .search(&SearchParams {
+            has_issues: Some(false),
+            ..params
+        })
+        .unwrap();
+    assert_eq!(result.total, 1);
+    assert_eq!(result.projects[0].project.name, "Sports Car");
+}
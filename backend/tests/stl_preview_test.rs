@@ -69,6 +69,41 @@ async fn test_stl_preview_generation() {
     }
 }
 
+#[tokio::test]
+async fn test_3mf_embedded_thumbnail_used_as_preview() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let db_path = temp_dir.path().join("test.db");
+    let cache_dir = temp_dir.path().join("cache");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+
+    let pool = create_pool(db_path.to_str().unwrap()).unwrap();
+    glyptotheka_backend::db::migrations::run_migrations(&pool).unwrap();
+
+    let image_cache = ImageCacheService::new(cache_dir.clone(), pool.clone());
+    let preview_service = StlPreviewService::new(image_cache, pool);
+
+    // Build a minimal 3MF package containing only a thumbnail
+    let thumbnail = vec![0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 1, 2, 3];
+    let model_path = temp_dir.path().join("model.3mf");
+    {
+        let file = std::fs::File::create(&model_path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file::<_, ()>("Metadata/thumbnail.png", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(&thumbnail).unwrap();
+        zip.finish().unwrap();
+    }
+
+    let preview_path = preview_service
+        .generate_preview(model_path.to_str().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&preview_path).unwrap(), thumbnail);
+}
+
 fn find_test_stl_file() -> Option<String> {
     // Try to find a small STL file in the example directory
     let example_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -5,6 +5,8 @@ export interface FolderInfo {
   path: string;
   project_count: number;
   has_images: boolean;
+  /** Folder a symlinked folder leads to */
+  link_target: string | null;
 }
 
 export interface ProjectWithPreview {
@@ -32,6 +34,10 @@ export interface BreadcrumbItem {
   path: string;
 }
 
+/** Browse URLs of one library root, or of the default root */
+const browseBase = (rootId?: number) =>
+  rootId !== undefined ? `/api/roots/${rootId}/browse` : '/api/browse';
+
 export const browseAPI = {
   /**
    * Fetch folder contents at a given path
@@ -39,16 +45,18 @@ export const browseAPI = {
   async getFolderContents(
     path: string = '',
     page?: number,
-    perPage?: number
+    perPage?: number,
+    rootId?: number
   ): Promise<FolderContents> {
     const params = new URLSearchParams();
     if (page) params.append('page', page.toString());
     if (perPage) params.append('per_page', perPage.toString());
 
     const queryString = params.toString();
+    const base = browseBase(rootId);
     const url = path
-      ? `/api/browse/${path}${queryString ? `?${queryString}` : ''}`
-      : `/api/browse${queryString ? `?${queryString}` : ''}`;
+      ? `${base}/${path}${queryString ? `?${queryString}` : ''}`
+      : `${base}${queryString ? `?${queryString}` : ''}`;
 
     const response = await apiClient.get<FolderContents>(url);
     return response.data;
@@ -57,10 +65,9 @@ export const browseAPI = {
   /**
    * Fetch breadcrumb trail for a given path
    */
-  async getBreadcrumb(path: string = ''): Promise<BreadcrumbItem[]> {
-    const url = path
-      ? `/api/browse/breadcrumb/${path}`
-      : `/api/browse/breadcrumb`;
+  async getBreadcrumb(path: string = '', rootId?: number): Promise<BreadcrumbItem[]> {
+    const base = browseBase(rootId);
+    const url = path ? `${base}/breadcrumb/${path}` : `${base}/breadcrumb`;
 
     const response = await apiClient.get<BreadcrumbItem[]>(url);
     return response.data;
@@ -59,6 +59,8 @@ export interface FolderInfo {
   path: string;
   project_count: number;
   has_images: boolean;
+  /** Folder a symlinked folder leads to */
+  link_target: string | null;
 }
 
 export interface ProjectWithPreview {
@@ -141,6 +143,7 @@ export interface FolderContents {
   total_projects: number;
   is_leaf_project: boolean; // T042: Indicates if current path is a leaf project
   project_details: ProjectDetails | null; // Project details when path IS a project
+  link_target: string | null; // Folder shown when current_path lies in a symlinked folder
 }
 
 // T027: Fetch folder contents with cancellation support
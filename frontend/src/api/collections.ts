@@ -0,0 +1,111 @@
+import axios from './client';
+import { Project, StlFile } from '../types/project';
+
+export interface Collection {
+  id: number;
+  name: string;
+  description: string | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface CollectionSummary extends Collection {
+  item_count: number;
+}
+
+export interface CollectionItem {
+  id: number;
+  /** Copies to print */
+  quantity: number;
+  notes: string | null;
+  /** The project, or the project the model file belongs to */
+  project: Project;
+  /** Set when the item is a single model file */
+  stl_file: StlFile | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface CollectionWithItems extends Collection {
+  /** In collection order */
+  items: CollectionItem[];
+}
+
+/** Missing fields are left unchanged; an empty description clears it */
+export interface CollectionRequest {
+  name?: string;
+  description?: string;
+}
+
+/** Either `project_id` or `stl_file_id` */
+export interface AddCollectionItemRequest {
+  project_id?: number;
+  stl_file_id?: number;
+  quantity?: number;
+  notes?: string;
+  /** Appended when missing */
+  position?: number;
+}
+
+/** An empty string clears the notes */
+export interface UpdateCollectionItemRequest {
+  quantity?: number;
+  notes?: string;
+  position?: number;
+}
+
+export const collectionsAPI = {
+  listCollections: async (): Promise<CollectionSummary[]> => {
+    const response = await axios.get('/api/collections');
+    return response.data;
+  },
+
+  getCollection: async (id: number): Promise<CollectionWithItems> => {
+    const response = await axios.get(`/api/collections/${id}`);
+    return response.data;
+  },
+
+  createCollection: async (request: CollectionRequest): Promise<CollectionWithItems> => {
+    const response = await axios.post('/api/collections', request);
+    return response.data;
+  },
+
+  updateCollection: async (id: number, request: CollectionRequest): Promise<CollectionWithItems> => {
+    const response = await axios.patch(`/api/collections/${id}`, request);
+    return response.data;
+  },
+
+  deleteCollection: async (id: number): Promise<void> => {
+    await axios.delete(`/api/collections/${id}`);
+  },
+
+  addItem: async (id: number, request: AddCollectionItemRequest): Promise<CollectionWithItems> => {
+    const response = await axios.post(`/api/collections/${id}/items`, request);
+    return response.data;
+  },
+
+  updateItem: async (
+    id: number,
+    itemId: number,
+    request: UpdateCollectionItemRequest
+  ): Promise<CollectionWithItems> => {
+    const response = await axios.patch(`/api/collections/${id}/items/${itemId}`, request);
+    return response.data;
+  },
+
+  removeItem: async (id: number, itemId: number): Promise<CollectionWithItems> => {
+    const response = await axios.delete(`/api/collections/${id}/items/${itemId}`);
+    return response.data;
+  },
+
+  /** Every item of the collection, in the new order */
+  reorderItems: async (id: number, itemIds: number[]): Promise<CollectionWithItems> => {
+    const response = await axios.put(`/api/collections/${id}/items`, { item_ids: itemIds });
+    return response.data;
+  },
+
+  downloadZip: async (id: number): Promise<Blob> => {
+    const response = await axios.get(`/api/collections/${id}/download`, { responseType: 'blob' });
+    return response.data;
+  },
+};
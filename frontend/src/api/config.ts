@@ -1,5 +1,12 @@
 import axios from './client';
 
+export interface PrinterProfile {
+  name: string;
+  build_x: number;
+  build_y: number;
+  build_z: number;
+}
+
 export interface AppConfig {
   id: number;
   root_path: string | null;
@@ -8,12 +15,34 @@ export interface AppConfig {
   images_per_page: number;
   created_at: number;
   updated_at: number;
+  printer_profiles: PrinterProfile[];
+  scan_schedule: string | null;
+  scan_interval_minutes: number | null;
+  ignore_patterns: string[];
+  follow_symlinks: boolean;
+  write_sidecars: boolean;
+  creator_folder_level: number | null;
+  release_folder_pattern: string | null;
 }
 
 export interface UpdateConfigRequest {
   root_path?: string;
   cache_max_size_mb?: number;
   images_per_page?: number;
+  printer_profiles?: PrinterProfile[];
+  /** Cron expression (UTC); an empty string disables it */
+  scan_schedule?: string;
+  /** Minutes between rescans; 0 disables them */
+  scan_interval_minutes?: number;
+  /** Gitignore-style patterns, relative to the library root */
+  ignore_patterns?: string[];
+  follow_symlinks?: boolean;
+  /** Mirror tag and metadata edits into glyptotheka.json sidecar files */
+  write_sidecars?: boolean;
+  /** Folder level naming creators (1 = folders in a library root); 0 disables it */
+  creator_folder_level?: number;
+  /** Folder name pattern of release folders, e.g. 'YYYY-MM*'; an empty string disables it */
+  release_folder_pattern?: string;
 }
 
 export const configAPI = {
@@ -0,0 +1,27 @@
+import axios from './client';
+import { Project } from '../types/project';
+
+export interface Creator {
+  id: number;
+  name: string;
+  created_at: number;
+}
+
+export interface CreatorSummary extends Creator {
+  /** Projects with model files of their own */
+  project_count: number;
+  /** Newest first, by release date and then by when they were indexed */
+  latest_projects: Project[];
+}
+
+export const creatorsAPI = {
+  listCreators: async (q?: string): Promise<CreatorSummary[]> => {
+    const response = await axios.get('/api/creators', { params: q ? { q } : undefined });
+    return response.data;
+  },
+
+  getCreator: async (id: number): Promise<CreatorSummary> => {
+    const response = await axios.get(`/api/creators/${id}`);
+    return response.data;
+  },
+};
@@ -0,0 +1,26 @@
+import axios from './client';
+import { DuplicateGroup, DuplicateMode } from '../types/project';
+
+export interface DuplicatesResponse {
+  data: DuplicateGroup[];
+  meta: {
+    page: number;
+    per_page: number;
+    total: number;
+    total_pages: number;
+  };
+  total_wasted_bytes: number;
+}
+
+export const duplicatesAPI = {
+  list: async (
+    mode: DuplicateMode = 'exact',
+    page: number = 1,
+    perPage: number = 20
+  ): Promise<DuplicatesResponse> => {
+    const response = await axios.get<DuplicatesResponse>('/api/duplicates', {
+      params: { mode, page, per_page: perPage },
+    });
+    return response.data;
+  },
+};
@@ -1,5 +1,11 @@
 import axios from './client';
-import { Project, ProjectWithRelations, StlCategory, ImageFile } from '../types/project';
+import {
+  Project,
+  ProjectWithRelations,
+  StlCategory,
+  ImageFile,
+  FileAnalysis,
+} from '../types/project';
 
 export interface ProjectListResponse {
   projects: Project[];
@@ -13,6 +19,19 @@ export interface FilesResponse {
   per_page: number;
 }
 
+/** Missing fields are left unchanged; an empty string clears a field */
+export interface UpdateProjectRequest {
+  description?: string;
+  author?: string;
+  license?: string;
+  source_url?: string;
+  /** YYYY-MM-DD */
+  release_date?: string;
+  scale?: string;
+  /** Sets the creator by hand; an empty string goes back to the derived one */
+  creator?: string;
+}
+
 export const projectsAPI = {
   listRoot: async (): Promise<Project[]> => {
     const response = await axios.get<ProjectListResponse>('/api/projects');
@@ -24,6 +43,14 @@ export const projectsAPI = {
     return response.data;
   },
 
+  updateProject: async (
+    id: number,
+    update: UpdateProjectRequest
+  ): Promise<ProjectWithRelations> => {
+    const response = await axios.patch<ProjectWithRelations>(`/api/projects/${id}`, update);
+    return response.data;
+  },
+
   getProjectChildren: async (id: number): Promise<Project[]> => {
     const response = await axios.get<ProjectListResponse>(`/api/projects/${id}/children`);
     return response.data.projects;
@@ -41,6 +68,11 @@ export const projectsAPI = {
     return response.data;
   },
 
+  getFileAnalysis: async (fileId: number): Promise<FileAnalysis> => {
+    const response = await axios.get<FileAnalysis>(`/api/files/${fileId}/analysis`);
+    return response.data;
+  },
+
   getImageUrl: (hash: string): string => {
     return `${axios.defaults.baseURL}/api/images/${hash}`;
   },
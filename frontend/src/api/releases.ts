@@ -0,0 +1,99 @@
+import axios from './client';
+import { Project } from '../types/project';
+import { Creator } from './creators';
+
+export interface Release {
+  id: number;
+  name: string;
+  description: string | null;
+  creator_id: number | null;
+  /** YYYY-MM-DD */
+  release_date: string | null;
+  /** Folder the release was detected from */
+  folder_project_id: number | null;
+  /** Kept in sync with its folder until edited */
+  auto_detected: boolean;
+  created_at: number;
+  updated_at: number;
+  creator: Creator | null;
+}
+
+export interface ReleaseSummary extends Release {
+  project_count: number;
+}
+
+export interface ReleaseWithProjects extends Release {
+  /** In release order */
+  projects: Project[];
+}
+
+export interface CreateReleaseRequest {
+  name: string;
+  description?: string;
+  creator?: string;
+  release_date?: string;
+  project_ids?: number[];
+}
+
+/** Missing fields are left unchanged; an empty string clears a field */
+export interface UpdateReleaseRequest {
+  name?: string;
+  description?: string;
+  creator?: string;
+  release_date?: string;
+}
+
+export const releasesAPI = {
+  listReleases: async (params?: { q?: string; creator_id?: number }): Promise<ReleaseSummary[]> => {
+    const response = await axios.get('/api/releases', { params });
+    return response.data;
+  },
+
+  getRelease: async (id: number): Promise<ReleaseWithProjects> => {
+    const response = await axios.get(`/api/releases/${id}`);
+    return response.data;
+  },
+
+  createRelease: async (request: CreateReleaseRequest): Promise<ReleaseWithProjects> => {
+    const response = await axios.post('/api/releases', request);
+    return response.data;
+  },
+
+  updateRelease: async (id: number, request: UpdateReleaseRequest): Promise<ReleaseWithProjects> => {
+    const response = await axios.patch(`/api/releases/${id}`, request);
+    return response.data;
+  },
+
+  deleteRelease: async (id: number): Promise<void> => {
+    await axios.delete(`/api/releases/${id}`);
+  },
+
+  /** Replace the projects of a release, in order */
+  setProjects: async (id: number, projectIds: number[]): Promise<ReleaseWithProjects> => {
+    const response = await axios.put(`/api/releases/${id}/projects`, { project_ids: projectIds });
+    return response.data;
+  },
+
+  /** Add a project, or move it to `position` if it is already in the release */
+  addProject: async (id: number, projectId: number, position?: number): Promise<ReleaseWithProjects> => {
+    const response = await axios.post(`/api/releases/${id}/projects`, {
+      project_id: projectId,
+      position,
+    });
+    return response.data;
+  },
+
+  removeProject: async (id: number, projectId: number): Promise<ReleaseWithProjects> => {
+    const response = await axios.delete(`/api/releases/${id}/projects/${projectId}`);
+    return response.data;
+  },
+
+  getCoverUrl: (id: number): string => {
+    return `${axios.defaults.baseURL}/api/releases/${id}/cover`;
+  },
+
+  downloadZip: async (id: number): Promise<Blob> => {
+    const response = await axios.get(`/api/releases/${id}/download`, { responseType: 'blob' });
+    return response.data;
+  },
+};
@@ -0,0 +1,31 @@
+import axios from './client';
+
+export interface LibraryRoot {
+  id: number;
+  name: string;
+  path: string;
+  last_scan_at: number | null;
+  created_at: number;
+}
+
+export interface CreateLibraryRootRequest {
+  name: string;
+  path: string;
+}
+
+export const rootsAPI = {
+  listRoots: async (): Promise<LibraryRoot[]> => {
+    const response = await axios.get('/api/roots');
+    return response.data;
+  },
+
+  createRoot: async (root: CreateLibraryRootRequest): Promise<LibraryRoot> => {
+    const response = await axios.post('/api/roots', root);
+    return response.data;
+  },
+
+  /** Removes the root from the library index; files on disk are left alone */
+  deleteRoot: async (id: number): Promise<void> => {
+    await axios.delete(`/api/roots/${id}`);
+  },
+};
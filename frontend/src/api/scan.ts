@@ -1,4 +1,18 @@
 import axios from './client';
+import { PaginatedResponse } from '../types/api';
+
+export type ScanPhase = 'walking' | 'indexing' | 'inheritance' | 'previews';
+
+export interface ScanProgress {
+  phase: ScanPhase | null;
+  folders_visited: number;
+  files_processed: number;
+  current_path: string | null;
+  phase_completed: number;
+  phase_total: number | null;
+  elapsed_seconds: number;
+  estimated_remaining_seconds: number | null;
+}
 
 export interface ScanStatus {
   is_scanning: boolean;
@@ -11,16 +25,60 @@ export interface ScanStatus {
   files_updated?: number;
   files_removed?: number;
   errors?: string[];
+  cancelled?: boolean | null;
+  progress?: ScanProgress | null;
+}
+
+export type ScanMode = 'full' | 'incremental' | 'clean';
+export type ScanTrigger = 'manual' | 'scheduled' | 'watcher';
+export type ScanSessionStatus = 'running' | 'completed' | 'failed' | 'cancelled';
+
+export interface ScanSession {
+  id: number;
+  root_path: string;
+  scan_path: string | null;
+  mode: ScanMode;
+  triggered_by: ScanTrigger;
+  status: ScanSessionStatus;
+  started_at: number;
+  completed_at: number | null;
+  projects_found: number;
+  projects_added: number;
+  projects_updated: number;
+  projects_removed: number;
+  files_processed: number;
+  files_added: number;
+  files_updated: number;
+  files_removed: number;
+  stl_previews_generated: number;
+  stl_previews_queued: number;
+  stl_previews_cached: number;
+  errors_count: number;
+}
+
+export interface ScanSessionDetail extends ScanSession {
+  errors: string[];
 }
 
 export interface StartScanRequest {
   force?: boolean;
   clean?: boolean;
+  path?: string;
 }
 
 export const scanAPI = {
-  startScan: async (force?: boolean, clean?: boolean): Promise<ScanStatus> => {
-    const response = await axios.post('/api/scan', { force, clean });
+  startScan: async (
+    force?: boolean,
+    clean?: boolean,
+    path?: string,
+    rootId?: number
+  ): Promise<ScanStatus> => {
+    const response = await axios.post('/api/scan', { force, clean, path, root_id: rootId });
+    return response.data;
+  },
+
+  cancelScan: async (): Promise<ScanStatus> => {
+    const response = await axios.post('/api/scan/cancel');
     return response.data;
   },
 
@@ -28,4 +86,32 @@ export const scanAPI = {
     const response = await axios.get('/api/scan/status');
     return response.data;
   },
+
+  getScanHistory: async (page = 1, perPage = 20): Promise<PaginatedResponse<ScanSession>> => {
+    const response = await axios.get('/api/scan/history', {
+      params: { page, per_page: perPage },
+    });
+    return response.data;
+  },
+
+  getScanSession: async (id: number): Promise<ScanSessionDetail> => {
+    const response = await axios.get(`/api/scan/history/${id}`);
+    return response.data;
+  },
+
+  // Streams status updates while a scan runs; returns a function that closes the stream
+  subscribeToScanEvents: (
+    onProgress: (status: ScanStatus) => void,
+    onComplete: (status: ScanStatus) => void,
+  ): (() => void) => {
+    const source = new EventSource(`${axios.defaults.baseURL ?? ''}/api/scan/events`);
+    source.addEventListener('progress', (event) => {
+      onProgress(JSON.parse((event as MessageEvent).data));
+    });
+    source.addEventListener('complete', (event) => {
+      source.close();
+      onComplete(JSON.parse((event as MessageEvent).data));
+    });
+    return () => source.close();
+  },
 };
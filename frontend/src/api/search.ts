@@ -7,6 +7,15 @@ export interface SearchParams {
   page?: number;
   per_page?: number;
   leaf_only?: boolean;
+  max_x?: number;
+  max_y?: number;
+  max_z?: number;
+  min_z?: number;
+  printer?: string;
+  has_issues?: boolean;
+  root_id?: number;
+  /** Creator name */
+  creator?: string;
 }
 
 export interface SearchMeta {
@@ -44,6 +53,29 @@ export const searchApi = {
     if (params.leaf_only !== undefined) {
       queryParams.append('leaf_only', params.leaf_only.toString());
     }
+
+    for (const key of ['max_x', 'max_y', 'max_z', 'min_z'] as const) {
+      const value = params[key];
+      if (value !== undefined) {
+        queryParams.append(key, value.toString());
+      }
+    }
+
+    if (params.printer) {
+      queryParams.append('printer', params.printer);
+    }
+
+    if (params.has_issues !== undefined) {
+      queryParams.append('has_issues', params.has_issues.toString());
+    }
+
+    if (params.root_id !== undefined) {
+      queryParams.append('root_id', params.root_id.toString());
+    }
+
+    if (params.creator) {
+      queryParams.append('creator', params.creator);
+    }
     
     const response = await apiClient.get(`/api/search?${queryParams.toString()}`);
     return response.data;
@@ -58,7 +58,15 @@ const FileList: React.FC<Props> = ({ categories, images = [] }) => {
               <span className="text-2xl">🧊</span>
             </div>
           )}
-          <span className="truncate text-gray-900 dark:text-theme">{file.filename}</span>
+          <div className="flex flex-col min-w-0">
+            <span className="truncate text-gray-900 dark:text-theme">{file.filename}</span>
+            {file.analysis && (
+              <span className="text-xs text-gray-500 dark:text-theme-muted">
+                {file.analysis.size_x.toFixed(1)} × {file.analysis.size_y.toFixed(1)} × {file.analysis.size_z.toFixed(1)} mm
+                {' · '}~{file.analysis.estimated_mass.toFixed(0)} g
+              </span>
+            )}
+          </div>
         </div>
         <div className="flex items-center gap-4">
           <span className="text-sm text-gray-500 dark:text-theme-muted">
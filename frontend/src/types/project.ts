@@ -8,6 +8,7 @@ export interface Project {
   description: string | null;
   created_at: number;
   updated_at: number;
+  root_id: number | null;
 }
 
 export interface ImagePreview {
@@ -39,18 +40,95 @@ export interface ProjectWithRelations extends Project {
   image_count: number;
   tags: Tag[];
   inherited_images: ImagePreview[];  // T037: Add inherited images
+  also_appears_in: DuplicateProject[];
+  metadata: ProjectMetadata | null;
+  creator: { id: number; name: string; created_at: number } | null;
+  /** Collections holding the project or one of its model files */
+  collection_count: number;
+}
+
+// Imported from glyptotheka.json / .yaml sidecar files or edited
+export interface ProjectMetadata {
+  author: string | null;
+  license: string | null;
+  source_url: string | null;
+  release_date: string | null;
+  scale: string | null;
+  print_settings: Record<string, unknown> | null;
+}
+
+// Duplicate detection types
+export interface DuplicateProject {
+  project_id: number;
+  name: string;
+  full_path: string;
+  shared_files: number;
+}
+
+export interface DuplicateFile {
+  stl_file_id: number;
+  filename: string;
+  file_path: string;
+  project_id: number;
+  project_name: string;
+  project_path: string;
+}
+
+export type DuplicateMode = 'exact' | 'similar';
+
+export interface DuplicateGroup {
+  content_hash?: string;  // exact mode
+  geometry_fingerprint?: string;  // similar mode
+  file_size: number;
+  copies: number;
+  wasted_bytes: number;
+  files: DuplicateFile[];
 }
 
 // File types
+export interface MeshAnalysis {
+  stl_file_id: number;
+  size_x: number;
+  size_y: number;
+  size_z: number;
+  triangle_count: number;
+  surface_area: number;
+  volume: number;
+  estimated_mass: number;
+  geometry_fingerprint: string | null;
+  analyzed_at: number;
+}
+
+export interface MeshIssue {
+  issue_type:
+    | 'non_manifold_edges'
+    | 'open_edges'
+    | 'flipped_normals'
+    | 'degenerate_triangles'
+    | 'multiple_shells';
+  count: number;
+  message: string;
+}
+
+export interface FileAnalysis {
+  stl_file_id: number;
+  filename: string;
+  format: string;
+  analysis: MeshAnalysis | null;
+  issues: MeshIssue[];
+}
+
 export interface StlFile {
   id: number;
   project_id: number;
   filename: string;
   file_path: string;
   file_size: number;
+  format: string;
   category: string | null;
   preview_path: string | null;
   preview_generated_at: number | null;
+  analysis?: MeshAnalysis | null;
 }
 
 export interface StlCategory {